@@ -1,7 +1,44 @@
+pub mod alias;
+pub mod archive;
+pub mod branch_set;
+pub mod check_fleet;
 pub mod clone;
+pub mod completion;
+pub mod coverage;
+pub mod create;
+pub mod dissociate;
+pub mod doctor;
+pub mod du;
 pub mod edit;
+pub mod exec;
+pub mod external;
+pub mod gc;
+pub mod gc_store;
 pub mod initialize;
+pub mod inspect;
 pub mod issues;
 pub mod list;
+pub mod manifest;
+pub mod new;
+pub mod notify;
+pub mod origin;
+pub mod owners;
 pub mod path;
+pub mod pin_toolversion;
+pub mod pr;
+pub mod push;
 pub mod refresh;
+pub mod releases;
+pub mod remove;
+pub mod rescue;
+pub mod rpc;
+pub mod selftest;
+pub mod set;
+pub mod stash;
+pub mod stats;
+pub mod tag;
+pub mod template;
+pub mod token;
+pub mod ui;
+pub mod which;
+pub mod wiki;
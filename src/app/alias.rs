@@ -0,0 +1,167 @@
+//! User-defined aliases of commands in `[alias]` of config
+//!
+//! Aliases are expanded before parsing arguments like git aliases.
+//! Aliases starting with `!` are shell commands run with the arguments after the alias.
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use crate::app::external;
+use crate::config::Config;
+use crate::mure_error::Error;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Expansion {
+    /// arguments to be parsed as mure commands
+    Args(Vec<String>),
+    /// shell command of a `!` alias and the arguments after the alias
+    Shell { command: String, args: Vec<String> },
+}
+
+/// Expand the alias at the position of the subcommand. Built-in commands can't be overridden.
+/// Words of the alias are split by whitespace, so use a `!` alias for quoting.
+pub fn expand(
+    aliases: &BTreeMap<String, String>,
+    builtins: &[String],
+    args: Vec<String>,
+) -> Expansion {
+    let Some(index) = subcommand_index(&args) else {
+        return Expansion::Args(args);
+    };
+    let name = &args[index];
+    if builtins.contains(name) {
+        return Expansion::Args(args);
+    }
+    let Some(alias) = aliases.get(name) else {
+        return Expansion::Args(args);
+    };
+    if let Some(command) = alias.strip_prefix('!') {
+        return Expansion::Shell {
+            command: command.trim().to_string(),
+            args: args[index + 1..].to_vec(),
+        };
+    }
+    let mut expanded = args[..index].to_vec();
+    expanded.extend(alias.split_whitespace().map(str::to_string));
+    expanded.extend_from_slice(&args[index + 1..]);
+    Expansion::Args(expanded)
+}
+
+/// Index of the first argument which is not a global option
+pub fn subcommand_index(args: &[String]) -> Option<usize> {
+    let mut index = 1;
+    while index < args.len() {
+        match args[index].as_str() {
+            // global options with a value
+            "--log-file" | "--profile" => index += 2,
+            arg if arg.starts_with('-') => index += 1,
+            _ => return Some(index),
+        }
+    }
+    None
+}
+
+/// Run the shell command of a `!` alias with `sh` and return its exit code.
+/// The same environment variables as external subcommands are set for the command.
+pub fn run_shell(config: &Config, command: &str, args: &[String]) -> Result<i32, Error> {
+    let mut shell = Command::new("sh");
+    // "$@" passes the arguments after the alias to the command as they are
+    shell
+        .arg("-c")
+        .arg(format!("{command} \"$@\""))
+        .arg(command)
+        .args(args);
+    external::set_env(config, &mut shell);
+    let status = shell
+        .status()
+        .map_err(|e| Error::from_str(&format!("failed to run alias {command}: {e}")))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Show the aliases defined in config
+pub fn list(config: &Config) -> Result<(), Error> {
+    for (name, alias) in config.alias.iter().flatten() {
+        println!("{name} = {alias}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_expand() {
+        let aliases = BTreeMap::from([
+            ("up".to_string(), "refresh --all --quiet".to_string()),
+            ("list".to_string(), "list --full".to_string()),
+            ("st".to_string(), "!git status --short".to_string()),
+        ]);
+        let builtins = args(&["refresh", "list"]);
+        let expand = |input: &[&str]| expand(&aliases, &builtins, args(input));
+
+        assert_eq!(
+            expand(&["mure", "-v", "up", "--stale", "6h"]),
+            Expansion::Args(args(&[
+                "mure", "-v", "refresh", "--all", "--quiet", "--stale", "6h"
+            ]))
+        );
+        assert_eq!(
+            expand(&["mure", "--log-file", "up", "up"]),
+            Expansion::Args(args(&[
+                "mure",
+                "--log-file",
+                "up",
+                "refresh",
+                "--all",
+                "--quiet"
+            ]))
+        );
+        // built-in commands win
+        assert_eq!(
+            expand(&["mure", "list"]),
+            Expansion::Args(args(&["mure", "list"]))
+        );
+        assert_eq!(
+            expand(&["mure", "unknown"]),
+            Expansion::Args(args(&["mure", "unknown"]))
+        );
+        assert_eq!(
+            expand(&["mure", "--profile", "work", "up"]),
+            Expansion::Args(args(&[
+                "mure",
+                "--profile",
+                "work",
+                "refresh",
+                "--all",
+                "--quiet"
+            ]))
+        );
+        assert_eq!(expand(&["mure"]), Expansion::Args(args(&["mure"])));
+        assert_eq!(
+            expand(&["mure", "st", "src"]),
+            Expansion::Shell {
+                command: "git status --short".to_string(),
+                args: args(&["src"])
+            }
+        );
+    }
+
+    #[test]
+    fn test_run_shell() {
+        let config = Config::default();
+        assert_eq!(
+            run_shell(
+                &config,
+                "test \"$MURE_BASE_DIR\" = \"\" && test",
+                &args(&["-n", "x"])
+            )
+            .unwrap(),
+            0
+        );
+        assert_eq!(run_shell(&config, "exit 3;", &[]).unwrap(), 3);
+    }
+}
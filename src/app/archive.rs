@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use crate::app::list::read_symlink_as_mure_repo;
+use crate::config::{Config, ConfigSupport};
+use crate::gh::GhClient;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+/// Move the symlink of the repository into the archive directory.
+/// Archived repositories are not listed nor refreshed. With `remote`, the GitHub repository is archived too.
+pub fn archive(config: &Config, name: &str, remote: bool) -> Result<(), Error> {
+    let from = config.base_path().join(name);
+    let to = config.archive_path().join(name);
+    if remote {
+        set_archived_remote(&from, true)?;
+    }
+    std::fs::create_dir_all(config.archive_path())?;
+    move_symlink(&from, &to)?;
+    println!("{}", Message::Archived { name });
+    Ok(())
+}
+
+/// Move the symlink of the repository back from the archive directory.
+/// With `remote`, the GitHub repository is unarchived too.
+pub fn unarchive(config: &Config, name: &str, remote: bool) -> Result<(), Error> {
+    let from = config.archive_path().join(name);
+    let to = config.base_path().join(name);
+    if remote {
+        set_archived_remote(&from, false)?;
+    }
+    move_symlink(&from, &to)?;
+    println!("{}", Message::Unarchived { name });
+    Ok(())
+}
+
+fn set_archived_remote(symlink: &Path, archived: bool) -> Result<(), Error> {
+    let mure_repo = read_symlink_as_mure_repo(&symlink.to_path_buf())?;
+    GhClient::new().set_archived(&mure_repo.repo.fully_qualified_name(), archived)
+}
+
+fn move_symlink(from: &Path, to: &Path) -> Result<(), Error> {
+    match std::fs::symlink_metadata(from) {
+        Ok(metadata) if metadata.is_symlink() => (),
+        _ => return Err(Error::from_str(&format!("{} is not found", from.display()))),
+    }
+    if std::fs::symlink_metadata(to).is_ok() {
+        return Err(Error::from_str(&format!("{} already exists", to.display())));
+    }
+    std::fs::rename(from, to)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::list::{search_archived_repo, search_mure_repo};
+    use crate::test_fixture::config_with_base_dir;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_archive() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let store = config.repo_store_path("github.com", "kitsuyui", "mure");
+        std::fs::create_dir_all(&store).unwrap();
+        std::os::unix::fs::symlink(&store, config.base_path().join("mure")).unwrap();
+        assert_eq!(search_mure_repo(&config).len(), 1);
+        assert_eq!(search_archived_repo(&config).len(), 0);
+
+        archive(&config, "mure", false).unwrap();
+        assert_eq!(search_mure_repo(&config).len(), 0);
+        let archived = search_archived_repo(&config);
+        assert_eq!(archived.len(), 1);
+        assert_eq!(
+            archived[0].as_ref().unwrap().repo.name_with_owner(),
+            "kitsuyui/mure"
+        );
+
+        let Err(error) = archive(&config, "mure", false) else {
+            unreachable!();
+        };
+        assert!(error.to_string().ends_with("is not found"));
+
+        unarchive(&config, "mure", false).unwrap();
+        assert_eq!(search_mure_repo(&config).len(), 1);
+        assert_eq!(search_archived_repo(&config).len(), 0);
+
+        let Err(error) = unarchive(&config, "mure", false) else {
+            unreachable!();
+        };
+        assert!(error.to_string().ends_with("is not found"));
+    }
+}
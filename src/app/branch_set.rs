@@ -0,0 +1,453 @@
+//! The same branch across repositories for changes spanning multiple services
+//!
+//! `branch-create` records the repositories of the branch in the state,
+//! so that `branch-status` can show the progress of each of them later.
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use git2::{BranchType, Repository};
+
+use crate::config::{Config, ConfigSupport};
+use crate::git::{DryRun, RepositorySupport};
+use crate::i18n::Message;
+use crate::mure_error::Error;
+use crate::state::{BranchSet, BranchStart, State};
+
+/// Progress of the branch in a repository
+#[derive(Debug, PartialEq, Eq)]
+enum BranchStatus {
+    /// the branch was deleted or never created
+    Missing,
+    /// no commits on the branch yet
+    NotStarted,
+    /// commits ahead of the default branch and whether all of them are pushed
+    InProgress { commits: usize, pushed: bool },
+    /// the default branch contains the commits of the branch
+    Merged,
+}
+
+impl BranchStatus {
+    fn describe(&self) -> String {
+        match self {
+            BranchStatus::Missing => "missing".to_string(),
+            BranchStatus::NotStarted => "not started".to_string(),
+            BranchStatus::InProgress { commits, pushed } => {
+                let pushed = if *pushed { "pushed" } else { "not pushed" };
+                format!("{commits} commits ({pushed})")
+            }
+            BranchStatus::Merged => "merged".to_string(),
+        }
+    }
+}
+
+/// Create the branch from the tip of the default branch of origin in each repository.
+/// Failures don't stop the others. Repositories which already have the branch join the set as is.
+pub fn branch_create(
+    config: &Config,
+    branch: &str,
+    repositories: &[String],
+    switch: bool,
+) -> Result<(), Error> {
+    let mut started = BTreeMap::new();
+    for name in repositories {
+        match create_in(config, name, branch, switch) {
+            Ok((start, created)) => {
+                if !DryRun::is_enabled() {
+                    if created {
+                        println!("{}", Message::CreatedBranch { name, branch });
+                    } else {
+                        println!("{}", Message::BranchAlreadyExists { name, branch });
+                    }
+                }
+                started.insert(name.to_string(), start);
+            }
+            Err(e) => println!("{name}: {e}"),
+        }
+    }
+    if started.is_empty() {
+        return Err(Error::from_str(&format!(
+            "{branch} is not created in any repository"
+        )));
+    }
+    if !DryRun::is_enabled() {
+        let mut state = State::load()?;
+        state.record_branch_set(branch, now(), started);
+        state.save()?;
+    }
+    Ok(())
+}
+
+/// Returns where the branch started and whether it was created
+fn create_in(
+    config: &Config,
+    name: &str,
+    branch: &str,
+    switch: bool,
+) -> Result<(BranchStart, bool), Error> {
+    let repo = Repository::open(config.base_path().join(name))?;
+    let Some(base) = repo.guess_default_branch("origin")? else {
+        return Err(Error::from_str("default branch is unknown"));
+    };
+    let created = match repo.find_branch(branch, BranchType::Local) {
+        Ok(_) => false,
+        Err(_) => {
+            // the commit instead of origin/<base> not to set it as the upstream
+            let commit = repo
+                .revparse_single(&format!("origin/{base}"))?
+                .peel_to_commit()?;
+            repo.create_branch(branch, &commit.id().to_string())?;
+            true
+        }
+    };
+    let commit = match repo.find_branch(branch, BranchType::Local) {
+        Ok(local) => local.get().peel_to_commit()?.id().to_string(),
+        // not created in dry-run
+        Err(_) => String::new(),
+    };
+    if switch {
+        repo.switch(branch)?;
+    }
+    Ok((BranchStart { base, commit }, created))
+}
+
+/// Show the progress of the branch in each repository of the set
+pub fn branch_status(config: &Config, branch: &str) -> Result<(), Error> {
+    let set = load_set(branch)?;
+    for (name, start) in &set.repositories {
+        match status_of(config, name, branch, start) {
+            Ok(status) => println!("{name}\t{}", status.describe()),
+            Err(e) => println!("{name}\t{e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Push the branch in each repository of the set where it has commits, setting the upstream
+pub fn branch_push(config: &Config, branch: &str) -> Result<(), Error> {
+    let set = load_set(branch)?;
+    for (name, start) in &set.repositories {
+        if let Err(e) = push_in(config, name, branch, start) {
+            println!("{name}: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn push_in(config: &Config, name: &str, branch: &str, start: &BranchStart) -> Result<(), Error> {
+    let repo = Repository::open(config.base_path().join(name))?;
+    match status_in(&repo, branch, start)? {
+        BranchStatus::Missing => println!("{}", Message::BranchNotFound { name, branch }),
+        BranchStatus::NotStarted | BranchStatus::Merged => {
+            println!("{}", Message::NothingToPush { name, branch })
+        }
+        BranchStatus::InProgress { .. } => {
+            repo.push_upstream("origin", branch)?;
+            if !DryRun::is_enabled() {
+                println!("{}", Message::PushedBranch { name, branch });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Delete the branch merged into the default branch in each repository of the set.
+/// Branches with commits not merged yet are kept.
+/// The set is forgotten once the branch is gone from all of the repositories.
+pub fn branch_clean(config: &Config, branch: &str) -> Result<(), Error> {
+    let set = load_set(branch)?;
+    let mut remaining = vec![];
+    for (name, start) in &set.repositories {
+        match clean_in(config, name, branch, start) {
+            Ok(true) => (),
+            Ok(false) => remaining.push(name.to_string()),
+            Err(e) => {
+                println!("{name}: {e}");
+                remaining.push(name.to_string());
+            }
+        }
+    }
+    if DryRun::is_enabled() {
+        return Ok(());
+    }
+    let mut state = State::load()?;
+    if remaining.is_empty() {
+        state.branch_sets.remove(branch);
+    } else if let Some(set) = state.branch_sets.get_mut(branch) {
+        set.repositories.retain(|name, _| remaining.contains(name));
+    }
+    state.save()
+}
+
+/// Returns whether the branch is gone from the repository
+fn clean_in(config: &Config, name: &str, branch: &str, start: &BranchStart) -> Result<bool, Error> {
+    let repo = Repository::open(config.base_path().join(name))?;
+    // whether it is merged is told by origin, which must not be stale
+    if repo.find_remote("origin").is_ok() {
+        repo.fetch_remote_prune("origin")?;
+    }
+    match status_in(&repo, branch, start)? {
+        BranchStatus::Missing => Ok(true),
+        BranchStatus::InProgress { .. } => {
+            println!("{}", Message::KeptUnmergedBranch { name, branch });
+            Ok(false)
+        }
+        BranchStatus::NotStarted | BranchStatus::Merged => {
+            // the branch checked out can't be deleted
+            if repo
+                .get_current_branch()
+                .is_ok_and(|current| current == branch)
+            {
+                repo.switch(&start.base)?;
+            }
+            // merged into origin is checked above, which git branch -d doesn't know
+            repo.force_delete_branch(branch)?;
+            if !DryRun::is_enabled() {
+                println!("{}", Message::DeletedBranchIn { name, branch });
+            }
+            clean_remote(&repo, name, branch, start)?;
+            Ok(true)
+        }
+    }
+}
+
+/// Delete the branch pushed to origin too, unless someone pushed commits not merged yet
+fn clean_remote(
+    repo: &Repository,
+    name: &str,
+    branch: &str,
+    start: &BranchStart,
+) -> Result<(), Error> {
+    let Ok(remote) = repo.find_branch(&format!("origin/{branch}"), BranchType::Remote) else {
+        return Ok(());
+    };
+    let remote_branch = format!("origin/{branch}");
+    let tip = remote.get().peel_to_commit()?.id();
+    let base = repo
+        .revparse_single(&format!("origin/{}", start.base))?
+        .peel_to_commit()?
+        .id();
+    if tip != base && !repo.graph_descendant_of(base, tip)? {
+        println!(
+            "{}",
+            Message::KeptUnmergedBranch {
+                name,
+                branch: &remote_branch
+            }
+        );
+        return Ok(());
+    }
+    repo.push_delete("origin", branch, &tip.to_string())?;
+    if !DryRun::is_enabled() {
+        println!(
+            "{}",
+            Message::DeletedBranchIn {
+                name,
+                branch: &remote_branch
+            }
+        );
+    }
+    Ok(())
+}
+
+fn load_set(branch: &str) -> Result<BranchSet, Error> {
+    let mut state = State::load()?;
+    state.branch_sets.remove(branch).ok_or_else(|| {
+        Error::from_str(&format!(
+            "branch set {branch} is not found. Create it by branch-create first"
+        ))
+    })
+}
+
+fn status_of(
+    config: &Config,
+    name: &str,
+    branch: &str,
+    start: &BranchStart,
+) -> Result<BranchStatus, Error> {
+    let repo = Repository::open(config.base_path().join(name))?;
+    status_in(&repo, branch, start)
+}
+
+fn status_in(repo: &Repository, branch: &str, start: &BranchStart) -> Result<BranchStatus, Error> {
+    let Ok(local) = repo.find_branch(branch, BranchType::Local) else {
+        return Ok(BranchStatus::Missing);
+    };
+    let tip = local.get().peel_to_commit()?.id();
+    let base = repo
+        .revparse_single(&format!("origin/{}", start.base))?
+        .peel_to_commit()?
+        .id();
+    let (ahead, _) = repo.graph_ahead_behind(tip, base)?;
+    if ahead > 0 {
+        let pushed = local.upstream().is_ok() && !repo.has_unpushed_commits(branch)?;
+        return Ok(BranchStatus::InProgress {
+            commits: ahead,
+            pushed,
+        });
+    }
+    if tip.to_string() == start.commit {
+        Ok(BranchStatus::NotStarted)
+    } else {
+        Ok(BranchStatus::Merged)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{config_with_base_dir, Fixture};
+    use assay::assay;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(BranchStatus::Missing.describe(), "missing");
+        assert_eq!(
+            BranchStatus::InProgress {
+                commits: 2,
+                pushed: false
+            }
+            .describe(),
+            "2 commits (not pushed)"
+        );
+    }
+
+    #[assay]
+    fn test_branch_create_and_status() {
+        let state_dir = Temp::new_dir().unwrap();
+        std::env::set_var("MURE_STATE_PATH", state_dir.join("state.json"));
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let origin = Fixture::create().unwrap();
+        origin.create_empty_commit("initial commit").unwrap();
+        let origin_path = origin.repo.workdir().unwrap().to_str().unwrap();
+        let names = ["api", "web", "db"].map(str::to_string);
+        for name in &names {
+            let repo = Repository::clone(origin_path, config.base_path().join(name)).unwrap();
+            repo.config()
+                .unwrap()
+                .set_str("user.name", "tester")
+                .unwrap();
+            repo.config()
+                .unwrap()
+                .set_str("user.email", "test@example.com")
+                .unwrap();
+        }
+        let open = |name: &str| Repository::open(config.base_path().join(name)).unwrap();
+        let start = open("api").head().unwrap().peel_to_commit().unwrap().id();
+
+        let mut repositories = names.to_vec();
+        repositories.push("unknown".to_string());
+        branch_create(&config, "feature", &repositories, false).unwrap();
+        let set = State::load().unwrap().branch_sets["feature"].clone();
+        assert_eq!(
+            set.repositories.keys().collect::<Vec<_>>(),
+            vec!["api", "db", "web"]
+        );
+        assert_eq!(set.repositories["api"].commit, start.to_string());
+        // not switched
+        assert_ne!(open("api").get_current_branch().unwrap(), "feature");
+        // not tracking the default branch
+        assert!(open("api")
+            .find_branch("feature", BranchType::Local)
+            .unwrap()
+            .upstream()
+            .is_err());
+
+        // api is in progress
+        let api = open("api");
+        api.switch("feature").unwrap();
+        api.command(&["commit", "--allow-empty", "-m", "change api"])
+            .unwrap();
+        // web is merged
+        let web = open("web");
+        web.switch("feature").unwrap();
+        web.command(&["commit", "--allow-empty", "-m", "change web"])
+            .unwrap();
+        let merge_into_origin = |name: &str| {
+            let path = config.base_path().join(name);
+            origin
+                .repo
+                .command(&["fetch", path.to_str().unwrap(), "feature"])
+                .unwrap();
+            origin
+                .repo
+                .command(&["merge", "--no-edit", "FETCH_HEAD"])
+                .unwrap();
+            open(name).command(&["fetch", "origin"]).unwrap();
+        };
+        merge_into_origin("web");
+        let status =
+            |name: &str| status_of(&config, name, "feature", &set.repositories[name]).unwrap();
+        assert_eq!(
+            status("api"),
+            BranchStatus::InProgress {
+                commits: 1,
+                pushed: false
+            }
+        );
+        assert_eq!(status("web"), BranchStatus::Merged);
+        assert_eq!(status("db"), BranchStatus::NotStarted);
+        open("db").delete_branch("feature").unwrap();
+        assert_eq!(status("db"), BranchStatus::Missing);
+
+        // joining again keeps the branch
+        branch_create(&config, "feature", &["api".to_string()], true).unwrap();
+        assert_eq!(
+            State::load().unwrap().branch_sets["feature"].repositories["api"].commit,
+            set.repositories["api"].commit
+        );
+        branch_status(&config, "feature").unwrap();
+        assert!(branch_status(&config, "unknown").is_err());
+        assert!(branch_create(&config, "other", &["unknown".to_string()], false).is_err());
+
+        // only api has commits to push
+        branch_push(&config, "feature").unwrap();
+        assert!(origin
+            .repo
+            .find_branch("feature", BranchType::Local)
+            .is_ok());
+        assert_eq!(
+            status("api"),
+            BranchStatus::InProgress {
+                commits: 1,
+                pushed: true
+            }
+        );
+
+        // web is merged and deleted even though it is checked out, api is not merged yet
+        branch_clean(&config, "feature").unwrap();
+        assert!(open("web")
+            .find_branch("feature", BranchType::Local)
+            .is_err());
+        assert!(open("api")
+            .find_branch("feature", BranchType::Local)
+            .is_ok());
+        assert_eq!(
+            State::load().unwrap().branch_sets["feature"]
+                .repositories
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["api"]
+        );
+
+        merge_into_origin("api");
+        branch_clean(&config, "feature").unwrap();
+        assert!(open("api")
+            .find_branch("feature", BranchType::Local)
+            .is_err());
+        // the pushed branch is deleted on origin too
+        assert!(origin
+            .repo
+            .find_branch("feature", BranchType::Local)
+            .is_err());
+        assert!(!State::load().unwrap().branch_sets.contains_key("feature"));
+    }
+}
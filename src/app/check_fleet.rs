@@ -0,0 +1,197 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::app::list::{search_mure_repo, RepoFilter};
+use crate::config::Config;
+use crate::i18n::Message;
+use crate::lang;
+use crate::misc::bulk::{self, BulkOptions, BulkSummary};
+use crate::misc::permission::is_permission_denied;
+use crate::mure_error::Error;
+
+/// Formatting and lint commands for each language. They run in order and stop at the first failure.
+const PRESETS: [(&str, &[&[&str]]); 5] = [
+    (
+        "rust",
+        &[
+            &["cargo", "fmt", "--check"],
+            &["cargo", "clippy", "--quiet", "--", "-D", "warnings"],
+        ],
+    ),
+    // gofmt -l succeeds even if files are not formatted
+    (
+        "go",
+        &[
+            &["sh", "-c", "test -z \"$(gofmt -l .)\""],
+            &["go", "vet", "./..."],
+        ],
+    ),
+    (
+        "python",
+        &[&["ruff", "format", "--check"], &["ruff", "check"]],
+    ),
+    ("typescript", &[&["prettier", "--check", "."]]),
+    ("javascript", &[&["prettier", "--check", "."]]),
+];
+
+/// Commands of the preset for the language. Empty if there is no preset.
+fn preset(lang: &str) -> &'static [&'static [&'static str]] {
+    PRESETS
+        .iter()
+        .find(|(name, _)| *name == lang)
+        .map_or(&[], |(_, commands)| commands)
+}
+
+/// Result of a repository other than failure
+#[derive(Debug, PartialEq, Eq)]
+enum CheckResult {
+    Passed,
+    /// no preset for the language
+    Skipped(&'static str),
+    /// the repository is not accessible, e.g. owned by another user
+    PermissionDenied,
+}
+
+/// Run the preset of the detected language in each repository with `jobs` repositories at once
+/// and summarize which of them fail formatting or lint checks.
+pub fn check_fleet(
+    config: &Config,
+    filter: &RepoFilter,
+    bulk: &BulkOptions,
+) -> Result<BulkSummary, Error> {
+    let mut repos = vec![];
+    for repo in filter.apply(search_mure_repo(config))? {
+        match repo {
+            Ok(mure_repo) => repos.push(mure_repo),
+            Err(e) => println!("{}", e.message()),
+        }
+    }
+    if repos.is_empty() {
+        println!("{}", Message::NoRepositoriesFound);
+        return Ok(BulkSummary::default());
+    }
+    let mut results = Vec::with_capacity(repos.len());
+    let summary = bulk::run(
+        &repos,
+        &bulk.with_progress(),
+        |mure_repo| {
+            let lang = lang::detect(&mure_repo.absolute_path);
+            match preset(lang) {
+                _ if is_permission_denied(&mure_repo.absolute_path) => {
+                    Ok(CheckResult::PermissionDenied)
+                }
+                [] => Ok(CheckResult::Skipped(lang)),
+                commands => {
+                    run_checks(&mure_repo.absolute_path, commands).map(|_| CheckResult::Passed)
+                }
+            }
+        },
+        |index, result| results.push((index, result)),
+    );
+    results.sort_by_key(|(index, _)| *index);
+
+    let (mut passed, mut skipped) = (0, 0);
+    for (index, result) in &results {
+        let name = &repos[*index].repo.repo;
+        let message = match result {
+            Ok(CheckResult::Passed) => {
+                passed += 1;
+                Message::CheckPassed { name }
+            }
+            Err(command) => Message::CheckFailed { name, command },
+            Ok(CheckResult::Skipped(lang)) => {
+                skipped += 1;
+                Message::NoCheckPreset { name, lang }
+            }
+            Ok(CheckResult::PermissionDenied) => {
+                skipped += 1;
+                Message::PermissionDenied { name }
+            }
+        };
+        println!("{message}");
+    }
+    println!(
+        "{}",
+        Message::CheckFleetSummary {
+            passed,
+            failed: summary.failed,
+            skipped
+        }
+    );
+    if summary.cancelled > 0 {
+        println!(
+            "{}",
+            Message::CancelledByFailFast {
+                count: summary.cancelled
+            }
+        );
+    }
+    Ok(summary)
+}
+
+/// Run the commands in the directory until one of them fails. Their output is not shown.
+/// Returns the command which failed.
+fn run_checks(path: &Path, commands: &[&[&str]]) -> Result<(), String> {
+    for command in commands {
+        let Some((program, args)) = command.split_first() else {
+            continue;
+        };
+        let span = tracing::info_span!("check", path = %path.display());
+        let _enter = span.enter();
+        tracing::info!(?command, "executing");
+        let status = Command::new(program)
+            .args(args)
+            .current_dir(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        match status {
+            Ok(status) if status.success() => (),
+            Ok(status) => {
+                tracing::error!("{program} exited with {status}");
+                return Err(command.join(" "));
+            }
+            Err(e) => {
+                tracing::error!("failed to execute {program}: {e}");
+                return Err(command.join(" "));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::config_with_base_dir;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_preset() {
+        assert_eq!(preset("rust")[0], ["cargo", "fmt", "--check"]);
+        assert_eq!(preset("typescript"), preset("javascript"));
+        assert!(preset(lang::UNKNOWN).is_empty());
+    }
+
+    #[test]
+    fn test_run_checks() {
+        let temp_dir = Temp::new_dir().unwrap();
+        assert_eq!(run_checks(&temp_dir, &[&["true"], &["true"]]), Ok(()));
+        assert_eq!(
+            run_checks(&temp_dir, &[&["true"], &["sh", "-c", "exit 1"], &["true"]]),
+            Err("sh -c exit 1".to_string())
+        );
+        assert_eq!(
+            run_checks(&temp_dir, &[&["mure-nonexistent-command"]]),
+            Err("mure-nonexistent-command".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_fleet() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        check_fleet(&config, &RepoFilter::default(), &BulkOptions::new(4, false)).unwrap();
+    }
+}
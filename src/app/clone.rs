@@ -1,18 +1,652 @@
-use crate::config::Config;
-use crate::git::RepositorySupport;
+use crate::app::list::RepoFilter;
+use crate::app::refresh::{refresh_main, RefreshOptions};
+use crate::app::tag;
+use crate::config::{CloneProtocol, Config};
+use crate::event::OutputFormat;
+use crate::git::{DryRun, RepositorySupport};
 use crate::github::repo::RepoInfo;
+use crate::github::rest::get_gist_owner;
+use crate::github::token::get_github_token;
+use crate::i18n::Message;
+use crate::misc::bandwidth::{objects_size, Pacer};
+use crate::misc::bulk::{self, BulkOptions, BulkSummary};
 use crate::verbosity::Verbosity;
 use crate::{config::ConfigSupport, mure_error::Error};
 use std::fs as std_fs;
 use std::os::unix::fs as unix_fs;
+use std::path::{Path, PathBuf};
 
-pub fn clone(config: &Config, repo_url: &str, verbosity: Verbosity) -> Result<(), Error> {
-    let parsed = RepoInfo::parse_url(repo_url);
-    let Some(repo_info) = parsed else {
+/// Options of clone.
+/// Values come from the `[clone]` section of config and are overridden by command line flags.
+#[derive(Debug, Default, Clone)]
+pub struct CloneOptions {
+    /// Clone submodules too
+    pub recurse_submodules: bool,
+    /// Clone submodules also as repositories of mure
+    pub register_submodules: bool,
+    /// Name or path of an existing repository to share objects with (git alternates)
+    pub reference: Option<String>,
+    /// Share objects with a clone of the same name under another owner (e.g. the upstream of a fork)
+    pub auto_reference: bool,
+    /// Subdirectory of base_dir to create the symlink in, e.g. `clients/acme`
+    pub into: Option<PathBuf>,
+    /// Keep a bare mirror and share its objects with the working clone
+    pub mirror: bool,
+    /// Refresh the repository if it is already cloned
+    pub update: bool,
+}
+
+impl CloneOptions {
+    pub fn from_config(config: &Config) -> Self {
+        let clone = config.clone.as_ref();
+        CloneOptions {
+            recurse_submodules: clone.and_then(|c| c.recurse_submodules).unwrap_or(false),
+            register_submodules: clone.and_then(|c| c.register_submodules).unwrap_or(false),
+            reference: None,
+            auto_reference: clone.and_then(|c| c.auto_reference).unwrap_or(false),
+            into: None,
+            mirror: clone.and_then(|c| c.mirror).unwrap_or(false),
+            update: false,
+        }
+    }
+}
+
+pub fn clone(
+    config: &Config,
+    repo_url: &str,
+    options: &CloneOptions,
+    verbosity: Verbosity,
+) -> Result<(), Error> {
+    let repo_info = parse_repo_url(repo_url)?;
+    let cloned = clone_as(config, repo_url, &repo_info, options, verbosity)?;
+    if DryRun::is_enabled() {
+        return Ok(());
+    }
+    if !cloned && options.update {
+        update(config, &repo_info)?;
+    }
+    tag::assign_groups(config, &repo_info);
+    // submodules are unknown until the repository is cloned
+    if options.register_submodules {
+        register_submodules(config, &repo_info, options, verbosity)?;
+    }
+    Ok(())
+}
+
+/// The owner of a gist cloned by SSH (git@gist.github.com:<id>.git) is looked up by the API
+fn parse_repo_url(repo_url: &str) -> Result<RepoInfo, Error> {
+    if let Some(repo_info) = RepoInfo::parse_url(repo_url) {
+        return Ok(repo_info);
+    }
+    let Some(id) = RepoInfo::parse_gist_ssh_url(repo_url) else {
         return Err(Error::from_str("invalid repo url"));
     };
-    let tobe_clone = config.repo_store_path(&repo_info.domain, &repo_info.owner, &repo_info.repo);
+    let owner = get_gist_owner(&id, &get_github_token()?)?;
+    Ok(RepoInfo::gist(&owner, &id))
+}
+
+/// Refresh the repository already cloned
+fn update(config: &Config, repo_info: &RepoInfo) -> Result<(), Error> {
+    let path = config.repo_store_path(&repo_info.domain, &repo_info.owner, &repo_info.repo);
+    refresh_main(
+        config,
+        false,
+        Some(path.display().to_string()),
+        &RefreshOptions::from_config(config),
+        OutputFormat::Text,
+        &RepoFilter::default(),
+        &BulkOptions::new(1, false),
+    )?;
+    Ok(())
+}
+
+/// Result of cloning repositories listed in a file
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BatchCloneSummary {
+    pub cloned: Vec<String>,
+    /// Entries already cloned
+    pub skipped: Vec<String>,
+    /// Entries and the reason of failure
+    pub failed: Vec<(String, String)>,
+    /// Entries not started because of `--fail-fast`
+    pub cancelled: usize,
+}
+
+impl BatchCloneSummary {
+    fn describe(&self) -> Vec<String> {
+        let mut lines = vec![Message::BatchCloneSummary {
+            cloned: self.cloned.len(),
+            skipped: self.skipped.len(),
+            failed: self.failed.len(),
+        }
+        .to_string()];
+        for (entry, reason) in &self.failed {
+            lines.push(Message::FailedToClone { url: entry, reason }.to_string());
+        }
+        if self.cancelled > 0 {
+            lines.push(
+                Message::CancelledByFailFast {
+                    count: self.cancelled,
+                }
+                .to_string(),
+            );
+        }
+        lines
+    }
+}
+
+/// Clone repositories listed in the file (one URL or `owner/repo` per line) with `jobs` clones at once.
+/// Entries already cloned are skipped and failures don't stop the others unless `fail_fast`.
+pub fn clone_from_file(
+    config: &Config,
+    path: &Path,
+    options: &CloneOptions,
+    bulk: &BulkOptions,
+) -> Result<BulkSummary, Error> {
+    let content = std_fs::read_to_string(path)?;
+    clone_all(config, &parse_entries(&content), options, bulk)
+}
+
+/// Clone the repositories of the urls which are not cloned yet with `jobs` clones at once
+pub fn clone_all(
+    config: &Config,
+    entries: &[String],
+    options: &CloneOptions,
+    bulk: &BulkOptions,
+) -> Result<BulkSummary, Error> {
+    let pacer = Pacer::from_config(config)?;
+    let summary = clone_entries(config, entries, options, bulk, pacer.as_ref());
+    if !DryRun::is_enabled() {
+        // after all clones not to save the state concurrently
+        for repo_info in summary
+            .cloned
+            .iter()
+            .filter_map(|url| RepoInfo::parse_url(url))
+        {
+            tag::assign_groups(config, &repo_info);
+        }
+    }
+    for line in summary.describe() {
+        println!("{line}");
+    }
+    Ok(BulkSummary {
+        succeeded: summary.cloned.len() + summary.skipped.len(),
+        failed: summary.failed.len(),
+        cancelled: summary.cancelled,
+    })
+}
+
+/// Entries of the file. Blank lines and lines starting with `#` are ignored.
+fn parse_entries(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            // owner/repo is a shorthand of a repository on github.com
+            if line.contains(':') || line.split('/').count() != 2 {
+                line.to_string()
+            } else {
+                format!("https://github.com/{line}")
+            }
+        })
+        .collect()
+}
+
+fn clone_entries(
+    config: &Config,
+    entries: &[String],
+    options: &CloneOptions,
+    bulk: &BulkOptions,
+    pacer: Option<&Pacer>,
+) -> BatchCloneSummary {
+    let dry_run = DryRun::is_enabled();
+    let mut summary = BatchCloneSummary::default();
+    let result = bulk::run(
+        entries,
+        &bulk.with_progress(),
+        |entry| {
+            let result = clone_missing(config, entry, options);
+            if let (Ok(true), Some(pacer)) = (&result, pacer) {
+                pacer.pace(cloned_size(config, entry));
+            }
+            result
+        },
+        |index, result| {
+            let entry = &entries[index];
+            match result {
+                Ok(true) => {
+                    if !dry_run {
+                        println!("{}", Message::Cloned { url: entry });
+                    }
+                    summary.cloned.push(entry.clone());
+                }
+                Ok(false) => summary.skipped.push(entry.clone()),
+                Err(e) => summary.failed.push((entry.clone(), e.message())),
+            }
+        },
+    );
+    summary.cancelled = result.cancelled;
+    // keep the order of the file regardless of which clone finished first
+    let position = |entry: &String| entries.iter().position(|e| e == entry);
+    summary.cloned.sort_by_key(position);
+    summary.skipped.sort_by_key(position);
+    summary.failed.sort_by_key(|(entry, _)| position(entry));
+    summary
+}
+
+/// Bytes of objects of the repository cloned from the url
+fn cloned_size(config: &Config, repo_url: &str) -> u64 {
+    match RepoInfo::parse_url(repo_url) {
+        Some(info) => objects_size(&config.repo_store_path(&info.domain, &info.owner, &info.repo)),
+        None => 0,
+    }
+}
+
+/// Clone the repository unless it is already in the store (only linked then). Returns whether it was cloned.
+fn clone_missing(config: &Config, repo_url: &str, options: &CloneOptions) -> Result<bool, Error> {
+    let repo_info = parse_repo_url(repo_url)?;
+    // output of git is suppressed not to interleave
+    if !clone_as(config, repo_url, &repo_info, options, Verbosity::Quiet)? {
+        return Ok(false);
+    }
+    if options.register_submodules && !DryRun::is_enabled() {
+        register_submodules(config, &repo_info, options, Verbosity::Quiet)?;
+    }
+    Ok(true)
+}
+
+/// Clone submodules of the cloned repository as repositories of mure.
+/// Submodules already cloned or not on GitHub (e.g. relative urls) are skipped.
+fn register_submodules(
+    config: &Config,
+    repo_info: &RepoInfo,
+    options: &CloneOptions,
+    verbosity: Verbosity,
+) -> Result<(), Error> {
+    let path = config.repo_store_path(&repo_info.domain, &repo_info.owner, &repo_info.repo);
+    let repo = git2::Repository::open(path)?;
+    let options = CloneOptions {
+        register_submodules: false,
+        reference: None,
+        ..options.clone()
+    };
+    for url in repo.submodule_urls()? {
+        let Some(submodule) = RepoInfo::parse_url(&url) else {
+            println!("{}", Message::SkippedSubmodule { url: &url });
+            continue;
+        };
+        let store_path =
+            config.repo_store_path(&submodule.domain, &submodule.owner, &submodule.repo);
+        if matches!(store_state(&store_path), Ok(StoreState::Complete)) {
+            continue;
+        }
+        if let Err(e) = clone_as(config, &url, &submodule, &options, verbosity) {
+            println!(
+                "{}",
+                Message::FailedToCloneSubmodule {
+                    url: &url,
+                    reason: &e.message()
+                }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Clone the wiki companion (`<repo>.wiki.git`) of the repository.
+/// It is stored next to the repository and linked as `<repo>.wiki`.
+pub fn clone_wiki(config: &Config, repo_url: &str, verbosity: Verbosity) -> Result<(), Error> {
+    let Some(repo_info) = RepoInfo::parse_url(repo_url) else {
+        return Err(Error::from_str("invalid repo url"));
+    };
+    let wiki_url = RepoInfo::wiki_url(repo_url);
+    clone_as(
+        config,
+        &wiki_url,
+        &repo_info.wiki(),
+        &CloneOptions::default(),
+        verbosity,
+    )?;
+    Ok(())
+}
+
+/// What clone does: clone the url into the store and link it from base_dir
+#[derive(Debug, PartialEq, Eq)]
+struct ClonePlan {
+    url: String,
+    store_path: PathBuf,
+    link_path: PathBuf,
+    recurse_submodules: bool,
+    /// objects are shared with this repository
+    reference: Option<PathBuf>,
+    /// the reference was found automatically, so the clone doesn't fail if it can't be used
+    reference_if_able: bool,
+    /// bare mirror to be cloned first (`[clone] mirror`). It is also the reference.
+    mirror: Option<PathBuf>,
+}
+
+impl ClonePlan {
+    fn new(
+        config: &Config,
+        repo_url: &str,
+        repo_info: &RepoInfo,
+        options: &CloneOptions,
+    ) -> Result<ClonePlan, Error> {
+        let mirror = options
+            .mirror
+            .then(|| config.repo_mirror_path(&repo_info.domain, &repo_info.owner, &repo_info.repo));
+        let reference = match &options.reference {
+            _ if mirror.is_some() => mirror.clone(),
+            Some(reference) => Some(resolve_reference(config, reference)?),
+            None if options.auto_reference => find_same_name_clone(config, repo_info),
+            None => None,
+        };
+        let link_path = match &options.into {
+            Some(into) => {
+                // keep the symlink inside base_dir
+                if !into
+                    .components()
+                    .all(|c| matches!(c, std::path::Component::Normal(_)))
+                {
+                    return Err(Error::from_str(&format!(
+                        "{} is not a relative path in base_dir",
+                        into.display()
+                    )));
+                }
+                config.base_path().join(into).join(&repo_info.repo)
+            }
+            None => config.repo_work_path(&repo_info.domain, &repo_info.owner, &repo_info.repo),
+        };
+        Ok(ClonePlan {
+            url: resolve_clone_url(config, repo_url, repo_info),
+            store_path: config.repo_store_path(
+                &repo_info.domain,
+                &repo_info.owner,
+                &repo_info.repo,
+            ),
+            link_path,
+            recurse_submodules: options.recurse_submodules,
+            reference_if_able: reference.is_some()
+                && options.reference.is_none()
+                && mirror.is_none(),
+            reference,
+            mirror,
+        })
+    }
+
+    fn describe(&self) -> Vec<String> {
+        let mut lines = vec![];
+        if let Some(mirror) = self.mirror.as_ref().filter(|mirror| !mirror.exists()) {
+            lines.push(
+                Message::WouldMirror {
+                    url: &self.url,
+                    path: &mirror.display().to_string(),
+                }
+                .to_string(),
+            );
+        }
+        lines.push(
+            Message::WouldClone {
+                url: &self.url,
+                path: &self.store_path.display().to_string(),
+            }
+            .to_string(),
+        );
+        if let Some(reference) = &self.reference {
+            lines.push(
+                Message::WouldShareObjects {
+                    path: &reference.display().to_string(),
+                }
+                .to_string(),
+            );
+        }
+        lines.push(
+            Message::WouldCreateSymlink {
+                link: &self.link_path.display().to_string(),
+                target: &self.store_path.display().to_string(),
+            }
+            .to_string(),
+        );
+        lines
+    }
+
+    fn git_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if self.recurse_submodules {
+            args.push("--recurse-submodules".to_string());
+        }
+        if let Some(reference) = &self.reference {
+            let option = if self.reference_if_able {
+                "--reference-if-able"
+            } else {
+                "--reference"
+            };
+            args.push(option.to_string());
+            args.push(reference.display().to_string());
+        }
+        args
+    }
+}
+
+/// Repository in the store given by name (in base_dir) or path
+fn resolve_reference(config: &Config, reference: &str) -> Result<PathBuf, Error> {
+    let path = Path::new(reference);
+    let path = if path.exists() {
+        path.to_path_buf()
+    } else {
+        config.base_path().join(reference)
+    };
+    // a symlink in base_dir is resolved not to depend on it
+    match std_fs::canonicalize(&path) {
+        Ok(path) if git2::Repository::open(&path).is_ok() => Ok(path),
+        _ => Err(Error::from_str(&format!(
+            "{reference} is not a git repository"
+        ))),
+    }
+}
+
+/// Clone of the same repository name under another owner of the same domain.
+/// Forks usually keep the name of the upstream so they share most objects.
+/// Clones which don't borrow objects themselves (likely the upstream) are preferred.
+fn find_same_name_clone(config: &Config, repo_info: &RepoInfo) -> Option<PathBuf> {
+    let domain_path = config.repos_store_path().join(&repo_info.domain);
+    let mut candidates = domain_path
+        .read_dir()
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy() != repo_info.owner)
+        .map(|entry| entry.path().join(&repo_info.repo))
+        .filter_map(|path| {
+            let repo = git2::Repository::open(&path).ok()?;
+            let borrowing = !repo.alternates().ok()?.is_empty();
+            Some((borrowing, path))
+        })
+        .collect::<Vec<_>>();
+    candidates.sort();
+    candidates.into_iter().next().map(|(_, path)| path)
+}
+
+/// State of the repository in the store
+#[derive(Debug, PartialEq, Eq)]
+enum StoreState {
+    Missing,
+    /// Left by an interrupted clone: nothing is checked out yet
+    Partial,
+    Complete,
+}
 
+fn store_state(path: &Path) -> Result<StoreState, Error> {
+    if !path.exists() {
+        return Ok(StoreState::Missing);
+    }
+    // a clone checks out the files only after fetching all objects
+    let untouched = path
+        .read_dir()?
+        .flatten()
+        .all(|entry| entry.file_name() == ".git");
+    match git2::Repository::open(path) {
+        Ok(repo) if repo.head().is_ok() => Ok(StoreState::Complete),
+        // a clone of an empty repository has no commit and no object
+        Ok(repo) if !repo.is_bare() && is_unborn(&repo) && !has_objects(&repo) => {
+            Ok(StoreState::Complete)
+        }
+        // branches are never lost even if HEAD is unborn, e.g. after `git switch --orphan`
+        Ok(repo) if (repo.is_bare() || untouched) && !has_branches(&repo) => {
+            Ok(StoreState::Partial)
+        }
+        Ok(_) => Ok(StoreState::Complete),
+        Err(_) if untouched => Ok(StoreState::Partial),
+        Err(_) => Err(Error::from_str(&format!(
+            "{} exists but is not a git repository",
+            path.display()
+        ))),
+    }
+}
+
+fn is_unborn(repo: &git2::Repository) -> bool {
+    matches!(repo.head(), Err(e) if e.code() == git2::ErrorCode::UnbornBranch)
+}
+
+/// A clone creates refs only after receiving all objects
+fn has_branches(repo: &git2::Repository) -> bool {
+    ["refs/heads/*", "refs/remotes/*"].iter().any(|glob| {
+        repo.references_glob(glob)
+            .is_ok_and(|mut references| references.next().is_some())
+    })
+}
+
+/// An interrupted clone has received some objects (or a temporary pack) before HEAD is set
+fn has_objects(repo: &git2::Repository) -> bool {
+    let Ok(entries) = repo.path().join("objects").read_dir() else {
+        return false;
+    };
+    entries
+        .flatten()
+        .any(|entry| match entry.file_name().to_str() {
+            Some("info") => false,
+            Some("pack") => entry
+                .path()
+                .read_dir()
+                .is_ok_and(|mut packs| packs.next().is_some()),
+            _ => true,
+        })
+}
+
+/// Remove what an interrupted clone left so that it can be cloned again
+fn remove_partial_clone(path: &Path) -> Result<(), Error> {
+    std_fs::remove_dir_all(path)?;
+    println!(
+        "{}",
+        Message::RemovedPartialClone {
+            path: &path.display().to_string()
+        }
+    );
+    Ok(())
+}
+
+/// The repository in the store must be a clone of the url, not another repository moved there
+fn verify_origin(path: &Path, repo_info: &RepoInfo) -> Result<(), Error> {
+    let repo = git2::Repository::open(path)?;
+    // a repository created by `mure new` has no origin until it is published
+    let Some(url) = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|remote| remote.url().map(str::to_string))
+    else {
+        return Ok(());
+    };
+    // names on GitHub are case insensitive
+    let matches = RepoInfo::parse_url(&url).is_some_and(|origin| {
+        origin
+            .fully_qualified_name()
+            .eq_ignore_ascii_case(&repo_info.fully_qualified_name())
+    }) || RepoInfo::parse_gist_ssh_url(&url)
+        .is_some_and(|id| repo_info.is_gist() && id == repo_info.repo);
+    if !matches {
+        return Err(Error::from_str(&format!(
+            "{} already exists but its origin is {url}",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Link `link` to `target` unless it is already linked
+fn ensure_symlink(target: &Path, link: &Path) -> Result<(), Error> {
+    if std_fs::symlink_metadata(link).is_ok() {
+        let linked = std_fs::read_link(link).is_ok()
+            && std_fs::canonicalize(link).ok() == std_fs::canonicalize(target).ok();
+        if linked {
+            return Ok(());
+        }
+        return Err(Error::from_str(&format!(
+            "{} already exists and is not a link to {}",
+            link.display(),
+            target.display()
+        )));
+    }
+    if DryRun::is_enabled() {
+        println!(
+            "{}",
+            Message::WouldCreateSymlink {
+                link: &link.display().to_string(),
+                target: &target.display().to_string(),
+            }
+        );
+        return Ok(());
+    }
+    if let Some(link_dir) = link.parent() {
+        std_fs::create_dir_all(link_dir)?;
+    }
+    match unix_fs::symlink(target, link) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(Error::from_str("failed to create symlink")),
+    }
+}
+
+/// Clone the repository into the store and link it. Returns whether it was cloned.
+/// A repository already in the store is only linked, so cloning again is harmless.
+fn clone_as(
+    config: &Config,
+    repo_url: &str,
+    repo_info: &RepoInfo,
+    options: &CloneOptions,
+    verbosity: Verbosity,
+) -> Result<bool, Error> {
+    let plan = ClonePlan::new(config, repo_url, repo_info, options)?;
+    let state = store_state(&plan.store_path)?;
+    if state == StoreState::Complete {
+        verify_origin(&plan.store_path, repo_info)?;
+        ensure_symlink(&plan.store_path, &plan.link_path)?;
+        if verbosity > Verbosity::Quiet {
+            let path = plan.store_path.display().to_string();
+            println!("{}", Message::AlreadyCloned { path: &path });
+        }
+        return Ok(false);
+    }
+    let args = plan.git_args();
+    if DryRun::is_enabled() {
+        if state == StoreState::Partial {
+            let path = plan.store_path.display().to_string();
+            println!("{}", Message::WouldRemove { path: &path });
+        }
+        for line in plan.describe() {
+            println!("{line}");
+        }
+        return Ok(true);
+    }
+    let ClonePlan {
+        url: repo_url,
+        store_path: tobe_clone,
+        link_path: link_to,
+        mirror,
+        ..
+    } = plan;
+
+    if state == StoreState::Partial {
+        remove_partial_clone(&tobe_clone)?;
+    }
     // create dir if not exist (mkdir -p)
     std_fs::create_dir_all(tobe_clone.as_os_str())?;
 
@@ -20,61 +654,535 @@ pub fn clone(config: &Config, repo_url: &str, verbosity: Verbosity) -> Result<()
         return Err(Error::from_str("invalid repo url (maybe root dir)"));
     };
 
-    let result = <git2::Repository as RepositorySupport>::clone(repo_url, parent)?;
+    let span = tracing::info_span!("clone", repo = repo_info.name_with_owner());
+    let _enter = span.enter();
+    if let Some(mirror) = &mirror {
+        if store_state(mirror)? == StoreState::Partial {
+            remove_partial_clone(mirror)?;
+        }
+    }
+    if let Some(mirror) = mirror.filter(|mirror| !mirror.exists()) {
+        if let Some(mirror_dir) = mirror.parent() {
+            std_fs::create_dir_all(mirror_dir)?;
+        }
+        tracing::info!(url = repo_url, "mirroring");
+        <git2::Repository as RepositorySupport>::clone_mirror(&repo_url, &mirror)?;
+    }
+    tracing::info!(url = repo_url, "cloning");
+    let result = if args.is_empty() {
+        <git2::Repository as RepositorySupport>::clone(&repo_url, parent)
+    } else {
+        let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+        git2::Repository::clone_with_args(&repo_url, parent, &args)
+    };
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            // git empties the directory on failure. Not to be mistaken for a clone later
+            let _ = std_fs::remove_dir(&tobe_clone);
+            return Err(e.into());
+        }
+    };
     match verbosity {
         Verbosity::Quiet => (),
         Verbosity::Normal => {
             println!("{}", result.raw.stderr);
         }
-        Verbosity::Verbose => {
+        Verbosity::Verbose | Verbosity::Debug => {
             println!("{}", result.raw.stderr);
             println!("{}", result.raw.stdout);
         }
     }
+    ensure_symlink(&tobe_clone, &link_to)?;
+    Ok(true)
+}
+
+fn resolve_clone_url(config: &Config, repo_url: &str, repo_info: &RepoInfo) -> String {
+    match config.clone.as_ref().and_then(|c| c.protocol) {
+        Some(CloneProtocol::Ssh) => repo_info.ssh_url(),
+        Some(CloneProtocol::Https) => repo_info.https_url(),
+        None if is_clonable_as_is(repo_url, repo_info) => repo_url.to_string(),
+        None if repo_url.starts_with("http") => repo_info.https_url(),
+        None => repo_info.ssh_url(),
+    }
+}
 
-    let link_to = config.repo_work_path(&repo_info.domain, &repo_info.owner, &repo_info.repo);
-    match unix_fs::symlink(tobe_clone, link_to) {
-        Ok(_) => Ok(()),
-        Err(_) => Err(Error::from_str("failed to create symlink")),
+/// Urls copied from the browser (e.g. `/tree/main`) can't be cloned as they are
+fn is_clonable_as_is(repo_url: &str, repo_info: &RepoInfo) -> bool {
+    if !repo_url.starts_with("http") {
+        return true;
     }
+    let base = format!(
+        "https://{}/{}",
+        repo_info.domain,
+        repo_info.name_with_owner()
+    );
+    matches!(repo_url.strip_prefix(&base), Some("" | "/" | ".git"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CloneConfig;
+    use crate::test_fixture::config_with_base_dir;
     use mktemp::Temp;
 
     #[test]
     fn test_clone() {
         let temp_dir = Temp::new_dir().expect("failed to create temp dir");
-        let config_file = format!(
-            r#"
-        [core]
-        base_dir = "{}"
-
-        [github]
-        username = "kitsuyui"
-
-        [shell]
-        cd_shims = "mucd"
-    "#,
-            temp_dir.as_os_str().to_str().unwrap()
-        );
-        let config: Config = toml::from_str(&config_file).unwrap();
+        let config = config_with_base_dir(&temp_dir);
 
         match clone(
             &config,
             "https://github.com/kitsuyui/mure",
+            &CloneOptions::default(),
             Verbosity::Normal,
         ) {
             Ok(_) => {}
             Err(_) => unreachable!(),
         }
-        let config: Config = toml::from_str(&config_file).unwrap();
 
-        let Err(error) = clone(&config, "", Verbosity::Normal) else {
+        let Err(error) = clone(&config, "", &CloneOptions::default(), Verbosity::Normal) else {
             unreachable!();
         };
         assert_eq!(error.to_string(), "invalid repo url");
+
+        let Err(error) = clone_wiki(&config, "", Verbosity::Normal) else {
+            unreachable!();
+        };
+        assert_eq!(error.to_string(), "invalid repo url");
+    }
+
+    #[test]
+    fn test_clone_dry_run() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let url = "https://github.com/kitsuyui/mure";
+        let repo_info = RepoInfo::parse_url(url).unwrap();
+        let plan = ClonePlan::new(&config, url, &repo_info, &CloneOptions::default()).unwrap();
+        let store_path = temp_dir.join("repo/github.com/kitsuyui/mure");
+        let link_path = temp_dir.join("mure");
+        assert_eq!(
+            plan.describe(),
+            vec![
+                format!("(dry-run) clone {url} into {}", store_path.display()),
+                format!(
+                    "(dry-run) create symlink {} -> {}",
+                    link_path.display(),
+                    store_path.display()
+                ),
+            ]
+        );
+
+        DryRun::set(true);
+        clone(&config, url, &CloneOptions::default(), Verbosity::Normal).unwrap();
+        DryRun::set(false);
+        assert!(!store_path.exists());
+        assert!(!link_path.exists());
+    }
+
+    /// A repository in the store as cloned
+    fn commit(repo: &git2::Repository) {
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "tester").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        repo.command(&["commit", "--allow-empty", "-m", "first"])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_store_state() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let path = temp_dir.join("mure");
+        assert_eq!(store_state(&path).unwrap(), StoreState::Missing);
+        std_fs::create_dir_all(&path).unwrap();
+        assert_eq!(store_state(&path).unwrap(), StoreState::Partial);
+        // clone of an empty repository
+        let repo = git2::Repository::init(&path).unwrap();
+        assert_eq!(store_state(&path).unwrap(), StoreState::Complete);
+        // interrupted while fetching
+        repo.blob(b"fetched").unwrap();
+        assert_eq!(store_state(&path).unwrap(), StoreState::Partial);
+        // files of the user are never removed
+        std_fs::write(path.join("notes.txt"), "draft").unwrap();
+        assert_eq!(store_state(&path).unwrap(), StoreState::Complete);
+        std_fs::remove_file(path.join("notes.txt")).unwrap();
+        commit(&repo);
+        assert_eq!(store_state(&path).unwrap(), StoreState::Complete);
+        let head = repo.head().unwrap().name().unwrap().to_string();
+        // `git switch --orphan` leaves HEAD unborn but the branches are kept
+        repo.set_head("refs/heads/orphan").unwrap();
+        assert_eq!(store_state(&path).unwrap(), StoreState::Complete);
+        let bare_path = temp_dir.join("mure.git");
+        let bare = git2::Repository::init_bare(&bare_path).unwrap();
+        assert_eq!(store_state(&bare_path).unwrap(), StoreState::Partial);
+        repo.command(&[
+            "push",
+            bare_path.to_str().unwrap(),
+            &format!("{head}:refs/heads/other"),
+        ])
+        .unwrap();
+        assert!(bare.head().is_err());
+        assert_eq!(store_state(&bare_path).unwrap(), StoreState::Complete);
+
+        let not_repo = temp_dir.join("not-repo");
+        std_fs::create_dir_all(&not_repo).unwrap();
+        std_fs::write(not_repo.join("file"), "").unwrap();
+        assert!(store_state(&not_repo).is_err());
+    }
+
+    #[test]
+    fn test_clone_already_cloned() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let store_path = config.repo_store_path("github.com", "kitsuyui", "mure");
+        commit(&git2::Repository::init(&store_path).unwrap());
+
+        // only the missing symlink is created, again and again
+        let url = "https://github.com/kitsuyui/mure";
+        let link_path = temp_dir.join("mure");
+        for _ in 0..2 {
+            clone(&config, url, &CloneOptions::default(), Verbosity::Normal).unwrap();
+            assert_eq!(std_fs::read_link(&link_path).unwrap(), store_path);
+        }
+        let repo_info = RepoInfo::parse_url(url).unwrap();
+        assert!(!clone_missing(&config, url, &CloneOptions::default()).unwrap());
+
+        // the origin is verified
+        let repo = git2::Repository::open(&store_path).unwrap();
+        repo.remote("origin", "git@github.com:Kitsuyui/mure.git")
+            .unwrap();
+        clone(&config, url, &CloneOptions::default(), Verbosity::Quiet).unwrap();
+        repo.remote_set_url("origin", "https://github.com/someone/other")
+            .unwrap();
+        let Err(error) = clone(&config, url, &CloneOptions::default(), Verbosity::Quiet) else {
+            unreachable!();
+        };
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{} already exists but its origin is https://github.com/someone/other",
+                store_path.display()
+            )
+        );
+        repo.remote_delete("origin").unwrap();
+
+        // another directory in the way is kept
+        std_fs::remove_file(&link_path).unwrap();
+        std_fs::create_dir(&link_path).unwrap();
+        let Err(error) = clone_as(
+            &config,
+            url,
+            &repo_info,
+            &CloneOptions::default(),
+            Verbosity::Quiet,
+        ) else {
+            unreachable!();
+        };
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{} already exists and is not a link to {}",
+                link_path.display(),
+                store_path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_clone_plan_into() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let url = "https://github.com/kitsuyui/mure";
+        let repo_info = RepoInfo::parse_url(url).unwrap();
+        let plan_into = |into: &str| {
+            let options = CloneOptions {
+                into: Some(PathBuf::from(into)),
+                ..Default::default()
+            };
+            ClonePlan::new(&config, url, &repo_info, &options)
+        };
+        let plan = plan_into("clients/acme").unwrap();
+        assert_eq!(plan.link_path, temp_dir.join("clients/acme/mure"));
+        assert_eq!(
+            plan.store_path,
+            temp_dir.join("repo/github.com/kitsuyui/mure")
+        );
+        for into in ["../outside", "/absolute", "clients/../.."] {
+            let Err(error) = plan_into(into) else {
+                unreachable!();
+            };
+            assert_eq!(
+                error.to_string(),
+                format!("{into} is not a relative path in base_dir")
+            );
+        }
+    }
+
+    #[test]
+    fn test_clone_plan_mirror() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let mut config = config_with_base_dir(&temp_dir);
+        config.clone = Some(CloneConfig {
+            mirror: Some(true),
+            ..Default::default()
+        });
+        let url = "https://github.com/kitsuyui/mure";
+        let repo_info = RepoInfo::parse_url(url).unwrap();
+        let options = CloneOptions::from_config(&config);
+        assert!(options.mirror);
+        let plan = ClonePlan::new(&config, url, &repo_info, &options).unwrap();
+        let mirror = temp_dir.join("mirror/github.com/kitsuyui/mure.git");
+        assert_eq!(plan.mirror, Some(mirror.clone()));
+        assert_eq!(plan.reference, Some(mirror.clone()));
+        assert_eq!(
+            plan.git_args(),
+            vec!["--reference".to_string(), mirror.display().to_string()]
+        );
+        assert_eq!(
+            plan.describe()[0],
+            format!("(dry-run) mirror {url} into {}", mirror.display())
+        );
+
+        // the mirror is cloned only once
+        std_fs::create_dir_all(&mirror).unwrap();
+        let plan = ClonePlan::new(&config, url, &repo_info, &options).unwrap();
+        assert!(plan.describe()[0].starts_with("(dry-run) clone"));
+    }
+
+    #[test]
+    fn test_clone_plan_with_reference() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let upstream = config.repo_store_path("github.com", "kitsuyui", "mure");
+        git2::Repository::init(&upstream).unwrap();
+        unix_fs::symlink(&upstream, config.base_path().join("mure")).unwrap();
+        let upstream = std_fs::canonicalize(upstream).unwrap();
+
+        let url = "https://github.com/someone/mure";
+        let repo_info = RepoInfo::parse_url(url).unwrap();
+        let plan_with = |options: CloneOptions| ClonePlan::new(&config, url, &repo_info, &options);
+
+        // by name or path
+        for reference in ["mure", upstream.to_str().unwrap()] {
+            let plan = plan_with(CloneOptions {
+                reference: Some(reference.to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(plan.reference, Some(upstream.clone()));
+            assert_eq!(
+                plan.git_args(),
+                vec!["--reference".to_string(), upstream.display().to_string()]
+            );
+            assert_eq!(
+                plan.describe()[1],
+                format!("(dry-run) share objects with {}", upstream.display())
+            );
+        }
+        let Err(error) = plan_with(CloneOptions {
+            reference: Some("unknown".to_string()),
+            ..Default::default()
+        }) else {
+            unreachable!();
+        };
+        assert_eq!(error.to_string(), "unknown is not a git repository");
+
+        // automatically
+        let plan = plan_with(CloneOptions {
+            auto_reference: true,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            plan.git_args(),
+            vec![
+                "--reference-if-able".to_string(),
+                plan.reference.as_ref().unwrap().display().to_string()
+            ]
+        );
+        assert_eq!(
+            plan.reference
+                .map(|path| std_fs::canonicalize(path).unwrap()),
+            Some(upstream.clone())
+        );
+
+        // the upstream rather than another fork borrowing from it
+        let fork = config.repo_store_path("github.com", "another", "mure");
+        git2::Repository::init(&fork).unwrap();
+        std_fs::create_dir_all(fork.join(".git/objects/info")).unwrap();
+        std_fs::write(
+            fork.join(".git/objects/info/alternates"),
+            format!("{}\n", upstream.join(".git/objects").display()),
+        )
+        .unwrap();
+        let found = find_same_name_clone(&config, &repo_info).unwrap();
+        assert_eq!(std_fs::canonicalize(found).unwrap(), upstream);
+        std_fs::remove_dir_all(&fork).unwrap();
+
+        let repo_info = RepoInfo::parse_url("https://github.com/kitsuyui/mure").unwrap();
+        assert_eq!(find_same_name_clone(&config, &repo_info), None);
+        assert!(plan_with(CloneOptions::default())
+            .unwrap()
+            .git_args()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_entries() {
+        let content = "# team repos\n\nkitsuyui/mure\n  https://github.com/kitsuyui/dotfiles  \ngit@github.com:kitsuyui/mure.git\n";
+        assert_eq!(
+            parse_entries(content),
+            vec![
+                "https://github.com/kitsuyui/mure",
+                "https://github.com/kitsuyui/dotfiles",
+                "git@github.com:kitsuyui/mure.git",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clone_entries() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        commit(
+            &git2::Repository::init(temp_dir.join("repo/github.com/kitsuyui/dotfiles")).unwrap(),
+        );
+        let entries =
+            parse_entries("kitsuyui/mure\nkitsuyui/dotfiles\ninvalid\nkitsuyui/mure-ui\n");
+
+        DryRun::set(true);
+        let summary = clone_entries(
+            &config,
+            &entries,
+            &CloneOptions::default(),
+            &BulkOptions::new(2, false),
+            None,
+        );
+        DryRun::set(false);
+        assert_eq!(
+            summary,
+            BatchCloneSummary {
+                cloned: vec![
+                    "https://github.com/kitsuyui/mure".to_string(),
+                    "https://github.com/kitsuyui/mure-ui".to_string(),
+                ],
+                skipped: vec!["https://github.com/kitsuyui/dotfiles".to_string()],
+                failed: vec![("invalid".to_string(), "invalid repo url".to_string())],
+                cancelled: 0,
+            }
+        );
+        assert!(!temp_dir.join("repo/github.com/kitsuyui/mure").exists());
+        assert_eq!(
+            summary.describe(),
+            vec![
+                "2 cloned, 1 skipped (already cloned), 1 failed",
+                "Failed to clone invalid: invalid repo url",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clone_options_from_config() {
+        let config: Config = toml::from_str(
+            r#"
+        [core]
+        base_dir = "~/.dev"
+
+        [github]
+        username = "kitsuyui"
+
+        [shell]
+        cd_shims = "mucd"
+    "#,
+        )
+        .unwrap();
+        let options = CloneOptions::from_config(&config);
+        assert!(!options.recurse_submodules);
+        assert!(!options.register_submodules);
+
+        let config: Config = toml::from_str(
+            r#"
+        [core]
+        base_dir = "~/.dev"
+
+        [github]
+        username = "kitsuyui"
+
+        [shell]
+        cd_shims = "mucd"
+
+        [clone]
+        recurse_submodules = true
+    "#,
+        )
+        .unwrap();
+        let options = CloneOptions::from_config(&config);
+        assert!(options.recurse_submodules);
+        assert!(!options.register_submodules);
+    }
+
+    #[test]
+    fn test_resolve_clone_url() {
+        let repo_info = RepoInfo::parse_url("https://github.com/kitsuyui/mure").unwrap();
+        let config_with = |protocol: &str| -> Config {
+            toml::from_str(&format!(
+                r#"
+            [core]
+            base_dir = "~/.dev"
+
+            [github]
+            username = "kitsuyui"
+
+            {protocol}
+        "#
+            ))
+            .unwrap()
+        };
+
+        let config = config_with("");
+        assert_eq!(
+            resolve_clone_url(&config, "https://github.com/kitsuyui/mure", &repo_info),
+            "https://github.com/kitsuyui/mure"
+        );
+
+        let config = config_with("[clone]\nprotocol = \"ssh\"");
+        assert_eq!(
+            resolve_clone_url(&config, "https://github.com/kitsuyui/mure", &repo_info),
+            "git@github.com:kitsuyui/mure.git"
+        );
+
+        let config = config_with("[clone]\nprotocol = \"https\"");
+        assert_eq!(
+            resolve_clone_url(&config, "git@github.com:kitsuyui/mure.git", &repo_info),
+            "https://github.com/kitsuyui/mure.git"
+        );
+
+        let config = config_with("");
+        let url = "https://github.com/kitsuyui/mure/tree/main/src";
+        assert_eq!(
+            resolve_clone_url(&config, url, &RepoInfo::parse_url(url).unwrap()),
+            "https://github.com/kitsuyui/mure.git"
+        );
+        for (url, tobe) in [
+            (
+                "https://gist.github.com/kitsuyui/0123abcd#file-mure-md",
+                "https://gist.github.com/kitsuyui/0123abcd.git",
+            ),
+            (
+                "git@gist.github.com:0123abcd.git",
+                "git@gist.github.com:0123abcd.git",
+            ),
+        ] {
+            let repo_info = RepoInfo::gist("kitsuyui", "0123abcd");
+            assert_eq!(resolve_clone_url(&config, url, &repo_info), tobe);
+        }
+
+        let config = config_with("[clone]\nprotocol = \"ssh\"");
+        let url = "https://gist.github.com/kitsuyui/0123abcd";
+        assert_eq!(
+            resolve_clone_url(&config, url, &RepoInfo::parse_url(url).unwrap()),
+            "git@gist.github.com:0123abcd.git"
+        );
     }
 }
@@ -0,0 +1,100 @@
+//! Shell completion generated from the clap definitions
+//!
+//! For zsh, positional arguments of repository names are completed by `mure completion --list-names`.
+
+use clap::Command;
+use clap_complete::{generate, Shell};
+
+use crate::mure_error::Error;
+
+/// Subcommands and their positional argument which takes a repository name
+const REPOSITORY_ARGUMENTS: [(&str, &str); 4] = [
+    ("refresh", "repository"),
+    ("remove", "name"),
+    ("path", "name"),
+    ("edit", "name"),
+];
+
+/// zsh function completing repository names
+const ZSH_REPOSITORIES_FUNCTION: &str = r#"(( $+functions[_mure_repositories] )) ||
+_mure_repositories() {
+    local repositories; repositories=(${(f)"$(mure completion --list-names 2>/dev/null)"})
+    _describe -t repositories 'repository' repositories "$@"
+}
+"#;
+
+/// Print the completion script for the shell
+pub fn completion(shell: Shell, command: &mut Command, name: &str) -> Result<(), Error> {
+    let mut script = vec![];
+    generate(shell, command, name, &mut script);
+    let script = String::from_utf8_lossy(&script);
+    match shell {
+        Shell::Zsh => print!("{}", with_repository_completion(&script)),
+        _ => print!("{script}"),
+    }
+    Ok(())
+}
+
+/// Complete repository arguments of the zsh script with `_mure_repositories` instead of `_default`
+fn with_repository_completion(script: &str) -> String {
+    let mut top_level = false;
+    let mut subcommand = None;
+    let mut lines = vec![];
+    for line in script.lines() {
+        if line.contains("curcontext=") {
+            top_level = line.contains(":mure-command-");
+        } else if line.starts_with('(') && line.ends_with(')') && !line.contains(' ') {
+            subcommand = top_level.then(|| line.trim_matches(['(', ')']));
+        } else if line == "if [ \"$funcstack[1]\" = \"_mure\" ]; then" {
+            lines.push(ZSH_REPOSITORIES_FUNCTION.to_string());
+        }
+        let is_repository = REPOSITORY_ARGUMENTS.iter().any(|(command, argument)| {
+            subcommand == Some(*command)
+                && line
+                    .trim_start_matches(['\'', ':'])
+                    .starts_with(&format!("{argument} -- "))
+        });
+        match line.strip_suffix(":_default' \\") {
+            Some(spec) if is_repository => lines.push(format!("{spec}:_mure_repositories' \\")),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_repository_arguments() {
+        // keep in sync with the clap definitions
+        let command = Cli::command();
+        for (subcommand, argument) in REPOSITORY_ARGUMENTS {
+            let subcommand = command.find_subcommand(subcommand).unwrap();
+            assert!(subcommand
+                .get_positionals()
+                .any(|arg| arg.get_id() == argument));
+        }
+    }
+
+    #[test]
+    fn test_with_repository_completion() {
+        let mut script = vec![];
+        generate(Shell::Zsh, &mut Cli::command(), "mure", &mut script);
+        let script = with_repository_completion(&String::from_utf8(script).unwrap());
+        assert_eq!(
+            script.matches(":_mure_repositories' \\").count(),
+            REPOSITORY_ARGUMENTS.len()
+        );
+        assert!(script.contains(
+            "'::repository -- repository to refresh. if not specified, current directory is used:_mure_repositories' \\"
+        ));
+        // e.g. archive and `tag remove` are left as they are
+        assert!(script.contains("':name -- repository name:_default' \\"));
+        let function = script.find("_mure_repositories() {").unwrap();
+        assert!(function < script.find("if [ \"$funcstack[1]\" = \"_mure\" ]").unwrap());
+    }
+}
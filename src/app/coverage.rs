@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use git2::Repository;
+
+use crate::codecov::{coverage_text, get_repository_coverage, RepoBranch};
+use crate::config::Config;
+use crate::git::RepositorySupport;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+use super::list::search_mure_repo;
+
+/// Show the latest coverage of the default branch of each managed repository
+pub fn coverage(config: &Config) -> Result<(), Error> {
+    let repos = search_mure_repo(config);
+    if repos.is_empty() {
+        println!("{}", Message::NoRepositoriesFound);
+        return Ok(());
+    }
+    // Codecov API is queried per owner
+    let mut owners: BTreeMap<String, Vec<RepoBranch>> = BTreeMap::new();
+    for repo in repos {
+        match repo {
+            Ok(mure_repo) => {
+                match local_repo_branch(&mure_repo.repo.repo, &mure_repo.absolute_path) {
+                    Ok(branch) => owners
+                        .entry(mure_repo.repo.owner.clone())
+                        .or_default()
+                        .push(branch),
+                    Err(e) => println!("{}: {}", mure_repo.repo.name_with_owner(), e),
+                }
+            }
+            Err(e) => {
+                println!("{}", e.message());
+            }
+        }
+    }
+
+    println!("Coverage\tBranch\tRepository");
+    for (owner, branches) in owners {
+        // e.g. without CODECOV_TOKEN. Repositories are still listed with N/A
+        let coverages = get_repository_coverage(&owner, &branches).unwrap_or_else(|e| {
+            eprintln!("{owner}: {e}");
+            vec![]
+        });
+        for branch in branches {
+            // Repositories not on Codecov are shown as N/A
+            let coverage = coverages
+                .iter()
+                .find(|c| c.name == branch.name)
+                .and_then(|c| c.coverage);
+            println!(
+                "{}\t{}\t{}/{}",
+                coverage_text(coverage),
+                branch.branch,
+                owner,
+                branch.name
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the default branch and its HEAD commit from the local repository.
+/// origin/HEAD is preferred and the current branch is used as a fallback.
+fn local_repo_branch(name: &str, path: &Path) -> Result<RepoBranch, Error> {
+    let repo = Repository::open(path)?;
+    let branch = match repo.remote_default_branch("origin")? {
+        Some(branch) => branch,
+        None => repo.get_current_branch()?,
+    };
+    let commit_id = match repo.refname_to_id(&format!("refs/remotes/origin/{branch}")) {
+        Ok(id) => id,
+        Err(_) => repo.refname_to_id("HEAD")?,
+    };
+    Ok(RepoBranch {
+        name: name.to_string(),
+        branch,
+        commit_id: commit_id.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::Fixture;
+
+    #[test]
+    fn test_local_repo_branch() {
+        let fixture = Fixture::create().unwrap();
+        let path = fixture.repo.workdir().unwrap();
+
+        // empty repository has no branch
+        assert!(local_repo_branch("test", path).is_err());
+
+        fixture.create_empty_commit("initial commit").unwrap();
+        fixture
+            .repo
+            .command(&["switch", "-c", "main"])
+            .expect("failed to switch to main branch");
+        let repo_branch = local_repo_branch("test", path).unwrap();
+        assert_eq!(repo_branch.name, "test");
+        assert_eq!(repo_branch.branch, "main");
+        assert_eq!(
+            repo_branch.commit_id,
+            fixture.repo.refname_to_id("HEAD").unwrap().to_string()
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Create a repository on GitHub and clone it as usual
+//!
+//! `--template` is either a template repository on GitHub (`owner/repo`) or a local directory.
+//! Files of a local template are rendered like `mure template apply` and pushed as the first commit.
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+use crate::app::clone::{clone, CloneOptions};
+use crate::app::template::{read_templates, render_file};
+use crate::config::{Config, ConfigSupport};
+use crate::gh::GhClient;
+use crate::git::{DryRun, RepositorySupport};
+use crate::github::repo::RepoInfo;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+use crate::verbosity::Verbosity;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Template {
+    /// template repository on GitHub, e.g. `myorg/service-template`
+    Remote(String),
+    /// directory of template files
+    Local(PathBuf),
+}
+
+impl Template {
+    /// Existing directories are local templates and the others are repositories on GitHub
+    fn parse(template: &str) -> Template {
+        let path = PathBuf::from(shellexpand::tilde(template).to_string());
+        if path.is_dir() {
+            Template::Local(path)
+        } else {
+            Template::Remote(template.to_string())
+        }
+    }
+}
+
+pub fn create(
+    config: &Config,
+    name: &str,
+    template: Option<&str>,
+    public: bool,
+    verbosity: Verbosity,
+) -> Result<(), Error> {
+    let url = format!("https://github.com/{name}");
+    let Some(repo_info) = RepoInfo::parse_url(&url).filter(|_| name.split('/').count() == 2) else {
+        return Err(Error::from_str(&format!(
+            "invalid repository name {name}. It must be owner/repo"
+        )));
+    };
+    let template = template.map(Template::parse);
+    let remote_template = match &template {
+        Some(Template::Remote(template)) => Some(template.as_str()),
+        _ => None,
+    };
+    let name = repo_info.name_with_owner();
+    if DryRun::is_enabled() {
+        println!("{}", Message::WouldCreateRepository { name: &name });
+    } else {
+        GhClient::new().create_repository(&name, remote_template, public)?;
+        println!("{}", Message::CreatedRepository { name: &name });
+    }
+    clone(config, &url, &CloneOptions::from_config(config), verbosity)?;
+
+    let Some(Template::Local(dir)) = template else {
+        return Ok(());
+    };
+    if DryRun::is_enabled() {
+        return Ok(());
+    }
+    let path = config.repo_store_path(&repo_info.domain, &repo_info.owner, &repo_info.repo);
+    let files = initialize(&path, &repo_info, &dir)?;
+    let repo = Repository::open(&path)?;
+    repo.push_upstream("origin", &repo.get_current_branch()?)?;
+    println!(
+        "{}",
+        Message::TemplateApplied {
+            name: &repo_info.repo,
+            files: &files.join(", "),
+            branch: &repo.get_current_branch()?,
+        }
+    );
+    Ok(())
+}
+
+/// Write rendered files of the local template into the new repository and commit them
+pub fn initialize(
+    path: &Path,
+    repo_info: &RepoInfo,
+    template_dir: &Path,
+) -> Result<Vec<String>, Error> {
+    let templates = read_templates(template_dir)?;
+    if templates.is_empty() {
+        return Err(Error::from_str(&format!(
+            "No template files found in {}",
+            template_dir.display()
+        )));
+    }
+    let mut files = vec![];
+    for (relative, content) in templates {
+        let file_path = path.join(&relative);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, render_file(&content, repo_info))?;
+        files.push(relative.to_string_lossy().to_string());
+    }
+    let repo = Repository::open(path)?;
+    let paths = files.iter().map(String::as_str).collect::<Vec<_>>();
+    repo.add_paths(&paths)?;
+    repo.commit_staged("Initialize from template")?;
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{config_with_base_dir, Fixture};
+    use mktemp::Temp;
+
+    #[test]
+    fn test_template_parse() {
+        let temp_dir = Temp::new_dir().unwrap();
+        assert_eq!(
+            Template::parse(temp_dir.to_str().unwrap()),
+            Template::Local(temp_dir.to_path_buf())
+        );
+        assert_eq!(
+            Template::parse("myorg/service-template"),
+            Template::Remote("myorg/service-template".to_string())
+        );
+    }
+
+    #[test]
+    fn test_initialize() {
+        let template_dir = Temp::new_dir().unwrap();
+        std::fs::create_dir_all(template_dir.join("src")).unwrap();
+        std::fs::write(template_dir.join("README.md"), "# {{repo}} by {{owner}}\n").unwrap();
+        std::fs::write(template_dir.join("src").join("main.rs"), "fn main() {}\n").unwrap();
+
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        let path = repo.workdir().unwrap();
+        let repo_info = RepoInfo::parse_url("https://github.com/myorg/newsvc").unwrap();
+        let files = initialize(path, &repo_info, &template_dir).unwrap();
+        assert_eq!(files, vec!["README.md", "src/main.rs"]);
+        assert_eq!(
+            std::fs::read_to_string(path.join("README.md")).unwrap(),
+            "# newsvc by myorg\n"
+        );
+        assert!(repo.is_clean().unwrap());
+        assert_eq!(
+            repo.head().unwrap().peel_to_commit().unwrap().message(),
+            Some("Initialize from template\n")
+        );
+
+        let empty = Temp::new_dir().unwrap();
+        assert!(initialize(path, &repo_info, &empty).is_err());
+    }
+
+    #[test]
+    fn test_create_invalid_name() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        for name in ["newsvc", "myorg/newsvc/extra"] {
+            assert_eq!(
+                create(&config, name, None, false, Verbosity::Quiet)
+                    .unwrap_err()
+                    .to_string(),
+                format!("invalid repository name {name}. It must be owner/repo")
+            );
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use git2::Repository;
+
+use crate::app::path::resolve;
+use crate::config::Config;
+use crate::git::{DryRun, RepositorySupport};
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+/// Stop borrowing objects from the repository given by `clone --reference`
+/// by copying them into the repository, so that the referenced one can be removed safely.
+pub fn dissociate(config: &Config, name: &str) -> Result<(), Error> {
+    let repo = Repository::open(resolve(config, name)?)?;
+    if repo.alternates()?.is_empty() {
+        println!("{}", Message::NotSharingObjects { name });
+        return Ok(());
+    }
+    repo.repack_all()?;
+    let alternates = repo.path().join("objects").join("info").join("alternates");
+    if DryRun::is_enabled() {
+        let path = alternates.display().to_string();
+        println!("{}", Message::WouldRemove { path: &path });
+        return Ok(());
+    }
+    std::fs::remove_file(alternates)?;
+    println!("{}", Message::Dissociated { name });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::remove::{referencing_repositories, remove};
+    use crate::config::ConfigSupport;
+    use crate::test_fixture::{config_with_base_dir, Fixture};
+    use assay::assay;
+    use mktemp::Temp;
+
+    #[assay]
+    fn test_dissociate() {
+        let state_dir = Temp::new_dir().unwrap();
+        std::env::set_var("MURE_STATE_PATH", state_dir.join("state.json"));
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let upstream = Fixture::create().unwrap();
+        upstream.create_empty_commit("initial commit").unwrap();
+        let upstream_path = upstream.into_store(&config, "mure", true);
+
+        let fork_parent = config.repos_store_path().join("github.com").join("someone");
+        std::fs::create_dir_all(&fork_parent).unwrap();
+        Repository::clone_with_args(
+            upstream_path.to_str().unwrap(),
+            &fork_parent,
+            &["--reference", upstream_path.to_str().unwrap()],
+        )
+        .unwrap();
+        let fork_path = fork_parent.join("mure");
+        std::os::unix::fs::symlink(&fork_path, config.base_path().join("mure-fork")).unwrap();
+
+        assert_eq!(
+            referencing_repositories(&config, &upstream_path).unwrap(),
+            vec![fork_path.clone()]
+        );
+        let Err(error) = remove(&config, "mure", true) else {
+            unreachable!();
+        };
+        assert_eq!(
+            error.to_string(),
+            "mure shares its objects with github.com/someone/mure. Run `mure dissociate` for them first"
+        );
+
+        DryRun::set(true);
+        dissociate(&config, "mure-fork").unwrap();
+        DryRun::set(false);
+        assert!(!Repository::open(&fork_path)
+            .unwrap()
+            .alternates()
+            .unwrap()
+            .is_empty());
+
+        dissociate(&config, "mure-fork").unwrap();
+        assert!(referencing_repositories(&config, &upstream_path)
+            .unwrap()
+            .is_empty());
+        remove(&config, "mure", false).unwrap();
+        let fork = Repository::open(&fork_path).unwrap();
+        assert!(fork.alternates().unwrap().is_empty());
+        assert!(fork.head().unwrap().peel_to_commit().is_ok());
+
+        // nothing to do
+        dissociate(&config, "mure-fork").unwrap();
+    }
+}
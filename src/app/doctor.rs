@@ -0,0 +1,350 @@
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::app::list::{search_archived_repo, search_mure_repo};
+use crate::config::{Config, ConfigSupport};
+use crate::gh::GhClient;
+use crate::git::{RepositorySupport, SignatureStatus};
+use crate::github::api::{get_viewer_login, GITHUB_GRAPHQL_URL};
+use crate::github::token::get_github_token;
+use crate::misc::permission::{is_permission_denied, ownership_hint};
+use crate::mure_error::Error;
+use crate::provider::Cli;
+
+const GITHUB_DOMAIN: &str = "github.com";
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Tiny public repository to check cloning works
+pub const TINY_REPO_URL: &str = "https://github.com/octocat/Hello-World.git";
+
+/// Check the environment step by step and report the result of each check.
+/// `network` adds connectivity checks for questions like "why does clone hang here?"
+pub fn doctor(config: &Config, network: bool) -> Result<(), Error> {
+    let mut failures = 0;
+    let mut check = |name: &str, f: &dyn Fn() -> Result<String, Error>| {
+        let started = Instant::now();
+        let result = f();
+        println!("{}", format_check(name, &result, started.elapsed()));
+        if result.is_err() {
+            failures += 1;
+        }
+    };
+
+    check("git", &|| command_version("git"));
+    check("gh", &check_gh);
+    check("base_dir", &|| {
+        let base_path = config.base_path();
+        if base_path.is_dir() {
+            Ok(base_path.display().to_string())
+        } else {
+            Err(Error::from_str(&format!(
+                "{} is not a directory",
+                base_path.display()
+            )))
+        }
+    });
+    check("GH_TOKEN", &|| {
+        get_github_token().map(|_| "set".to_string())
+    });
+    check("alternates", &|| check_alternates(config));
+    check("permissions", &|| check_permissions(config));
+    check("signatures", &|| check_signatures(config));
+
+    if network {
+        check(&format!("resolve {GITHUB_DOMAIN}"), &|| {
+            resolve(GITHUB_DOMAIN, 443).map(|addr| addr.ip().to_string())
+        });
+        check(&format!("connect {GITHUB_DOMAIN}:443 (https)"), &|| {
+            connect(GITHUB_DOMAIN, 443)
+        });
+        check(&format!("connect {GITHUB_DOMAIN}:22 (ssh)"), &|| {
+            connect(GITHUB_DOMAIN, 22)
+        });
+        check("GitHub API with GH_TOKEN", &|| {
+            let token = get_github_token()?;
+            let login = get_viewer_login(GITHUB_GRAPHQL_URL, &token, TIMEOUT)?;
+            Ok(format!("authenticated as {login}"))
+        });
+        check(&format!("clone {TINY_REPO_URL}"), &|| clone_tiny_repo());
+    }
+
+    if failures > 0 {
+        return Err(Error::from_str(&format!("{failures} checks failed")));
+    }
+    Ok(())
+}
+
+/// One line of the report. e.g. `[ OK ] git: git version 2.43.0 (3ms)`
+/// Only the last line of the error is shown because git prints progress before the cause.
+pub fn format_check(name: &str, result: &Result<String, Error>, elapsed: Duration) -> String {
+    let elapsed = elapsed.as_millis();
+    match result {
+        Ok(detail) => format!("[ OK ] {name}: {detail} ({elapsed}ms)"),
+        Err(e) => {
+            let message = e.message();
+            let cause = message.trim().lines().last().unwrap_or_default();
+            format!("[FAIL] {name}: {cause} ({elapsed}ms)")
+        }
+    }
+}
+
+fn command_version(program: &str) -> Result<String, Error> {
+    let output = Command::new(program).arg("--version").output()?;
+    if !output.status.success() {
+        return Err(Error::from_str(&format!("{program} --version failed")));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or_default().to_string())
+}
+
+/// Version of gh with the accounts it is logged in with, if any
+fn check_gh() -> Result<String, Error> {
+    let status = Cli::gh().status()?;
+    let accounts = GhClient::new().auth_status().unwrap_or_default();
+    if accounts.is_empty() {
+        return Ok(status);
+    }
+    let accounts = accounts
+        .iter()
+        .map(|account| account.describe())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(format!("{status}: {accounts}"))
+}
+
+/// Repositories cloned with `--reference` are broken once the shared objects are removed
+fn check_alternates(config: &Config) -> Result<String, Error> {
+    let mut sharing = 0;
+    let mut broken = vec![];
+    for mure_repo in search_mure_repo(config)
+        .into_iter()
+        .chain(search_archived_repo(config))
+        .flatten()
+    {
+        let Ok(repo) = git2::Repository::open(&mure_repo.absolute_path) else {
+            continue;
+        };
+        let alternates = repo.alternates()?;
+        if !alternates.is_empty() {
+            sharing += 1;
+        }
+        for alternate in alternates.iter().filter(|path| !path.is_dir()) {
+            broken.push(format!(
+                "{} shares missing objects {}",
+                mure_repo.repo.repo,
+                alternate.display()
+            ));
+        }
+    }
+    if !broken.is_empty() {
+        // the report shows only one line
+        return Err(Error::from_str(&broken.join(", ")));
+    }
+    Ok(format!("{sharing} repositories share objects"))
+}
+
+/// Repositories owned by another user are skipped by bulk commands, so suggest fixing the ownership
+fn check_permissions(config: &Config) -> Result<String, Error> {
+    let mut checked = 0;
+    let mut denied = vec![];
+    for mure_repo in search_mure_repo(config)
+        .into_iter()
+        .chain(search_archived_repo(config))
+        .flatten()
+    {
+        checked += 1;
+        if is_permission_denied(&mure_repo.absolute_path) {
+            denied.push(format!(
+                "{}: {}",
+                mure_repo.repo.repo,
+                ownership_hint(&mure_repo.absolute_path)
+            ));
+        }
+    }
+    if !denied.is_empty() {
+        // the report shows only one line
+        return Err(Error::from_str(&denied.join(", ")));
+    }
+    Ok(format!("{checked} repositories are accessible"))
+}
+
+/// Signatures of the HEAD commits. Unsigned commits are only counted because signing is optional,
+/// but bad or revoked signatures fail the check.
+fn check_signatures(config: &Config) -> Result<String, Error> {
+    let mut signed = 0;
+    let mut checked = 0;
+    let mut invalid = vec![];
+    for mure_repo in search_mure_repo(config).into_iter().flatten() {
+        let Ok(repo) = git2::Repository::open(&mure_repo.absolute_path) else {
+            continue;
+        };
+        // repositories without commits have nothing to verify
+        let Ok(output) = repo.verify_head_signature() else {
+            continue;
+        };
+        checked += 1;
+        match output.interpreted_to {
+            status if status.is_signed() => signed += 1,
+            status @ (SignatureStatus::Bad | SignatureStatus::RevokedKey) => {
+                invalid.push(format!("{}: {status:?}", mure_repo.repo.repo))
+            }
+            _ => (),
+        }
+    }
+    if !invalid.is_empty() {
+        // the report shows only one line
+        return Err(Error::from_str(&format!(
+            "invalid signatures of HEAD: {}",
+            invalid.join(", ")
+        )));
+    }
+    Ok(format!("{signed} of {checked} HEAD commits are signed"))
+}
+
+fn resolve(host: &str, port: u16) -> Result<SocketAddr, Error> {
+    match (host, port).to_socket_addrs()?.next() {
+        Some(addr) => Ok(addr),
+        None => Err(Error::from_str(&format!("{host} is not resolved"))),
+    }
+}
+
+fn connect(host: &str, port: u16) -> Result<String, Error> {
+    let addr = resolve(host, port)?;
+    TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    Ok(format!("connected to {addr}"))
+}
+
+/// Shallow clone the tiny repository into a temporary directory and remove it.
+/// Slow transfer is aborted instead of hanging.
+fn clone_tiny_repo() -> Result<String, Error> {
+    let temp_dir = std::env::temp_dir().join(format!("mure-doctor-{}", std::process::id()));
+    std::fs::create_dir_all(&temp_dir)?;
+    let result = git2::Repository::clone_with_args(
+        TINY_REPO_URL,
+        &temp_dir,
+        &[
+            "--depth",
+            "1",
+            "-c",
+            "http.lowSpeedLimit=1000",
+            "-c",
+            "http.lowSpeedTime=10",
+        ],
+    );
+    std::fs::remove_dir_all(&temp_dir)?;
+    result?;
+    Ok("cloned".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{config_with_base_dir, Fixture};
+    use mktemp::Temp;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_format_check() {
+        assert_eq!(
+            format_check(
+                "git",
+                &Ok("git version 2.43.0".to_string()),
+                Duration::from_millis(3)
+            ),
+            "[ OK ] git: git version 2.43.0 (3ms)"
+        );
+        assert_eq!(
+            format_check(
+                "GH_TOKEN",
+                &Err(Error::from_str("GH_TOKEN is not set\n")),
+                Duration::from_millis(0)
+            ),
+            "[FAIL] GH_TOKEN: GH_TOKEN is not set (0ms)"
+        );
+        assert_eq!(
+            format_check(
+                "clone",
+                &Err(Error::from_str(
+                    "Cloning into 'Hello-World'...\nfatal: unable to access\n"
+                )),
+                Duration::from_millis(20)
+            ),
+            "[FAIL] clone: fatal: unable to access (20ms)"
+        );
+    }
+
+    #[test]
+    fn test_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_eq!(
+            connect("127.0.0.1", port).unwrap(),
+            format!("connected to 127.0.0.1:{port}")
+        );
+        drop(listener);
+        assert!(connect("127.0.0.1", port).is_err());
+    }
+
+    #[test]
+    fn test_check_alternates() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        assert_eq!(
+            check_alternates(&config).unwrap(),
+            "0 repositories share objects"
+        );
+
+        let upstream = Fixture::create().unwrap();
+        upstream.create_empty_commit("initial commit").unwrap();
+        let upstream_path = upstream.into_store(&config, "mure", false);
+        let fork_path = config.repo_store_path("github.com", "someone", "mure-fork");
+        std::fs::create_dir_all(fork_path.parent().unwrap()).unwrap();
+        git2::Repository::clone_with_args(
+            upstream_path.to_str().unwrap(),
+            fork_path.parent().unwrap(),
+            &["--reference", upstream_path.to_str().unwrap()],
+        )
+        .unwrap();
+        std::fs::rename(fork_path.parent().unwrap().join("mure"), &fork_path).unwrap();
+        std::os::unix::fs::symlink(&fork_path, config.base_path().join("mure-fork")).unwrap();
+        assert_eq!(
+            check_alternates(&config).unwrap(),
+            "1 repositories share objects"
+        );
+        assert_eq!(
+            check_permissions(&config).unwrap(),
+            "1 repositories are accessible"
+        );
+
+        std::fs::remove_dir_all(&upstream_path).unwrap();
+        assert!(check_alternates(&config)
+            .unwrap_err()
+            .to_string()
+            .starts_with("mure-fork shares missing objects"));
+    }
+
+    #[test]
+    fn test_check_signatures() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let fixture = Fixture::create().unwrap();
+        fixture
+            .repo
+            .config()
+            .unwrap()
+            .set_bool("commit.gpgsign", false)
+            .unwrap();
+        fixture.create_empty_commit("initial commit").unwrap();
+        fixture.into_store(&config, "mure", true);
+        assert_eq!(
+            check_signatures(&config).unwrap(),
+            "0 of 1 HEAD commits are signed"
+        );
+    }
+
+    #[test]
+    fn test_command_version() {
+        assert!(command_version("git").unwrap().starts_with("git version"));
+        assert!(command_version("mure-no-such-command").is_err());
+    }
+}
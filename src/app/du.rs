@@ -0,0 +1,129 @@
+//! Disk usage of each repository to decide what to archive or shallow-clone
+use std::path::Path;
+
+use serde_derive::Serialize;
+
+use crate::app::list::{search_mure_repo, RepoFilter};
+use crate::i18n::Message;
+use crate::misc::size::{disk_usage, format_size};
+use crate::{config::Config, mure_error::Error};
+
+/// A row of `mure du`. Sizes are in bytes.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DiskUsage {
+    pub name: String,
+    /// `.git` directory
+    pub git: u64,
+    /// files other than `.git`
+    pub worktree: u64,
+    pub total: u64,
+}
+
+/// Show the disk usage of the repositories in the store, the largest first
+pub fn du(config: &Config, filter: &RepoFilter, json: bool) -> Result<(), Error> {
+    let mut usages = vec![];
+    for repo in filter.apply(search_mure_repo(config))? {
+        match repo {
+            Ok(mure_repo) => {
+                // the symlink in base_dir points to the store
+                let path = std::fs::canonicalize(&mure_repo.absolute_path)?;
+                usages.push(disk_usage_of(&mure_repo.repo.repo, &path)?);
+            }
+            Err(e) => println!("{}", e.message()),
+        }
+    }
+    if usages.is_empty() {
+        println!("{}", Message::NoRepositoriesFound);
+        return Ok(());
+    }
+    usages.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.name.cmp(&b.name)));
+    if json {
+        println!("{}", serde_json::to_string_pretty(&usages)?);
+    } else {
+        print!("{}", format_table(&usages));
+    }
+    Ok(())
+}
+
+fn disk_usage_of(name: &str, path: &Path) -> Result<DiskUsage, Error> {
+    let total = disk_usage(path)?;
+    let git_path = path.join(".git");
+    let git = if git_path.exists() {
+        disk_usage(&git_path)?
+    } else {
+        0
+    };
+    Ok(DiskUsage {
+        name: name.to_string(),
+        git,
+        worktree: total - git,
+        total,
+    })
+}
+
+fn format_table(usages: &[DiskUsage]) -> String {
+    let mut table = "Name\tGit\tWorktree\tTotal\n".to_string();
+    for usage in usages {
+        table.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            usage.name,
+            format_size(usage.git),
+            format_size(usage.worktree),
+            format_size(usage.total),
+        ));
+    }
+    let sum = |size: fn(&DiskUsage) -> u64| format_size(usages.iter().map(size).sum());
+    table.push_str(&format!(
+        "Total\t{}\t{}\t{}\n",
+        sum(|usage| usage.git),
+        sum(|usage| usage.worktree),
+        sum(|usage| usage.total),
+    ));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::Fixture;
+
+    #[test]
+    fn test_disk_usage_of() {
+        let fixture = Fixture::create().unwrap();
+        fixture.create_file("data", &"x".repeat(4096)).unwrap();
+        let path = fixture.repo.workdir().unwrap();
+        let usage = disk_usage_of("mure", path).unwrap();
+        assert_eq!(usage.worktree, 4096);
+        assert!(usage.git > 0);
+        assert_eq!(usage.total, usage.git + usage.worktree);
+    }
+
+    #[test]
+    fn test_format_table() {
+        let usages = [
+            DiskUsage {
+                name: "mure".to_string(),
+                git: 2048,
+                worktree: 1024,
+                total: 3072,
+            },
+            DiskUsage {
+                name: "dotfiles".to_string(),
+                git: 1024,
+                worktree: 0,
+                total: 1024,
+            },
+        ];
+        assert_eq!(
+            format_table(&usages),
+            "Name\tGit\tWorktree\tTotal\n\
+             mure\t2.0 KiB\t1.0 KiB\t3.0 KiB\n\
+             dotfiles\t1.0 KiB\t0 B\t1.0 KiB\n\
+             Total\t3.0 KiB\t1.0 KiB\t4.0 KiB\n"
+        );
+        assert_eq!(
+            serde_json::to_string(&usages[1]).unwrap(),
+            r#"{"name":"dotfiles","git":1024,"worktree":0,"total":1024}"#
+        );
+    }
+}
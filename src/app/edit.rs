@@ -1,28 +1,71 @@
 /// Implementation of the edit subcommand
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 use git2::Repository;
 
 use crate::config::{Config, ConfigSupport};
 use crate::mure_error::Error;
 
-pub fn edit(config: &Config, repository: String) -> Result<(), Error> {
+/// Open the repository with the editor.
+/// With `wait`, GUI editors are told to wait until the window is closed.
+pub fn edit(config: &Config, repository: String, wait: bool) -> Result<(), Error> {
     let mure_root_dir = config.base_path();
-    let path = mure_root_dir.join(repository);
-    let editor = get_editor(config, &path)?;
+    let path = mure_root_dir.join(&repository);
+    let mut editor = get_editor(config, &repository, &path)?;
+    if wait {
+        editor = with_wait_flag(&editor);
+    }
     open_editor(&editor, &path)?;
     Ok(())
 }
 
-pub fn open_editor(editor: &str, path: &PathBuf) -> Result<(), Error> {
-    // editor is not only a command name but also can have arguments, so first separate the arguments
-    // maybe we can use shlex crate to parse the command and arguments
-    let mut editor_args = editor.split_whitespace();
-    if let Some(editor) = editor_args.next() {
-        let result = std::process::Command::new(editor)
-            .args(editor_args)
-            .arg(path)
-            .status()?;
+/// Start the editor of the repository without waiting for it, for callers which own stdio (e.g. rpc).
+/// The editor gets no stdin and stdout so that it can't read or corrupt the caller's stream.
+pub fn spawn_editor(config: &Config, repository: &str) -> Result<(), Error> {
+    let path = config.base_path().join(repository);
+    let editor = get_editor(config, repository, &path)?;
+    let Some(mut command) = editor_command(&editor, &path) else {
+        return Err(Error::from_str("No editor found"));
+    };
+    let mut child = command.stdin(Stdio::null()).stdout(Stdio::null()).spawn()?;
+    // reap the editor when it exits
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Flag to make the GUI editor block until the window is closed.
+/// Terminal editors such as vim block anyway, so they have none.
+fn wait_flag(program: &str) -> Option<&'static str> {
+    match program {
+        "code" | "code-insiders" | "codium" | "cursor" | "windsurf" | "subl" | "zed" | "atom"
+        | "idea" | "pycharm" | "goland" | "rustrover" | "webstorm" | "clion" => Some("--wait"),
+        "mate" => Some("-w"),
+        "gvim" | "mvim" => Some("-f"),
+        _ => None,
+    }
+}
+
+/// Append the wait flag of the editor if it is known and not given yet
+fn with_wait_flag(editor: &str) -> String {
+    let mut args = editor.split_whitespace();
+    let Some(program) = args.next() else {
+        return editor.to_string();
+    };
+    let name = Path::new(program)
+        .file_name()
+        .map_or(program.to_string(), |name| {
+            name.to_string_lossy().to_string()
+        });
+    match wait_flag(&name) {
+        Some(flag) if !args.any(|arg| arg == flag) => format!("{editor} {flag}"),
+        _ => editor.to_string(),
+    }
+}
+
+pub fn open_editor(editor: &str, path: &Path) -> Result<(), Error> {
+    if let Some(mut command) = editor_command(editor, path) {
+        let result = command.status()?;
         if !result.success() {
             return Err(Error::from_str("Failed to open editor"));
         }
@@ -30,12 +73,30 @@ pub fn open_editor(editor: &str, path: &PathBuf) -> Result<(), Error> {
     Ok(())
 }
 
+/// Command opening the path with the editor. None if the editor is empty.
+fn editor_command(editor: &str, path: &Path) -> Option<Command> {
+    // editor is not only a command name but also can have arguments, so first separate the arguments
+    // maybe we can use shlex crate to parse the command and arguments
+    let mut editor_args = editor.split_whitespace();
+    let mut command = Command::new(editor_args.next()?);
+    command.args(editor_args).arg(path);
+    Some(command)
+}
+
 /// Get the editor by priority
-/// 1. editor in the config file
-/// 2. git config core.editor
-/// 3. $EDITOR environment variable
-/// 4. error if none of the above is set
-fn get_editor(config: &Config, path: &PathBuf) -> Result<String, Error> {
+/// 1. editor of the repository in the config file (`[repo."<name>"] editor`)
+/// 2. editor in the config file
+/// 3. git config core.editor
+/// 4. $EDITOR environment variable
+/// 5. error if none of the above is set
+fn get_editor(config: &Config, repository: &str, path: &PathBuf) -> Result<String, Error> {
+    if let Some(editor) = config
+        .repo_config(repository)
+        .and_then(|repo| repo.editor.as_ref())
+    {
+        return Ok(editor.to_string());
+    }
+
     if let Ok(editor) = get_editor_from_config(config) {
         return Ok(editor);
     }
@@ -119,6 +180,48 @@ mod tests {
         assert_eq!(result.unwrap(), "super_editor");
     }
 
+    #[test]
+    fn test_get_editor_of_repository() {
+        let temp = mktemp::Temp::new_dir().unwrap();
+        let mut config = get_test_config();
+        config.repo = Some(
+            [(
+                "mure".to_string(),
+                crate::config::RepoConfig {
+                    editor: Some("idea".to_string()),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+        );
+        assert_eq!(get_editor(&config, "mure", &temp).unwrap(), "idea");
+        assert_eq!(get_editor(&config, "other", &temp).unwrap(), "great_editor");
+    }
+
+    #[test]
+    fn test_with_wait_flag() {
+        assert_eq!(with_wait_flag("code"), "code --wait");
+        assert_eq!(
+            with_wait_flag("/usr/local/bin/code -n"),
+            "/usr/local/bin/code -n --wait"
+        );
+        assert_eq!(with_wait_flag("code --wait"), "code --wait");
+        assert_eq!(with_wait_flag("mate"), "mate -w");
+        assert_eq!(with_wait_flag("vim"), "vim");
+        assert_eq!(with_wait_flag(""), "");
+    }
+
+    #[test]
+    fn test_spawn_editor() {
+        let temp = mktemp::Temp::new_dir().unwrap();
+        let mut config = get_test_config();
+        config.core.base_dir = temp.to_str().unwrap().to_string();
+        config.core.editor = Some("true".to_string());
+        assert!(spawn_editor(&config, "mure").is_ok());
+        config.core.editor = Some("mure-no-such-editor".to_string());
+        assert!(spawn_editor(&config, "mure").is_err());
+    }
+
     #[test]
     fn test_open_editor() {
         let temp = mktemp::Temp::new_dir().unwrap();
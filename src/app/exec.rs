@@ -0,0 +1,157 @@
+use std::process::{Command, ExitStatus};
+
+use crate::app::list::{search_mure_repo, MureRepo, RepoFilter};
+use crate::config::Config;
+use crate::i18n::Message;
+use crate::lang;
+use crate::misc::bulk::{self, BulkOptions, BulkSummary};
+use crate::misc::output::{use_color, OutputMode};
+use crate::mure_error::Error;
+
+/// Run the command in each repository. Output of the command is presented by `output`.
+/// `{name}` and `{lang}` in the command are replaced for each repository.
+/// A command exiting with non-zero status counts as a failure.
+pub fn exec(
+    config: &Config,
+    command: &[String],
+    filter: &RepoFilter,
+    bulk: &BulkOptions,
+    output: OutputMode,
+) -> Result<BulkSummary, Error> {
+    if command.is_empty() {
+        return Err(Error::from_str("command is not specified"));
+    }
+    let repos = filter.apply(search_mure_repo(config))?;
+    if repos.is_empty() {
+        println!("{}", Message::NoRepositoriesFound);
+        return Ok(BulkSummary::default());
+    }
+    let mut mure_repos = vec![];
+    for repo in repos {
+        match repo {
+            Ok(mure_repo) => mure_repos.push(mure_repo),
+            Err(e) => {
+                tracing::error!("{e}");
+                println!("{}", e.message());
+            }
+        }
+    }
+    let color = use_color();
+    // no progress because the commands may write to the terminal directly
+    let summary = bulk::run(
+        &mure_repos,
+        bulk,
+        |mure_repo| {
+            let command = expand(command, mure_repo);
+            let Some((program, args)) = command.split_first() else {
+                return Ok(vec![]);
+            };
+            let name = &mure_repo.repo.repo;
+            let span = tracing::info_span!("exec", repo = name);
+            let _enter = span.enter();
+            tracing::info!(?command, "executing");
+            let result = output.run(
+                Command::new(program)
+                    .args(args)
+                    .current_dir(&mure_repo.absolute_path),
+                name,
+                color,
+            );
+            let (status, captured) = match result {
+                Ok((status, captured)) => (Ok(status), captured),
+                Err(e) => (Err(e), vec![]),
+            };
+            match describe_status(program, status) {
+                Some(message) => Err((message, captured)),
+                None => Ok(captured),
+            }
+        },
+        |index, result| {
+            let name = &mure_repos[index].repo.repo;
+            match result {
+                Ok(captured) => output.show(name, &captured, None, color),
+                Err((message, captured)) => output.show(name, &captured, Some(&message), color),
+            }
+        },
+    );
+    if summary.cancelled > 0 {
+        println!(
+            "{}",
+            Message::CancelledByFailFast {
+                count: summary.cancelled
+            }
+        );
+    }
+    Ok(summary)
+}
+
+/// The message to show unless the command succeeded
+fn describe_status(program: &str, status: std::io::Result<ExitStatus>) -> Option<String> {
+    match status {
+        Ok(status) if status.success() => {
+            tracing::info!("succeeded");
+            None
+        }
+        Ok(status) => {
+            tracing::error!("{program} exited with {status}");
+            Some(
+                Message::ExitedWith {
+                    program,
+                    status: &status.to_string(),
+                }
+                .to_string(),
+            )
+        }
+        Err(e) => {
+            tracing::error!("failed to execute {program}: {e}");
+            Some(format!("failed to execute {program}: {e}"))
+        }
+    }
+}
+
+/// Replace placeholders of the repository in each argument
+fn expand(command: &[String], mure_repo: &MureRepo) -> Vec<String> {
+    let lang = lang::detect(&mure_repo.absolute_path);
+    command
+        .iter()
+        .map(|arg| {
+            arg.replace("{name}", &mure_repo.repo.repo)
+                .replace("{lang}", lang)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::config_with_base_dir;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_exec() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let filter = RepoFilter::default();
+        let bulk = BulkOptions::default();
+        let output = OutputMode::Grouped;
+        assert!(exec(&config, &[], &filter, &bulk, output).is_err());
+        exec(&config, &["true".to_string()], &filter, &bulk, output).unwrap();
+    }
+
+    #[test]
+    fn test_expand() {
+        let temp_dir = Temp::new_dir().unwrap();
+        std::fs::write(temp_dir.join("go.mod"), "module example.com/mure").unwrap();
+        let mure_repo = MureRepo {
+            relative_path: temp_dir.join("mure"),
+            absolute_path: temp_dir.to_path_buf(),
+            repo: crate::github::repo::RepoInfo::parse_url("https://github.com/kitsuyui/mure")
+                .unwrap(),
+        };
+        let command = ["echo", "{name} is written in {lang}"].map(String::from);
+        assert_eq!(
+            expand(&command, &mure_repo),
+            vec!["echo", "mure is written in go"]
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! External subcommands `mure-<name>` on PATH like git and cargo
+//!
+//! An unknown subcommand runs the executable `mure-<name>` with the arguments after it,
+//! so that mure can be extended without forking.
+use std::ffi::OsStr;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::app::alias::subcommand_index;
+use crate::app::refresh::get_git_repository_from_current_dir;
+use crate::config::{resolve_config_path, Config, ConfigSupport};
+use crate::mure_error::Error;
+
+/// The executable and its arguments if the subcommand is neither built-in nor missing on PATH
+pub fn find_command(builtins: &[String], args: &[String]) -> Option<(PathBuf, Vec<String>)> {
+    let index = subcommand_index(args)?;
+    let name = &args[index];
+    if builtins.contains(name) {
+        return None;
+    }
+    let program = find_in(name, &std::env::var_os("PATH")?)?;
+    Some((program, args[index + 1..].to_vec()))
+}
+
+/// `mure-<name>` in the directories of PATH
+fn find_in(name: &str, path: &OsStr) -> Option<PathBuf> {
+    let file_name = format!("mure-{name}");
+    std::env::split_paths(path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Run the external subcommand and return its exit code
+pub fn run(config: &Config, program: &Path, args: &[String]) -> Result<i32, Error> {
+    let mut command = Command::new(program);
+    command.args(args);
+    set_env(config, &mut command);
+    let status = command
+        .status()
+        .map_err(|e| Error::from_str(&format!("failed to run {}: {e}", program.display())))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// `MURE_BASE_DIR`, `MURE_CONFIG_PATH` and, inside a repository, `MURE_REPO_PATH`
+/// for commands run on behalf of mure
+pub fn set_env(config: &Config, command: &mut Command) {
+    command.env("MURE_BASE_DIR", config.base_path());
+    if let Ok(config_path) = resolve_config_path() {
+        command.env("MURE_CONFIG_PATH", config_path);
+    }
+    if let Ok(repo_path) = get_git_repository_from_current_dir(config) {
+        command.env("MURE_REPO_PATH", repo_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_find_in() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let program = temp_dir.join("mure-hello");
+        std::os::unix::fs::symlink("/bin/sh", &program).unwrap();
+        std::fs::write(temp_dir.join("mure-noexec"), "").unwrap();
+        let path = std::env::join_paths([Path::new("/nonexistent"), &temp_dir]).unwrap();
+
+        assert_eq!(find_in("hello", &path), Some(program.clone()));
+        assert_eq!(find_in("noexec", &path), None);
+        assert_eq!(find_in("missing", &path), None);
+
+        let args = ["-c".to_string(), "exit 3".to_string()];
+        assert_eq!(run(&Config::default(), &program, &args).unwrap(), 3);
+    }
+}
@@ -0,0 +1,119 @@
+//! `git gc` across repositories to keep the store from ballooning
+//!
+//! Repositories fetched by refresh every day pile up loose objects and small packs,
+//! which `git gc` packs together and prunes.
+use std::path::Path;
+
+use git2::Repository;
+
+use crate::app::list::{search_mure_repo, RepoFilter};
+use crate::config::Config;
+use crate::git::RepositorySupport;
+use crate::i18n::Message;
+use crate::misc::bulk::{self, BulkOptions, BulkSummary};
+use crate::misc::size::{disk_usage, format_size};
+use crate::mure_error::Error;
+
+/// Run `git gc` in each repository with `jobs` repositories at once
+/// and show the size of the git directories before and after.
+pub fn gc(
+    config: &Config,
+    filter: &RepoFilter,
+    aggressive: bool,
+    bulk: &BulkOptions,
+) -> Result<BulkSummary, Error> {
+    let mut repos = vec![];
+    for repo in filter.apply(search_mure_repo(config))? {
+        match repo {
+            Ok(mure_repo) => repos.push(mure_repo),
+            Err(e) => println!("{}", e.message()),
+        }
+    }
+    if repos.is_empty() {
+        println!("{}", Message::NoRepositoriesFound);
+        return Ok(BulkSummary::default());
+    }
+    let mut results = Vec::with_capacity(repos.len());
+    let summary = bulk::run(
+        &repos,
+        &bulk.with_progress(),
+        |mure_repo| gc_repository(&mure_repo.absolute_path, aggressive),
+        |index, result| results.push((index, result)),
+    );
+    results.sort_by_key(|(index, _)| *index);
+
+    let (mut total_before, mut total_after) = (0, 0);
+    for (index, result) in results {
+        let name = &repos[index].repo.repo;
+        match result {
+            Ok((before, after)) => {
+                total_before += before;
+                total_after += after;
+                println!("{name}\t{}", format_change(before, after));
+            }
+            Err(e) => println!("{name}\t{e}"),
+        }
+    }
+    println!("Total\t{}", format_change(total_before, total_after));
+    Ok(summary)
+}
+
+/// Size of the git directory before and after gc
+fn gc_repository(path: &Path, aggressive: bool) -> Result<(u64, u64), Error> {
+    let repo = Repository::open(path)?;
+    let git_dir = repo.path();
+    let before = disk_usage(git_dir)?;
+    repo.gc(aggressive)?;
+    Ok((before, disk_usage(git_dir)?))
+}
+
+/// e.g. `12.0 MiB -> 8.5 MiB`
+fn format_change(before: u64, after: u64) -> String {
+    format!("{} -> {}", format_size(before), format_size(after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::DryRun;
+    use crate::test_fixture::Fixture;
+
+    #[test]
+    fn test_gc_repository() {
+        let fixture = Fixture::create().unwrap();
+        for i in 0..10 {
+            fixture
+                .create_file(&format!("file{i}"), &"content ".repeat(1000 * i))
+                .unwrap();
+            fixture.repo.command(&["add", "."]).unwrap();
+            fixture.create_empty_commit(&format!("commit {i}")).unwrap();
+        }
+        let path = fixture.repo.workdir().unwrap();
+        // directories of loose objects like `objects/ab`
+        let loose = || {
+            std::fs::read_dir(fixture.repo.path().join("objects"))
+                .unwrap()
+                .flatten()
+                .filter(|entry| entry.file_name().len() == 2)
+                .count()
+        };
+        let loose_before = loose();
+        assert!(loose_before > 0);
+
+        DryRun::set(true);
+        let (before, after) = gc_repository(path, false).unwrap();
+        DryRun::set(false);
+        assert_eq!(before, after);
+        assert_eq!(loose(), loose_before);
+
+        // tiny repositories may even grow by the index of the pack
+        let (before, after) = gc_repository(path, true).unwrap();
+        assert!(before > 0 && after > 0);
+        assert_eq!(loose(), 0);
+    }
+
+    #[test]
+    fn test_format_change() {
+        assert_eq!(format_change(2048, 1024), "2.0 KiB -> 1.0 KiB");
+    }
+}
@@ -0,0 +1,195 @@
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+use crate::app::remove::{has_unsaved, orphaned_repositories, referencing_repositories};
+use crate::config::{Config, ConfigSupport};
+use crate::i18n::Message;
+use crate::misc::datetime::format_date;
+use crate::misc::size::{disk_usage, format_size};
+use crate::mure_error::Error;
+
+/// A repository in the store which no symlink points to
+#[derive(Debug)]
+struct StoreEntry {
+    path: PathBuf,
+    size: u64,
+    /// seconds since the epoch of the HEAD commit
+    last_commit: Option<i64>,
+    unsaved: bool,
+}
+
+impl StoreEntry {
+    fn inspect(path: PathBuf) -> Result<StoreEntry, Error> {
+        Ok(StoreEntry {
+            size: disk_usage(&path)?,
+            last_commit: last_commit_time(&path),
+            unsaved: has_unsaved(&path),
+            path,
+        })
+    }
+
+    fn describe(&self) -> String {
+        let last_commit = self
+            .last_commit
+            .map_or("N/A".to_string(), |t| format_date(t.max(0) as u64));
+        let unsaved = if self.unsaved {
+            "\t(uncommitted changes)"
+        } else {
+            ""
+        };
+        format!(
+            "{}\t{}\t{last_commit}{unsaved}",
+            self.path.display(),
+            format_size(self.size),
+        )
+    }
+}
+
+/// What to do with a store entry
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Delete,
+    Archive,
+    Skip,
+}
+
+impl Action {
+    /// Anything other than `d` or `a` is skipped to be safe
+    fn from_answer(answer: &str) -> Action {
+        match answer.trim().to_lowercase().as_str() {
+            "d" | "delete" => Action::Delete,
+            "a" | "archive" => Action::Archive,
+            _ => Action::Skip,
+        }
+    }
+}
+
+/// List repositories in the store whose symlink was deleted manually,
+/// then delete or archive each of them after confirmation.
+pub fn gc_store(config: &Config) -> Result<(), Error> {
+    let stdin = std::io::stdin();
+    gc_store_with(config, &mut stdin.lock(), &mut std::io::stdout())
+}
+
+fn gc_store_with(
+    config: &Config,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    let entries = orphaned_repositories(config)?
+        .into_iter()
+        .map(StoreEntry::inspect)
+        .collect::<Result<Vec<_>, Error>>()?;
+    if entries.is_empty() {
+        writeln!(output, "{}", Message::NoOrphanedRepositories)?;
+        return Ok(());
+    }
+    for entry in &entries {
+        writeln!(output, "{}", entry.describe())?;
+    }
+    let total = entries.iter().map(|entry| entry.size).sum();
+    writeln!(output, "Total\t{}", format_size(total))?;
+    for entry in &entries {
+        let path = entry.path.display().to_string();
+        write!(output, "{}", Message::ConfirmGcStore { path: &path })?;
+        output.flush()?;
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            break;
+        }
+        match Action::from_answer(&answer) {
+            Action::Delete if !referencing_repositories(config, &entry.path)?.is_empty() => {
+                writeln!(
+                    output,
+                    "{}",
+                    Message::SkippedRemovingReferenced { path: &path }
+                )?;
+            }
+            Action::Delete => {
+                std::fs::remove_dir_all(&entry.path)?;
+                writeln!(output, "{}", Message::Removed { path: &path })?;
+            }
+            Action::Archive => {
+                let name = archive(config, &entry.path)?;
+                writeln!(output, "{}", Message::Archived { name: &name })?;
+            }
+            Action::Skip => (),
+        }
+    }
+    Ok(())
+}
+
+/// Link the repository from the archive directory so that it is managed by mure again
+fn archive(config: &Config, path: &Path) -> Result<String, Error> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| Error::from_str(&format!("{} has no name", path.display())))?;
+    let archive_path = config.archive_path();
+    std::fs::create_dir_all(&archive_path)?;
+    let link = archive_path.join(&name);
+    if std::fs::symlink_metadata(&link).is_ok() {
+        return Err(Error::from_str(&format!(
+            "{} already exists",
+            link.display()
+        )));
+    }
+    std::os::unix::fs::symlink(path, &link)?;
+    Ok(name)
+}
+
+fn last_commit_time(path: &Path) -> Option<i64> {
+    let repo = Repository::open(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.time().seconds())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{config_with_base_dir, Fixture};
+    use mktemp::Temp;
+
+    #[test]
+    fn test_action_from_answer() {
+        assert_eq!(Action::from_answer("d\n"), Action::Delete);
+        assert_eq!(Action::from_answer("Archive"), Action::Archive);
+        assert_eq!(Action::from_answer("\n"), Action::Skip);
+        assert_eq!(Action::from_answer("yes"), Action::Skip);
+    }
+
+    #[test]
+    fn test_gc_store() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let mut stores = vec![];
+        for name in ["linked", "old", "kept", "reused"] {
+            let fixture = Fixture::create().unwrap();
+            fixture.create_empty_commit("initial commit").unwrap();
+            stores.push(fixture.into_store(&config, name, name == "linked"));
+        }
+
+        let mut input = "a\n\nd\n".as_bytes();
+        let mut output = vec![];
+        gc_store_with(&config, &mut input, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(!output.contains("linked"));
+        assert!(output.contains("Archived kept"));
+        assert!(output.contains(&format!("Removed {}", stores[3].display())));
+        assert!(stores[0].exists());
+        assert!(stores[1].exists());
+        assert!(!stores[3].exists());
+        assert_eq!(
+            std::fs::canonicalize(config.archive_path().join("kept")).unwrap(),
+            std::fs::canonicalize(&stores[2]).unwrap()
+        );
+
+        let mut output = vec![];
+        gc_store_with(&config, &mut "".as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with(&stores[1].display().to_string()));
+        assert!(!output.contains("kept"));
+    }
+}
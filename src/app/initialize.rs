@@ -6,15 +6,40 @@ pub fn init() -> Result<Config, Error> {
     Ok(config)
 }
 
+/// Create the config only if it doesn't exist yet.
+/// Other errors such as an undefined profile or an invalid value are returned as they are.
 pub fn get_config_or_initialize() -> Result<Config, Error> {
     match get_config() {
-        Ok(config) => Ok(config),
-        Err(_) => match init() {
-            // if not found, create config
-            // TODO: with dialog
-            // TODO: care other error cases
-            Ok(config) => Ok(config),
-            Err(e) => Err(e),
-        },
+        // TODO: with dialog
+        Err(Error::ConfigNotFound(_)) => init(),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assay::assay;
+    use mktemp::Temp;
+
+    #[test]
+    #[assay(env = [("MURE_PROFILE", "home")])]
+    fn test_get_config_or_initialize() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config_path = temp_dir.join(".mure.toml");
+        std::env::set_var("MURE_CONFIG_PATH", &config_path);
+
+        // created only if missing
+        std::env::remove_var("MURE_PROFILE");
+        let config = get_config_or_initialize().unwrap();
+        assert_eq!(config.core.base_dir, "~/.dev");
+        assert!(config_path.exists());
+
+        // the cause is reported instead of "config file already exists"
+        std::env::set_var("MURE_PROFILE", "home");
+        match get_config_or_initialize() {
+            Ok(_) => unreachable!("profile home is not defined"),
+            Err(e) => assert_eq!(e.message(), "profile home is not defined in [profiles]"),
+        }
     }
 }
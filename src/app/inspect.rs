@@ -0,0 +1,266 @@
+//! Explanation of an entry of the work directory for "why doesn't mure see this repository?"
+
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+use crate::app::list::{
+    find_symlinks, read_directory_as_mure_repo, read_symlink_as_mure_repo, search_mure_repo,
+};
+use crate::config::{Config, ConfigSupport};
+use crate::github::repo::RepoInfo;
+use crate::mure_error::Error;
+
+/// What mure knows about the entry
+#[derive(Debug, PartialEq, Eq)]
+struct Inspection {
+    path: PathBuf,
+    /// `symlink`, `directory` or `file`
+    kind: &'static str,
+    /// where the symlink points to as it is written
+    target: Option<PathBuf>,
+    in_store: bool,
+    repo: Option<RepoInfo>,
+    /// listed by `mure list` and handled by `refresh --all` and so on
+    listed: bool,
+    problems: Vec<String>,
+}
+
+impl Inspection {
+    fn describe(&self) -> String {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        let mut lines = vec![
+            format!("path: {}", self.path.display()),
+            format!("kind: {}", self.kind),
+            format!(
+                "target: {}",
+                self.target
+                    .as_ref()
+                    .map_or("N/A".to_string(), |target| target.display().to_string())
+            ),
+            format!("in store: {}", yes_no(self.in_store)),
+            format!(
+                "repository: {}",
+                self.repo
+                    .as_ref()
+                    .map_or("N/A".to_string(), RepoInfo::fully_qualified_name)
+            ),
+            format!("listed: {}", yes_no(self.listed)),
+        ];
+        if !self.problems.is_empty() {
+            lines.push("problems:".to_string());
+            lines.extend(self.problems.iter().map(|problem| format!("- {problem}")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Print whether the entry is a repository managed by mure and why not if it isn't
+pub fn inspect_symlink(config: &Config, path: &Path) -> Result<(), Error> {
+    println!("{}", inspect(config, path)?.describe());
+    Ok(())
+}
+
+fn inspect(config: &Config, path: &Path) -> Result<Inspection, Error> {
+    // not canonicalized not to resolve the symlink itself
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    let metadata = std::fs::symlink_metadata(&path)
+        .map_err(|_| Error::from_str(&format!("{} doesn't exist", path.display())))?;
+    let kind = if metadata.file_type().is_symlink() {
+        "symlink"
+    } else if metadata.is_dir() {
+        "directory"
+    } else {
+        "file"
+    };
+    let mut problems = vec![];
+    let resolved = std::fs::canonicalize(&path).ok();
+    let store = std::fs::canonicalize(config.repos_store_path()).ok();
+    let in_store =
+        matches!((&resolved, &store), (Some(resolved), Some(store)) if resolved.starts_with(store));
+
+    let repo = match kind {
+        "symlink" => match read_symlink_as_mure_repo(&path) {
+            Ok(mure_repo) => {
+                if !in_store {
+                    problems.push(format!(
+                        "the target is not in the store {}",
+                        config.repos_store_path().display()
+                    ));
+                }
+                Some(mure_repo.repo)
+            }
+            Err(e) => {
+                problems.push(e.message());
+                None
+            }
+        },
+        "directory" => {
+            let mure_repo = read_directory_as_mure_repo(&path);
+            if mure_repo.is_none() {
+                problems.push(
+                    "directories are managed only if they are git repositories named after their origin on GitHub".to_string(),
+                );
+            }
+            mure_repo.map(|mure_repo| mure_repo.repo)
+        }
+        _ => {
+            problems.push("neither a symlink nor a directory".to_string());
+            None
+        }
+    };
+    if let (Some(repo), Some(resolved)) = (&repo, &resolved) {
+        problems.extend(origin_problem(repo, resolved));
+    }
+
+    let listed = search_mure_repo(config)
+        .into_iter()
+        .flatten()
+        .any(|mure_repo| mure_repo.relative_path == path);
+    if !listed {
+        problems.extend(listing_problem(config, &path));
+    }
+
+    Ok(Inspection {
+        path: path.clone(),
+        kind,
+        target: std::fs::read_link(&path).ok(),
+        in_store,
+        repo,
+        listed,
+        problems,
+    })
+}
+
+/// Inconsistency between the repository derived from the path and its origin
+fn origin_problem(repo: &RepoInfo, resolved: &Path) -> Option<String> {
+    let Ok(git_repo) = Repository::open(resolved) else {
+        return Some("the target is not a git repository".to_string());
+    };
+    let remote = git_repo.find_remote("origin").ok()?;
+    let origin = RepoInfo::parse_url(remote.url()?)?;
+    // names on GitHub are case insensitive
+    if origin
+        .fully_qualified_name()
+        .eq_ignore_ascii_case(&repo.fully_qualified_name())
+    {
+        return None;
+    }
+    Some(format!(
+        "origin is {} but the path is of {}. Run `mure list --check-origin --fix`",
+        origin.fully_qualified_name(),
+        repo.fully_qualified_name()
+    ))
+}
+
+/// Why mure doesn't find the entry when searching base_dir
+fn listing_problem(config: &Config, path: &Path) -> Option<String> {
+    let base_path = config.base_path();
+    let archive_path = config.archive_path();
+    if path.starts_with(&archive_path) {
+        return Some("archived. Run `mure unarchive` to list it again".to_string());
+    }
+    if !path.starts_with(&base_path) {
+        return Some(format!("not in base_dir {}", base_path.display()));
+    }
+    let found = find_symlinks(config, &base_path)
+        .unwrap_or_default()
+        .contains(&path.to_path_buf());
+    if found {
+        return Some("excluded by [core] exclude or .mureignore".to_string());
+    }
+    let hidden = path
+        .strip_prefix(&base_path)
+        .ok()?
+        .parent()?
+        .components()
+        .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    if hidden {
+        return Some("in a hidden directory".to_string());
+    }
+    Some(format!(
+        "deeper than [core] search_depth = {}, or in a git repository",
+        config.search_depth()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::config_with_base_dir;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_inspect() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let mut config = config_with_base_dir(&temp_dir);
+        config.core.exclude = Some(vec!["kitsuyui/excluded".to_string()]);
+        let create = |owner: &str, name: &str, link: &str| {
+            let store_path = config.repo_store_path("github.com", owner, name);
+            let repo = Repository::init(&store_path).unwrap();
+            repo.remote("origin", &format!("https://github.com/{owner}/{name}"))
+                .unwrap();
+            let link = config.base_path().join(link);
+            std::fs::create_dir_all(link.parent().unwrap()).unwrap();
+            std::os::unix::fs::symlink(&store_path, &link).unwrap();
+            (store_path, link)
+        };
+
+        let (store_path, link) = create("kitsuyui", "mure", "mure");
+        let inspection = inspect(&config, &link).unwrap();
+        assert_eq!(
+            inspection,
+            Inspection {
+                path: link.clone(),
+                kind: "symlink",
+                target: Some(store_path.clone()),
+                in_store: true,
+                repo: RepoInfo::parse_url("https://github.com/kitsuyui/mure"),
+                listed: true,
+                problems: vec![],
+            }
+        );
+        assert_eq!(
+            inspection.describe(),
+            format!(
+                "path: {}\nkind: symlink\ntarget: {}\nin store: yes\nrepository: github.com/kitsuyui/mure\nlisted: yes",
+                link.display(),
+                store_path.display()
+            )
+        );
+
+        let (_, excluded) = create("kitsuyui", "excluded", "excluded");
+        assert_eq!(
+            inspect(&config, &excluded).unwrap().problems,
+            vec!["excluded by [core] exclude or .mureignore"]
+        );
+        let (_, deep) = create("kitsuyui", "deep", "a/b/c/d/deep");
+        assert!(inspect(&config, &deep).unwrap().problems[0].starts_with("deeper than"));
+        let (_, hidden) = create("kitsuyui", "hidden", ".hidden/hidden");
+        assert_eq!(
+            inspect(&config, &hidden).unwrap().problems,
+            vec!["in a hidden directory"]
+        );
+
+        // dangling
+        let dangling = config.base_path().join("dangling");
+        std::os::unix::fs::symlink(temp_dir.join("nowhere"), &dangling).unwrap();
+        let inspection = inspect(&config, &dangling).unwrap();
+        assert_eq!(inspection.repo, None);
+        assert!(inspection.problems[0].ends_with("doesn't exist (dangling symlink)"));
+
+        // origin changed
+        Repository::open(&store_path)
+            .unwrap()
+            .remote_set_url("origin", "https://github.com/someone/other")
+            .unwrap();
+        assert!(inspect(&config, &link).unwrap().problems[0]
+            .starts_with("origin is github.com/someone/other"));
+
+        assert!(inspect(&config, &temp_dir.join("missing")).is_err());
+    }
+}
@@ -1,30 +1,268 @@
 use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::codecov::{get_repository_coverage, Coverage, RepoBranch};
-use crate::config::Config;
+use crate::codecov::{coverage_text, get_repository_coverage, Coverage, RepoBranch};
+use crate::config::{Config, ConfigSupport};
 use crate::github;
-use crate::github::api::search_repository_query::SearchRepositoryQueryReposEdgesNodeOnRepository;
+use crate::github::api::search_repository_query::{
+    SearchRepositoryQueryReposEdgesNodeOnRepository,
+    SearchRepositoryQueryReposEdgesNodeOnRepositoryDefaultBranchRefTargetOn, StatusState,
+};
+use crate::github::api::{RepositoryHealth, WorkItem};
+use crate::misc::datetime::{self, parse_timestamp};
 use crate::mure_error::Error;
 
-pub fn show_issues_main(config: &Config, queries: &[String]) -> Result<(), Error> {
-    let queries = if queries.is_empty() {
+use super::list::search_mure_repo;
+
+/// Maximum length of a search query of GitHub
+const MAX_QUERY_LENGTH: usize = 256;
+
+/// Repositories to be shown: by search queries, of the organization (and its team),
+/// or the repositories cloned locally
+pub enum IssueScope {
+    Queries(Vec<String>),
+    Org { org: String, team: Option<String> },
+    Local,
+}
+
+/// Columns of the table of `mure issues`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Column {
+    Issues,
+    Prs,
+    Branch,
+    Ci,
+    Coverage,
+    LastRelease,
+    Url,
+    License,
+    Archived,
+    /// whether Dependabot alerts are enabled
+    VulnerabilityAlerts,
+    /// number of open Dependabot alerts
+    DependabotAlerts,
+}
+
+/// Columns shown without `--columns`
+pub const DEFAULT_COLUMNS: [Column; 7] = [
+    Column::Issues,
+    Column::Prs,
+    Column::Branch,
+    Column::Ci,
+    Column::Coverage,
+    Column::LastRelease,
+    Column::Url,
+];
+
+/// Order of rows of `mure issues`. Larger counts and newer releases come first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SortKey {
+    Issues,
+    #[default]
+    Prs,
+    Release,
+    Name,
+}
+
+/// How the table of `mure issues` is shown
+#[derive(Debug, Default)]
+pub struct TableOptions {
+    /// `DEFAULT_COLUMNS` if empty
+    pub columns: Vec<Column>,
+    pub sort: SortKey,
+    pub reverse: bool,
+    pub limit: Option<usize>,
+}
+
+impl Column {
+    fn header(&self) -> &'static str {
+        match self {
+            Column::Issues => "Issues",
+            Column::Prs => "PRs",
+            Column::Branch => "Branch",
+            Column::Ci => "CI",
+            Column::Coverage => "Coverage",
+            Column::LastRelease => "LastRelease",
+            Column::Url => "URL",
+            Column::License => "License",
+            Column::Archived => "Archived",
+            Column::VulnerabilityAlerts => "VulnerabilityAlerts",
+            Column::DependabotAlerts => "DependabotAlerts",
+        }
+    }
+
+    /// Fields of the repository requested separately for the column, if any
+    fn health_selection(&self) -> Option<&'static str> {
+        match self {
+            Column::License => Some("licenseInfo { spdxId name }"),
+            Column::Archived => Some("isArchived"),
+            Column::VulnerabilityAlerts => Some("hasVulnerabilityAlertsEnabled"),
+            Column::DependabotAlerts => Some("vulnerabilityAlerts(states: OPEN) { totalCount }"),
+            _ => None,
+        }
+    }
+
+    /// `-` is shown when the health of the repository is not available, e.g. without admin rights
+    fn value(&self, summary: &RepositorySummary, health: Option<&RepositoryHealth>) -> String {
+        let yes_no = |value: bool| if value { "yes" } else { "no" }.to_string();
+        let health_value = match self {
+            Column::Issues => return summary.github.number_of_issues.to_string(),
+            Column::Prs => return summary.github.number_of_pull_requests.to_string(),
+            Column::Branch => return summary.default_branch(),
+            Column::Ci => return summary.github.ci_status.text().to_string(),
+            Column::Coverage => return summary.coverage_text(),
+            Column::LastRelease => return summary.github.last_release_at.clone(),
+            Column::Url => return summary.github.url.clone(),
+            Column::License => health.map(|health| match &health.license_info {
+                Some(license) => license.spdx_id.clone().unwrap_or(license.name.clone()),
+                None => "none".to_string(),
+            }),
+            Column::Archived => health.and_then(|health| health.is_archived.map(yes_no)),
+            Column::VulnerabilityAlerts => {
+                health.and_then(|health| health.has_vulnerability_alerts_enabled.map(yes_no))
+            }
+            Column::DependabotAlerts => health.and_then(|health| {
+                health
+                    .vulnerability_alerts
+                    .as_ref()
+                    .map(|alerts| alerts.total_count.to_string())
+            }),
+        };
+        health_value.unwrap_or("-".to_string())
+    }
+}
+
+pub fn show_issues_main(
+    config: &Config,
+    queries: &[String],
+    org: Option<String>,
+    team: Option<String>,
+    local: bool,
+    rate_limit: bool,
+    options: &TableOptions,
+) -> Result<(), Error> {
+    let scope = if local {
+        IssueScope::Local
+    } else if let Some(org) = org {
+        IssueScope::Org { org, team }
+    } else if queries.is_empty() {
         if config.github.is_both_query_and_queries_set() {
             return Err(Error::from_str(
                 "Both query and queries are set. Please set only one of them.",
             ));
         }
-        config.github.get_queries()
+        IssueScope::Queries(config.github.get_queries()?)
     } else {
-        queries.to_vec()
+        IssueScope::Queries(queries.to_vec())
     };
-    let username = config.github.username.to_string();
-    match show_issues(&username, &queries) {
+    match show_issues(config, &scope, options) {
         Ok(_) => (),
         Err(e) => println!("{e}"),
     }
+    if rate_limit {
+        show_rate_limits(config);
+    }
+    Ok(())
+}
+
+/// Personal work queue of issues and pull requests across all repositories
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WorkQueue {
+    /// Pull requests waiting for my review. They block others so they come first.
+    ReviewRequested,
+    /// Issues and pull requests assigned to me
+    Assigned,
+}
+
+impl WorkQueue {
+    fn query(&self) -> &'static str {
+        match self {
+            WorkQueue::ReviewRequested => "is:open is:pr review-requested:@me archived:false",
+            WorkQueue::Assigned => "is:open assignee:@me archived:false",
+        }
+    }
+
+    fn text(&self) -> &'static str {
+        match self {
+            WorkQueue::ReviewRequested => "review",
+            WorkQueue::Assigned => "assigned",
+        }
+    }
+}
+
+/// Show the work queues as a flat list: review requests first, then the oldest first
+pub fn show_work_queue(config: &Config, queues: &[WorkQueue]) -> Result<(), Error> {
+    let mut items = vec![];
+    for host in issue_hosts(config) {
+        let token = match github::token::get_github_token_for(&host) {
+            Ok(token) => token,
+            Err(e) => {
+                println!("{host}: {e}");
+                continue;
+            }
+        };
+        let url = config.graphql_url(&host);
+        for &queue in queues {
+            match github::api::search_work_items(&url, &token, queue.query()) {
+                Ok(found) => items.extend(found.into_iter().map(|item| (queue, item))),
+                Err(e) => println!("{host}: {e}"),
+            }
+        }
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    println!("Kind\tAge\tRepository\tTitle\tURL");
+    for (queue, item) in prioritize(items) {
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            queue.text(),
+            format_age(now, &item.created_at),
+            item.repository,
+            item.title,
+            item.url
+        );
+    }
     Ok(())
 }
 
+/// Sort by the queue and then the oldest first. Items in several queues are kept in the first.
+fn prioritize(mut items: Vec<(WorkQueue, WorkItem)>) -> Vec<(WorkQueue, WorkItem)> {
+    // timestamps of the same format are sorted chronologically as strings
+    items.sort_by(|(a_queue, a), (b_queue, b)| {
+        (a_queue, &a.created_at).cmp(&(b_queue, &b.created_at))
+    });
+    let mut seen = HashSet::new();
+    items.retain(|(_, item)| seen.insert(item.url.clone()));
+    items
+}
+
+/// Age like `3d`, `5h` or `12m` of the timestamp
+fn format_age(now: u64, created_at: &str) -> String {
+    let Some(created_at) = parse_timestamp(created_at) else {
+        return "-".to_string();
+    };
+    datetime::format_age(now.saturating_sub(created_at))
+}
+
+/// Show the remaining budget of GitHub API of each host for debugging
+fn show_rate_limits(config: &Config) {
+    for host in issue_hosts(config) {
+        let result = github::token::get_github_token_for(&host)
+            .and_then(|token| github::api::get_rate_limit(&config.graphql_url(&host), &token));
+        match result {
+            Ok(Some(rate_limit)) => println!(
+                "{host}: rate limit {}/{} remaining ({} used), resets at {}",
+                rate_limit.remaining, rate_limit.limit, rate_limit.used, rate_limit.reset_at
+            ),
+            Ok(None) => println!("{host}: rate limit is not available"),
+            Err(e) => println!("{host}: {e}"),
+        }
+    }
+}
+
 pub struct RepositorySummary {
     github: GitHubRepoSummary,
     codecov: Option<Coverage>,
@@ -35,22 +273,24 @@ impl RepositorySummary {
         RepositorySummary { github, codecov }
     }
 
-    fn number_of_pull_requests(&self) -> i64 {
+    pub fn number_of_pull_requests(&self) -> i64 {
         self.github.number_of_pull_requests
     }
 
-    fn number_of_issues(&self) -> i64 {
+    pub fn number_of_issues(&self) -> i64 {
         self.github.number_of_issues
     }
 
+    pub fn name(&self) -> &str {
+        &self.github.name
+    }
+
+    pub fn coverage(&self) -> Option<f64> {
+        self.codecov.as_ref().and_then(|c| c.coverage)
+    }
+
     fn coverage_text(&self) -> String {
-        match &self.codecov {
-            Some(c) => match c.coverage {
-                Some(coverage) => format!("{:.2}%", coverage),
-                None => "N/A".to_string(),
-            },
-            None => "N/A".to_string(),
-        }
+        coverage_text(self.codecov.as_ref().and_then(|c| c.coverage))
     }
 
     fn default_branch(&self) -> String {
@@ -63,13 +303,61 @@ impl RepositorySummary {
 
 pub struct GitHubRepoSummary {
     // | "\(.issues.totalCount)\t\(.pullRequests.totalCount)\t\(.defaultBranchRef.name)\t\(.url)"'
-    #[allow(dead_code)]
     pub name: String,
     pub number_of_issues: i64,
     pub number_of_pull_requests: i64,
     pub default_branch_name: Option<String>,
     pub url: String,
     pub last_release_at: String,
+    pub ci_status: CiStatus,
+}
+
+/// Status of CI (statusCheckRollup) on the latest commit of the default branch
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CiStatus {
+    Pass,
+    Fail,
+    Pending,
+    Unknown,
+}
+
+impl CiStatus {
+    fn from_state(state: &StatusState) -> CiStatus {
+        match state {
+            StatusState::SUCCESS => CiStatus::Pass,
+            StatusState::ERROR | StatusState::FAILURE => CiStatus::Fail,
+            StatusState::EXPECTED | StatusState::PENDING => CiStatus::Pending,
+            StatusState::Other(_) => CiStatus::Unknown,
+        }
+    }
+
+    fn from_api(repo: &SearchRepositoryQueryReposEdgesNodeOnRepository) -> CiStatus {
+        let Some(target) = repo
+            .default_branch_ref
+            .as_ref()
+            .and_then(|default_branch_ref| default_branch_ref.target.as_ref())
+        else {
+            return CiStatus::Unknown;
+        };
+        match &target.on {
+            SearchRepositoryQueryReposEdgesNodeOnRepositoryDefaultBranchRefTargetOn::Commit(
+                commit,
+            ) => match &commit.status_check_rollup {
+                Some(rollup) => CiStatus::from_state(&rollup.state),
+                None => CiStatus::Unknown,
+            },
+            _ => CiStatus::Unknown,
+        }
+    }
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            CiStatus::Pass => "pass",
+            CiStatus::Fail => "fail",
+            CiStatus::Pending => "pending",
+            CiStatus::Unknown => "-",
+        }
+    }
 }
 
 impl GitHubRepoSummary {
@@ -95,6 +383,7 @@ impl GitHubRepoSummary {
                     "****-**-**".to_string()
                 })
                 .unwrap_or("****-**-**".to_string()),
+            ci_status: CiStatus::from_api(repo),
         }
     }
 }
@@ -120,66 +409,525 @@ impl RepoBranch {
     }
 }
 
+/// Summaries of the repositories. Codecov is requested only `with_coverage`.
 pub fn repository_summary(
-    username: &str,
-    repos: &Vec<SearchRepositoryQueryReposEdgesNodeOnRepository>,
+    repos: &[SearchRepositoryQueryReposEdgesNodeOnRepository],
+    with_coverage: bool,
 ) -> Result<Vec<RepositorySummary>, Error> {
-    let mut results: Vec<GitHubRepoSummary> = Vec::new();
-    for repo in repos {
-        results.push(GitHubRepoSummary::new_from_api(repo));
-    }
-
-    let branch_repos = &repos.iter().map(RepoBranch::from_api).collect();
-
-    let coverage_summary = get_repository_coverage(username, branch_repos)?;
-    let coverage_map = coverage_summary
-        .into_iter()
-        .map(|c| (c.name.to_string(), c))
-        .collect::<std::collections::HashMap<String, Coverage>>();
+    let coverage_map = if with_coverage {
+        fetch_coverages(repos)
+    } else {
+        HashMap::new()
+    };
     let mut results: Vec<RepositorySummary> = Vec::new();
     for repo in repos {
         let gh_summary = GitHubRepoSummary::new_from_api(repo);
-        let cov_summary = coverage_map.get(&repo.name).cloned();
+        let cov_summary = split_repository_url(&repo.url)
+            .and_then(|(_, owner, name)| coverage_map.get(&format!("{owner}/{name}")))
+            .cloned();
         let summary = RepositorySummary::new(gh_summary, cov_summary);
         results.push(summary);
     }
+    Ok(results)
+}
+
+/// Coverages keyed by `owner/name`, as repositories of different owners may have the same name.
+/// Codecov is queried per owner of the repositories.
+/// Coverage is optional. Repositories are shown with N/A when Codecov is not available.
+fn fetch_coverages(
+    repos: &[SearchRepositoryQueryReposEdgesNodeOnRepository],
+) -> HashMap<String, Coverage> {
+    let mut owners: BTreeMap<String, Vec<RepoBranch>> = BTreeMap::new();
+    for repo in repos {
+        if let Some((_, owner, _)) = split_repository_url(&repo.url) {
+            owners
+                .entry(owner)
+                .or_default()
+                .push(RepoBranch::from_api(repo));
+        }
+    }
+    let mut coverage_map = HashMap::new();
+    for (owner, branches) in owners {
+        match get_repository_coverage(&owner, &branches) {
+            Ok(coverages) => {
+                coverage_map.extend(
+                    coverages
+                        .into_iter()
+                        .map(|c| (format!("{owner}/{}", c.name), c)),
+                );
+            }
+            Err(e) => tracing::warn!("failed to get coverages of {owner}: {e}"),
+        }
+    }
+    coverage_map
+}
 
-    results.sort_by_key(|r| {
-        (
-            Reverse(r.number_of_pull_requests()),
-            Reverse(r.number_of_issues()),
-        )
-    });
+/// Sort the summaries by the key. Ties are broken by the other count and then by name.
+pub fn sort_summaries(summaries: &mut [RepositorySummary], sort: SortKey, reverse: bool) {
+    match sort {
+        SortKey::Issues => summaries.sort_by(|a, b| {
+            (
+                Reverse(a.number_of_issues()),
+                Reverse(a.number_of_pull_requests()),
+                a.name(),
+            )
+                .cmp(&(
+                    Reverse(b.number_of_issues()),
+                    Reverse(b.number_of_pull_requests()),
+                    b.name(),
+                ))
+        }),
+        SortKey::Prs => summaries.sort_by(|a, b| {
+            (
+                Reverse(a.number_of_pull_requests()),
+                Reverse(a.number_of_issues()),
+                a.name(),
+            )
+                .cmp(&(
+                    Reverse(b.number_of_pull_requests()),
+                    Reverse(b.number_of_issues()),
+                    b.name(),
+                ))
+        }),
+        // `****-**-**` of repositories without releases comes last
+        SortKey::Release => summaries.sort_by(|a, b| {
+            (Reverse(&a.github.last_release_at), a.name())
+                .cmp(&(Reverse(&b.github.last_release_at), b.name()))
+        }),
+        SortKey::Name => summaries.sort_by(|a, b| a.name().cmp(b.name())),
+    }
+    if reverse {
+        summaries.reverse();
+    }
+}
+
+/// Summaries of repositories matching the queries of config
+pub fn fetch_repository_summaries(config: &Config) -> Result<Vec<RepositorySummary>, Error> {
+    let repos = search_all_hosts(config, &IssueScope::Queries(config.github.get_queries()?))?;
+    let mut summaries = repository_summary(&repos, true)?;
+    sort_summaries(&mut summaries, SortKey::default(), false);
+    Ok(summaries)
+}
+
+/// Hosts to be queried: github.com and the hosts configured in `[hosts]`.
+/// Other hosts of cloned repositories (e.g. gist.github.com, GitLab) may have no GraphQL API,
+/// and the token must not be sent to them.
+fn issue_hosts(config: &Config) -> BTreeSet<String> {
+    let mut hosts = BTreeSet::from(["github.com".to_string()]);
+    if let Some(configured) = &config.hosts {
+        hosts.extend(configured.keys().cloned());
+    }
+    hosts
+}
 
+/// Search queries listing the repositories with `repo:` qualifiers within the length limit
+fn repository_queries(repos: &[String]) -> Vec<String> {
+    let mut queries: Vec<String> = vec![];
+    for repo in repos {
+        let qualifier = format!("repo:{repo}");
+        match queries.last_mut() {
+            Some(query) if query.len() + 1 + qualifier.len() <= MAX_QUERY_LENGTH => {
+                query.push(' ');
+                query.push_str(&qualifier);
+            }
+            _ => queries.push(qualifier),
+        }
+    }
+    queries
+}
+
+/// Search queries of the scope on the host
+fn scope_queries(url: &str, token: &str, scope: &IssueScope) -> Result<Vec<String>, Error> {
+    match scope {
+        IssueScope::Queries(queries) => Ok(queries.clone()),
+        IssueScope::Org { org, team } => {
+            let repos = github::api::list_org_repositories(url, token, org, team.as_deref())?;
+            Ok(repository_queries(&repos))
+        }
+        IssueScope::Local => Err(Error::from_str("local repositories are not searched")),
+    }
+}
+
+/// Owners and names of the repositories cloned locally from the host.
+/// Wikis are skipped because they are not repositories of the API.
+fn local_repository_names(config: &Config, host: &str) -> Vec<(String, String)> {
+    let mut names = search_mure_repo(config)
+        .into_iter()
+        .flatten()
+        .filter(|mure_repo| mure_repo.repo.domain == host && !mure_repo.repo.is_wiki())
+        .map(|mure_repo| (mure_repo.repo.owner, mure_repo.repo.repo))
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Fetch the repositories cloned locally one by one instead of searching
+fn fetch_local_repositories(
+    config: &Config,
+    host: &str,
+    url: &str,
+    token: &str,
+) -> Result<Vec<SearchRepositoryQueryReposEdgesNodeOnRepository>, Error> {
+    let names = local_repository_names(config, host);
+    let repos = github::api::get_repositories(url, token, &names)?;
+    let mut results = vec![];
+    for ((owner, name), repo) in names.iter().zip(repos) {
+        match repo {
+            Some(repo) => results.push(repo),
+            None => println!("{host}: {owner}/{name} is not found"),
+        }
+    }
     Ok(results)
 }
 
-pub fn show_issues(username: &str, queries: &Vec<String>) -> Result<(), Error> {
-    let Ok(token) = github::token::get_github_token() else {
-        return Err(Error::from_str("GH_TOKEN is not set"));
+/// Search repositories on each host with its GraphQL endpoint
+pub fn search_all_hosts(
+    config: &Config,
+    scope: &IssueScope,
+) -> Result<Vec<SearchRepositoryQueryReposEdgesNodeOnRepository>, Error> {
+    let hosts = issue_hosts(config);
+    let mut results = vec![];
+    for host in &hosts {
+        let token = match github::token::get_github_token_for(host) {
+            Ok(token) => token,
+            // Only github.com is searched by default
+            Err(e) if hosts.len() == 1 => return Err(e),
+            Err(e) => {
+                println!("{host}: {e}");
+                continue;
+            }
+        };
+        let url = config.graphql_url(host);
+        let repos = match scope {
+            IssueScope::Local => fetch_local_repositories(config, host, &url, &token),
+            _ => scope_queries(&url, &token, scope).and_then(|queries| {
+                github::api::search_all_repositories_by_queries(&url, &token, &queries)
+            }),
+        };
+        match repos {
+            Ok(mut repos) => results.append(&mut repos),
+            Err(e) => println!("{host}: {e}"),
+        }
+    }
+    Ok(results)
+}
+
+pub fn show_issues(
+    config: &Config,
+    scope: &IssueScope,
+    options: &TableOptions,
+) -> Result<(), Error> {
+    let columns = if options.columns.is_empty() {
+        &DEFAULT_COLUMNS[..]
+    } else {
+        &options.columns
     };
-    match github::api::search_all_repositories_by_queries(&token, queries) {
+    match search_all_hosts(config, scope) {
         Err(e) => println!("{e}"),
-        Ok(result) => {
-            match repository_summary(username, &result) {
-                Ok(results) => {
-                    // header
-                    println!("Issues\tPRs\tBranch\tCoverage\tLastRelease\tURL");
-                    for result in results {
-                        println!(
-                            "{}\t{}\t{}\t{}\t{}\t{}",
-                            result.github.number_of_issues,
-                            result.github.number_of_pull_requests,
-                            result.default_branch(),
-                            result.coverage_text(),
-                            result.github.last_release_at,
-                            result.github.url,
-                        );
-                    }
+        Ok(result) => match repository_summary(&result, columns.contains(&Column::Coverage)) {
+            Ok(mut results) => {
+                sort_summaries(&mut results, options.sort, options.reverse);
+                if let Some(limit) = options.limit {
+                    results.truncate(limit);
+                }
+                let health = fetch_health(config, &results, columns);
+                let header = columns.iter().map(Column::header).collect::<Vec<_>>();
+                println!("{}", header.join("\t"));
+                for result in results {
+                    let health = health.get(&result.github.url);
+                    let row = columns
+                        .iter()
+                        .map(|column| column.value(&result, health))
+                        .collect::<Vec<_>>();
+                    println!("{}", row.join("\t"));
                 }
-                Err(e) => println!("{e}"),
             }
-        }
+            Err(e) => println!("{e}"),
+        },
     };
     Ok(())
 }
+
+/// Health of the repositories by URL for the columns. Nothing is requested without health columns.
+fn fetch_health(
+    config: &Config,
+    results: &[RepositorySummary],
+    columns: &[Column],
+) -> HashMap<String, RepositoryHealth> {
+    let mut health = HashMap::new();
+    let selection = columns
+        .iter()
+        .filter_map(Column::health_selection)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if selection.is_empty() {
+        return health;
+    }
+    let mut repos_by_host: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    for result in results {
+        if let Some((host, owner, name)) = split_repository_url(&result.github.url) {
+            repos_by_host.entry(host).or_default().push((owner, name));
+        }
+    }
+    for (host, repos) in repos_by_host {
+        let fetched = github::token::get_github_token_for(&host).and_then(|token| {
+            github::api::get_repository_health(
+                &config.graphql_url(&host),
+                &token,
+                &repos,
+                &selection,
+            )
+        });
+        match fetched {
+            Ok(fetched) => {
+                for ((owner, name), repo_health) in repos.iter().zip(fetched) {
+                    if let Some(repo_health) = repo_health {
+                        health.insert(format!("https://{host}/{owner}/{name}"), repo_health);
+                    }
+                }
+            }
+            Err(e) => println!("{host}: {e}"),
+        }
+    }
+    health
+}
+
+/// Host, owner and name of `https://host/owner/name`. GitHub Enterprise hosts are included.
+fn split_repository_url(url: &str) -> Option<(String, String, String)> {
+    let path = url.strip_prefix("https://")?;
+    let mut parts = path.trim_end_matches('/').split('/');
+    let (host, owner, name) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((host.to_string(), owner.to_string(), name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::config_with_base_dir;
+
+    #[test]
+    fn test_ci_status() {
+        assert_eq!(CiStatus::from_state(&StatusState::SUCCESS), CiStatus::Pass);
+        assert_eq!(CiStatus::from_state(&StatusState::FAILURE), CiStatus::Fail);
+        assert_eq!(CiStatus::from_state(&StatusState::ERROR), CiStatus::Fail);
+        assert_eq!(
+            CiStatus::from_state(&StatusState::PENDING),
+            CiStatus::Pending
+        );
+        assert_eq!(
+            CiStatus::from_state(&StatusState::Other("NEW".to_string())),
+            CiStatus::Unknown
+        );
+        assert_eq!(CiStatus::Pass.text(), "pass");
+        assert_eq!(CiStatus::Unknown.text(), "-");
+    }
+
+    #[test]
+    fn test_column_value() {
+        let summary = RepositorySummary::new(
+            GitHubRepoSummary {
+                name: "mure".to_string(),
+                number_of_issues: 3,
+                number_of_pull_requests: 1,
+                default_branch_name: None,
+                url: "https://github.com/kitsuyui/mure".to_string(),
+                last_release_at: "2024-05-01".to_string(),
+                ci_status: CiStatus::Pass,
+            },
+            None,
+        );
+        let row = |health: Option<&RepositoryHealth>| {
+            [
+                Column::Issues,
+                Column::Branch,
+                Column::Coverage,
+                Column::License,
+                Column::Archived,
+                Column::VulnerabilityAlerts,
+                Column::DependabotAlerts,
+            ]
+            .iter()
+            .map(|column| column.value(&summary, health))
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(row(None), ["3", "main", "N/A", "-", "-", "-", "-"]);
+        let health = RepositoryHealth {
+            license_info: Some(github::api::LicenseInfo {
+                spdx_id: Some("BSD-3-Clause".to_string()),
+                name: "BSD 3-Clause".to_string(),
+            }),
+            is_archived: Some(false),
+            has_vulnerability_alerts_enabled: Some(true),
+            vulnerability_alerts: Some(github::api::TotalCount { total_count: 2 }),
+        };
+        assert_eq!(
+            row(Some(&health)),
+            ["3", "main", "N/A", "BSD-3-Clause", "no", "yes", "2"]
+        );
+        assert_eq!(
+            row(Some(&RepositoryHealth::default()))[3..],
+            ["none", "-", "-", "-"]
+        );
+        assert!(DEFAULT_COLUMNS
+            .iter()
+            .all(|column| column.health_selection().is_none()));
+    }
+
+    #[test]
+    fn test_sort_summaries() {
+        let summary = |name: &str, issues: i64, prs: i64, release: &str| {
+            RepositorySummary::new(
+                GitHubRepoSummary {
+                    name: name.to_string(),
+                    number_of_issues: issues,
+                    number_of_pull_requests: prs,
+                    default_branch_name: None,
+                    url: format!("https://github.com/kitsuyui/{name}"),
+                    last_release_at: release.to_string(),
+                    ci_status: CiStatus::Unknown,
+                },
+                None,
+            )
+        };
+        let mut summaries = vec![
+            summary("a", 1, 2, "****-**-**"),
+            summary("b", 5, 0, "2024-01-01"),
+            summary("c", 1, 2, "2024-05-01"),
+        ];
+        let names = |summaries: &[RepositorySummary]| {
+            summaries
+                .iter()
+                .map(|summary| summary.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        sort_summaries(&mut summaries, SortKey::Prs, false);
+        assert_eq!(names(&summaries), ["a", "c", "b"]);
+        sort_summaries(&mut summaries, SortKey::Issues, false);
+        assert_eq!(names(&summaries), ["b", "a", "c"]);
+        sort_summaries(&mut summaries, SortKey::Release, false);
+        assert_eq!(names(&summaries), ["c", "b", "a"]);
+        sort_summaries(&mut summaries, SortKey::Name, true);
+        assert_eq!(names(&summaries), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_split_repository_url() {
+        assert_eq!(
+            split_repository_url("https://ghe.corp/team/app"),
+            Some((
+                "ghe.corp".to_string(),
+                "team".to_string(),
+                "app".to_string()
+            ))
+        );
+        assert_eq!(split_repository_url("https://github.com/kitsuyui"), None);
+        assert_eq!(split_repository_url("git@github.com:kitsuyui/mure"), None);
+    }
+
+    fn work_item(url: &str, created_at: &str) -> WorkItem {
+        WorkItem {
+            repository: "kitsuyui/mure".to_string(),
+            title: "title".to_string(),
+            url: url.to_string(),
+            created_at: created_at.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_prioritize() {
+        let items = prioritize(vec![
+            (
+                WorkQueue::Assigned,
+                work_item("old", "2024-01-01T00:00:00Z"),
+            ),
+            (
+                WorkQueue::Assigned,
+                work_item("both", "2024-03-01T00:00:00Z"),
+            ),
+            (
+                WorkQueue::ReviewRequested,
+                work_item("new", "2024-05-01T00:00:00Z"),
+            ),
+            (
+                WorkQueue::ReviewRequested,
+                work_item("both", "2024-03-01T00:00:00Z"),
+            ),
+        ]);
+        assert_eq!(
+            items
+                .iter()
+                .map(|(queue, item)| (*queue, item.url.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (WorkQueue::ReviewRequested, "both"),
+                (WorkQueue::ReviewRequested, "new"),
+                (WorkQueue::Assigned, "old"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_age() {
+        let now = 1714564800; // 2024-05-01T12:00:00Z
+        assert_eq!(format_age(now, "2024-05-01T11:48:00Z"), "12m");
+        assert_eq!(format_age(now, "2024-05-01T07:00:00Z"), "5h");
+        assert_eq!(format_age(now, "2024-04-28T12:00:00Z"), "3d");
+        assert_eq!(format_age(now, "invalid"), "-");
+    }
+
+    #[test]
+    fn test_repository_queries() {
+        assert!(repository_queries(&[]).is_empty());
+        assert_eq!(
+            repository_queries(&["org/a".to_string(), "org/b".to_string()]),
+            vec!["repo:org/a repo:org/b"]
+        );
+        let repos = (0..30)
+            .map(|i| format!("org/repo{i:02}"))
+            .collect::<Vec<_>>();
+        let queries = repository_queries(&repos);
+        assert_eq!(queries.len(), 2);
+        assert!(queries.iter().all(|q| q.len() <= MAX_QUERY_LENGTH));
+        assert_eq!(queries.join(" ").split(' ').count(), 30);
+        assert!(queries[1].starts_with("repo:org/repo"));
+    }
+
+    #[test]
+    fn test_issue_hosts() {
+        let temp_dir = mktemp::Temp::new_dir().unwrap();
+        let base = temp_dir.as_path();
+        let store = base.join("repo").join("ghe.corp").join("team").join("app");
+        std::fs::create_dir_all(&store).unwrap();
+        std::os::unix::fs::symlink(&store, base.join("app")).unwrap();
+        let gist = base
+            .join("repo")
+            .join("gist.github.com")
+            .join("kitsuyui")
+            .join("0123abcd");
+        std::fs::create_dir_all(&gist).unwrap();
+        std::os::unix::fs::symlink(&gist, base.join("0123abcd")).unwrap();
+        let config = config_with_base_dir(base);
+        assert_eq!(
+            issue_hosts(&config).into_iter().collect::<Vec<_>>(),
+            vec!["github.com"]
+        );
+        let config = Config {
+            hosts: toml::from_str("[\"ghe.corp\"]\n").unwrap(),
+            ..config
+        };
+        assert_eq!(
+            issue_hosts(&config).into_iter().collect::<Vec<_>>(),
+            vec!["ghe.corp", "github.com"]
+        );
+        std::os::unix::fs::symlink(&store, base.join("app2")).unwrap();
+        assert_eq!(
+            local_repository_names(&config, "ghe.corp"),
+            vec![("team".to_string(), "app".to_string())]
+        );
+        assert!(local_repository_names(&config, "github.com").is_empty());
+    }
+}
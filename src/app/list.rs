@@ -1,18 +1,66 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+use glob::Pattern;
 
 use crate::config::{Config, ConfigSupport};
+use crate::git::RepositorySupport;
 use crate::github::repo::RepoInfo;
+use crate::i18n::Message;
+use crate::lang;
 use crate::mure_error::Error;
+use crate::state::State;
+
+/// Number of recent commits to inspect for `--unsigned`
+const SIGNATURE_CHECK_DEPTH: usize = 10;
 
-pub fn list(config: &Config, path: bool, full: bool) -> Result<(), Error> {
-    let repos = search_mure_repo(config);
+pub fn list(
+    config: &Config,
+    path: bool,
+    full: bool,
+    unsigned: bool,
+    archived: bool,
+    tree: bool,
+    filter: &RepoFilter,
+) -> Result<(), Error> {
+    let repos = if archived {
+        search_archived_repo(config)
+    } else {
+        search_mure_repo(config)
+    };
+    let repos = filter.apply(repos)?;
     if repos.is_empty() {
-        println!("No repositories found");
+        println!("{}", Message::NoRepositoriesFound);
+        return Ok(());
+    }
+    if tree {
+        let mut found = vec![];
+        for repo in repos {
+            match repo {
+                Ok(mure_repo) => found.push(mure_repo.repo),
+                Err(e) => println!("{}", e.message()),
+            }
+        }
+        print!("{}", format_tree(&found));
         return Ok(());
     }
     for repo in repos {
         match repo {
             Ok(mure_repo) => {
+                if unsigned {
+                    match count_unsigned_commits(&mure_repo) {
+                        Ok(0) => continue,
+                        Ok(count) => {
+                            println!(
+                                "{}\t{count} of last {SIGNATURE_CHECK_DEPTH} commits are not signed",
+                                mure_repo.repo.name_with_owner()
+                            );
+                        }
+                        Err(e) => println!("{}: {}", mure_repo.repo.name_with_owner(), e),
+                    }
+                    continue;
+                }
                 if full && path {
                     #[allow(clippy::expect_used)]
                     let abpath = mure_repo
@@ -41,31 +89,179 @@ pub fn list(config: &Config, path: bool, full: bool) -> Result<(), Error> {
     Ok(())
 }
 
+/// Tree of repositories grouped by domain and owner with the number of repositories in each group
+fn format_tree(repos: &[RepoInfo]) -> String {
+    let mut groups: BTreeMap<&str, BTreeMap<&str, Vec<&str>>> = BTreeMap::new();
+    for repo in repos {
+        groups
+            .entry(&repo.domain)
+            .or_default()
+            .entry(&repo.owner)
+            .or_default()
+            .push(&repo.repo);
+    }
+    let mut lines = vec![];
+    for (domain_index, (domain, owners)) in groups.iter().enumerate() {
+        let count: usize = owners.values().map(|names| names.len()).sum();
+        lines.push(format!("{domain} ({count})"));
+        let domain_last = domain_index + 1 == groups.len();
+        for (owner_index, (owner, names)) in owners.iter().enumerate() {
+            let owner_last = owner_index + 1 == owners.len();
+            let (branch, indent) = if owner_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            lines.push(format!("{branch}{owner} ({})", names.len()));
+            let mut names = names.clone();
+            names.sort();
+            for (name_index, name) in names.iter().enumerate() {
+                let name_branch = if name_index + 1 == names.len() {
+                    "└── "
+                } else {
+                    "├── "
+                };
+                lines.push(format!("{indent}{name_branch}{name}"));
+            }
+        }
+        if !domain_last {
+            lines.push(String::new());
+        }
+    }
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+/// Count unsigned commits in the recent history of the default branch.
+/// origin/HEAD is used as the default branch if it is known, otherwise HEAD.
+fn count_unsigned_commits(mure_repo: &MureRepo) -> Result<usize, Error> {
+    let repo = Repository::open(&mure_repo.absolute_path)?;
+    let revision = match repo.find_reference("refs/remotes/origin/HEAD") {
+        Ok(_) => "origin/HEAD",
+        Err(_) => "HEAD",
+    };
+    let commits = repo
+        .commit_signatures(revision, SIGNATURE_CHECK_DEPTH)?
+        .interpreted_to;
+    Ok(commits
+        .iter()
+        .filter(|(_, status)| !status.is_signed())
+        .count())
+}
+
 pub struct MureRepo {
     pub relative_path: PathBuf,
     pub absolute_path: PathBuf,
     pub repo: RepoInfo,
 }
 
+/// Filter to narrow down repositories of commands that work on many repositories
+#[derive(Debug, Default, Clone)]
+pub struct RepoFilter {
+    pub tag: Option<String>,
+    /// Language detected locally, e.g. `rust`
+    pub lang: Option<String>,
+}
+
+impl RepoFilter {
+    pub fn tagged(tag: Option<String>) -> RepoFilter {
+        RepoFilter {
+            tag,
+            ..Default::default()
+        }
+    }
+
+    /// Filter of `--tag` and `--filter key=value`. Keys are `tag` and `lang`.
+    pub fn parse(tag: Option<String>, filters: &[String]) -> Result<RepoFilter, Error> {
+        let mut filter = RepoFilter::tagged(tag);
+        for text in filters {
+            match text.split_once('=') {
+                Some(("tag", value)) => filter.tag = Some(value.to_string()),
+                Some(("lang", value)) => filter.lang = Some(value.to_lowercase()),
+                _ => {
+                    return Err(Error::from_str(&format!(
+                        "invalid filter {text}. Use key=value (keys: tag, lang)"
+                    )))
+                }
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Keep repositories matching the filter. Errors are kept to be reported by the caller.
+    pub fn apply(
+        &self,
+        repos: Vec<Result<MureRepo, Error>>,
+    ) -> Result<Vec<Result<MureRepo, Error>>, Error> {
+        if self.tag.is_none() && self.lang.is_none() {
+            return Ok(repos);
+        }
+        let state = match self.tag {
+            Some(_) => State::load()?,
+            None => State::default(),
+        };
+        Ok(repos
+            .into_iter()
+            .filter(|repo| match repo {
+                Ok(mure_repo) => self.matches(&state, mure_repo),
+                Err(_) => true,
+            })
+            .collect())
+    }
+
+    fn matches(&self, state: &State, mure_repo: &MureRepo) -> bool {
+        let tagged = match &self.tag {
+            Some(tag) => state.has_tag(&mure_repo.repo.repo, tag),
+            None => true,
+        };
+        tagged
+            && self
+                .lang
+                .as_ref()
+                .is_none_or(|lang| lang::detect(&mure_repo.absolute_path) == lang)
+    }
+}
+
 pub fn search_mure_repo(config: &Config) -> Vec<Result<MureRepo, Error>> {
+    search_mure_repo_in(config, &config.base_path())
+}
+
+/// Search repositories archived by `mure archive`
+pub fn search_archived_repo(config: &Config) -> Vec<Result<MureRepo, Error>> {
+    let archive_path = config.archive_path();
+    if !archive_path.exists() {
+        return vec![];
+    }
+    search_mure_repo_in(config, &archive_path)
+}
+
+fn search_mure_repo_in(config: &Config, dir: &Path) -> Vec<Result<MureRepo, Error>> {
     let mut repos = vec![];
-    match config.base_path().read_dir() {
-        Ok(dir) => {
-            dir.for_each(|entry| {
-                if let Ok(entry) = entry {
-                    let metadata = match std::fs::symlink_metadata(entry.path()) {
-                        Ok(metadata) => metadata,
-                        Err(_) => return,
-                    };
-                    if !metadata.is_symlink() {
-                        return;
-                    }
-                    match read_symlink_as_mure_repo(&entry.path()) {
-                        Ok(mure_repo) => repos.push(Ok(mure_repo)),
-                        Err(e) => repos.push(Err(e)),
+    let mut excludes = vec![];
+    for pattern in config.exclude_patterns() {
+        match Pattern::new(&pattern) {
+            Ok(pattern) => excludes.push(pattern),
+            Err(e) => repos.push(Err(Error::from_str(&format!(
+                "invalid exclude pattern {pattern}: {e}"
+            )))),
+        }
+    }
+    match find_work_paths(config, dir) {
+        Ok(paths) => {
+            for path in paths {
+                let mure_repo = if path.is_symlink() {
+                    read_symlink_as_mure_repo(&path)
+                } else {
+                    match read_directory_as_mure_repo(&path) {
+                        Some(mure_repo) => Ok(mure_repo),
+                        None => continue,
                     }
+                };
+                match mure_repo {
+                    Ok(mure_repo) if is_excluded(&mure_repo.repo, &excludes) => (),
+                    Ok(mure_repo) => repos.push(Ok(mure_repo)),
+                    Err(e) => repos.push(Err(e)),
                 }
-            });
+            }
         }
         Err(_) => {
             repos.push(Err(Error::from_str("failed to read dir")));
@@ -74,23 +270,125 @@ pub fn search_mure_repo(config: &Config) -> Vec<Result<MureRepo, Error>> {
     repos
 }
 
-fn read_symlink_as_mure_repo(path: &PathBuf) -> Result<MureRepo, Error> {
+/// Symlinks in the directory and its subdirectories (up to `search_depth` levels).
+/// The store, the archive and mirror directories, hidden directories and git repositories are not searched.
+pub fn find_symlinks(config: &Config, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    Ok(find_work_paths(config, dir)?
+        .into_iter()
+        .filter(|path| path.is_symlink())
+        .collect())
+}
+
+/// Symlinks in the directory and all of its subdirectories including hidden ones.
+/// Unlike find_symlinks, links nested by `clone --into` at any depth are found,
+/// so that repositories linked from them are not regarded as orphaned.
+pub fn find_all_symlinks(config: &Config, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    Ok(walk_work_paths(config, dir, usize::MAX, true)?
+        .into_iter()
+        .filter(|path| path.is_symlink())
+        .collect())
+}
+
+/// Symlinks and git repositories in the directory and its subdirectories (up to `search_depth` levels)
+fn find_work_paths(config: &Config, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    walk_work_paths(config, dir, config.search_depth(), false)
+}
+
+fn walk_work_paths(
+    config: &Config,
+    dir: &Path,
+    max_depth: usize,
+    hidden: bool,
+) -> Result<Vec<PathBuf>, Error> {
+    let skipped = [
+        config.repos_store_path(),
+        config.archive_path(),
+        config.mirrors_path(),
+    ];
+    let mut found = vec![];
+    let mut dirs = vec![(dir.to_path_buf(), 0)];
+    while let Some((current, depth)) = dirs.pop() {
+        let entries = match current.read_dir() {
+            Ok(entries) => entries,
+            Err(e) if depth == 0 => return Err(e.into()),
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // file_type of DirEntry doesn't follow symlinks
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_symlink() {
+                found.push(path);
+            } else if !file_type.is_dir()
+                || skipped.contains(&path)
+                || (!hidden && entry.file_name().to_string_lossy().starts_with('.'))
+            {
+                continue;
+            } else if path.join(".git").exists() {
+                found.push(path);
+            } else if depth < max_depth {
+                dirs.push((path, depth + 1));
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Git repository placed directly in base_dir (not a symlink to the store).
+/// It is managed by mure only if its origin is on GitHub and the directory is named after it.
+pub fn read_directory_as_mure_repo(path: &Path) -> Option<MureRepo> {
+    let repo = Repository::open(path).ok()?;
+    let remote = repo.find_remote("origin").ok()?;
+    let info = RepoInfo::parse_url(remote.url()?)?;
+    if path.file_name()? != info.repo.as_str() {
+        return None;
+    }
+    Some(MureRepo {
+        relative_path: path.to_path_buf(),
+        absolute_path: std::fs::canonicalize(path).ok()?,
+        repo: info,
+    })
+}
+
+/// Whether any of patterns matches `repo`, `owner/repo` or `domain/owner/repo`
+fn is_excluded(repo: &RepoInfo, excludes: &[Pattern]) -> bool {
+    let names = [
+        repo.repo.clone(),
+        repo.name_with_owner(),
+        repo.fully_qualified_name(),
+    ];
+    excludes
+        .iter()
+        .any(|pattern| names.iter().any(|name| pattern.matches(name)))
+}
+
+/// Repository of the symlink pointing to `<domain>/<owner>/<repo>` of the store
+pub fn read_symlink_as_mure_repo(path: &PathBuf) -> Result<MureRepo, Error> {
+    let error = |reason: &str| Error::from_str(&format!("{}: {reason}", path.display()));
     let absolute_path = match std::fs::canonicalize(path) {
         Ok(path) => path,
-        Err(_) => return Err(Error::from_str("failed to get absolute path")),
+        Err(_) => {
+            let target = std::fs::read_link(path).map_or("the target".to_string(), |target| {
+                target.display().to_string()
+            });
+            return Err(error(&format!("{target} doesn't exist (dangling symlink)")));
+        }
     };
     let Some(owner) = absolute_path.parent() else {
-        return Err(Error::from_str("failed to get owner"));
+        return Err(error("failed to get owner from the target"));
     };
     let Some(domain) = owner.parent() else {
-        return Err(Error::from_str("failed to get domain"));
+        return Err(error("failed to get domain from the target"));
     };
     let repo_name = match absolute_path.file_name() {
         Some(path) => match path.to_str() {
             Some(path) => path.to_string(),
-            None => return Err(Error::from_str("failed to get repo name")),
+            None => return Err(error("the name of the target is not UTF-8")),
         },
-        None => return Err(Error::from_str("failed to get repo name")),
+        None => return Err(error("failed to get repo name from the target")),
     };
     let repo = match (owner.file_name(), domain.file_name()) {
         (Some(owner), Some(domain)) => RepoInfo {
@@ -98,7 +396,7 @@ fn read_symlink_as_mure_repo(path: &PathBuf) -> Result<MureRepo, Error> {
             domain: domain.to_string_lossy().to_string(),
             repo: repo_name,
         },
-        _ => return Err(Error::from_str("failed to get owner or domain")),
+        _ => return Err(error("failed to get owner or domain from the target")),
     };
     Ok(MureRepo {
         relative_path: path.clone(),
@@ -109,38 +407,25 @@ fn read_symlink_as_mure_repo(path: &PathBuf) -> Result<MureRepo, Error> {
 
 #[cfg(test)]
 mod tests {
+    use assay::assay;
     use mktemp::Temp;
 
     use crate::verbosity::Verbosity;
 
     use super::*;
+    use crate::test_fixture::config_with_base_dir;
 
     #[test]
     fn test_search_mure_repo() {
         let temp_dir = Temp::new_dir().expect("failed to create temp dir");
 
-        let config: Config = toml::from_str(
-            format!(
-                r#"
-            [core]
-            base_dir = "{}"
-
-            [github]
-            username = "kitsuyui"
-
-            [shell]
-            cd_shims = "mucd"
-        "#,
-                temp_dir.to_str().unwrap()
-            )
-            .as_str(),
-        )
-        .unwrap();
+        let config = config_with_base_dir(&temp_dir);
         let repos = search_mure_repo(&config);
         assert_eq!(repos.len(), 0);
         crate::app::clone::clone(
             &config,
             "https://github.com/kitsuyui/mure",
+            &crate::app::clone::CloneOptions::default(),
             Verbosity::Normal,
         )
         .unwrap();
@@ -162,30 +447,215 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_find_symlinks() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let base = config.base_path();
+        let store_path = config.repo_store_path("github.com", "kitsuyui", "mure");
+        Repository::init(&store_path).unwrap();
+        let link = |path: PathBuf| {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::os::unix::fs::symlink(&store_path, &path).unwrap();
+            path
+        };
+        let top = link(base.join("mure"));
+        let nested = link(base.join("clients/acme/mure"));
+        let hidden = link(base.join(".hidden/mure"));
+        let deep = link(base.join("a/b/c/d/mure"));
+        link(base.join("plain/.git/mure"));
+        link(config.archive_path().join("mure"));
+        assert_eq!(
+            find_symlinks(&config, &base).unwrap(),
+            vec![nested.clone(), top.clone()]
+        );
+        assert_eq!(
+            find_all_symlinks(&config, &base).unwrap(),
+            vec![hidden, deep, nested.clone(), top]
+        );
+
+        let repos = search_mure_repo(&config);
+        assert_eq!(repos.len(), 2);
+        let repo = repos
+            .into_iter()
+            .flatten()
+            .find(|r| r.relative_path == nested);
+        assert_eq!(
+            repo.unwrap().repo.fully_qualified_name(),
+            "github.com/kitsuyui/mure"
+        );
+        assert!(find_symlinks(&config, &base.join("nowhere")).is_err());
+    }
+
+    #[test]
+    fn test_search_real_directories() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let mut config = config_with_base_dir(&temp_dir);
+        config.core.search_depth = Some(1);
+        let base = config.base_path();
+        let init = |path: &str, origin: Option<&str>| {
+            let repo = Repository::init(base.join(path)).unwrap();
+            if let Some(origin) = origin {
+                repo.remote("origin", origin).unwrap();
+            }
+        };
+        init("work/mure", Some("https://github.com/kitsuyui/mure.git"));
+        init("renamed", Some("https://github.com/kitsuyui/mure.git"));
+        init("local", None);
+        init("a/b/deep", Some("https://github.com/kitsuyui/deep.git"));
+
+        let repos: Vec<MureRepo> = search_mure_repo(&config).into_iter().flatten().collect();
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].relative_path, base.join("work/mure"));
+        assert_eq!(
+            repos[0].repo.fully_qualified_name(),
+            "github.com/kitsuyui/mure"
+        );
+        assert!(find_symlinks(&config, &base).unwrap().is_empty());
+        assert_eq!(config.search_depth(), 1);
+    }
+
+    #[assay]
+    fn test_repo_filter() {
+        let state_dir = Temp::new_dir().unwrap();
+        std::env::set_var("MURE_STATE_PATH", state_dir.join("state.json"));
+        let mut state = State::default();
+        state.add_tags("mure", &["rust".to_string()]);
+        state.save().unwrap();
+
+        let repo = |name: &str| MureRepo {
+            relative_path: PathBuf::from(name),
+            absolute_path: PathBuf::from(name),
+            repo: RepoInfo {
+                domain: "github.com".to_string(),
+                owner: "kitsuyui".to_string(),
+                repo: name.to_string(),
+            },
+        };
+        let repos = || {
+            vec![
+                Ok(repo("mure")),
+                Ok(repo("other")),
+                Err(Error::from_str("broken")),
+            ]
+        };
+
+        assert_eq!(RepoFilter::default().apply(repos()).unwrap().len(), 3);
+        let filtered = RepoFilter::tagged(Some("rust".to_string()))
+            .apply(repos())
+            .unwrap();
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].as_ref().unwrap().repo.repo, "mure");
+        assert!(filtered[1].is_err());
+        let filtered = RepoFilter::tagged(Some("work".to_string()))
+            .apply(repos())
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+    }
+
+    #[test]
+    fn test_repo_filter_lang() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let repo = |name: &str, manifest: &str| {
+            let path = temp_dir.join(name);
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join(manifest), "").unwrap();
+            Ok(MureRepo {
+                relative_path: PathBuf::from(name),
+                absolute_path: path,
+                repo: RepoInfo {
+                    domain: "github.com".to_string(),
+                    owner: "kitsuyui".to_string(),
+                    repo: name.to_string(),
+                },
+            })
+        };
+        let repos = vec![repo("mure", "Cargo.toml"), repo("web", "package.json")];
+
+        let filter = RepoFilter::parse(None, &["lang=Rust".to_string()]).unwrap();
+        assert_eq!(filter.lang, Some("rust".to_string()));
+        let filtered = filter.apply(repos).unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].as_ref().unwrap().repo.repo, "mure");
+
+        let filter = RepoFilter::parse(None, &["tag=oss".to_string()]).unwrap();
+        assert_eq!(filter.tag, Some("oss".to_string()));
+        assert_eq!(
+            RepoFilter::parse(None, &["owner=kitsuyui".to_string()])
+                .unwrap_err()
+                .to_string(),
+            "invalid filter owner=kitsuyui. Use key=value (keys: tag, lang)"
+        );
+    }
+
+    #[test]
+    fn test_format_tree() {
+        let repo = |domain: &str, owner: &str, name: &str| RepoInfo {
+            domain: domain.to_string(),
+            owner: owner.to_string(),
+            repo: name.to_string(),
+        };
+        let repos = vec![
+            repo("github.com", "kitsuyui", "mure"),
+            repo("ghe.example.com", "team", "app"),
+            repo("github.com", "kitsuyui", "dotfiles"),
+            repo("github.com", "rust-lang", "rust"),
+        ];
+        assert_eq!(
+            format_tree(&repos),
+            "ghe.example.com (1)
+└── team (1)
+    └── app
+
+github.com (3)
+├── kitsuyui (2)
+│   ├── dotfiles
+│   └── mure
+└── rust-lang (1)
+    └── rust
+"
+        );
+        assert_eq!(format_tree(&[]), "");
+    }
+
+    #[test]
+    fn test_exclude() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let base = temp_dir.as_path();
+        for repo in ["mure", "archive-old", "forked"] {
+            let owner = if repo == "forked" {
+                "forks"
+            } else {
+                "kitsuyui"
+            };
+            let store = base.join("repo").join("github.com").join(owner).join(repo);
+            std::fs::create_dir_all(&store).unwrap();
+            std::os::unix::fs::symlink(&store, base.join(repo)).unwrap();
+        }
+        std::fs::write(base.join(".mureignore"), "# comment\n\nforks/**\n").unwrap();
+
+        let mut config = config_with_base_dir(base);
+        config.core.exclude = Some(vec!["archive-*".to_string()]);
+        let repos = search_mure_repo(&config);
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].as_ref().unwrap().repo.repo, "mure");
+
+        let mut config = config_with_base_dir(base);
+        config.core.exclude = Some(vec!["[invalid".to_string()]);
+        let repos = search_mure_repo(&config);
+        assert_eq!(repos.iter().filter(|repo| repo.is_err()).count(), 1);
+        assert_eq!(repos.iter().filter(|repo| repo.is_ok()).count(), 2);
+    }
+
     #[test]
     fn test_app() {
         let temp_dir = Temp::new_dir().expect("failed to create temp dir");
 
-        let config: Config = toml::from_str(
-            format!(
-                r#"
-            [core]
-            base_dir = "{}"
-
-            [github]
-            username = "kitsuyui"
-
-            [shell]
-            cd_shims = "mucd"
-        "#,
-                temp_dir.to_str().unwrap()
-            )
-            .as_str(),
-        )
-        .unwrap();
+        let config = config_with_base_dir(&temp_dir);
         crate::app::clone::clone(
             &config,
             "https://github.com/kitsuyui/mure",
+            &crate::app::clone::CloneOptions::default(),
             Verbosity::Normal,
         )
         .unwrap();
@@ -193,9 +663,13 @@ mod tests {
         assert_eq!(repos.len(), 1);
 
         // list doesn't panic
-        list(&config, false, false).unwrap();
-        list(&config, true, false).unwrap();
-        list(&config, false, true).unwrap();
-        list(&config, true, true).unwrap();
+        let filter = RepoFilter::default();
+        list(&config, false, false, false, false, false, &filter).unwrap();
+        list(&config, true, false, false, false, false, &filter).unwrap();
+        list(&config, false, true, false, false, false, &filter).unwrap();
+        list(&config, true, true, false, false, false, &filter).unwrap();
+        list(&config, false, false, true, false, false, &filter).unwrap();
+        list(&config, false, false, false, true, false, &filter).unwrap();
+        list(&config, false, false, false, false, true, &filter).unwrap();
     }
 }
@@ -0,0 +1,329 @@
+//! Export and import of the managed repositories in formats of other tools
+//!
+//! - `repo-manifest`: manifest XML of [repo](https://gerrit.googlesource.com/git-repo/)
+//! - `gita`: `repos.csv` of [gita](https://github.com/nosarthur/gita)
+//! - `ghq`: output of `ghq list`
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::app::clone::{clone_all, CloneOptions};
+use crate::app::list::search_mure_repo;
+use crate::config::{Config, ConfigSupport};
+use crate::git::RepositorySupport;
+use crate::github::repo::RepoInfo;
+use crate::misc::bulk::{BulkOptions, BulkSummary};
+use crate::mure_error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ManifestFormat {
+    RepoManifest,
+    Gita,
+    Ghq,
+}
+
+/// A repository managed by mure
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    repo: RepoInfo,
+    /// the symlink or directory in base_dir
+    path: PathBuf,
+    /// default branch of origin if known
+    revision: Option<String>,
+}
+
+/// Print the managed repositories in the format
+pub fn export(config: &Config, format: ManifestFormat) -> Result<(), Error> {
+    let base_path = config.base_path();
+    let mut entries = vec![];
+    for mure_repo in search_mure_repo(config) {
+        let mure_repo = match mure_repo {
+            Ok(mure_repo) => mure_repo,
+            Err(e) => {
+                eprintln!("{}", e.message());
+                continue;
+            }
+        };
+        let revision = Repository::open(&mure_repo.absolute_path)
+            .ok()
+            .and_then(|repo| repo.guess_default_branch("origin").ok().flatten());
+        entries.push(Entry {
+            repo: mure_repo.repo,
+            path: mure_repo.relative_path,
+            revision,
+        });
+    }
+    let output = match format {
+        ManifestFormat::RepoManifest => to_repo_manifest(&entries, &base_path),
+        ManifestFormat::Gita => to_gita(&entries),
+        ManifestFormat::Ghq => to_ghq(&entries),
+    };
+    print!("{output}");
+    Ok(())
+}
+
+/// Clone the repositories listed in the file of the format which are not cloned yet
+pub fn import(
+    config: &Config,
+    format: ManifestFormat,
+    path: &Path,
+    bulk: &BulkOptions,
+) -> Result<BulkSummary, Error> {
+    let content = std::fs::read_to_string(path)?;
+    let urls = match format {
+        ManifestFormat::RepoManifest => from_repo_manifest(&content)?,
+        ManifestFormat::Gita => from_gita(&content),
+        ManifestFormat::Ghq => from_ghq(&content),
+    };
+    clone_all(config, &urls, &CloneOptions::from_config(config), bulk)
+}
+
+/// One remote for each domain and projects named `owner/repo` at the same path as in base_dir
+fn to_repo_manifest(entries: &[Entry], base_path: &Path) -> String {
+    let domains = entries
+        .iter()
+        .map(|entry| entry.repo.domain.as_str())
+        .collect::<BTreeSet<_>>();
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        "<manifest>".to_string(),
+    ];
+    for domain in domains {
+        lines.push(format!(
+            r#"  <remote name="{}" fetch="https://{}/" />"#,
+            escape(domain),
+            escape(domain)
+        ));
+    }
+    for entry in entries {
+        let path = entry.path.strip_prefix(base_path).unwrap_or(&entry.path);
+        let revision = entry
+            .revision
+            .as_ref()
+            .map(|revision| format!(r#" revision="{}""#, escape(revision)))
+            .unwrap_or_default();
+        lines.push(format!(
+            r#"  <project name="{}" path="{}" remote="{}"{revision} />"#,
+            escape(&entry.repo.name_with_owner()),
+            escape(&path.to_string_lossy()),
+            escape(&entry.repo.domain),
+        ));
+    }
+    lines.push("</manifest>".to_string());
+    lines.join("\n") + "\n"
+}
+
+/// `path,name,type,flags` of each repository without a header
+fn to_gita(entries: &[Entry]) -> String {
+    entries
+        .iter()
+        .map(|entry| format!("{},{},,\n", entry.path.display(), entry.repo.repo))
+        .collect()
+}
+
+/// `domain/owner/repo` of each repository
+fn to_ghq(entries: &[Entry]) -> String {
+    entries
+        .iter()
+        .map(|entry| format!("{}\n", entry.repo.fully_qualified_name()))
+        .collect()
+}
+
+static TAG: Lazy<Regex> = Lazy::new(|| {
+    #[allow(clippy::unwrap_used)]
+    Regex::new(r"<(remote|default|project)\b([^>]*)>").unwrap()
+});
+
+static ATTRIBUTE: Lazy<Regex> = Lazy::new(|| {
+    #[allow(clippy::unwrap_used)]
+    Regex::new(r#"([\w-]+)\s*=\s*"([^"]*)""#).unwrap()
+});
+
+/// URLs of the projects. Projects without a remote or with a relative fetch URL are errors.
+fn from_repo_manifest(content: &str) -> Result<Vec<String>, Error> {
+    let mut remotes = BTreeMap::new();
+    let mut default_remote = None;
+    let mut projects = vec![];
+    for caps in TAG.captures_iter(content) {
+        let attributes = ATTRIBUTE
+            .captures_iter(&caps[2])
+            .map(|attribute| (attribute[1].to_string(), unescape(&attribute[2])))
+            .collect::<BTreeMap<_, _>>();
+        match &caps[1] {
+            "remote" => {
+                if let (Some(name), Some(fetch)) = (attributes.get("name"), attributes.get("fetch"))
+                {
+                    remotes.insert(name.clone(), fetch.clone());
+                }
+            }
+            "default" => default_remote = attributes.get("remote").cloned(),
+            _ => projects.push(attributes),
+        }
+    }
+    let mut urls = vec![];
+    for project in projects {
+        let Some(name) = project.get("name") else {
+            continue;
+        };
+        let Some(remote) = project.get("remote").or(default_remote.as_ref()) else {
+            return Err(Error::from_str(&format!("{name} has no remote")));
+        };
+        let Some(fetch) = remotes.get(remote).filter(|fetch| fetch.contains("://")) else {
+            return Err(Error::from_str(&format!(
+                "remote {remote} of {name} is not defined with an absolute fetch URL"
+            )));
+        };
+        urls.push(format!("{}/{name}", fetch.trim_end_matches('/')));
+    }
+    Ok(urls)
+}
+
+/// URLs of origin of the repositories at the paths in the first column.
+/// Repositories which don't exist here are skipped as their URLs are unknown.
+fn from_gita(content: &str) -> Vec<String> {
+    let mut urls = vec![];
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let path = line.split(',').next().unwrap_or_default();
+        let url = Repository::open(path).ok().and_then(|repo| {
+            let remote = repo.find_remote("origin").ok()?;
+            remote.url().map(str::to_string)
+        });
+        match url {
+            Some(url) => urls.push(url),
+            None => eprintln!("{path}: skipped (no origin found)"),
+        }
+    }
+    urls
+}
+
+/// `domain/owner/repo` as https URLs
+fn from_ghq(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("https://{line}"))
+        .collect()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&gt;", ">")
+        .replace("&lt;", "<")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::Fixture;
+
+    fn entries(base_path: &Path) -> Vec<Entry> {
+        vec![
+            Entry {
+                repo: RepoInfo::parse_url("https://github.com/kitsuyui/mure").unwrap(),
+                path: base_path.join("mure"),
+                revision: Some("main".to_string()),
+            },
+            Entry {
+                repo: RepoInfo::parse_url("https://github.com/acme/api").unwrap(),
+                path: base_path.join("clients/acme/api"),
+                revision: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_repo_manifest() {
+        let base_path = PathBuf::from("/home/you/.dev");
+        let manifest = to_repo_manifest(&entries(&base_path), &base_path);
+        assert_eq!(
+            manifest,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest>
+  <remote name="github.com" fetch="https://github.com/" />
+  <project name="kitsuyui/mure" path="mure" remote="github.com" revision="main" />
+  <project name="acme/api" path="clients/acme/api" remote="github.com" />
+</manifest>
+"#
+        );
+        assert_eq!(
+            from_repo_manifest(&manifest).unwrap(),
+            vec![
+                "https://github.com/kitsuyui/mure",
+                "https://github.com/acme/api"
+            ]
+        );
+
+        // a manifest of repo itself
+        let manifest = r#"<manifest>
+  <remote name="aosp" fetch="https://android.googlesource.com" />
+  <remote fetch=".." name="relative" />
+  <default revision="main" remote="aosp" />
+  <project path="build/make" name="platform/build" groups="pdk" />
+  <project name="kitsuyui/mure" remote="relative"/>
+</manifest>"#;
+        assert_eq!(
+            from_repo_manifest(manifest).unwrap_err().to_string(),
+            "remote relative of kitsuyui/mure is not defined with an absolute fetch URL"
+        );
+        assert_eq!(
+            from_repo_manifest(&manifest.replace(r#" remote="relative""#, "")).unwrap(),
+            vec![
+                "https://android.googlesource.com/platform/build",
+                "https://android.googlesource.com/kitsuyui/mure"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gita() {
+        let base_path = PathBuf::from("/home/you/.dev");
+        assert_eq!(
+            to_gita(&entries(&base_path)),
+            "/home/you/.dev/mure,mure,,\n/home/you/.dev/clients/acme/api,api,,\n"
+        );
+
+        let fixture = Fixture::create().unwrap();
+        fixture
+            .repo
+            .remote("origin", "https://github.com/kitsuyui/mure")
+            .unwrap();
+        let path = fixture.repo.workdir().unwrap().display().to_string();
+        assert_eq!(
+            from_gita(&format!("{path},mure,,\n/nonexistent,missing,,\n")),
+            vec!["https://github.com/kitsuyui/mure"]
+        );
+    }
+
+    #[test]
+    fn test_ghq() {
+        let ghq = to_ghq(&entries(Path::new("/")));
+        assert_eq!(ghq, "github.com/kitsuyui/mure\ngithub.com/acme/api\n");
+        assert_eq!(
+            from_ghq(&ghq),
+            vec![
+                "https://github.com/kitsuyui/mure",
+                "https://github.com/acme/api"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(r#"a&b<"c">"#), "a&amp;b&lt;&quot;c&quot;&gt;");
+        assert_eq!(unescape(&escape(r#"a&b<"c">"#)), r#"a&b<"c">"#);
+    }
+}
@@ -0,0 +1,195 @@
+//! Start a brand new repository locally in the store layout, without a remote yet
+//!
+//! Files come from `[template] new_dir` (default `~/.mure/new`) rendered like `mure template apply`,
+//! e.g. README.md, LICENSE and .gitignore. Without the directory, only a README is written.
+use std::os::unix::fs as unix_fs;
+use std::path::Path;
+
+use git2::Repository;
+
+use crate::app::create::initialize;
+use crate::config::{Config, ConfigSupport};
+use crate::gh::GhClient;
+use crate::git::{DryRun, RepositorySupport};
+use crate::github::repo::RepoInfo;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+/// Create the repository `owner/repo` (or `repo` of github.username) in the store with the first commit
+/// and link it from base_dir. With `github`, it is also created on GitHub and pushed.
+pub fn new(
+    config: &Config,
+    name: &str,
+    template: Option<&str>,
+    github: bool,
+    public: bool,
+) -> Result<(), Error> {
+    let repo_info = parse_name(config, name)?;
+    let path = config.repo_store_path(&repo_info.domain, &repo_info.owner, &repo_info.repo);
+    let link = config.repo_work_path(&repo_info.domain, &repo_info.owner, &repo_info.repo);
+    for path in [&path, &link] {
+        if std::fs::symlink_metadata(path).is_ok() {
+            return Err(Error::from_str(&format!(
+                "{} already exists",
+                path.display()
+            )));
+        }
+    }
+    let path_text = path.display().to_string();
+    if DryRun::is_enabled() {
+        println!(
+            "{}",
+            Message::WouldCreateLocalRepository { path: &path_text }
+        );
+        if github {
+            let name = repo_info.name_with_owner();
+            println!("{}", Message::WouldCreateRepository { name: &name });
+        }
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&path)?;
+    Repository::init(&path)?;
+    let template_dir = match template {
+        Some(dir) => shellexpand::tilde(dir).to_string().into(),
+        None => config.new_template_path(),
+    };
+    // the default directory is optional
+    if template.is_some() || template_dir.is_dir() {
+        initialize(&path, &repo_info, &template_dir)?;
+    } else {
+        initialize_readme(&path, &repo_info)?;
+    }
+    if let Some(parent) = link.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    unix_fs::symlink(&path, &link)?;
+    println!("{}", Message::CreatedLocalRepository { path: &path_text });
+
+    if github {
+        let name = repo_info.name_with_owner();
+        GhClient::new().publish_repository(&name, &path, public)?;
+        println!("{}", Message::CreatedRepository { name: &name });
+    } else {
+        let link_text = link.display().to_string();
+        println!(
+            "{}",
+            Message::NextStepsForRemote {
+                path: &link_text,
+                url: &repo_info.ssh_url(),
+            }
+        );
+    }
+    Ok(())
+}
+
+/// `owner/repo` as it is, or `repo` owned by github.username
+fn parse_name(config: &Config, name: &str) -> Result<RepoInfo, Error> {
+    let name_with_owner = if name.contains('/') {
+        name.to_string()
+    } else {
+        format!("{}/{name}", config.github.username()?)
+    };
+    RepoInfo::parse_url(&format!("https://github.com/{name_with_owner}"))
+        .filter(|_| name_with_owner.split('/').count() == 2)
+        .ok_or_else(|| {
+            Error::from_str(&format!(
+                "invalid repository name {name}. It must be owner/repo or repo"
+            ))
+        })
+}
+
+/// The first commit with only a README
+fn initialize_readme(path: &Path, repo_info: &RepoInfo) -> Result<(), Error> {
+    std::fs::write(path.join("README.md"), format!("# {}\n", repo_info.repo))?;
+    let repo = Repository::open(path)?;
+    repo.add_paths(&["README.md"])?;
+    repo.commit_staged("Initial commit")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Template;
+    use crate::test_fixture::config_with_base_dir;
+    use assay::assay;
+    use mktemp::Temp;
+
+    fn config(base_dir: &Path) -> Config {
+        let mut config = config_with_base_dir(base_dir);
+        config.template = Some(Template {
+            new_dir: Some(format!("{}/no-such-templates", base_dir.display())),
+            ..Default::default()
+        });
+        config
+    }
+
+    #[test]
+    fn test_parse_name() {
+        let config = config(Path::new("/tmp"));
+        assert_eq!(
+            parse_name(&config, "myorg/newsvc")
+                .unwrap()
+                .name_with_owner(),
+            "myorg/newsvc"
+        );
+        assert_eq!(
+            parse_name(&config, "newsvc").unwrap().name_with_owner(),
+            "kitsuyui/newsvc"
+        );
+        assert!(parse_name(&config, "a/b/c").is_err());
+    }
+
+    #[test]
+    #[assay(
+        env = [
+            ("GIT_AUTHOR_NAME", "tester"),
+            ("GIT_AUTHOR_EMAIL", "test@example.com"),
+            ("GIT_COMMITTER_NAME", "tester"),
+            ("GIT_COMMITTER_EMAIL", "test@example.com"),
+        ]
+    )]
+    fn test_new() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config(&temp_dir);
+
+        new(&config, "plain", None, false, false).unwrap();
+        let link = config.base_path().join("plain");
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(
+            std::fs::read_to_string(link.join("README.md")).unwrap(),
+            "# plain\n"
+        );
+        let repo = Repository::open(&link).unwrap();
+        assert!(repo.is_clean().unwrap());
+        assert!(repo.remotes().unwrap().is_empty());
+        assert!(new(&config, "plain", None, false, false).is_err());
+
+        let template_dir = Temp::new_dir().unwrap();
+        std::fs::write(template_dir.join("LICENSE"), "Copyright {{owner}}\n").unwrap();
+        std::fs::write(template_dir.join(".gitignore"), "/target\n").unwrap();
+        new(
+            &config,
+            "myorg/templated",
+            template_dir.to_str(),
+            false,
+            false,
+        )
+        .unwrap();
+        let path = config.repo_store_path("github.com", "myorg", "templated");
+        assert_eq!(
+            std::fs::read_to_string(path.join("LICENSE")).unwrap(),
+            "Copyright myorg\n"
+        );
+        assert!(path.join(".gitignore").exists());
+
+        DryRun::set(true);
+        new(&config, "dry", None, true, false).unwrap();
+        DryRun::set(false);
+        assert!(std::fs::symlink_metadata(config.base_path().join("dry")).is_err());
+    }
+}
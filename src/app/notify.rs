@@ -0,0 +1,248 @@
+//! Unread GitHub notifications of the repositories cloned locally
+//!
+//! The inbox is polled with the cached ETag, so running it repeatedly costs no rate limit
+//! while nothing new arrives. Classic tokens need the `notifications` or `repo` scope.
+use std::collections::HashSet;
+
+use serde_derive::Deserialize;
+
+use crate::app::list::search_mure_repo;
+use crate::app::ui::open_browser;
+use crate::config::Config;
+use crate::github::rest::{get_json, patch, EtagCache, GITHUB_REST_URL};
+use crate::github::token::get_github_token;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+const GITHUB_DOMAIN: &str = "github.com";
+
+/// Notification thread as the REST API returns
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Notification {
+    pub id: String,
+    /// e.g. `review_requested`, `mention`
+    pub reason: String,
+    pub subject: Subject,
+    pub repository: NotificationRepository,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Subject {
+    pub title: String,
+    /// e.g. `PullRequest`, `Issue`, `Release`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// API URL of the subject. None for discussions.
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NotificationRepository {
+    /// e.g. `kitsuyui/mure`
+    pub full_name: String,
+    pub html_url: String,
+}
+
+/// Show unread notifications of the repositories in base_dir, or of all repositories with `all`
+pub fn list(config: &Config, all: bool) -> Result<(), Error> {
+    let token = get_github_token()?;
+    let mut cache = EtagCache::load()?;
+    // the first page has the latest 100 threads, which is enough for an inbox
+    let notifications: Vec<Notification> = get_json(
+        &format!("{GITHUB_REST_URL}/notifications?per_page=100"),
+        &token,
+        &mut cache,
+    )?;
+    cache.save()?;
+    let notifications = if all {
+        notifications
+    } else {
+        managed_only(notifications, &managed_names(config))
+    };
+    if notifications.is_empty() {
+        println!("{}", Message::NoUnreadNotifications);
+        return Ok(());
+    }
+    print!("{}", format_table(&notifications));
+    Ok(())
+}
+
+/// Mark the notification threads as read
+pub fn read(ids: &[String]) -> Result<(), Error> {
+    let token = get_github_token()?;
+    for id in ids {
+        patch(&thread_url(id), &token)?;
+        println!("{}", Message::MarkedNotificationAsRead { id });
+    }
+    Ok(())
+}
+
+/// Open the subject of the notification (e.g. the pull request) in the browser
+pub fn open(id: &str) -> Result<(), Error> {
+    let token = get_github_token()?;
+    let mut cache = EtagCache::load()?;
+    let notification: Notification = get_json(&thread_url(id), &token, &mut cache)?;
+    cache.save()?;
+    open_browser(&html_url(&notification))
+}
+
+fn thread_url(id: &str) -> String {
+    format!("{GITHUB_REST_URL}/notifications/threads/{id}")
+}
+
+/// `owner/repo` of the repositories of github.com in base_dir in lower case
+fn managed_names(config: &Config) -> HashSet<String> {
+    search_mure_repo(config)
+        .into_iter()
+        .flatten()
+        .filter(|mure_repo| mure_repo.repo.domain == GITHUB_DOMAIN)
+        .map(|mure_repo| mure_repo.repo.name_with_owner().to_lowercase())
+        .collect()
+}
+
+/// Names on GitHub are case insensitive
+fn managed_only(notifications: Vec<Notification>, names: &HashSet<String>) -> Vec<Notification> {
+    notifications
+        .into_iter()
+        .filter(|notification| names.contains(&notification.repository.full_name.to_lowercase()))
+        .collect()
+}
+
+/// Page of the subject on GitHub. The repository page if the subject can't be told from its API URL.
+fn html_url(notification: &Notification) -> String {
+    let repo_url = &notification.repository.html_url;
+    let api_prefix = format!(
+        "{GITHUB_REST_URL}/repos/{}/",
+        notification.repository.full_name
+    );
+    let path = notification
+        .subject
+        .url
+        .as_deref()
+        .and_then(|url| url.strip_prefix(&api_prefix));
+    match path.and_then(|path| path.split_once('/')) {
+        Some(("pulls", number)) => format!("{repo_url}/pull/{number}"),
+        Some(("issues", number)) => format!("{repo_url}/issues/{number}"),
+        Some(("commits", sha)) => format!("{repo_url}/commit/{sha}"),
+        // the API URL has the id of the release, not the tag
+        Some(("releases", _)) => format!("{repo_url}/releases"),
+        _ if notification.subject.kind == "Discussion" => format!("{repo_url}/discussions"),
+        _ => repo_url.to_string(),
+    }
+}
+
+fn format_table(notifications: &[Notification]) -> String {
+    let mut table = "Id\tRepository\tType\tTitle\tReason\n".to_string();
+    for notification in notifications {
+        table.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            notification.id,
+            notification.repository.full_name,
+            notification.subject.kind,
+            notification.subject.title,
+            notification.reason,
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notifications() -> Vec<Notification> {
+        serde_json::from_str(
+            r#"[
+            {
+                "id": "101",
+                "unread": true,
+                "reason": "review_requested",
+                "updated_at": "2026-10-16T09:00:00Z",
+                "subject": {
+                    "title": "Support gist",
+                    "url": "https://api.github.com/repos/kitsuyui/mure/pulls/12",
+                    "latest_comment_url": null,
+                    "type": "PullRequest"
+                },
+                "repository": {
+                    "full_name": "kitsuyui/mure",
+                    "html_url": "https://github.com/kitsuyui/mure"
+                }
+            },
+            {
+                "id": "102",
+                "unread": true,
+                "reason": "subscribed",
+                "updated_at": "2026-10-15T09:00:00Z",
+                "subject": {
+                    "title": "v1.0.0",
+                    "url": "https://api.github.com/repos/octocat/Hello-World/releases/1",
+                    "latest_comment_url": null,
+                    "type": "Release"
+                },
+                "repository": {
+                    "full_name": "octocat/Hello-World",
+                    "html_url": "https://github.com/octocat/Hello-World"
+                }
+            }
+        ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_managed_only() {
+        let names = HashSet::from(["kitsuyui/mure".to_string()]);
+        let managed = managed_only(notifications(), &names);
+        assert_eq!(managed.len(), 1);
+        assert_eq!(managed[0].id, "101");
+        let names = HashSet::from(["octocat/hello-world".to_string()]);
+        assert_eq!(managed_only(notifications(), &names)[0].id, "102");
+    }
+
+    #[test]
+    fn test_html_url() {
+        let [pull_request, release] = <[Notification; 2]>::try_from(notifications()).unwrap();
+        assert_eq!(
+            html_url(&pull_request),
+            "https://github.com/kitsuyui/mure/pull/12"
+        );
+        assert_eq!(
+            html_url(&release),
+            "https://github.com/octocat/Hello-World/releases"
+        );
+        let with_subject = |kind: &str, url: Option<&str>| Notification {
+            subject: Subject {
+                title: String::new(),
+                kind: kind.to_string(),
+                url: url.map(str::to_string),
+            },
+            ..pull_request.clone()
+        };
+        assert_eq!(
+            html_url(&with_subject(
+                "Issue",
+                Some("https://api.github.com/repos/kitsuyui/mure/issues/3")
+            )),
+            "https://github.com/kitsuyui/mure/issues/3"
+        );
+        assert_eq!(
+            html_url(&with_subject("Discussion", None)),
+            "https://github.com/kitsuyui/mure/discussions"
+        );
+        assert_eq!(
+            html_url(&with_subject("CheckSuite", None)),
+            "https://github.com/kitsuyui/mure"
+        );
+    }
+
+    #[test]
+    fn test_format_table() {
+        assert_eq!(
+            format_table(&notifications()),
+            "Id\tRepository\tType\tTitle\tReason\n\
+             101\tkitsuyui/mure\tPullRequest\tSupport gist\treview_requested\n\
+             102\toctocat/Hello-World\tRelease\tv1.0.0\tsubscribed\n"
+        );
+    }
+}
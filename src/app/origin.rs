@@ -0,0 +1,182 @@
+//! Verification that the store path of each repository reflects its origin
+//!
+//! If origin is changed after cloning, refresh and API lookups act on the repository of the path.
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+use crate::app::list::{search_archived_repo, search_mure_repo, MureRepo, RepoFilter};
+use crate::config::{Config, ConfigSupport};
+use crate::github::repo::RepoInfo;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+/// Repository whose origin points to another repository than the store path
+#[derive(Debug, PartialEq, Eq)]
+struct Mismatch {
+    link: PathBuf,
+    store_path: PathBuf,
+    stored: RepoInfo,
+    origin: RepoInfo,
+}
+
+/// Report repositories whose origin doesn't match the store path.
+/// With `fix`, they are moved to the store path of origin and their symlinks are updated.
+pub fn check_origin(
+    config: &Config,
+    archived: bool,
+    fix: bool,
+    filter: &RepoFilter,
+) -> Result<(), Error> {
+    let repos = if archived {
+        search_archived_repo(config)
+    } else {
+        search_mure_repo(config)
+    };
+    let mut mismatches = vec![];
+    for repo in filter.apply(repos)? {
+        match repo.and_then(|mure_repo| find_mismatch(&mure_repo)) {
+            Ok(Some(mismatch)) => mismatches.push(mismatch),
+            Ok(None) => (),
+            Err(e) => println!("{}", e.message()),
+        }
+    }
+    if mismatches.is_empty() {
+        println!("{}", Message::AllOriginsMatch);
+        return Ok(());
+    }
+    for mismatch in &mismatches {
+        println!(
+            "{}",
+            Message::OriginMismatch {
+                link: &mismatch.link.display().to_string(),
+                origin: &mismatch.origin.fully_qualified_name(),
+                stored: &mismatch.stored.fully_qualified_name(),
+            }
+        );
+        if fix {
+            match repair(config, mismatch) {
+                Ok(to) => println!(
+                    "{}",
+                    Message::MovedRepository {
+                        from: &mismatch.store_path.display().to_string(),
+                        to: &to.display().to_string(),
+                    }
+                ),
+                Err(e) => println!("{}", e.message()),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn find_mismatch(mure_repo: &MureRepo) -> Result<Option<Mismatch>, Error> {
+    let name = mure_repo.repo.name_with_owner();
+    let repo = Repository::open(&mure_repo.absolute_path)?;
+    let url = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|remote| remote.url().map(str::to_string))
+        .ok_or_else(|| Error::from_str(&format!("{name}: origin is not found")))?;
+    let Some(origin) = RepoInfo::parse_url(&url) else {
+        return Err(Error::from_str(&format!(
+            "{name}: origin {url} is not a GitHub repository"
+        )));
+    };
+    // names on GitHub are case insensitive
+    if origin
+        .fully_qualified_name()
+        .eq_ignore_ascii_case(&mure_repo.repo.fully_qualified_name())
+    {
+        return Ok(None);
+    }
+    Ok(Some(Mismatch {
+        link: mure_repo.relative_path.clone(),
+        store_path: mure_repo.absolute_path.clone(),
+        stored: mure_repo.repo.clone(),
+        origin,
+    }))
+}
+
+/// Move the repository to the store path of origin and point the symlink to it
+fn repair(config: &Config, mismatch: &Mismatch) -> Result<PathBuf, Error> {
+    let origin = &mismatch.origin;
+    let to = config.repo_store_path(&origin.domain, &origin.owner, &origin.repo);
+    if to.exists() {
+        return Err(Error::from_str(&format!(
+            "{} already exists. Remove either of them first",
+            to.display()
+        )));
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(&mismatch.store_path, &to)?;
+    relink(&mismatch.link, &to)?;
+    Ok(to)
+}
+
+fn relink(link: &Path, to: &Path) -> Result<(), Error> {
+    std::fs::remove_file(link)?;
+    std::os::unix::fs::symlink(to, link)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::list::read_symlink_as_mure_repo;
+    use crate::test_fixture::config_with_base_dir;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_check_origin() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let create = |owner: &str, name: &str, origin: &str| {
+            let store_path = config.repo_store_path("github.com", owner, name);
+            let repo = Repository::init(&store_path).unwrap();
+            repo.remote("origin", origin).unwrap();
+            let link = config.base_path().join(name);
+            std::os::unix::fs::symlink(&store_path, &link).unwrap();
+            link
+        };
+        let matched = create("kitsuyui", "mure", "https://github.com/Kitsuyui/Mure.git");
+        let moved = create("kitsuyui", "old", "git@github.com:someone/new.git");
+
+        let mure_repo = |link: &PathBuf| read_symlink_as_mure_repo(link).unwrap();
+        assert_eq!(find_mismatch(&mure_repo(&matched)).unwrap(), None);
+        let mismatch = find_mismatch(&mure_repo(&moved)).unwrap().unwrap();
+        assert_eq!(
+            mismatch.origin.fully_qualified_name(),
+            "github.com/someone/new"
+        );
+        assert_eq!(
+            mismatch.stored.fully_qualified_name(),
+            "github.com/kitsuyui/old"
+        );
+
+        check_origin(&config, false, true, &RepoFilter::default()).unwrap();
+        let to = config.repo_store_path("github.com", "someone", "new");
+        assert!(to.exists());
+        assert_eq!(
+            std::fs::canonicalize(&moved).unwrap(),
+            std::fs::canonicalize(&to).unwrap()
+        );
+        assert_eq!(find_mismatch(&mure_repo(&moved)).unwrap(), None);
+
+        // the destination is taken
+        let taken = create("kitsuyui", "taken", "https://github.com/someone/new");
+        let mismatch = find_mismatch(&mure_repo(&taken)).unwrap().unwrap();
+        assert!(repair(&config, &mismatch).is_err());
+
+        let no_origin = config.repo_store_path("github.com", "kitsuyui", "local");
+        Repository::init(&no_origin).unwrap();
+        let link = config.base_path().join("local");
+        std::os::unix::fs::symlink(&no_origin, &link).unwrap();
+        assert_eq!(
+            find_mismatch(&mure_repo(&link)).unwrap_err().to_string(),
+            "kitsuyui/local: origin is not found"
+        );
+    }
+}
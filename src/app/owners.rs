@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::github::api::{get_owner, owner_query::OwnerQueryRepositoryOwnerOn};
+use crate::github::token::get_github_token;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+use super::list::search_mure_repo;
+
+const GITHUB_DOMAIN: &str = "github.com";
+
+/// Show the number of managed repositories per owner with the type of the owner and my role in it
+pub fn owners(config: &Config) -> Result<(), Error> {
+    let repos = search_mure_repo(config);
+    if repos.is_empty() {
+        println!("{}", Message::NoRepositoriesFound);
+        return Ok(());
+    }
+    let mut owners: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for repo in repos {
+        match repo {
+            Ok(mure_repo) => {
+                *owners
+                    .entry((mure_repo.repo.domain, mure_repo.repo.owner))
+                    .or_default() += 1;
+            }
+            Err(e) => println!("{}", e.message()),
+        }
+    }
+
+    // Counts are still useful without API access
+    let token = match get_github_token() {
+        Ok(token) => Some(token),
+        Err(e) => {
+            println!("{e}");
+            None
+        }
+    };
+    println!("Repositories\tType\tRole\tOwner");
+    for ((domain, owner), count) in owners {
+        let (kind, role) = match &token {
+            Some(token) if domain == GITHUB_DOMAIN => match get_owner(token, &owner) {
+                Ok(Some(info)) => kind_and_role(&info.on),
+                Ok(None) => ("not found", "-"),
+                Err(e) => {
+                    println!("{owner}: {e}");
+                    ("unknown", "-")
+                }
+            },
+            _ => ("unknown", "-"),
+        };
+        let name = match domain.as_str() {
+            GITHUB_DOMAIN => owner,
+            _ => format!("{domain}/{owner}"),
+        };
+        println!("{count}\t{kind}\t{role}\t{name}");
+    }
+    Ok(())
+}
+
+/// Type of the owner and my role in it
+fn kind_and_role(owner: &OwnerQueryRepositoryOwnerOn) -> (&'static str, &'static str) {
+    match owner {
+        OwnerQueryRepositoryOwnerOn::Organization(org) => {
+            let role = if org.viewer_can_administer {
+                "admin"
+            } else if org.viewer_is_a_member {
+                "member"
+            } else {
+                "none"
+            };
+            ("organization", role)
+        }
+        OwnerQueryRepositoryOwnerOn::User(user) => {
+            let role = if user.is_viewer { "self" } else { "none" };
+            ("user", role)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::api::owner_query::{
+        OwnerQueryRepositoryOwnerOnOrganization, OwnerQueryRepositoryOwnerOnUser,
+    };
+    use crate::test_fixture::config_with_base_dir;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_kind_and_role() {
+        let org = |viewer_can_administer, viewer_is_a_member| {
+            OwnerQueryRepositoryOwnerOn::Organization(OwnerQueryRepositoryOwnerOnOrganization {
+                viewer_can_administer,
+                viewer_is_a_member,
+            })
+        };
+        assert_eq!(kind_and_role(&org(true, true)), ("organization", "admin"));
+        assert_eq!(kind_and_role(&org(false, true)), ("organization", "member"));
+        assert_eq!(kind_and_role(&org(false, false)), ("organization", "none"));
+
+        let user = |is_viewer| {
+            OwnerQueryRepositoryOwnerOn::User(OwnerQueryRepositoryOwnerOnUser { is_viewer })
+        };
+        assert_eq!(kind_and_role(&user(true)), ("user", "self"));
+        assert_eq!(kind_and_role(&user(false)), ("user", "none"));
+    }
+
+    #[test]
+    fn test_owners_without_repositories() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        owners(&config).unwrap();
+    }
+}
@@ -1,11 +1,139 @@
+use std::io::Write;
 use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use clap_complete::Shell;
+
+use crate::app::clone::{clone, CloneOptions};
+use crate::app::list::search_mure_repo;
 use crate::config::{Config, ConfigSupport};
+use crate::github::api::{search_all_repositories, GITHUB_GRAPHQL_URL};
+use crate::github::repo::RepoInfo;
+use crate::github::token::get_github_token;
+use crate::i18n::Message;
 use crate::mure_error::Error;
+use crate::remote_cache::RemoteCache;
+use crate::verbosity::Verbosity;
 
-pub fn path(config: &Config, name: &str) -> Result<(), Error> {
-    println!("{}", resolve(config, name)?.display());
-    Ok(())
+/// Exit code of `mure path` for a repository of the user on GitHub which is not cloned yet
+pub const NOT_CLONED_EXIT_CODE: i32 = 3;
+
+/// Repositories of the user on GitHub are fetched again after this
+const REMOTE_CACHE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Print the path of the repository.
+/// Without the name (e.g. `mucd` without arguments), the repository is picked with the fuzzy finder.
+/// Nothing is printed if the picker is cancelled.
+/// If the name is a repository of the user on GitHub which is not cloned yet, it is returned
+/// after suggesting to clone it, or cloned first with `clone_missing`.
+pub fn path(
+    config: &Config,
+    name: Option<&str>,
+    picker: Option<&str>,
+    clone_missing: bool,
+) -> Result<Option<RepoInfo>, Error> {
+    let name = match name.filter(|name| !name.is_empty()) {
+        Some(name) => name.to_string(),
+        None => {
+            let picker = picker.map_or_else(|| config.resolve_picker(), str::to_string);
+            match pick(&picker, &candidate_names(config))? {
+                Some(name) => name,
+                None => return Ok(None),
+            }
+        }
+    };
+    let missing = !config.base_path().join(&name).exists();
+    if let Some(repo_info) = missing
+        .then(|| find_remote_repository(config, &name))
+        .flatten()
+    {
+        let url = repo_info.https_url();
+        if !clone_missing {
+            eprintln!(
+                "{}",
+                Message::NotCloned {
+                    name: &name,
+                    url: &url
+                }
+            );
+            return Ok(Some(repo_info));
+        }
+        let options = CloneOptions::from_config(config);
+        clone(config, &url, &options, Verbosity::Quiet)?;
+        println!("{}", resolve(config, &repo_info.repo)?.display());
+        return Ok(None);
+    }
+    println!("{}", resolve(config, &name)?.display());
+    Ok(None)
+}
+
+/// Repository of the name among the repositories of the user on GitHub.
+/// The cached list is used if GitHub is not available.
+fn find_remote_repository(config: &Config, name: &str) -> Option<RepoInfo> {
+    let mut cache = RemoteCache::load().unwrap_or_default();
+    if !cache.is_fresh(now(), REMOTE_CACHE_MAX_AGE) {
+        match fetch_remote_urls(config) {
+            Ok(urls) => {
+                cache = RemoteCache {
+                    fetched_at: now(),
+                    urls,
+                };
+                if let Err(e) = cache.save() {
+                    tracing::warn!("failed to save the remote cache: {e}");
+                }
+            }
+            Err(e) => tracing::warn!("failed to fetch repositories: {e}"),
+        }
+    }
+    cache.find(name)
+}
+
+fn fetch_remote_urls(config: &Config) -> Result<Vec<String>, Error> {
+    let token = get_github_token()?;
+    let query = format!("user:{} fork:true", config.github.username()?);
+    Ok(search_all_repositories(GITHUB_GRAPHQL_URL, &token, &query)?
+        .into_iter()
+        .map(|repo| repo.url)
+        .collect())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Let the user choose one of the names with the picker command (fzf, sk or compatible).
+/// None if nothing is chosen.
+fn pick(picker: &str, names: &[String]) -> Result<Option<String>, Error> {
+    // picker is not only a command name but also can have arguments like editor
+    let mut args = picker.split_whitespace();
+    let Some(program) = args.next() else {
+        return Err(Error::from_str("picker is empty"));
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            Error::from_str(&format!(
+                "failed to run picker {program}: {e}. Install it or set `shell.picker` in .mure.toml"
+            ))
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the picker may exit before reading all names
+        let _ = stdin.write_all(format!("{}\n", names.join("\n")).as_bytes());
+    }
+    let output = child.wait_with_output()?;
+    let chosen = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // fzf exits with 130 when cancelled
+    if !output.status.success() || chosen.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(chosen))
 }
 
 pub fn shell_shims(config: &Config) -> String {
@@ -17,11 +145,97 @@ fn shell_shims_for_cd_directly(bin_name: &str, fn_name: &str) -> String {
     format!("function {fn_name}() {{ local p=$({bin_name} path \"$1\") && cd \"$p\" }}\n")
 }
 
-fn resolve(config: &Config, name: &str) -> Result<PathBuf, Error> {
+/// Key binding (Ctrl-G) to pick a repository with fzf.
+/// The chosen repository is cd'ed into on an empty command line, otherwise its path is inserted.
+pub fn shell_widget(shell: Shell) -> Result<String, Error> {
+    shell_widget_for("mure", shell)
+}
+
+fn shell_widget_for(bin_name: &str, shell: Shell) -> Result<String, Error> {
+    let pick = format!("{bin_name} candidates | fzf --height 40% --reverse");
+    let widget = match shell {
+        Shell::Zsh => format!(
+            r#"function _mure_widget() {{
+  local name p
+  name=$({pick}) && p=$({bin_name} path "$name") || {{ zle reset-prompt; return }}
+  if [[ -z "$BUFFER" ]]; then
+    cd "$p"
+  else
+    LBUFFER+="${{(q)p}}"
+  fi
+  zle reset-prompt
+}}
+zle -N _mure_widget
+bindkey '^G' _mure_widget
+"#
+        ),
+        Shell::Bash => format!(
+            r#"function _mure_widget() {{
+  local name p
+  name=$({pick}) && p=$({bin_name} path "$name") || return
+  if [[ -z "$READLINE_LINE" ]]; then
+    cd "$p"
+  else
+    p=$(printf '%q' "$p")
+    READLINE_LINE="${{READLINE_LINE:0:$READLINE_POINT}}$p${{READLINE_LINE:$READLINE_POINT}}"
+    READLINE_POINT=$((READLINE_POINT + ${{#p}}))
+  fi
+}}
+bind -x '"\C-g": _mure_widget'
+"#
+        ),
+        Shell::Fish => format!(
+            r#"function _mure_widget
+  set -l name ({pick}); and set -l p ({bin_name} path $name); or begin; commandline -f repaint; return; end
+  set -l line (commandline)
+  if test -z "$line"
+    cd $p
+  else
+    commandline -i (string escape -- $p)
+  end
+  commandline -f repaint
+end
+bind \cg _mure_widget
+"#
+        ),
+        _ => {
+            return Err(Error::from_str(&format!(
+                "widget is not supported for {shell}"
+            )))
+        }
+    };
+    Ok(widget)
+}
+
+/// Print names of repositories for pickers and completion
+pub fn candidates(config: &Config) -> Result<(), Error> {
+    for name in candidate_names(config) {
+        println!("{name}");
+    }
+    Ok(())
+}
+
+fn candidate_names(config: &Config) -> Vec<String> {
+    let mut names = search_mure_repo(config)
+        .into_iter()
+        .flatten()
+        .map(|mure_repo| mure_repo.repo.repo)
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+pub fn resolve(config: &Config, name: &str) -> Result<PathBuf, Error> {
     let path_ = config.base_path().join(name);
     if path_.is_dir() && path_.exists() {
         return Ok(path_);
     }
+    if path_.exists() {
+        return Err(Error::from_str(&format!(
+            "{} is not a directory",
+            path_.display()
+        )));
+    }
     Err(Error::from_str(
         format!("{} is not a git repository", path_.display()).as_str(),
     ))
@@ -30,9 +244,11 @@ fn resolve(config: &Config, name: &str) -> Result<PathBuf, Error> {
 #[cfg(test)]
 mod tests {
     use crate::config::{Core, GitHub, Shell};
+    use assay::assay;
     use mktemp::Temp;
 
     use super::*;
+    use crate::test_fixture::config_with_base_dir;
 
     #[test]
     fn test_resolve_path() {
@@ -41,15 +257,19 @@ mod tests {
             core: Core {
                 base_dir: temp.as_path().to_str().unwrap().to_string(),
                 editor: None,
+                ..Default::default()
             },
             github: GitHub {
-                username: "".to_string(),
+                username: None,
                 query: None,
                 queries: None,
+                token_env: None,
             },
             shell: Some(Shell {
                 cd_shims: Some("mucd".to_string()),
+                picker: None,
             }),
+            ..Default::default()
         };
         git2::Repository::init(config.base_path().join("test_repo")).unwrap();
         let path = resolve(&config, "test_repo").unwrap();
@@ -65,6 +285,40 @@ mod tests {
             .unwrap_err()
             .to_string()
             .ends_with("test_repo2 is not a git repository"));
+
+        std::fs::write(config.base_path().join("file"), "").unwrap();
+        assert!(resolve(&config, "file")
+            .unwrap_err()
+            .to_string()
+            .ends_with("file is not a directory"));
+    }
+
+    #[assay(
+        env = [
+          ("GH_TOKEN", ""),
+        ]
+      )]
+    fn test_path_not_cloned() {
+        let cache_dir = Temp::new_dir().unwrap();
+        std::env::set_var("MURE_REMOTE_CACHE_PATH", cache_dir.join("remote.json"));
+        let temp = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp);
+        RemoteCache {
+            fetched_at: now(),
+            urls: vec!["https://github.com/kitsuyui/mure".to_string()],
+        }
+        .save()
+        .unwrap();
+        git2::Repository::init(config.base_path().join("dotfiles")).unwrap();
+
+        assert_eq!(path(&config, Some("dotfiles"), None, false).unwrap(), None);
+        assert_eq!(
+            path(&config, Some("mure"), None, false)
+                .unwrap()
+                .map(|repo| repo.name_with_owner()),
+            Some("kitsuyui/mure".to_string())
+        );
+        assert!(path(&config, Some("unknown"), None, false).is_err());
     }
 
     #[test]
@@ -73,15 +327,19 @@ mod tests {
             core: Core {
                 base_dir: "".to_string(),
                 editor: None,
+                ..Default::default()
             },
             github: GitHub {
-                username: "".to_string(),
+                username: None,
                 query: None,
                 queries: None,
+                token_env: None,
             },
             shell: Some(Shell {
                 cd_shims: Some("mucd".to_string()),
+                picker: None,
             }),
+            ..Default::default()
         };
         let shims = shell_shims(&config);
         assert_eq!(
@@ -89,4 +347,43 @@ mod tests {
             "function mucd() { local p=$(mure path \"$1\") && cd \"$p\" }\n"
         );
     }
+
+    #[test]
+    fn test_shell_widget() {
+        use clap_complete::Shell;
+
+        let zsh = shell_widget(Shell::Zsh).unwrap();
+        assert!(zsh.contains("mure candidates | fzf"));
+        assert!(zsh.contains("bindkey '^G' _mure_widget"));
+        let bash = shell_widget(Shell::Bash).unwrap();
+        assert!(bash.contains(r#"bind -x '"\C-g": _mure_widget'"#));
+        let fish = shell_widget(Shell::Fish).unwrap();
+        assert!(fish.contains("bind \\cg _mure_widget"));
+        assert!(shell_widget(Shell::PowerShell).is_err());
+    }
+
+    #[test]
+    fn test_pick() {
+        let names = vec!["mure".to_string(), "dotfiles".to_string()];
+        assert_eq!(pick("head -n 1", &names).unwrap(), Some("mure".to_string()));
+        assert_eq!(
+            pick("tail -n 1", &names).unwrap(),
+            Some("dotfiles".to_string())
+        );
+        // cancelled
+        assert_eq!(pick("false", &names).unwrap(), None);
+        assert_eq!(pick("true", &names).unwrap(), None);
+        assert!(pick("mure-no-such-picker", &names)
+            .unwrap_err()
+            .to_string()
+            .starts_with("failed to run picker mure-no-such-picker"));
+        assert!(pick("", &names).is_err());
+    }
+
+    #[test]
+    fn test_candidates() {
+        let temp = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp);
+        candidates(&config).unwrap();
+    }
 }
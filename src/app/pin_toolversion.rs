@@ -0,0 +1,319 @@
+//! Audit toolchain pin files of repositories against `[toolchain]` of config
+//!
+//! - `rust-toolchain.toml` (`channel`) and `rust-toolchain` pin `rust`
+//! - `.tool-versions` of asdf/mise pins each tool of the line
+//! - `.nvmrc` pins `nodejs`
+//!
+//! Only existing files and tools listed in `[toolchain]` are compared. Files are never created.
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use git2::{BranchType, Repository};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::app::list::{search_mure_repo, RepoFilter};
+use crate::config::Config;
+use crate::git::RepositorySupport;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+/// Pin files in the order of audit
+const PIN_FILES: [&str; 4] = [
+    "rust-toolchain.toml",
+    "rust-toolchain",
+    ".tool-versions",
+    ".nvmrc",
+];
+
+/// A pinned version which differs from the desired one
+#[derive(Debug, PartialEq, Eq)]
+struct Drift {
+    file: &'static str,
+    tool: String,
+    current: String,
+    desired: String,
+}
+
+static CHANNEL: Lazy<Regex> = Lazy::new(|| {
+    #[allow(clippy::unwrap_used)]
+    Regex::new(r#"(?m)^(\s*channel\s*=\s*)"([^"]*)""#).unwrap()
+});
+
+/// Report drift of pinned versions in each repository.
+/// With `update`, pin files are rewritten and committed on the branch.
+pub fn pin_toolversion(
+    config: &Config,
+    filter: &RepoFilter,
+    update: bool,
+    branch: &str,
+) -> Result<(), Error> {
+    let desired = config.toolchain.clone().unwrap_or_default();
+    if desired.is_empty() {
+        return Err(Error::from_str("no [toolchain] in config"));
+    }
+    let mut drifted = false;
+    for mure_repo in filter.apply(search_mure_repo(config))? {
+        let mure_repo = match mure_repo {
+            Ok(mure_repo) => mure_repo,
+            Err(e) => {
+                println!("{}", e.message());
+                continue;
+            }
+        };
+        let name = &mure_repo.repo.repo;
+        let path = &mure_repo.absolute_path;
+        let drifts = audit(path, &desired);
+        for drift in &drifts {
+            drifted = true;
+            println!(
+                "{}",
+                Message::ToolchainDrift {
+                    name,
+                    file: drift.file,
+                    tool: &drift.tool,
+                    current: &drift.current,
+                    desired: &drift.desired,
+                }
+            );
+        }
+        if !update || drifts.is_empty() {
+            continue;
+        }
+        match update_pins(path, &desired, branch) {
+            Ok(files) if files.is_empty() => {
+                println!("{}", Message::ToolchainUpToDateOnBranch { name, branch })
+            }
+            Ok(files) => println!(
+                "{}",
+                Message::ToolchainUpdated {
+                    name,
+                    files: &files.join(", "),
+                    branch
+                }
+            ),
+            Err(e) => println!("{name}: {e}"),
+        }
+    }
+    if !drifted {
+        println!("{}", Message::ToolchainsPinned);
+    }
+    Ok(())
+}
+
+fn audit(path: &Path, desired: &BTreeMap<String, String>) -> Vec<Drift> {
+    let mut drifts = vec![];
+    for file in PIN_FILES {
+        let Ok(content) = std::fs::read_to_string(path.join(file)) else {
+            continue;
+        };
+        for (tool, current) in pins(file, &content) {
+            let Some(wanted) = desired.get(&tool) else {
+                continue;
+            };
+            if !same_version(&current, wanted) {
+                drifts.push(Drift {
+                    file,
+                    tool,
+                    current,
+                    desired: wanted.clone(),
+                });
+            }
+        }
+    }
+    drifts
+}
+
+/// (tool, version) pinned in the file
+fn pins(file: &str, content: &str) -> Vec<(String, String)> {
+    match file {
+        "rust-toolchain.toml" => CHANNEL
+            .captures(content)
+            .map(|caps| vec![("rust".to_string(), caps[2].to_string())])
+            .unwrap_or_default(),
+        "rust-toolchain" | ".nvmrc" => {
+            let tool = if file == ".nvmrc" { "nodejs" } else { "rust" };
+            match content.trim() {
+                "" => vec![],
+                version => vec![(tool.to_string(), version.to_string())],
+            }
+        }
+        _ => content
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .filter_map(|line| {
+                let mut words = line.split_whitespace();
+                Some((words.next()?.to_string(), words.next()?.to_string()))
+            })
+            .collect(),
+    }
+}
+
+/// `.nvmrc` is often written as `v20.11.0`
+fn same_version(current: &str, desired: &str) -> bool {
+    current.trim_start_matches('v') == desired.trim_start_matches('v')
+}
+
+/// The content with the versions of the tools replaced by the desired ones
+fn rewrite(file: &str, content: &str, desired: &BTreeMap<String, String>) -> String {
+    match file {
+        "rust-toolchain.toml" => match desired.get("rust") {
+            Some(version) => CHANNEL
+                .replace(content, format!(r#"${{1}}"{version}""#))
+                .to_string(),
+            None => content.to_string(),
+        },
+        "rust-toolchain" | ".nvmrc" => {
+            let tool = if file == ".nvmrc" { "nodejs" } else { "rust" };
+            match desired.get(tool) {
+                Some(version) => format!("{version}\n"),
+                None => content.to_string(),
+            }
+        }
+        _ => content
+            .lines()
+            .map(|line| {
+                let mut words = line.split_whitespace();
+                let tool = words.next().unwrap_or_default();
+                match desired.get(tool) {
+                    // fallback versions after the first one are kept
+                    Some(version) if !line.trim_start().starts_with('#') => std::iter::once(tool)
+                        .chain(std::iter::once(version.as_str()))
+                        .chain(words.skip(1))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    _ => line.to_string(),
+                }
+            })
+            .map(|line| line + "\n")
+            .collect(),
+    }
+}
+
+/// Rewrite the drifted files and commit them on the branch, which is created if missing.
+/// Nothing is committed if the branch already has the pins from the last update.
+fn update_pins(
+    path: &Path,
+    desired: &BTreeMap<String, String>,
+    branch: &str,
+) -> Result<Vec<String>, Error> {
+    let repo = Repository::open(path)?;
+    if !repo.is_clean()? {
+        return Err(Error::from_str("working tree is not clean"));
+    }
+    // updated before: the branch may already have some of the pins
+    if repo.find_branch(branch, BranchType::Local).is_ok() {
+        repo.switch(branch)?;
+    } else {
+        repo.switch_create(branch)?;
+    }
+    let mut files = audit(path, desired)
+        .iter()
+        .map(|drift| drift.file)
+        .collect::<Vec<_>>();
+    files.dedup();
+    if files.is_empty() {
+        return Ok(vec![]);
+    }
+    for file in &files {
+        let content = std::fs::read_to_string(path.join(file))?;
+        std::fs::write(path.join(file), rewrite(file, &content, desired))?;
+    }
+    repo.add_paths(&files)?;
+    repo.commit_staged("Pin toolchain versions")?;
+    Ok(files.iter().map(|file| file.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::Fixture;
+
+    fn desired() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("rust".to_string(), "1.79.0".to_string()),
+            ("nodejs".to_string(), "20.11.0".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_pins_and_rewrite() {
+        let toml = "[toolchain]\nchannel = \"1.75.0\"\ncomponents = [\"clippy\"]\n";
+        assert_eq!(
+            pins("rust-toolchain.toml", toml),
+            vec![("rust".to_string(), "1.75.0".to_string())]
+        );
+        assert_eq!(
+            rewrite("rust-toolchain.toml", toml, &desired()),
+            "[toolchain]\nchannel = \"1.79.0\"\ncomponents = [\"clippy\"]\n"
+        );
+
+        let tool_versions = "# tools\nnodejs 18.0.0 system\npython 3.12.0\n";
+        assert_eq!(
+            pins(".tool-versions", tool_versions),
+            vec![
+                ("nodejs".to_string(), "18.0.0".to_string()),
+                ("python".to_string(), "3.12.0".to_string())
+            ]
+        );
+        assert_eq!(
+            rewrite(".tool-versions", tool_versions, &desired()),
+            "# tools\nnodejs 20.11.0 system\npython 3.12.0\n"
+        );
+
+        assert_eq!(
+            pins(".nvmrc", "v18\n"),
+            vec![("nodejs".to_string(), "v18".to_string())]
+        );
+        assert_eq!(rewrite(".nvmrc", "v18\n", &desired()), "20.11.0\n");
+        assert!(same_version("v20.11.0", "20.11.0"));
+    }
+
+    #[test]
+    fn test_audit_and_update() {
+        let fixture = Fixture::create().unwrap();
+        fixture
+            .create_file("rust-toolchain.toml", "[toolchain]\nchannel = \"1.79.0\"\n")
+            .unwrap();
+        fixture.create_file(".nvmrc", "v18\n").unwrap();
+        fixture
+            .repo
+            .command(&["add", "rust-toolchain.toml", ".nvmrc"])
+            .unwrap();
+        fixture.create_empty_commit("initial commit").unwrap();
+        let path = fixture.repo.workdir().unwrap();
+
+        let drifts = audit(path, &desired());
+        assert_eq!(
+            drifts,
+            vec![Drift {
+                file: ".nvmrc",
+                tool: "nodejs".to_string(),
+                current: "v18".to_string(),
+                desired: "20.11.0".to_string(),
+            }]
+        );
+
+        let files = update_pins(path, &desired(), "mure/toolchain").unwrap();
+        assert_eq!(files, vec![".nvmrc"]);
+        assert_eq!(fixture.repo.get_current_branch().unwrap(), "mure/toolchain");
+        assert!(fixture.repo.is_clean().unwrap());
+        assert!(audit(path, &desired()).is_empty());
+
+        // run again from the default branch: the existing branch is reused
+        fixture.repo.command(&["switch", "-"]).unwrap();
+        assert!(!audit(path, &desired()).is_empty());
+        let files = update_pins(path, &desired(), "mure/toolchain").unwrap();
+        assert!(files.is_empty());
+        assert_eq!(fixture.repo.get_current_branch().unwrap(), "mure/toolchain");
+
+        // the desired version changed since the last update
+        fixture.repo.command(&["switch", "-"]).unwrap();
+        let mut newer = desired();
+        newer.insert("nodejs".to_string(), "22.2.0".to_string());
+        let files = update_pins(path, &newer, "mure/toolchain").unwrap();
+        assert_eq!(files, vec![".nvmrc"]);
+        assert_eq!(fixture.repo.get_current_branch().unwrap(), "mure/toolchain");
+        assert!(audit(path, &newer).is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+//! Pull requests of a repository in base_dir, to review them without leaving mure
+use std::cmp::Reverse;
+
+use git2::{BranchType, Repository};
+
+use crate::app::list::read_symlink_as_mure_repo;
+use crate::config::{Config, ConfigSupport};
+use crate::gh::{self, GhClient};
+use crate::git::{DryRun, RepositorySupport};
+use crate::github::api::open_pull_requests;
+use crate::github::api::open_pull_requests_query::OpenPullRequestsQueryRepositoryPullRequestsNodes as PullRequest;
+use crate::github::token::get_github_token;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+/// Show the open pull requests of the repository.
+/// gh is asked instead if GH_TOKEN is missing or the GitHub API fails.
+pub fn list(config: &Config, name: &str) -> Result<(), Error> {
+    let mure_repo = read_symlink_as_mure_repo(&config.base_path().join(name))?;
+    let table = match get_github_token()
+        .and_then(|token| open_pull_requests(&token, &mure_repo.repo.owner, &mure_repo.repo.repo))
+    {
+        Ok(mut pull_requests) if !pull_requests.is_empty() => {
+            pull_requests.sort_by_key(|pull_request| Reverse(pull_request.number));
+            format_table(&pull_requests)
+        }
+        Ok(_) => String::new(),
+        Err(e) => {
+            tracing::warn!("failed to list pull requests by the GitHub API: {e}");
+            let mut pull_requests = GhClient::new().pr_list(&mure_repo.absolute_path)?;
+            pull_requests.sort_by_key(|pull_request| Reverse(pull_request.number));
+            format_gh_table(&pull_requests)
+        }
+    };
+    if table.is_empty() {
+        println!("{}", Message::NoOpenPullRequests { name });
+        return Ok(());
+    }
+    print!("{table}");
+    Ok(())
+}
+
+/// Fetch the head of the pull request into the local branch `pr/<number>` and switch to it.
+/// The branch is fast-forwarded if it was checked out before.
+pub fn checkout(config: &Config, name: &str, number: i64) -> Result<(), Error> {
+    let mure_repo = read_symlink_as_mure_repo(&config.base_path().join(name))?;
+    let repo = Repository::open(&mure_repo.absolute_path)?;
+    let branch = branch_name(number);
+    if DryRun::is_enabled() {
+        println!(
+            "{}",
+            Message::WouldCheckOutPullRequest {
+                number,
+                branch: &branch
+            }
+        );
+        return Ok(());
+    }
+    checkout_pull_request(&repo, number)?;
+    println!(
+        "{}",
+        Message::CheckedOutPullRequest {
+            number,
+            branch: &branch
+        }
+    );
+    Ok(())
+}
+
+fn branch_name(number: i64) -> String {
+    format!("pr/{number}")
+}
+
+/// GitHub keeps the head of every pull request as refs/pull/<number>/head, even of forks
+fn checkout_pull_request(repo: &Repository, number: i64) -> Result<(), Error> {
+    let branch = branch_name(number);
+    repo.fetch_refspec("origin", &format!("refs/pull/{number}/head"))?;
+    if repo.find_branch(&branch, BranchType::Local).is_ok() {
+        repo.switch(&branch)?;
+        repo.merge_fast_forward("FETCH_HEAD")?;
+    } else {
+        repo.create_branch(&branch, "FETCH_HEAD")?;
+        repo.switch(&branch)?;
+    }
+    Ok(())
+}
+
+fn format_table(pull_requests: &[PullRequest]) -> String {
+    let mut table = "Number\tTitle\tBranch\tAuthor\n".to_string();
+    for pull_request in pull_requests {
+        let draft = if pull_request.is_draft {
+            "[draft] "
+        } else {
+            ""
+        };
+        // the author is missing if the account was deleted
+        let author = pull_request
+            .author
+            .as_ref()
+            .map_or("ghost", |author| author.login.as_str());
+        table.push_str(&format!(
+            "#{}\t{draft}{}\t{} -> {}\t{author}\n",
+            pull_request.number,
+            pull_request.title,
+            pull_request.head_ref_name,
+            pull_request.base_ref_name,
+        ));
+    }
+    table
+}
+
+/// gh doesn't tell drafts and authors, so the table has the URL instead
+fn format_gh_table(pull_requests: &[gh::PullRequest]) -> String {
+    if pull_requests.is_empty() {
+        return String::new();
+    }
+    let mut table = "Number\tTitle\tBranch\tURL\n".to_string();
+    for pull_request in pull_requests {
+        table.push_str(&format!(
+            "#{}\t{}\t{} -> {}\t{}\n",
+            pull_request.number,
+            pull_request.title,
+            pull_request.head_ref_name,
+            pull_request.base_ref_name,
+            pull_request.url,
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::api::open_pull_requests_query::{
+        OpenPullRequestsQueryRepositoryPullRequestsNodesAuthor as Author,
+        OpenPullRequestsQueryRepositoryPullRequestsNodesAuthorOn as AuthorOn,
+    };
+    use crate::test_fixture::Fixture;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_format_table() {
+        let pull_request = PullRequest {
+            number: 12,
+            title: "Support gist".to_string(),
+            url: "https://github.com/kitsuyui/mure/pull/12".to_string(),
+            is_draft: false,
+            author: Some(Author {
+                login: "kitsuyui".to_string(),
+                on: AuthorOn::User,
+            }),
+            base_ref_name: "main".to_string(),
+            head_ref_name: "gist".to_string(),
+            is_cross_repository: false,
+        };
+        let draft = PullRequest {
+            number: 13,
+            title: "WIP".to_string(),
+            is_draft: true,
+            author: None,
+            ..pull_request.clone()
+        };
+        assert_eq!(
+            format_table(&[draft, pull_request]),
+            "Number\tTitle\tBranch\tAuthor\n\
+             #13\t[draft] WIP\tgist -> main\tghost\n\
+             #12\tSupport gist\tgist -> main\tkitsuyui\n"
+        );
+    }
+
+    #[test]
+    fn test_format_gh_table() {
+        let pull_request = gh::PullRequest {
+            number: 12,
+            title: "Support gist".to_string(),
+            head_ref_name: "gist".to_string(),
+            base_ref_name: "main".to_string(),
+            url: "https://github.com/kitsuyui/mure/pull/12".to_string(),
+        };
+        assert_eq!(
+            format_gh_table(&[pull_request]),
+            "Number\tTitle\tBranch\tURL\n\
+             #12\tSupport gist\tgist -> main\thttps://github.com/kitsuyui/mure/pull/12\n"
+        );
+        assert_eq!(format_gh_table(&[]), "");
+    }
+
+    #[test]
+    fn test_checkout_pull_request() {
+        let origin = Fixture::create().unwrap();
+        let update_pull_request = |message: &str| {
+            origin.repo.command(&["switch", "feature"]).unwrap();
+            origin.create_empty_commit(message).unwrap();
+            origin
+                .repo
+                .command(&["update-ref", "refs/pull/1/head", "HEAD"])
+                .unwrap();
+            origin.repo.command(&["switch", "-"]).unwrap();
+        };
+        origin.create_empty_commit("initial commit").unwrap();
+        origin.repo.command(&["branch", "feature"]).unwrap();
+        update_pull_request("proposal");
+        let origin_path = origin.repo.workdir().unwrap().to_str().unwrap();
+
+        let temp_dir = Temp::new_dir().unwrap();
+        let repo = Repository::clone(origin_path, &temp_dir).unwrap();
+        let head_message = |repo: &Repository| {
+            let commit = repo.head().unwrap().peel_to_commit().unwrap();
+            commit.message().unwrap_or_default().to_string()
+        };
+
+        checkout_pull_request(&repo, 1).unwrap();
+        assert_eq!(repo.get_current_branch().unwrap(), "pr/1");
+        assert_eq!(head_message(&repo), "proposal\n");
+
+        // pushed to the pull request after the checkout
+        update_pull_request("review fix");
+        repo.command(&["switch", "-"]).unwrap();
+        checkout_pull_request(&repo, 1).unwrap();
+        assert_eq!(repo.get_current_branch().unwrap(), "pr/1");
+        assert_eq!(head_message(&repo), "review fix\n");
+
+        assert!(checkout_pull_request(&repo, 2).is_err());
+    }
+}
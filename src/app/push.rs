@@ -0,0 +1,217 @@
+use std::io::{BufRead, Write};
+
+use git2::Repository;
+
+use crate::app::list::{search_mure_repo, MureRepo};
+use crate::config::Config;
+use crate::git::{DryRun, RepositorySupport};
+use crate::i18n::Message;
+use crate::misc::permission::is_permission_denied;
+use crate::mure_error::Error;
+
+/// What `push --all-clean` does with a repository
+#[derive(Debug, PartialEq, Eq)]
+enum PushPlan {
+    /// the default branch is strictly ahead of origin and the working tree is clean
+    Push { branch: String, ahead: usize },
+    /// the default branch has commits to push but they can't be pushed safely
+    Skip { branch: String, reason: String },
+    /// nothing to push
+    UpToDate,
+}
+
+/// Push the default branch of every repository where it is strictly ahead of origin
+/// and the working tree is clean, after confirmation.
+/// The comparison is with the remote-tracking branch, so run `mure refresh` beforehand to compare with the latest.
+pub fn push_all_clean(config: &Config, yes: bool) -> Result<(), Error> {
+    let stdin = std::io::stdin();
+    push_all_clean_with(config, yes, &mut stdin.lock(), &mut std::io::stdout())
+}
+
+fn push_all_clean_with(
+    config: &Config,
+    yes: bool,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    let mut targets: Vec<(MureRepo, String)> = vec![];
+    for mure_repo in search_mure_repo(config) {
+        let mure_repo = match mure_repo {
+            Ok(mure_repo) => mure_repo,
+            Err(e) => {
+                writeln!(output, "{}", e.message())?;
+                continue;
+            }
+        };
+        let name = &mure_repo.repo.repo;
+        if is_permission_denied(&mure_repo.absolute_path) {
+            writeln!(output, "{}", Message::PermissionDenied { name })?;
+            continue;
+        }
+        match plan(&mure_repo) {
+            Ok(PushPlan::Push { branch, ahead }) => {
+                writeln!(
+                    output,
+                    "{name}: {}",
+                    Message::CommitsToPush {
+                        branch: &branch,
+                        count: ahead
+                    }
+                )?;
+                targets.push((mure_repo, branch));
+            }
+            Ok(PushPlan::Skip { branch, reason }) => {
+                writeln!(
+                    output,
+                    "{}",
+                    Message::SkippedPush {
+                        name,
+                        branch: &branch,
+                        reason: &reason
+                    }
+                )?;
+            }
+            Ok(PushPlan::UpToDate) => (),
+            Err(e) => writeln!(output, "{name}: {}", e.message())?,
+        }
+    }
+    if targets.is_empty() {
+        writeln!(output, "{}", Message::NoRepositoriesToPush)?;
+        return Ok(());
+    }
+    if !yes && !DryRun::is_enabled() {
+        write!(
+            output,
+            "{}",
+            Message::ConfirmPushAll {
+                count: targets.len()
+            }
+        )?;
+        output.flush()?;
+        let mut answer = String::new();
+        input.read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            return Ok(());
+        }
+    }
+    for (mure_repo, branch) in &targets {
+        let name = &mure_repo.repo.repo;
+        let result = Repository::open(&mure_repo.absolute_path)
+            .map_err(Error::from)
+            .and_then(|repo| Ok(repo.push("origin", branch)?));
+        match result {
+            Ok(_) if DryRun::is_enabled() => (),
+            Ok(_) => writeln!(output, "{}", Message::PushedBranch { name, branch })?,
+            Err(e) => writeln!(output, "{name}: {}", e.message())?,
+        }
+    }
+    Ok(())
+}
+
+fn plan(mure_repo: &MureRepo) -> Result<PushPlan, Error> {
+    let repo = Repository::open(&mure_repo.absolute_path)?;
+    let Some(branch) = repo.guess_default_branch("origin")? else {
+        return Ok(PushPlan::UpToDate);
+    };
+    let (ahead, behind) = repo.ahead_behind(&branch, &format!("origin/{branch}"))?;
+    if ahead == 0 {
+        return Ok(PushPlan::UpToDate);
+    }
+    let reason = if behind > 0 {
+        Some(format!("diverged: {ahead} ahead, {behind} behind"))
+    } else if !repo.is_clean()? {
+        Some("uncommitted changes".to_string())
+    } else {
+        None
+    };
+    Ok(match reason {
+        Some(reason) => PushPlan::Skip { branch, reason },
+        None => PushPlan::Push { branch, ahead },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{config_with_base_dir, Fixture};
+    use mktemp::Temp;
+
+    #[test]
+    fn test_push_all_clean() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let fixture_origin = Fixture::create().unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        fixture_origin
+            .repo
+            .command(&["switch", "-c", "main"])
+            .unwrap();
+        let origin_path = fixture_origin.repo.path().parent().unwrap();
+
+        let mut store_paths = vec![];
+        for name in ["ahead", "dirty", "same"] {
+            let fixture = Fixture::create().unwrap();
+            let repo = &fixture.repo;
+            repo.remote("origin", origin_path.to_str().unwrap())
+                .unwrap();
+            repo.command(&["fetch", "origin"]).unwrap();
+            repo.command(&["switch", "main"]).unwrap();
+            repo.command(&["remote", "set-head", "origin", "main"])
+                .unwrap();
+            if name != "same" {
+                fixture
+                    .create_empty_commit(&format!("local commit in {name}"))
+                    .unwrap();
+            }
+            if name == "dirty" {
+                fixture.create_file("dirty.txt", "dirty").unwrap();
+            }
+            store_paths.push(fixture.into_store(&config, name, true));
+        }
+        // the branch checked out in origin can't be pushed to
+        fixture_origin
+            .repo
+            .command(&["switch", "-c", "other"])
+            .unwrap();
+
+        let mut output = vec![];
+        push_all_clean_with(&config, false, &mut "n\n".as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("ahead: main has 1 commits to push"));
+        assert!(output.contains("dirty: skipped main (uncommitted changes)"));
+        assert!(!output.contains("same"));
+        assert!(!output.contains("pushed"));
+
+        let mut output = vec![];
+        push_all_clean_with(&config, false, &mut "y\n".as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("ahead: pushed main"));
+        let ahead = Repository::open(&store_paths[0]).unwrap();
+        assert_eq!(ahead.ahead_behind("main", "origin/main").unwrap(), (0, 0));
+        let dirty = Repository::open(&store_paths[1]).unwrap();
+        assert_eq!(dirty.ahead_behind("main", "origin/main").unwrap(), (1, 0));
+        // diverged from origin after the push of the other
+        dirty.command(&["fetch", "origin"]).unwrap();
+        assert_eq!(
+            plan(
+                &search_mure_repo(&config)
+                    .into_iter()
+                    .flatten()
+                    .find(|mure_repo| mure_repo.repo.repo == "dirty")
+                    .unwrap()
+            )
+            .unwrap(),
+            PushPlan::Skip {
+                branch: "main".to_string(),
+                reason: "diverged: 1 ahead, 1 behind".to_string()
+            }
+        );
+
+        let mut output = vec![];
+        push_all_clean_with(&config, true, &mut "".as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("No repositories to push"));
+    }
+}
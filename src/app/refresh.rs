@@ -1,40 +1,203 @@
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use git2::Repository;
+use git2::{BranchType, Repository};
+use glob::Pattern;
+use serde_derive::Serialize;
 
-use crate::config::{Config, ConfigSupport};
-use crate::gh::get_default_branch;
-use crate::git::{PullFastForwardStatus, RepositorySupport};
+use crate::config::{Config, ConfigSupport, RefreshMode};
+use crate::event::{Event, OutputFormat};
+use crate::gh::GhClient;
+use crate::git::{DryRun, PullFastForwardStatus, RepositorySupport, SkipHooks};
+use crate::github::api::{get_default_branches, open_pull_requests};
+use crate::github::repo::RepoInfo;
+use crate::github::token::{get_github_token, get_github_token_for};
+use crate::i18n::Message;
+use crate::misc::bandwidth::{objects_size, Pacer};
+use crate::misc::bulk::{self, BulkOptions, BulkSummary};
+use crate::misc::datetime::format_age;
+use crate::misc::output::{use_color, OutputMode};
+use crate::misc::permission::is_permission_denied;
 use crate::mure_error::Error;
+use crate::notification::post_webhook;
+use crate::refresh_cache::{RefreshCache, RefreshResult};
+use crate::state::State;
 use crate::verbosity::Verbosity;
 
-use super::list::search_mure_repo;
+use super::list::{search_mure_repo, MureRepo, RepoFilter};
+
+/// Options of refresh.
+/// Values come from the `[refresh]` section of config and are overridden by command line flags.
+#[derive(Debug, Default, Clone)]
+pub struct RefreshOptions {
+    /// Never switch branches. The default branch is updated by fetch instead.
+    pub keep_current_branch: bool,
+    /// Initialize and update submodules recursively after fast-forward.
+    pub update_submodules: bool,
+    /// Don't delete merged branches which open pull requests still target or are based on.
+    pub check_open_pull_requests: bool,
+    /// Branches to be fast-forwarded in addition to the default branch. They are never deleted.
+    pub sync_branches: Vec<String>,
+    /// Never delete merged branches.
+    pub keep_merged_branches: bool,
+    /// Delete only merged branches matching any of the glob patterns if not empty.
+    pub delete_only: Vec<String>,
+    /// Never delete branches matching any of the glob patterns.
+    pub protect_branches: Vec<String>,
+    /// Skip repositories refreshed successfully within the duration on `--all`.
+    pub stale: Option<Duration>,
+    /// Default branch asserted by `mure set default-branch`. origin/HEAD and gh are not consulted.
+    pub default_branch: Option<String>,
+    /// Fast-forward the default branch of forks from `upstream` remote.
+    pub sync_forks: bool,
+    /// Push the default branch synced from upstream to origin.
+    pub push_forks: bool,
+    /// Follow the default branch renamed on origin (e.g. master -> main) instead of failing.
+    pub fix_head: bool,
+    /// Don't run hooks of the repository (e.g. post-merge) triggered by git commands.
+    pub skip_hooks: bool,
+}
+
+impl RefreshOptions {
+    pub fn from_config(config: &Config) -> Self {
+        let refresh = config.refresh.as_ref();
+        let safe = refresh.and_then(|r| r.mode) == Some(RefreshMode::Safe);
+        RefreshOptions {
+            keep_current_branch: refresh.and_then(|r| r.keep_current_branch).unwrap_or(safe),
+            update_submodules: refresh.and_then(|r| r.update_submodules).unwrap_or(false),
+            check_open_pull_requests: refresh
+                .and_then(|r| r.check_open_pull_requests)
+                .unwrap_or(false),
+            sync_branches: vec![],
+            keep_merged_branches: !refresh
+                .and_then(|r| r.delete_merged_branches)
+                .unwrap_or(!safe),
+            delete_only: refresh
+                .and_then(|r| r.delete_only.clone())
+                .unwrap_or_default(),
+            protect_branches: refresh
+                .and_then(|r| r.protect_branches.clone())
+                .unwrap_or_default(),
+            stale: None,
+            default_branch: None,
+            sync_forks: refresh.and_then(|r| r.sync_forks).unwrap_or(false),
+            push_forks: refresh.and_then(|r| r.push_forks).unwrap_or(false),
+            fix_head: false,
+            skip_hooks: !refresh.and_then(|r| r.run_hooks).unwrap_or(true),
+        }
+    }
+
+    /// Override switching and deletion of config by the mode
+    pub fn apply_mode(&mut self, mode: RefreshMode) {
+        let safe = mode == RefreshMode::Safe;
+        self.keep_current_branch = safe;
+        self.keep_merged_branches = safe;
+    }
+
+    /// Options with settings of `[repo."<name>"]` and the default branch asserted in the state.
+    /// `run_hooks` of the repository wins over `--no-hooks`, e.g. for repositories needing git-lfs hooks.
+    pub fn for_repository(&self, config: &Config, state: &State, name: &str) -> Self {
+        let repo_config = config.repo_config(name);
+        RefreshOptions {
+            sync_branches: repo_config
+                .and_then(|r| r.sync_branches.clone())
+                .unwrap_or_default(),
+            skip_hooks: repo_config
+                .and_then(|r| r.run_hooks)
+                .map_or(self.skip_hooks, |run_hooks| !run_hooks),
+            default_branch: state.default_branch(name).map(str::to_string),
+            ..self.clone()
+        }
+    }
+}
 
 pub fn refresh_main(
     config: &Config,
     all: bool,
     repository: Option<String>,
-    verbosity: Verbosity,
-) -> Result<(), Error> {
+    options: &RefreshOptions,
+    format: OutputFormat,
+    filter: &RepoFilter,
+    bulk: &BulkOptions,
+) -> Result<BulkSummary, Error> {
+    let verbosity = Verbosity::current();
     if all {
-        refresh_all(config, verbosity)?;
+        refresh_all(config, options, verbosity, format, filter, bulk)
     } else {
         // If no repository is specified, use the current directory
-        let repo_path = get_git_repository_from_current_dir(config)?;
         let repo_path = match repository {
-            Some(repo) => repo,
-            None => repo_path.to_string_lossy().to_string(),
+            Some(repo) => resolve_repository(config, &repo)?,
+            None => get_git_repository_from_current_dir(config)?,
+        };
+        let repo_path = repo_path.to_string_lossy().to_string();
+        let name = Path::new(&repo_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let options = options.for_repository(config, &State::load()?, &name);
+        let result = refresh(&repo_path, &options, verbosity);
+        let summary = BulkSummary {
+            succeeded: usize::from(result.is_ok()),
+            failed: usize::from(result.is_err()),
+            cancelled: 0,
         };
-        match refresh(&repo_path, verbosity) {
-            Ok(r) => {
-                if let RefreshStatus::Update { message, .. } = r {
-                    println!("{message}");
+        let mut cache = load_refresh_cache();
+        record_refresh(&mut cache, &name, &result);
+        save_refresh_cache(&cache);
+        match format {
+            OutputFormat::Text => match result {
+                Ok(r) => {
+                    if let RefreshStatus::Update { message, .. } = r {
+                        println!("{message}");
+                    }
+                }
+                Err(e) => println!("{e}"),
+            },
+            OutputFormat::JsonLines => {
+                for event in refresh_events(&name, result) {
+                    event.emit();
                 }
             }
-            Err(e) => println!("{e}"),
         }
+        Ok(summary)
     }
-    Ok(())
+}
+
+/// Path of the repository given as a path, a URL, `owner/repo` of github.com
+/// or a name in base_dir, in this order.
+fn resolve_repository(config: &Config, target: &str) -> Result<PathBuf, Error> {
+    let path = Path::new(target);
+    if path.exists() {
+        return Ok(path.to_path_buf());
+    }
+    let repo_info =
+        RepoInfo::parse_url(target).or_else(|| match target.split('/').collect::<Vec<_>>()[..] {
+            [owner, repo] if !owner.is_empty() && !repo.is_empty() => Some(RepoInfo {
+                domain: "github.com".to_string(),
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+            }),
+            _ => None,
+        });
+    if let Some(repo_info) = repo_info {
+        let store_path =
+            config.repo_store_path(&repo_info.domain, &repo_info.owner, &repo_info.repo);
+        if store_path.is_dir() {
+            return Ok(store_path);
+        }
+        return Err(Error::from_str(&format!(
+            "{} is not cloned",
+            repo_info.name_with_owner()
+        )));
+    }
+    let work_path = config.base_path().join(target);
+    if work_path.is_dir() {
+        return Ok(work_path);
+    }
+    Err(Error::from_str(&format!(
+        "{target} is not a git repository"
+    )))
 }
 
 pub fn get_git_repository_from_current_dir(config: &Config) -> Result<PathBuf, Error> {
@@ -52,68 +215,434 @@ pub enum RefreshStatus {
     Update {
         switch_to_default: bool,
         message: String,
+        report: RefreshReport,
     },
 }
 
-#[derive(Debug)]
+/// Serialized in `--json-lines`. Variants are only added, never renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Reason {
     NotGitRepository,
     NoRemote,
+    /// e.g. owned by another user
+    PermissionDenied,
+}
+
+/// What refresh did to the repository, as opposed to the message for humans.
+/// Serialized in `--json-lines`. Fields are only added, never renamed or removed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct RefreshReport {
+    /// branch switched to, usually the default branch
+    pub switched_to: Option<String>,
+    pub pulled: PullStatus,
+    pub deleted_branches: Vec<String>,
+    /// commits of the local default branch not pushed to origin
+    pub to_push: usize,
 }
 
-pub fn refresh_all(config: &Config, verbosity: Verbosity) -> Result<(), Error> {
-    let repos = search_mure_repo(config);
+/// Result of pulling the default branch
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullStatus {
+    AlreadyUpToDate,
+    FastForwarded,
+    /// e.g. diverged from the remote
+    #[default]
+    NotPulled,
+}
+
+/// Refresh the repositories with `jobs` repositories at once.
+/// Repositories refreshed within `stale` are skipped.
+pub fn refresh_all(
+    config: &Config,
+    options: &RefreshOptions,
+    verbosity: Verbosity,
+    format: OutputFormat,
+    filter: &RepoFilter,
+    bulk: &BulkOptions,
+) -> Result<BulkSummary, Error> {
+    let repos = filter.apply(search_mure_repo(config))?;
     if repos.is_empty() {
-        println!("No repositories found");
-        return Ok(());
+        match format {
+            OutputFormat::Text => println!("{}", Message::NoRepositoriesFound),
+            OutputFormat::JsonLines => Event::Summary {
+                updated: 0,
+                skipped: 0,
+                failed: 0,
+                cancelled: 0,
+            }
+            .emit(),
+        }
+        return Ok(BulkSummary::default());
     }
+    let state = State::load()?;
+    let pacer = Pacer::from_config(config)?;
+    let mut summary = RefreshSummary::default();
+    let mut cache = load_refresh_cache();
+    let mut targets = vec![];
+    // prefixed with the repository name when refreshed in parallel
+    let output = OutputMode::resolve(None, bulk.jobs);
+    let color = use_color();
     for repo in repos {
         match repo {
             Ok(mure_repo) => {
-                println!("> Refreshing {}", mure_repo.repo.repo);
-                let result = refresh(
-                    #[allow(clippy::expect_used)]
-                    mure_repo
-                        .absolute_path
-                        .to_str()
-                        .expect("failed to convert to str"),
-                    verbosity,
-                );
-                match result {
-                    Ok(status) => match status {
-                        RefreshStatus::DoNothing(reason) => match reason {
-                            Reason::NotGitRepository => {
-                                println!("{} is not a git repository", mure_repo.repo.repo)
-                            }
-                            Reason::NoRemote => {
-                                println!("{} has no remote", mure_repo.repo.repo)
-                            }
-                        },
-                        RefreshStatus::Update {
-                            switch_to_default,
-                            message,
-                        } => {
-                            if switch_to_default {
-                                println!("Switched to {}", mure_repo.repo.repo)
-                            }
-                            println!("{message}")
-                        }
-                    },
-                    Err(e) => {
-                        println!("{}", e.message());
+                let name = &mure_repo.repo.repo;
+                if let Some(age) = options
+                    .stale
+                    .and_then(|stale| cache.fresh_age(name, now(), stale))
+                {
+                    if verbosity >= Verbosity::Verbose {
+                        let age = format_age(age);
+                        println!("{}", Message::SkippedFreshRepository { name, age: &age });
                     }
+                    continue;
+                }
+                targets.push(mure_repo);
+            }
+            Err(e) => match format {
+                OutputFormat::Text => println!("{}", e.message()),
+                OutputFormat::JsonLines => Event::Error {
+                    message: e.message(),
+                }
+                .emit(),
+            },
+        }
+    }
+    // only the repositories to be refreshed, not the ones skipped by `stale`
+    prefetch_default_branches(
+        config,
+        targets
+            .iter()
+            .filter(|mure_repo| state.default_branch(&mure_repo.repo.repo).is_none()),
+    );
+    let result = bulk::run(
+        &targets,
+        bulk,
+        |mure_repo| {
+            let name = &mure_repo.repo.repo;
+            if format == OutputFormat::JsonLines {
+                Event::Started {
+                    repository: name.to_string(),
+                }
+                .emit();
+            }
+            let objects_before = pacer
+                .as_ref()
+                .map(|_| objects_size(&mure_repo.absolute_path));
+            let result = refresh(
+                #[allow(clippy::expect_used)]
+                mure_repo
+                    .absolute_path
+                    .to_str()
+                    .expect("failed to convert to str"),
+                &options.for_repository(config, &state, name),
+                verbosity,
+            );
+            if let (Some(pacer), Some(before)) = (&pacer, objects_before) {
+                pacer.pace(objects_size(&mure_repo.absolute_path).saturating_sub(before));
+            }
+            result
+        },
+        |index, result| {
+            let name = &targets[index].repo.repo;
+            summary.record(name, &result);
+            record_refresh(&mut cache, name, &result);
+            match format {
+                // the header is shown with the result so that they stay together with --jobs
+                OutputFormat::Text => output.show_output(
+                    name,
+                    &Message::Refreshing { name }.to_string(),
+                    &refresh_result_text(name, &result),
+                    result.is_err(),
+                    color,
+                ),
+                OutputFormat::JsonLines => {
+                    for event in refresh_events(name, result) {
+                        event.emit();
+                    }
+                }
+            }
+        },
+    );
+    save_refresh_cache(&cache);
+    notify(config, &summary);
+    match format {
+        OutputFormat::Text if verbosity > Verbosity::Quiet => {
+            println!(
+                "{}",
+                Message::RefreshAllSummary {
+                    updated: summary.updated,
+                    skipped: summary.skipped,
+                    failed: summary.failed.len(),
                 }
+            );
+            if result.cancelled > 0 {
+                println!(
+                    "{}",
+                    Message::CancelledByFailFast {
+                        count: result.cancelled
+                    }
+                );
             }
+        }
+        OutputFormat::Text => (),
+        OutputFormat::JsonLines => Event::Summary {
+            updated: summary.updated,
+            skipped: summary.skipped,
+            failed: summary.failed.len(),
+            cancelled: result.cancelled,
+        }
+        .emit(),
+    }
+    Ok(result)
+}
+
+/// Look up default branches which can't be guessed locally at once and cache them as origin/HEAD,
+/// instead of running gh for each repository. Failures are left to `resolve_default_branch`.
+fn prefetch_default_branches<'a>(config: &Config, repos: impl Iterator<Item = &'a MureRepo>) {
+    let mut unknown: BTreeMap<&str, Vec<(Repository, &RepoInfo)>> = BTreeMap::new();
+    // wikis are not repositories of the API
+    for mure_repo in repos.filter(|mure_repo| !mure_repo.repo.is_wiki()) {
+        let Ok(repo) = Repository::open(&mure_repo.absolute_path) else {
+            continue;
+        };
+        if !matches!(repo.guess_default_branch("origin"), Ok(None)) {
+            continue;
+        }
+        unknown
+            .entry(&mure_repo.repo.domain)
+            .or_default()
+            .push((repo, &mure_repo.repo));
+    }
+    for (host, repos) in unknown {
+        let names = repos
+            .iter()
+            .map(|(_, info)| (info.owner.clone(), info.repo.clone()))
+            .collect::<Vec<_>>();
+        let branches = get_github_token_for(host)
+            .and_then(|token| get_default_branches(&config.graphql_url(host), &token, &names));
+        let branches = match branches {
+            Ok(branches) => branches,
             Err(e) => {
-                println!("{}", e.message());
+                tracing::warn!("failed to look up default branches on {host}: {e}");
+                continue;
+            }
+        };
+        for ((repo, _), branch) in repos.iter().zip(branches) {
+            if let Some(branch) = branch {
+                if let Err(e) = repo.set_remote_head("origin", &branch) {
+                    tracing::warn!("failed to cache the default branch: {}", Error::from(e));
+                }
             }
         }
     }
-    Ok(())
 }
 
-pub fn refresh(repo_path: &str, verbosity: Verbosity) -> Result<RefreshStatus, Error> {
+/// The cache is only an optimization. Failures to read it are treated as empty.
+fn load_refresh_cache() -> RefreshCache {
+    RefreshCache::load().unwrap_or_else(|e| {
+        tracing::warn!("failed to load the refresh cache: {e}");
+        RefreshCache::default()
+    })
+}
+
+fn save_refresh_cache(cache: &RefreshCache) {
+    if DryRun::is_enabled() {
+        return;
+    }
+    if let Err(e) = cache.save() {
+        tracing::warn!("failed to save the refresh cache: {e}");
+    }
+}
+
+fn record_refresh(cache: &mut RefreshCache, name: &str, result: &Result<RefreshStatus, Error>) {
+    let result = match result {
+        Ok(RefreshStatus::Update { .. }) => RefreshResult::Updated,
+        Ok(RefreshStatus::DoNothing(_)) => RefreshResult::Unchanged,
+        Err(e) => RefreshResult::Failed(e.message()),
+    };
+    cache.record(name, now(), result);
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Results of `refresh --all` to be posted as a notification
+#[derive(Debug, Default, PartialEq)]
+struct RefreshSummary {
+    updated: usize,
+    skipped: usize,
+    /// repository names and the errors
+    failed: Vec<(String, String)>,
+    /// repository names and the number of commits of the default branch to push
+    to_push: Vec<(String, usize)>,
+    /// repositories skipped because they are not accessible
+    permission_denied: Vec<String>,
+}
+
+impl RefreshSummary {
+    fn record(&mut self, name: &str, result: &Result<RefreshStatus, Error>) {
+        match result {
+            Ok(RefreshStatus::Update { report, .. }) => {
+                self.updated += 1;
+                if report.to_push > 0 {
+                    self.to_push.push((name.to_string(), report.to_push));
+                }
+            }
+            Ok(RefreshStatus::DoNothing(reason)) => {
+                self.skipped += 1;
+                if *reason == Reason::PermissionDenied {
+                    self.permission_denied.push(name.to_string());
+                }
+            }
+            Err(e) => self.failed.push((name.to_string(), e.message())),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = vec![format!(
+            "mure refresh: {} updated, {} skipped, {} failed",
+            self.updated,
+            self.skipped,
+            self.failed.len()
+        )];
+        for (name, error) in &self.failed {
+            let error = error.lines().next().unwrap_or_default();
+            lines.push(format!("- {name}: {error}"));
+        }
+        for (name, count) in &self.to_push {
+            lines.push(format!("- {name}: {count} commits to push"));
+        }
+        for name in &self.permission_denied {
+            lines.push(format!("- {name}: skipped (permission denied)"));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Post the summary to `[notifications] webhook_url` if it is set.
+/// Failure of posting doesn't fail refresh.
+fn notify(config: &Config, summary: &RefreshSummary) {
+    let Some(url) = config
+        .notifications
+        .as_ref()
+        .and_then(|n| n.webhook_url.as_ref())
+    else {
+        return;
+    };
+    if DryRun::is_enabled() {
+        return;
+    }
+    if let Err(e) = post_webhook(url, &summary.to_text()) {
+        tracing::error!("failed to post the notification: {e}");
+        eprintln!("Failed to post the notification: {e}");
+    }
+}
+
+/// Lines describing the result of refresh for humans
+fn refresh_result_text(name: &str, result: &Result<RefreshStatus, Error>) -> String {
+    match result {
+        Ok(status) => match status {
+            RefreshStatus::DoNothing(reason) => match reason {
+                Reason::NotGitRepository => format!("{}\n", Message::NotGitRepository { name }),
+                Reason::NoRemote => format!("{}\n", Message::NoRemote { name }),
+                Reason::PermissionDenied => format!("{}\n", Message::PermissionDenied { name }),
+            },
+            RefreshStatus::Update {
+                switch_to_default,
+                message,
+                ..
+            } => {
+                let mut text = String::new();
+                if *switch_to_default {
+                    text.push_str(&format!("{}\n", Message::SwitchedTo { branch: name }));
+                }
+                text.push_str(&format!("{message}\n"));
+                text
+            }
+        },
+        Err(e) => format!("{}\n", e.message()),
+    }
+}
+
+/// Convert the result of refresh to events for --json-lines
+fn refresh_events(name: &str, result: Result<RefreshStatus, Error>) -> Vec<Event> {
+    let (status, message, reason, report, error) = match result {
+        Ok(RefreshStatus::DoNothing(reason)) => {
+            let message = match reason {
+                Reason::NotGitRepository => "not a git repository",
+                Reason::NoRemote => "no remote",
+                Reason::PermissionDenied => "permission denied",
+            };
+            ("skipped", message.to_string(), Some(reason), None, None)
+        }
+        Ok(RefreshStatus::Update {
+            message, report, ..
+        }) => ("updated", message, None, Some(report), None),
+        Err(e) => ("failed", e.message(), None, None, Some(e.to_string())),
+    };
+    // Each line of messages from refresh is a progress
+    let progress = match status {
+        "updated" => message.as_str(),
+        _ => "",
+    };
+    let mut events = progress
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| Event::Progress {
+            repository: name.to_string(),
+            message: line.to_string(),
+        })
+        .collect::<Vec<_>>();
+    events.push(Event::Finished {
+        repository: name.to_string(),
+        status: status.to_string(),
+        message,
+        reason,
+        report,
+        error,
+    });
+    events
+}
+
+pub fn refresh(
+    repo_path: &str,
+    options: &RefreshOptions,
+    verbosity: Verbosity,
+) -> Result<RefreshStatus, Error> {
+    let span = tracing::info_span!("refresh", repo = repo_path);
+    let _enter = span.enter();
+    SkipHooks::set(options.skip_hooks);
+    let result = refresh_repository(repo_path, options, verbosity);
+    SkipHooks::set(false);
+    match &result {
+        Ok(RefreshStatus::DoNothing(reason)) => tracing::info!(?reason, "skipped"),
+        Ok(RefreshStatus::Update { message, .. }) => {
+            for line in message.lines().filter(|line| !line.is_empty()) {
+                tracing::info!("{line}");
+            }
+        }
+        Err(e) => tracing::error!("{e}"),
+    }
+    result
+}
+
+fn refresh_repository(
+    repo_path: &str,
+    options: &RefreshOptions,
+    verbosity: Verbosity,
+) -> Result<RefreshStatus, Error> {
     let mut messages = vec![];
+    let mut report = RefreshReport::default();
+    // before anything else because .git doesn't seem to exist if the directory is not readable
+    if is_permission_denied(Path::new(repo_path)) {
+        return Ok(RefreshStatus::DoNothing(Reason::PermissionDenied));
+    }
     if !PathBuf::from(repo_path).join(".git").exists() {
         return Ok(RefreshStatus::DoNothing(Reason::NotGitRepository));
     }
@@ -123,28 +652,108 @@ pub fn refresh(repo_path: &str, verbosity: Verbosity) -> Result<RefreshStatus, E
         return Ok(RefreshStatus::DoNothing(Reason::NoRemote));
     }
 
-    let default_branch = get_default_branch(&repo_path.into())?;
+    let mut default_branch = match &options.default_branch {
+        Some(branch) => branch.clone(),
+        None => resolve_default_branch(&repo, repo_path)?,
+    };
 
+    // the mirror first, so that the working clone fetches objects from it
+    for mirror in mirrors_of(&repo)? {
+        mirror.update_mirror()?;
+        let path = mirror.path().display().to_string();
+        messages.push(Message::UpdatedMirror { path: &path }.to_string());
+    }
     repo.fetch_prune()?;
+    // origin/HEAD cached at clone still points to the old name after the default branch is renamed
+    let stale_head = options.default_branch.is_none()
+        && repo
+            .find_branch(&format!("origin/{default_branch}"), BranchType::Remote)
+            .is_err();
+    if stale_head {
+        if let Some(renamed) = repo
+            .query_remote_head("origin")?
+            .interpreted_to
+            .filter(|branch| branch != &default_branch)
+        {
+            if !options.fix_head {
+                return Err(Error::from_str(
+                    &Message::DefaultBranchRenamed {
+                        from: &default_branch,
+                        to: &renamed,
+                    }
+                    .to_string(),
+                ));
+            }
+            follow_renamed_default_branch(&repo, &default_branch, &renamed, &mut messages)?;
+            default_branch = renamed;
+        }
+    }
+    let fork = options.sync_forks && is_fork(&repo)?;
+    if fork {
+        repo.fetch_remote_prune(UPSTREAM)?;
+    }
+
+    // Pushing is left to the user, so the default branch ahead of origin is neither switched to nor pulled.
+    // The branch may not exist locally yet.
+    let mut to_push = repo
+        .ahead_behind(&default_branch, &format!("origin/{default_branch}"))
+        .map_or(0, |(ahead, _)| ahead);
+    if fork && to_push > 0 {
+        // commits taken from upstream but not pushed to the fork yet are not the user's
+        to_push = commits_not_in_upstream(&repo, &default_branch)?;
+    }
+    if to_push > 0 {
+        report.to_push = to_push;
+        messages.push(
+            Message::CommitsToPush {
+                branch: &default_branch,
+                count: to_push,
+            }
+            .to_string(),
+        );
+    }
 
     // switch to default branch if current branch is clean
-    if repo.is_clean()? {
+    if to_push == 0 && !options.keep_current_branch && repo.is_clean()? {
         // git switch $default_branch
         repo.switch(&default_branch)?;
-        messages.push(format!("Switched to {default_branch}"));
+        report.switched_to = Some(default_branch.clone());
+        messages.push(
+            Message::SwitchedTo {
+                branch: &default_branch,
+            }
+            .to_string(),
+        );
     }
+    let current_branch = repo.get_current_branch().ok();
 
     // TODO: origin is hardcoded. If you have multiple remotes, you need to specify which one to use.
-    let result = repo.pull_fast_forwarded("origin", &default_branch);
-    if let Ok(out) = result {
+    let result = if to_push > 0 {
+        None
+    } else if options.keep_current_branch && current_branch.as_ref() != Some(&default_branch) {
+        // git fetch origin $default_branch:$default_branch
+        Some(repo.update_branch_from_remote("origin", &default_branch))
+    } else {
+        Some(repo.pull_fast_forwarded("origin", &default_branch))
+    };
+    let mut fast_forwarded = matches!(
+        &result,
+        Some(Ok(out)) if out.interpreted_to == PullFastForwardStatus::FastForwarded
+    );
+    if let Some(Ok(out)) = result {
+        report.pulled = match out.interpreted_to {
+            PullFastForwardStatus::AlreadyUpToDate => PullStatus::AlreadyUpToDate,
+            PullFastForwardStatus::FastForwarded => PullStatus::FastForwarded,
+            _ => PullStatus::NotPulled,
+        };
         match out.interpreted_to {
             PullFastForwardStatus::AlreadyUpToDate => match verbosity {
                 Verbosity::Quiet => (),
                 Verbosity::Normal => {
-                    messages.push("Already up to date".to_string());
+                    messages.push(Message::AlreadyUpToDate.to_string());
                 }
-                Verbosity::Verbose => {
-                    messages.push("Already up to date".to_string());
+                Verbosity::Verbose | Verbosity::Debug => {
+                    messages.push(Message::AlreadyUpToDate.to_string());
                     messages.push(out.raw.stderr);
                     messages.push(out.raw.stdout);
                 }
@@ -152,10 +761,10 @@ pub fn refresh(repo_path: &str, verbosity: Verbosity) -> Result<RefreshStatus, E
             PullFastForwardStatus::FastForwarded => match verbosity {
                 Verbosity::Quiet => (),
                 Verbosity::Normal => {
-                    messages.push("Fast-forwarded".to_string());
+                    messages.push(Message::FastForwarded.to_string());
                 }
-                Verbosity::Verbose => {
-                    messages.push("Fast-forwarded".to_string());
+                Verbosity::Verbose | Verbosity::Debug => {
+                    messages.push(Message::FastForwarded.to_string());
                     messages.push(out.raw.stderr);
                     messages.push(out.raw.stdout);
                 }
@@ -164,27 +773,356 @@ pub fn refresh(repo_path: &str, verbosity: Verbosity) -> Result<RefreshStatus, E
         };
     }
 
-    let merged_branches = repo.merged_branches()?.interpreted_to;
-    let delete_branches = merged_branches
+    if fork && to_push == 0 {
+        fast_forwarded |= sync_fork(
+            &repo,
+            &default_branch,
+            current_branch.as_ref(),
+            options.push_forks,
+            &mut messages,
+        )?;
+    }
+
+    if options.update_submodules && fast_forwarded {
+        messages.append(&mut update_submodules(&repo)?);
+    }
+
+    for branch in &options.sync_branches {
+        if branch == &default_branch {
+            continue;
+        }
+        if let Some(message) = sync_branch(&repo, branch, current_branch.as_ref(), verbosity)? {
+            messages.push(message);
+        }
+    }
+
+    let merged_branches = if options.keep_merged_branches {
+        vec![]
+    } else if options.keep_current_branch {
+        // HEAD may not be the default branch
+        repo.merged_branches_into(&default_branch)?.interpreted_to
+    } else {
+        repo.merged_branches()?.interpreted_to
+    };
+    let delete_only = compile_patterns(&options.delete_only)?;
+    let protect_branches = compile_patterns(&options.protect_branches)?;
+    let mut delete_branches = vec![];
+    for branch in merged_branches
         .iter()
         .filter(|&branch| !branch.eq(&default_branch))
-        .collect::<Vec<_>>();
+        .filter(|&branch| !options.sync_branches.contains(branch))
+        // the branch checked out can't be deleted
+        .filter(|&branch| current_branch.as_ref() != Some(branch))
+    {
+        match keep_reason(&repo, branch, &delete_only, &protect_branches)? {
+            Some(reason) => {
+                if verbosity >= Verbosity::Verbose {
+                    messages.push(reason.message(branch));
+                }
+            }
+            None => delete_branches.push(branch),
+        }
+    }
+
+    // Stacked or reopened pull requests break if their branches are deleted
+    let pull_request_branches = if options.check_open_pull_requests && !delete_branches.is_empty() {
+        match branches_of_open_pull_requests(&repo) {
+            Ok(branches) => branches,
+            Err(e) => {
+                messages.push(
+                    Message::SkippedDeletingMergedBranches {
+                        reason: &e.message(),
+                    }
+                    .to_string(),
+                );
+                return Ok(RefreshStatus::Update {
+                    switch_to_default: false,
+                    message: messages.join("\n"),
+                    report,
+                });
+            }
+        }
+    } else {
+        HashMap::new()
+    };
 
     for branch in delete_branches {
+        if let Some(number) = pull_request_branches.get(branch) {
+            messages.push(
+                Message::SkippedDeletingBranch {
+                    branch,
+                    number: *number,
+                }
+                .to_string(),
+            );
+            continue;
+        }
         repo.delete_branch(branch)?;
-        messages.push(format!("Deleted branch {branch}"));
+        report.deleted_branches.push(branch.to_string());
+        messages.push(Message::DeletedBranch { branch }.to_string());
     }
 
     Ok(RefreshStatus::Update {
         switch_to_default: false,
         message: messages.join("\n"),
+        report,
     })
 }
 
+/// Why a merged branch is kept by the branch deletion policy
+#[derive(Debug, PartialEq, Eq)]
+enum KeepReason {
+    Protected(String),
+    NotSelected,
+    Unpushed,
+}
+
+impl KeepReason {
+    fn message(&self, branch: &str) -> String {
+        match self {
+            KeepReason::Protected(pattern) => Message::KeptProtectedBranch { branch, pattern },
+            KeepReason::NotSelected => Message::KeptUnselectedBranch { branch },
+            KeepReason::Unpushed => Message::KeptUnpushedBranch { branch },
+        }
+        .to_string()
+    }
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Pattern>, Error> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern)
+                .map_err(|e| Error::from_str(&format!("invalid branch pattern {pattern}: {e}")))
+        })
+        .collect()
+}
+
+/// Decide whether the merged branch is kept. Protection is prior to `delete_only`.
+/// Branches ahead of their upstream are kept even if merged.
+fn keep_reason(
+    repo: &Repository,
+    branch: &str,
+    delete_only: &[Pattern],
+    protect_branches: &[Pattern],
+) -> Result<Option<KeepReason>, Error> {
+    if let Some(pattern) = protect_branches.iter().find(|p| p.matches(branch)) {
+        return Ok(Some(KeepReason::Protected(pattern.to_string())));
+    }
+    if !delete_only.is_empty() && !delete_only.iter().any(|p| p.matches(branch)) {
+        return Ok(Some(KeepReason::NotSelected));
+    }
+    if repo.has_unpushed_commits(branch)? {
+        return Ok(Some(KeepReason::Unpushed));
+    }
+    Ok(None)
+}
+
+/// Resolve the default branch locally first and ask gh only if it can't be guessed.
+/// The result of gh is cached as origin/HEAD not to hit the network next time.
+fn resolve_default_branch(repo: &Repository, repo_path: &str) -> Result<String, Error> {
+    if let Some(branch) = repo.guess_default_branch("origin")? {
+        return Ok(branch);
+    }
+    let branch = GhClient::new().default_branch(Path::new(repo_path))?;
+    if let Err(e) = repo.set_remote_head("origin", &branch) {
+        tracing::warn!("failed to cache the default branch: {}", Error::from(e));
+    }
+    Ok(branch)
+}
+
+/// Point origin/HEAD to the renamed default branch and replace the local branch of the old name.
+/// The old branch is renamed, or deleted if the new one exists locally and origin has all of its commits.
+fn follow_renamed_default_branch(
+    repo: &Repository,
+    from: &str,
+    to: &str,
+    messages: &mut Vec<String>,
+) -> Result<(), Error> {
+    repo.set_remote_head("origin", to)?;
+    let upstream = format!("origin/{to}");
+    if repo.find_branch(from, BranchType::Local).is_ok() {
+        if repo.find_branch(to, BranchType::Local).is_err() {
+            repo.rename_branch(from, to)?;
+            repo.set_upstream(to, &upstream)?;
+        } else if repo.ahead_behind(from, &upstream)?.0 == 0 {
+            if repo.get_current_branch().ok().as_deref() == Some(from) {
+                repo.switch(to)?;
+            }
+            repo.force_delete_branch(from)?;
+            messages.push(Message::DeletedBranch { branch: from }.to_string());
+        } else {
+            messages.push(Message::KeptUnpushedBranch { branch: from }.to_string());
+        }
+    }
+    messages.push(Message::FollowedRenamedDefaultBranch { from, to }.to_string());
+    Ok(())
+}
+
+/// Bare mirrors (`[clone] mirror`) which the repository borrows objects from
+fn mirrors_of(repo: &Repository) -> Result<Vec<Repository>, Error> {
+    let mut mirrors = vec![];
+    for objects in repo.alternates()? {
+        let Some(Ok(mirror)) = objects.parent().map(Repository::open_bare) else {
+            continue;
+        };
+        let is_mirror = mirror
+            .config()
+            .and_then(|config| config.get_bool("remote.origin.mirror"))
+            .unwrap_or(false);
+        if mirror.is_bare() && is_mirror {
+            mirrors.push(mirror);
+        }
+    }
+    Ok(mirrors)
+}
+
+/// Fast-forward the local branch from origin. Branches which don't exist locally are skipped.
+fn sync_branch(
+    repo: &Repository,
+    branch: &str,
+    current_branch: Option<&String>,
+    verbosity: Verbosity,
+) -> Result<Option<String>, Error> {
+    if repo.find_branch(branch, BranchType::Local).is_err() {
+        return Ok(None);
+    }
+    // the branch checked out can't be updated by fetch
+    let result = if current_branch.is_some_and(|current| current == branch) {
+        repo.pull_fast_forwarded("origin", branch)?
+    } else {
+        repo.update_branch_from_remote("origin", branch)?
+    };
+    let message = match result.interpreted_to {
+        PullFastForwardStatus::FastForwarded => Message::BranchFastForwarded { branch }.to_string(),
+        PullFastForwardStatus::AlreadyUpToDate => match verbosity {
+            Verbosity::Verbose | Verbosity::Debug => {
+                Message::BranchAlreadyUpToDate { branch }.to_string()
+            }
+            _ => return Ok(None),
+        },
+        PullFastForwardStatus::Abort => Message::BranchNotFastForwarded { branch }.to_string(),
+    };
+    Ok(Some(message))
+}
+
+/// Remote of the original repository of a fork. origin is the fork.
+const UPSTREAM: &str = "upstream";
+
+/// Whether the repository is a fork with both `origin` and `upstream` remotes
+fn is_fork(repo: &Repository) -> Result<bool, Error> {
+    let remotes = repo.remotes()?;
+    let has = |name: &str| remotes.iter().flatten().any(|remote| remote == name);
+    Ok(has("origin") && has(UPSTREAM))
+}
+
+/// Commits of the local branch in neither origin nor upstream
+fn commits_not_in_upstream(repo: &Repository, branch: &str) -> Result<usize, Error> {
+    let mut walk = repo.revwalk()?;
+    walk.push_ref(&format!("refs/heads/{branch}"))?;
+    walk.hide_ref(&format!("refs/remotes/origin/{branch}"))?;
+    // upstream may not have the branch of the same name
+    let _ = walk.hide_ref(&format!("refs/remotes/{UPSTREAM}/{branch}"));
+    Ok(walk.count())
+}
+
+/// Fast-forward the branch of the fork from upstream and push it to origin if `push`.
+/// Returns whether the branch moved.
+fn sync_fork(
+    repo: &Repository,
+    branch: &str,
+    current_branch: Option<&String>,
+    push: bool,
+    messages: &mut Vec<String>,
+) -> Result<bool, Error> {
+    // the branch checked out can't be updated by fetch
+    let result = if current_branch.is_some_and(|current| current == branch) {
+        repo.pull_fast_forwarded(UPSTREAM, branch)?
+    } else {
+        repo.update_branch_from_remote(UPSTREAM, branch)?
+    };
+    match result.interpreted_to {
+        PullFastForwardStatus::FastForwarded => {
+            messages.push(Message::FastForwardedFromUpstream { branch }.to_string())
+        }
+        PullFastForwardStatus::Abort => {
+            messages.push(Message::NotFastForwardedFromUpstream { branch }.to_string())
+        }
+        PullFastForwardStatus::AlreadyUpToDate => (),
+    }
+    // also pushes what was synced by the previous runs without push
+    let ahead_of_origin = repo
+        .ahead_behind(branch, &format!("origin/{branch}"))
+        .is_ok_and(|(ahead, _)| ahead > 0);
+    if push && ahead_of_origin {
+        repo.push("origin", branch)?;
+        messages.push(Message::PushedToOrigin { branch }.to_string());
+    }
+    Ok(result.interpreted_to == PullFastForwardStatus::FastForwarded)
+}
+
+/// Branches which open pull requests of origin target or are based on, with the PR number
+fn branches_of_open_pull_requests(repo: &Repository) -> Result<HashMap<String, i64>, Error> {
+    let remote = repo.find_remote("origin")?;
+    let Some(url) = remote.url() else {
+        return Err(Error::from_str("origin url is not valid utf-8"));
+    };
+    let Some(repo_info) = RepoInfo::parse_url(url) else {
+        return Err(Error::from_str(&format!(
+            "{url} is not a GitHub repository"
+        )));
+    };
+    let token = get_github_token()?;
+    let pull_requests = open_pull_requests(&token, &repo_info.owner, &repo_info.repo)?;
+    Ok(pull_request_branches(pull_requests.into_iter().map(|pr| {
+        (
+            pr.number,
+            pr.base_ref_name,
+            pr.head_ref_name,
+            pr.is_cross_repository,
+        )
+    })))
+}
+
+/// Map branch names to the pull request using them.
+/// Head branches of pull requests from forks are not branches of origin, so they are ignored.
+fn pull_request_branches(
+    pull_requests: impl Iterator<Item = (i64, String, String, bool)>,
+) -> HashMap<String, i64> {
+    let mut branches = HashMap::new();
+    for (number, base, head, is_cross_repository) in pull_requests {
+        branches.insert(base, number);
+        if !is_cross_repository {
+            branches.insert(head, number);
+        }
+    }
+    branches
+}
+
+/// Update submodules and report which of them moved
+fn update_submodules(repo: &Repository) -> Result<Vec<String>, Error> {
+    let before = repo
+        .submodule_heads()?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    if before.is_empty() {
+        return Ok(vec![]);
+    }
+    repo.update_submodules()?;
+    let messages = repo
+        .submodule_heads()?
+        .into_iter()
+        .filter(|(name, head)| before.get(name) != Some(head))
+        .map(|(name, _)| Message::UpdatedSubmodule { name: &name }.to_string())
+        .collect();
+    Ok(messages)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_fixture::Fixture;
+    use crate::test_fixture::{config_with_base_dir, Fixture};
+    use assay::assay;
     use mktemp::Temp;
 
     #[test]
@@ -200,7 +1138,11 @@ mod tests {
             .repo
             .command(&["switch", "-c", "main"])
             .unwrap();
-        let result = refresh(origin_path.to_str().unwrap(), Verbosity::Normal);
+        let result = refresh(
+            origin_path.to_str().unwrap(),
+            &RefreshOptions::default(),
+            Verbosity::Normal,
+        );
         match result {
             Ok(RefreshStatus::DoNothing(Reason::NoRemote)) => (),
             _ => unreachable!(),
@@ -223,7 +1165,11 @@ mod tests {
             .unwrap();
         let path = fixture.repo.path().parent().unwrap();
 
-        let result = refresh(path.to_str().unwrap(), Verbosity::Normal);
+        let result = refresh(
+            path.to_str().unwrap(),
+            &RefreshOptions::default(),
+            Verbosity::Normal,
+        );
         match result {
             Ok(RefreshStatus::Update {
                 switch_to_default, ..
@@ -237,6 +1183,745 @@ mod tests {
         drop(fixture);
     }
 
+    #[test]
+    fn test_refresh_mirror() {
+        let upstream = Fixture::create().unwrap();
+        upstream.create_empty_commit("initial commit").unwrap();
+        upstream.repo.command(&["switch", "-c", "main"]).unwrap();
+        let upstream_path = upstream.repo.workdir().unwrap().to_str().unwrap();
+
+        let temp_dir = Temp::new_dir().unwrap();
+        let mirror_path = temp_dir.join("mure.git");
+        Repository::clone_mirror(upstream_path, &mirror_path).unwrap();
+        Repository::git_command_on_dir(
+            &[
+                "clone",
+                "--reference",
+                mirror_path.to_str().unwrap(),
+                upstream_path,
+                "work",
+            ],
+            &temp_dir,
+        )
+        .unwrap();
+        let work_path = temp_dir.join("work");
+        let work = Repository::open(&work_path).unwrap();
+        assert_eq!(mirrors_of(&work).unwrap().len(), 1);
+        assert!(mirrors_of(&upstream.repo).unwrap().is_empty());
+
+        upstream.create_empty_commit("second commit").unwrap();
+        refresh(
+            work_path.to_str().unwrap(),
+            &RefreshOptions::default(),
+            Verbosity::Quiet,
+        )
+        .unwrap();
+        let head = upstream.repo.head().unwrap().target().unwrap();
+        let mirror = Repository::open_bare(&mirror_path).unwrap();
+        assert_eq!(mirror.refname_to_id("refs/heads/main").unwrap(), head);
+        assert_eq!(
+            work.refname_to_id("refs/remotes/origin/main").unwrap(),
+            head
+        );
+    }
+
+    #[test]
+    fn test_refresh_keep_current_branch() {
+        let fixture = Fixture::create().unwrap();
+        let fixture_origin = Fixture::create().unwrap();
+
+        let origin_path = fixture_origin.repo.path().parent().unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        fixture_origin
+            .repo
+            .command(&["switch", "-c", "main"])
+            .unwrap();
+
+        let repo = &fixture.repo;
+        repo.remote("origin", origin_path.to_str().unwrap())
+            .unwrap();
+        repo.command(&["fetch", "origin"]).unwrap();
+        repo.command(&["switch", "main"]).unwrap();
+        repo.command(&["remote", "set-head", "origin", "main"])
+            .unwrap();
+        repo.command(&["switch", "-c", "feature"]).unwrap();
+        fixture.create_empty_commit("feature commit").unwrap();
+        fixture_origin.create_empty_commit("second commit").unwrap();
+
+        let options = RefreshOptions {
+            keep_current_branch: true,
+            ..Default::default()
+        };
+        let path = repo.path().parent().unwrap();
+        // gh doesn't know the local origin. origin/HEAD is used instead.
+        let result = refresh(path.to_str().unwrap(), &options, Verbosity::Normal);
+        match result {
+            Ok(RefreshStatus::Update { message, .. }) => {
+                assert!(message.contains("Fast-forwarded"));
+                assert!(!message.contains("Switched"));
+            }
+            Ok(result) => unreachable!("{:?}", result),
+            Err(e) => unreachable!("{:?}", e),
+        }
+        assert_eq!(repo.get_current_branch().unwrap(), "feature");
+        assert_eq!(
+            repo.refname_to_id("refs/heads/main").unwrap(),
+            fixture_origin
+                .repo
+                .refname_to_id("refs/heads/main")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_refresh_commits_to_push() {
+        let fixture = Fixture::create().unwrap();
+        let fixture_origin = Fixture::create().unwrap();
+
+        let origin_path = fixture_origin.repo.path().parent().unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        fixture_origin
+            .repo
+            .command(&["switch", "-c", "main"])
+            .unwrap();
+
+        let repo = &fixture.repo;
+        repo.remote("origin", origin_path.to_str().unwrap())
+            .unwrap();
+        repo.command(&["fetch", "origin"]).unwrap();
+        repo.command(&["switch", "main"]).unwrap();
+        repo.command(&["remote", "set-head", "origin", "main"])
+            .unwrap();
+        fixture.create_empty_commit("local commit 1").unwrap();
+        fixture.create_empty_commit("local commit 2").unwrap();
+        repo.command(&["switch", "-c", "feature"]).unwrap();
+
+        let path = repo.path().parent().unwrap();
+        let result = refresh(
+            path.to_str().unwrap(),
+            &RefreshOptions::default(),
+            Verbosity::Normal,
+        );
+        match &result {
+            Ok(RefreshStatus::Update {
+                message, report, ..
+            }) => {
+                assert!(message.contains("main has 2 commits to push"));
+                assert_eq!(report.to_push, 2);
+                assert_eq!(report.switched_to, None);
+                assert_eq!(report.pulled, PullStatus::NotPulled);
+            }
+            Ok(result) => unreachable!("{:?}", result),
+            Err(e) => unreachable!("{:?}", e),
+        }
+        assert_eq!(repo.get_current_branch().unwrap(), "feature");
+
+        let mut summary = RefreshSummary::default();
+        summary.record("mure", &result);
+        assert_eq!(
+            summary.to_text(),
+            "mure refresh: 1 updated, 0 skipped, 0 failed\n- mure: 2 commits to push"
+        );
+    }
+
+    #[test]
+    fn test_refresh_check_open_pull_requests() {
+        let fixture = Fixture::create().unwrap();
+        let fixture_origin = Fixture::create().unwrap();
+
+        let origin_path = fixture_origin.repo.path().parent().unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        fixture_origin
+            .repo
+            .command(&["switch", "-c", "main"])
+            .unwrap();
+
+        let repo = &fixture.repo;
+        repo.remote("origin", origin_path.to_str().unwrap())
+            .unwrap();
+        repo.command(&["fetch", "origin"]).unwrap();
+        repo.command(&["switch", "main"]).unwrap();
+        repo.command(&["remote", "set-head", "origin", "main"])
+            .unwrap();
+        repo.command(&["branch", "merged"]).unwrap();
+
+        let options = RefreshOptions {
+            check_open_pull_requests: true,
+            ..Default::default()
+        };
+        let path = repo.path().parent().unwrap();
+        // Open pull requests of the local origin can't be known. Merged branches are kept.
+        let result = refresh(path.to_str().unwrap(), &options, Verbosity::Normal);
+        match result {
+            Ok(RefreshStatus::Update { message, .. }) => {
+                assert!(message.contains("Skipped deleting merged branches"));
+            }
+            Ok(result) => unreachable!("{:?}", result),
+            Err(e) => unreachable!("{:?}", e),
+        }
+        assert!(repo.find_branch("merged", BranchType::Local).is_ok());
+
+        let result = refresh(
+            path.to_str().unwrap(),
+            &RefreshOptions::default(),
+            Verbosity::Normal,
+        );
+        match result {
+            Ok(RefreshStatus::Update {
+                message, report, ..
+            }) => {
+                assert!(message.contains("Deleted branch merged"));
+                assert_eq!(report.deleted_branches, vec!["merged"]);
+            }
+            Ok(result) => unreachable!("{:?}", result),
+            Err(e) => unreachable!("{:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_refresh_asserted_default_branch() {
+        let fixture = Fixture::create().unwrap();
+        let fixture_origin = Fixture::create().unwrap();
+
+        let origin_path = fixture_origin.repo.path().parent().unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        let origin = &fixture_origin.repo;
+        origin.command(&["switch", "-c", "main"]).unwrap();
+        origin.command(&["branch", "develop"]).unwrap();
+
+        let repo = &fixture.repo;
+        repo.remote("origin", origin_path.to_str().unwrap())
+            .unwrap();
+        repo.command(&["fetch", "origin"]).unwrap();
+        repo.command(&["switch", "main"]).unwrap();
+        // origin/HEAD is wrong and can't be corrected
+        repo.command(&["remote", "set-head", "origin", "develop"])
+            .unwrap();
+
+        fixture_origin.create_empty_commit("main commit").unwrap();
+
+        let options = RefreshOptions {
+            default_branch: Some("main".to_string()),
+            ..Default::default()
+        };
+        let path = repo.path().parent().unwrap();
+        refresh(path.to_str().unwrap(), &options, Verbosity::Normal).unwrap();
+        assert_eq!(repo.get_current_branch().unwrap(), "main");
+        assert_eq!(
+            repo.refname_to_id("refs/heads/main").unwrap(),
+            origin.refname_to_id("refs/heads/main").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_refresh_sync_branches() {
+        let fixture = Fixture::create().unwrap();
+        let fixture_origin = Fixture::create().unwrap();
+
+        let origin_path = fixture_origin.repo.path().parent().unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        let origin = &fixture_origin.repo;
+        origin.command(&["switch", "-c", "main"]).unwrap();
+        origin.command(&["branch", "develop"]).unwrap();
+        origin.command(&["branch", "release"]).unwrap();
+
+        let repo = &fixture.repo;
+        repo.remote("origin", origin_path.to_str().unwrap())
+            .unwrap();
+        repo.command(&["fetch", "origin"]).unwrap();
+        repo.command(&["switch", "main"]).unwrap();
+        repo.command(&["branch", "develop", "origin/develop"])
+            .unwrap();
+        repo.command(&["branch", "release", "origin/release"])
+            .unwrap();
+        repo.command(&["remote", "set-head", "origin", "main"])
+            .unwrap();
+
+        origin.command(&["switch", "develop"]).unwrap();
+        fixture_origin
+            .create_empty_commit("develop commit")
+            .unwrap();
+        origin.command(&["switch", "main"]).unwrap();
+
+        let options = RefreshOptions {
+            sync_branches: vec![
+                "develop".to_string(),
+                "release".to_string(),
+                "missing".to_string(),
+            ],
+            ..Default::default()
+        };
+        let path = repo.path().parent().unwrap();
+        let result = refresh(path.to_str().unwrap(), &options, Verbosity::Normal);
+        match result {
+            Ok(RefreshStatus::Update { message, .. }) => {
+                assert!(message.contains("Fast-forwarded develop"));
+                // release is merged into main but it is kept
+                assert!(!message.contains("Deleted branch release"));
+            }
+            Ok(result) => unreachable!("{:?}", result),
+            Err(e) => unreachable!("{:?}", e),
+        }
+        assert_eq!(
+            repo.refname_to_id("refs/heads/develop").unwrap(),
+            origin.refname_to_id("refs/heads/develop").unwrap()
+        );
+        assert!(repo.find_branch("release", BranchType::Local).is_ok());
+        assert!(repo.find_branch("missing", BranchType::Local).is_err());
+    }
+
+    #[test]
+    fn test_refresh_renamed_default_branch() {
+        let origin_dir = Temp::new_dir().unwrap();
+        let origin = Repository::init_bare(&origin_dir).unwrap();
+        let origin_path = origin_dir.to_str().unwrap();
+        let maintainer = Fixture::create().unwrap();
+        maintainer.create_empty_commit("initial commit").unwrap();
+        maintainer
+            .repo
+            .command(&["branch", "-M", "master"])
+            .unwrap();
+        maintainer
+            .repo
+            .command(&["push", origin_path, "master"])
+            .unwrap();
+        origin.set_head("refs/heads/master").unwrap();
+
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        repo.remote("origin", origin_path).unwrap();
+        repo.command(&["fetch", "origin"]).unwrap();
+        repo.command(&["switch", "master"]).unwrap();
+        repo.command(&["remote", "set-head", "origin", "master"])
+            .unwrap();
+        let path = repo.workdir().unwrap().to_str().unwrap();
+
+        // renamed on GitHub
+        maintainer
+            .repo
+            .command(&["push", origin_path, "master:main"])
+            .unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        maintainer
+            .repo
+            .command(&["push", origin_path, "--delete", "master"])
+            .unwrap();
+
+        let Err(error) = refresh(path, &RefreshOptions::default(), Verbosity::Normal) else {
+            unreachable!();
+        };
+        assert_eq!(
+            error.to_string(),
+            "The default branch of origin was renamed from master to main. Run with --fix-head to follow it"
+        );
+
+        let options = RefreshOptions {
+            fix_head: true,
+            ..Default::default()
+        };
+        match refresh(path, &options, Verbosity::Normal) {
+            Ok(RefreshStatus::Update { message, .. }) => {
+                assert!(message.contains("Followed the default branch renamed from master to main"));
+            }
+            result => unreachable!("{:?}", result),
+        }
+        assert_eq!(repo.get_current_branch().unwrap(), "main");
+        assert!(repo.find_branch("master", BranchType::Local).is_err());
+        assert_eq!(
+            repo.remote_default_branch("origin").unwrap(),
+            Some("main".to_string())
+        );
+        let main = repo.find_branch("main", BranchType::Local).unwrap();
+        assert_eq!(
+            main.upstream().unwrap().name().unwrap(),
+            Some("origin/main")
+        );
+
+        // nothing to follow any more
+        assert!(refresh(path, &RefreshOptions::default(), Verbosity::Normal).is_ok());
+    }
+
+    #[test]
+    fn test_refresh_sync_forks() {
+        let upstream = Fixture::create().unwrap();
+        upstream.create_empty_commit("initial commit").unwrap();
+        upstream.repo.command(&["switch", "-c", "main"]).unwrap();
+        let upstream_path = upstream.repo.workdir().unwrap().to_str().unwrap();
+
+        // the fork is bare to be pushed to
+        let origin_dir = Temp::new_dir().unwrap();
+        let origin = Repository::init_bare(&origin_dir).unwrap();
+        let origin_path = origin_dir.to_str().unwrap();
+        upstream
+            .repo
+            .command(&["push", origin_path, "main"])
+            .unwrap();
+
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        repo.remote("origin", origin_path).unwrap();
+        repo.remote("upstream", upstream_path).unwrap();
+        repo.command(&["fetch", "origin"]).unwrap();
+        repo.command(&["switch", "main"]).unwrap();
+        repo.command(&["remote", "set-head", "origin", "main"])
+            .unwrap();
+
+        upstream.create_empty_commit("upstream commit").unwrap();
+        let upstream_head = upstream.repo.refname_to_id("refs/heads/main").unwrap();
+        let path = repo.workdir().unwrap().to_str().unwrap();
+
+        // without sync_forks, upstream is ignored
+        let result = refresh(path, &RefreshOptions::default(), Verbosity::Normal).unwrap();
+        assert!(
+            !matches!(&result, RefreshStatus::Update { message, .. } if message.contains("upstream"))
+        );
+        assert_ne!(
+            repo.refname_to_id("refs/heads/main").unwrap(),
+            upstream_head
+        );
+
+        let options = RefreshOptions {
+            sync_forks: true,
+            ..Default::default()
+        };
+        match refresh(path, &options, Verbosity::Normal) {
+            Ok(RefreshStatus::Update {
+                message, report, ..
+            }) => {
+                assert!(message.contains("Fast-forwarded main from upstream"));
+                assert!(!message.contains("Pushed"));
+                assert_eq!(report.to_push, 0);
+            }
+            result => unreachable!("{:?}", result),
+        }
+        assert_eq!(
+            repo.refname_to_id("refs/heads/main").unwrap(),
+            upstream_head
+        );
+        assert_ne!(
+            origin.refname_to_id("refs/heads/main").unwrap(),
+            upstream_head
+        );
+
+        // commits of upstream synced before are pushed even if nothing moved
+        let options = RefreshOptions {
+            sync_forks: true,
+            push_forks: true,
+            ..Default::default()
+        };
+        match refresh(path, &options, Verbosity::Normal) {
+            Ok(RefreshStatus::Update { message, .. }) => {
+                assert!(message.contains("Pushed main to origin"));
+            }
+            result => unreachable!("{:?}", result),
+        }
+        assert_eq!(
+            origin.refname_to_id("refs/heads/main").unwrap(),
+            upstream_head
+        );
+    }
+
+    #[test]
+    fn test_refresh_branch_deletion_policy() {
+        let fixture = Fixture::create().unwrap();
+        let fixture_origin = Fixture::create().unwrap();
+
+        let origin_path = fixture_origin.repo.path().parent().unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        let origin = &fixture_origin.repo;
+        origin.command(&["switch", "-c", "main"]).unwrap();
+        origin.command(&["branch", "unpushed"]).unwrap();
+        fixture_origin.create_empty_commit("second commit").unwrap();
+
+        let repo = &fixture.repo;
+        repo.remote("origin", origin_path.to_str().unwrap())
+            .unwrap();
+        repo.command(&["fetch", "origin"]).unwrap();
+        repo.command(&["switch", "main"]).unwrap();
+        repo.command(&["remote", "set-head", "origin", "main"])
+            .unwrap();
+        for branch in ["release/1.0", "feature/a", "fix/b"] {
+            repo.command(&["branch", branch]).unwrap();
+        }
+        // merged into main but ahead of its upstream
+        repo.command(&["branch", "unpushed", "main"]).unwrap();
+        repo.command(&["branch", "--set-upstream-to=origin/unpushed", "unpushed"])
+            .unwrap();
+
+        let path = repo.path().parent().unwrap();
+        let options = RefreshOptions {
+            keep_merged_branches: true,
+            ..Default::default()
+        };
+        refresh(path.to_str().unwrap(), &options, Verbosity::Normal).unwrap();
+        assert!(repo.find_branch("feature/a", BranchType::Local).is_ok());
+
+        let options = RefreshOptions {
+            delete_only: vec!["feature/*".to_string(), "release/*".to_string()],
+            protect_branches: vec!["release/*".to_string()],
+            ..Default::default()
+        };
+        let result = refresh(path.to_str().unwrap(), &options, Verbosity::Verbose);
+        match result {
+            Ok(RefreshStatus::Update { message, .. }) => {
+                assert!(message.contains("Deleted branch feature/a"));
+                assert!(message.contains("Kept branch release/1.0: protected by release/*"));
+                assert!(message.contains("Kept branch fix/b: not matching delete-only patterns"));
+            }
+            Ok(result) => unreachable!("{:?}", result),
+            Err(e) => unreachable!("{:?}", e),
+        }
+        assert!(repo.find_branch("feature/a", BranchType::Local).is_err());
+        assert!(repo.find_branch("release/1.0", BranchType::Local).is_ok());
+        assert!(repo.find_branch("fix/b", BranchType::Local).is_ok());
+
+        let result = refresh(
+            path.to_str().unwrap(),
+            &RefreshOptions::default(),
+            Verbosity::Verbose,
+        );
+        match result {
+            Ok(RefreshStatus::Update { message, .. }) => {
+                assert!(message.contains("Kept branch unpushed: it has unpushed commits"));
+            }
+            Ok(result) => unreachable!("{:?}", result),
+            Err(e) => unreachable!("{:?}", e),
+        }
+        assert!(repo.find_branch("unpushed", BranchType::Local).is_ok());
+        assert!(repo.find_branch("fix/b", BranchType::Local).is_err());
+
+        let options = RefreshOptions {
+            protect_branches: vec!["[".to_string()],
+            ..Default::default()
+        };
+        assert!(refresh(path.to_str().unwrap(), &options, Verbosity::Normal).is_err());
+    }
+
+    #[test]
+    fn test_pull_request_branches() {
+        let branches = pull_request_branches(
+            vec![
+                (1, "main".to_string(), "feature".to_string(), false),
+                (2, "feature".to_string(), "stacked".to_string(), false),
+                (3, "main".to_string(), "fork-branch".to_string(), true),
+            ]
+            .into_iter(),
+        );
+        assert_eq!(branches.get("feature"), Some(&2));
+        assert_eq!(branches.get("stacked"), Some(&2));
+        assert!(branches.contains_key("main"));
+        assert!(!branches.contains_key("fork-branch"));
+    }
+
+    #[test]
+    fn test_refresh_mode() {
+        let options_with = |refresh: &str| {
+            let config: Config = toml::from_str(&format!(
+                r#"
+                [core]
+                base_dir = "~/.dev"
+
+                [github]
+                username = "kitsuyui"
+
+                [refresh]
+                {refresh}
+            "#
+            ))
+            .unwrap();
+            let options = RefreshOptions::from_config(&config);
+            (options.keep_current_branch, options.keep_merged_branches)
+        };
+        assert_eq!(options_with(""), (false, false));
+        assert_eq!(options_with("mode = \"tidy\""), (false, false));
+        assert_eq!(options_with("mode = \"safe\""), (true, true));
+        assert_eq!(
+            options_with("mode = \"safe\"\ndelete_merged_branches = true"),
+            (true, false)
+        );
+
+        let mut options = RefreshOptions::default();
+        options.apply_mode(RefreshMode::Safe);
+        assert!(options.keep_current_branch && options.keep_merged_branches);
+        options.apply_mode(RefreshMode::Tidy);
+        assert!(!options.keep_current_branch && !options.keep_merged_branches);
+    }
+
+    #[test]
+    fn test_skip_hooks_for_repository() {
+        let config: Config = toml::from_str(
+            r#"
+            [core]
+            base_dir = "~/.dev"
+
+            [github]
+            username = "kitsuyui"
+
+            [refresh]
+            run_hooks = false
+
+            [repo."lfs-assets"]
+            run_hooks = true
+        "#,
+        )
+        .unwrap();
+        let options = RefreshOptions::from_config(&config);
+        assert!(options.skip_hooks);
+        let state = State::default();
+        assert!(options.for_repository(&config, &state, "mure").skip_hooks);
+        assert!(
+            !options
+                .for_repository(&config, &state, "lfs-assets")
+                .skip_hooks
+        );
+        // --no-hooks doesn't override the repository either
+        let options = RefreshOptions {
+            skip_hooks: true,
+            ..RefreshOptions::default()
+        };
+        assert!(
+            !options
+                .for_repository(&config, &state, "lfs-assets")
+                .skip_hooks
+        );
+    }
+
+    #[test]
+    fn test_resolve_repository() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let store_path = config.repo_store_path("github.com", "kitsuyui", "mure");
+        Repository::init(&store_path).unwrap();
+        let work_path = config.base_path().join("mure");
+        std::os::unix::fs::symlink(&store_path, &work_path).unwrap();
+
+        for target in [
+            "https://github.com/kitsuyui/mure",
+            "git@github.com:kitsuyui/mure.git",
+            "kitsuyui/mure",
+        ] {
+            assert_eq!(
+                resolve_repository(&config, target).unwrap(),
+                store_path,
+                "{target}"
+            );
+        }
+        assert_eq!(resolve_repository(&config, "mure").unwrap(), work_path);
+        assert_eq!(
+            resolve_repository(&config, work_path.to_str().unwrap()).unwrap(),
+            work_path
+        );
+        assert_eq!(
+            resolve_repository(&config, "someone/mure")
+                .unwrap_err()
+                .to_string(),
+            "someone/mure is not cloned"
+        );
+        assert_eq!(
+            resolve_repository(&config, "unknown")
+                .unwrap_err()
+                .to_string(),
+            "unknown is not a git repository"
+        );
+    }
+
+    #[test]
+    fn test_refresh_summary() {
+        let mut summary = RefreshSummary::default();
+        summary.record(
+            "a",
+            &Ok(RefreshStatus::Update {
+                switch_to_default: false,
+                message: "Fast-forwarded".to_string(),
+                report: RefreshReport::default(),
+            }),
+        );
+        summary.record("b", &Ok(RefreshStatus::DoNothing(Reason::NoRemote)));
+        summary.record(
+            "c",
+            &Err(Error::from_str("fatal: unable to access\nhint: retry")),
+        );
+        assert_eq!(
+            summary.to_text(),
+            "mure refresh: 1 updated, 1 skipped, 1 failed\n- c: fatal: unable to access"
+        );
+
+        summary.record("d", &Ok(RefreshStatus::DoNothing(Reason::PermissionDenied)));
+        assert_eq!(
+            summary.to_text(),
+            "mure refresh: 1 updated, 2 skipped, 1 failed\n- c: fatal: unable to access\n- d: skipped (permission denied)"
+        );
+    }
+
+    #[test]
+    fn test_refresh_events() {
+        let events = refresh_events(
+            "mure",
+            Ok(RefreshStatus::Update {
+                switch_to_default: false,
+                message: "Switched to main\nAlready up to date".to_string(),
+                report: RefreshReport {
+                    switched_to: Some("main".to_string()),
+                    pulled: PullStatus::AlreadyUpToDate,
+                    deleted_branches: vec![],
+                    to_push: 0,
+                },
+            }),
+        );
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            Event::Progress {
+                repository: "mure".to_string(),
+                message: "Switched to main".to_string(),
+            }
+        );
+        assert_eq!(
+            events[2],
+            Event::Finished {
+                repository: "mure".to_string(),
+                status: "updated".to_string(),
+                message: "Switched to main\nAlready up to date".to_string(),
+                reason: None,
+                report: Some(RefreshReport {
+                    switched_to: Some("main".to_string()),
+                    pulled: PullStatus::AlreadyUpToDate,
+                    deleted_branches: vec![],
+                    to_push: 0,
+                }),
+                error: None,
+            }
+        );
+
+        let events = refresh_events("mure", Ok(RefreshStatus::DoNothing(Reason::NoRemote)));
+        assert_eq!(
+            events,
+            vec![Event::Finished {
+                repository: "mure".to_string(),
+                status: "skipped".to_string(),
+                message: "no remote".to_string(),
+                reason: Some(Reason::NoRemote),
+                report: None,
+                error: None,
+            }]
+        );
+
+        let events = refresh_events("mure", Err(Error::from_str("failed")));
+        assert_eq!(events.len(), 1);
+    }
+
     #[test]
     fn test_not_git_repository() {
         let temp_dir = Temp::new_dir().expect("failed to create temp dir");
@@ -246,7 +1931,7 @@ mod tests {
             .to_str()
             .expect("failed to get path");
 
-        let result = refresh(path, Verbosity::Normal).unwrap();
+        let result = refresh(path, &RefreshOptions::default(), Verbosity::Normal).unwrap();
         match result {
             RefreshStatus::DoNothing(Reason::NotGitRepository) => {}
             _ => unreachable!(),
@@ -258,43 +1943,89 @@ mod tests {
         let fixture = Fixture::create().unwrap();
         let path = fixture.repo.path().parent().unwrap();
 
-        let result = refresh(path.to_str().unwrap(), Verbosity::Normal).unwrap();
+        let result = refresh(
+            path.to_str().unwrap(),
+            &RefreshOptions::default(),
+            Verbosity::Normal,
+        )
+        .unwrap();
         match result {
             RefreshStatus::DoNothing(Reason::NoRemote) => {}
             _ => unreachable!(),
         }
     }
 
+    #[assay]
+    fn test_refresh_all_stale() {
+        let cache_dir = Temp::new_dir().unwrap();
+        std::env::set_var("MURE_REFRESH_CACHE_PATH", cache_dir.join("refresh.json"));
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let store_path = config.repo_store_path("github.com", "kitsuyui", "mure");
+        Repository::init(&store_path).unwrap();
+        std::os::unix::fs::symlink(&store_path, config.base_path().join("mure")).unwrap();
+        let refresh_all_with = |stale: Option<Duration>| {
+            let options = RefreshOptions {
+                stale,
+                ..Default::default()
+            };
+            refresh_all(
+                &config,
+                &options,
+                Verbosity::Verbose,
+                OutputFormat::Text,
+                &RepoFilter::default(),
+                &BulkOptions::default(),
+            )
+            .unwrap();
+        };
+
+        let mut cache = RefreshCache::default();
+        cache.record("mure", now() - 60, RefreshResult::Unchanged);
+        cache.save().unwrap();
+        // refreshed a minute ago
+        refresh_all_with(Some(Duration::from_secs(3600)));
+        assert_eq!(RefreshCache::load().unwrap(), cache);
+
+        refresh_all_with(Some(Duration::from_secs(30)));
+        let record = RefreshCache::load().unwrap().repositories["mure"].clone();
+        assert!(record.refreshed_at >= now() - 10);
+        // no remote
+        assert_eq!(record.result, RefreshResult::Unchanged);
+    }
+
     #[test]
     fn test_refresh_all() {
         let temp_dir = Temp::new_dir().expect("failed to create temp dir");
 
-        let config: Config = toml::from_str(
-            format!(
-                r#"
-            [core]
-            base_dir = "{}"
-
-            [github]
-            username = "kitsuyui"
-
-            [shell]
-            cd_shims = "mucd"
-        "#,
-                temp_dir.to_str().unwrap()
-            )
-            .as_str(),
-        )
-        .unwrap();
+        let config = config_with_base_dir(&temp_dir);
         let repos = search_mure_repo(&config);
         assert_eq!(repos.len(), 0);
         crate::app::clone::clone(
             &config,
             "https://github.com/kitsuyui/mure",
+            &crate::app::clone::CloneOptions::default(),
             Verbosity::Normal,
         )
         .unwrap();
 
-        refresh_all(&config, Verbosity::Verbose).unwrap();
+        refresh_all(
+            &config,
+            &RefreshOptions::default(),
+            Verbosity::Verbose,
+            OutputFormat::Text,
+            &RepoFilter::default(),
+            &BulkOptions::default(),
+        )
+        .unwrap();
+        refresh_all(
+            &config,
+            &RefreshOptions::default(),
+            Verbosity::Verbose,
+            OutputFormat::JsonLines,
+            &RepoFilter::default(),
+            &BulkOptions::new(2, true),
+        )
+        .unwrap();
     }
 }
@@ -0,0 +1,205 @@
+//! Latest releases and tags of the cloned repositories to see which of them need a release
+//!
+//! The latest release comes from GitHub and the latest tag from the local default branch,
+//! so repositories without releases on GitHub are shown with their tags only.
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use git2::{DescribeFormatOptions, DescribeOptions, Repository};
+use serde_derive::Serialize;
+
+use crate::app::issues::{search_all_hosts, IssueScope};
+use crate::app::list::{search_mure_repo, RepoFilter};
+use crate::git::RepositorySupport;
+use crate::github::api::search_repository_query::SearchRepositoryQueryReposEdgesNodeOnRepositoryLatestRelease as LatestRelease;
+use crate::github::repo::RepoInfo;
+use crate::i18n::Message;
+use crate::misc::datetime::{format_date, parse_timestamp};
+use crate::{config::Config, mure_error::Error};
+
+/// A row of `mure releases`. Serialized in `--json`.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ReleaseStatus {
+    pub name: String,
+    /// tag of the latest release on GitHub
+    pub release: Option<String>,
+    /// `YYYY-MM-DD` of the latest release
+    pub released_at: Option<String>,
+    pub days_since_release: Option<u64>,
+    /// latest tag reachable from the default branch
+    pub tag: Option<String>,
+    /// commits of the default branch after the tag, or all of them without tags
+    pub unreleased_commits: Option<usize>,
+}
+
+/// Show the releases of the repositories, the ones with more unreleased commits first
+pub fn releases(config: &Config, filter: &RepoFilter, json: bool) -> Result<(), Error> {
+    let mure_repos = filter
+        .apply(search_mure_repo(config))?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if mure_repos.is_empty() {
+        println!("{}", Message::NoRepositoriesFound);
+        return Ok(());
+    }
+    // releases are optional. Tags are shown even if GitHub is not available.
+    let remote = match search_all_hosts(config, &IssueScope::Local) {
+        Ok(repos) => repos
+            .into_iter()
+            .filter_map(|repo| Some((RepoInfo::parse_url(&repo.url)?, repo.latest_release)))
+            .map(|(info, release)| ((info.domain, info.owner, info.repo), release))
+            .collect::<HashMap<_, _>>(),
+        Err(e) => {
+            println!("{e}");
+            HashMap::new()
+        }
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut statuses = mure_repos
+        .iter()
+        .map(|mure_repo| {
+            let info = &mure_repo.repo;
+            let key = (info.domain.clone(), info.owner.clone(), info.repo.clone());
+            let release = remote.get(&key).and_then(Option::as_ref);
+            let local = local_tag(&mure_repo.absolute_path);
+            if let Err(e) = &local {
+                tracing::warn!(repo = info.repo, "failed to read tags: {e}");
+            }
+            let (tag, unreleased_commits) = match local {
+                Ok((tag, commits)) => (tag, Some(commits)),
+                Err(_) => (None, None),
+            };
+            status(&info.repo, release, tag, unreleased_commits, now)
+        })
+        .collect::<Vec<_>>();
+    statuses.sort_by(|a, b| {
+        (std::cmp::Reverse(a.unreleased_commits), &a.name)
+            .cmp(&(std::cmp::Reverse(b.unreleased_commits), &b.name))
+    });
+    if json {
+        println!("{}", serde_json::to_string_pretty(&statuses)?);
+    } else {
+        print!("{}", format_table(&statuses));
+    }
+    Ok(())
+}
+
+fn status(
+    name: &str,
+    release: Option<&LatestRelease>,
+    tag: Option<String>,
+    unreleased_commits: Option<usize>,
+    now: u64,
+) -> ReleaseStatus {
+    let published_at = release
+        .and_then(|release| release.published_at.as_deref())
+        .and_then(parse_timestamp);
+    ReleaseStatus {
+        name: name.to_string(),
+        release: release.map(|release| release.tag_name.clone()),
+        released_at: published_at.map(format_date),
+        days_since_release: published_at.map(|at| now.saturating_sub(at) / 86400),
+        tag,
+        unreleased_commits,
+    }
+}
+
+/// The latest tag reachable from the default branch of origin and the number of commits after it
+fn local_tag(path: &Path) -> Result<(Option<String>, usize), Error> {
+    let repo = Repository::open(path)?;
+    // HEAD of repositories without origin
+    let head = match repo.guess_default_branch("origin")? {
+        Some(branch) => repo.revparse_single(&format!("refs/remotes/origin/{branch}"))?,
+        None => repo.revparse_single("HEAD")?,
+    }
+    .peel_to_commit()?;
+    let tag = head
+        .as_object()
+        .describe(DescribeOptions::new().describe_tags())
+        .and_then(|describe| {
+            describe.format(Some(DescribeFormatOptions::new().abbreviated_size(0)))
+        })
+        .ok();
+    let mut walk = repo.revwalk()?;
+    walk.push(head.id())?;
+    if let Some(tag) = &tag {
+        walk.hide(repo.revparse_single(tag)?.peel_to_commit()?.id())?;
+    }
+    Ok((tag, walk.count()))
+}
+
+fn format_table(statuses: &[ReleaseStatus]) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let mut table = "Name\tRelease\tDate\tDays\tTag\tUnreleased\n".to_string();
+    for status in statuses {
+        table.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            status.name,
+            optional(status.release.clone()),
+            optional(status.released_at.clone()),
+            optional(status.days_since_release.map(|days| days.to_string())),
+            optional(status.tag.clone()),
+            optional(status.unreleased_commits.map(|commits| commits.to_string())),
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::Fixture;
+
+    #[test]
+    fn test_status() {
+        let release = LatestRelease {
+            name: Some("v1.2.0".to_string()),
+            tag_name: "v1.2.0".to_string(),
+            published_at: Some("2024-01-01T00:00:00Z".to_string()),
+        };
+        let now = parse_timestamp("2024-01-31T12:00:00Z").unwrap();
+        assert_eq!(
+            status(
+                "mure",
+                Some(&release),
+                Some("v1.2.0".to_string()),
+                Some(3),
+                now,
+            ),
+            ReleaseStatus {
+                name: "mure".to_string(),
+                release: Some("v1.2.0".to_string()),
+                released_at: Some("2024-01-01".to_string()),
+                days_since_release: Some(30),
+                tag: Some("v1.2.0".to_string()),
+                unreleased_commits: Some(3),
+            }
+        );
+        assert_eq!(
+            format_table(&[status("dotfiles", None, None, None, now)]),
+            "Name\tRelease\tDate\tDays\tTag\tUnreleased\ndotfiles\t-\t-\t-\t-\t-\n"
+        );
+        assert_eq!(
+            serde_json::to_string(&status("dotfiles", None, None, Some(5), now)).unwrap(),
+            r#"{"name":"dotfiles","release":null,"released_at":null,"days_since_release":null,"tag":null,"unreleased_commits":5}"#
+        );
+    }
+
+    #[test]
+    fn test_local_tag() {
+        let fixture = Fixture::create().unwrap();
+        fixture.create_empty_commit("initial commit").unwrap();
+        let path = fixture.repo.workdir().unwrap();
+        assert_eq!(local_tag(path).unwrap(), (None, 1));
+
+        fixture.repo.command(&["tag", "v0.1.0"]).unwrap();
+        fixture.create_empty_commit("feature a").unwrap();
+        fixture.create_empty_commit("feature b").unwrap();
+        assert_eq!(local_tag(path).unwrap(), (Some("v0.1.0".to_string()), 2));
+    }
+}
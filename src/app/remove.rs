@@ -0,0 +1,450 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+use crate::app::list::find_all_symlinks;
+use crate::config::{Config, ConfigSupport};
+use crate::git::{DryRun, RepositorySupport};
+use crate::i18n::Message;
+use crate::mure_error::Error;
+use crate::state::State;
+
+/// A path to be removed by remove or prune
+#[derive(Debug, PartialEq, Eq)]
+enum Removal {
+    Symlink(PathBuf),
+    Directory(PathBuf),
+}
+
+impl Removal {
+    fn path(&self) -> &Path {
+        match self {
+            Removal::Symlink(path) | Removal::Directory(path) => path,
+        }
+    }
+
+    fn execute(&self) -> Result<(), Error> {
+        match self {
+            Removal::Symlink(path) => std::fs::remove_file(path)?,
+            Removal::Directory(path) => std::fs::remove_dir_all(path)?,
+        }
+        Ok(())
+    }
+}
+
+/// Remove the symlink of the repository and the repository in the store.
+/// Repositories with uncommitted changes are kept unless `force`.
+/// Symlinks to directories outside the store are refused, and the repository is kept
+/// while another symlink still links to it.
+pub fn remove(config: &Config, name: &str, force: bool) -> Result<(), Error> {
+    let Some(link) = [config.base_path(), config.archive_path()]
+        .into_iter()
+        .map(|dir| dir.join(name))
+        .find(|path| is_symlink(path))
+    else {
+        return Err(Error::from_str(&format!("{name} is not found")));
+    };
+    let mut plan = vec![];
+    if let Ok(store_path) = std::fs::canonicalize(&link) {
+        if !is_in_store(config, &store_path) {
+            return Err(Error::from_str(&format!(
+                "{name} links to {} which is not in the store. Remove the symlink by yourself if it is intended",
+                store_path.display()
+            )));
+        }
+        if let Some(other) = other_links(config, &link, &store_path)?.first() {
+            let path = store_path.display().to_string();
+            let other = other.display().to_string();
+            println!(
+                "{}",
+                Message::SkippedRemovingLinked {
+                    path: &path,
+                    link: &other
+                }
+            );
+            execute(&[Removal::Symlink(link)])?;
+            return forget_tags(name);
+        }
+        if !force && has_unsaved(&store_path) {
+            return Err(Error::from_str(&format!(
+                "{name} has uncommitted changes. Use --force to remove it anyway"
+            )));
+        }
+        // even --force doesn't break the repositories borrowing objects
+        let referencing = referencing_repositories(config, &store_path)?;
+        if !referencing.is_empty() {
+            let store = config.repos_store_path();
+            let names = referencing
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(&store)
+                        .unwrap_or(path)
+                        .display()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(Error::from_str(&format!(
+                "{name} shares its objects with {names}. Run `mure dissociate` for them first"
+            )));
+        }
+        plan.push(Removal::Directory(store_path));
+    }
+    plan.insert(0, Removal::Symlink(link));
+    execute(&plan)?;
+    forget_tags(name)
+}
+
+fn forget_tags(name: &str) -> Result<(), Error> {
+    if !DryRun::is_enabled() {
+        let mut state = State::load()?;
+        if state.tags.remove(name).is_some() {
+            state.save()?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether the canonical path is a directory under the store (not the store itself)
+fn is_in_store(config: &Config, path: &Path) -> bool {
+    std::fs::canonicalize(config.repos_store_path())
+        .is_ok_and(|store| path != store && path.starts_with(&store))
+}
+
+/// Symlinks in base_dir and the archive directory other than `link` which link to the target
+fn other_links(config: &Config, link: &Path, target: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut links = vec![];
+    for dir in [config.base_path(), config.archive_path()] {
+        if !dir.is_dir() {
+            continue;
+        }
+        for path in find_all_symlinks(config, &dir)? {
+            if path != link && std::fs::canonicalize(&path).is_ok_and(|path| path == target) {
+                links.push(path);
+            }
+        }
+    }
+    Ok(links)
+}
+
+/// Remove dangling symlinks to the store and repositories in the store which no symlink points to.
+/// Repositories with work not pushed anywhere are kept.
+pub fn prune(config: &Config) -> Result<(), Error> {
+    execute(&plan_prune(config)?)
+}
+
+fn execute(plan: &[Removal]) -> Result<(), Error> {
+    for removal in plan {
+        let path = removal.path().display().to_string();
+        if DryRun::is_enabled() {
+            println!("{}", Message::WouldRemove { path: &path });
+            continue;
+        }
+        removal.execute()?;
+        println!("{}", Message::Removed { path: &path });
+    }
+    Ok(())
+}
+
+fn plan_prune(config: &Config) -> Result<Vec<Removal>, Error> {
+    let (_, dangling) = scan_symlinks(config)?;
+    let mut plan = dangling
+        .into_iter()
+        .map(Removal::Symlink)
+        .collect::<Vec<_>>();
+    for repo in orphaned_repositories(config)? {
+        if has_unsaved(&repo) {
+            let path = repo.display().to_string();
+            println!("{}", Message::SkippedRemovingUnsaved { path: &path });
+            continue;
+        }
+        if has_unpushed(&repo) {
+            let path = repo.display().to_string();
+            println!("{}", Message::SkippedRemovingUnpushed { path: &path });
+            continue;
+        }
+        if !referencing_repositories(config, &repo)?.is_empty() {
+            let path = repo.display().to_string();
+            println!("{}", Message::SkippedRemovingReferenced { path: &path });
+            continue;
+        }
+        plan.push(Removal::Directory(repo));
+    }
+    Ok(plan)
+}
+
+/// Repositories in the store which no symlink in base_dir or the archive directory points to
+pub fn orphaned_repositories(config: &Config) -> Result<Vec<PathBuf>, Error> {
+    let (linked, _) = scan_symlinks(config)?;
+    let mut orphans = vec![];
+    for repo in store_repositories(config)? {
+        if !linked.contains(&std::fs::canonicalize(&repo)?) {
+            orphans.push(repo);
+        }
+    }
+    Ok(orphans)
+}
+
+/// Other repositories in the store which borrow objects of the repository via alternates.
+/// Removing it corrupts them.
+pub fn referencing_repositories(config: &Config, store_path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let store_path = std::fs::canonicalize(store_path)?;
+    let mut referencing = vec![];
+    for repo_path in store_repositories(config)? {
+        if std::fs::canonicalize(&repo_path)? == store_path {
+            continue;
+        }
+        let Ok(repo) = Repository::open(&repo_path) else {
+            continue;
+        };
+        if repo.alternates()?.iter().any(|alternate| {
+            std::fs::canonicalize(alternate).is_ok_and(|path| path.starts_with(&store_path))
+        }) {
+            referencing.push(repo_path);
+        }
+    }
+    Ok(referencing)
+}
+
+/// All repositories in the store, which are stored as repo/<domain>/<owner>/<repo>
+fn store_repositories(config: &Config) -> Result<Vec<PathBuf>, Error> {
+    let mut repos = vec![];
+    let store = config.repos_store_path();
+    if !store.is_dir() {
+        return Ok(repos);
+    }
+    for domain in subdirectories(&store)? {
+        for owner in subdirectories(&domain)? {
+            repos.extend(subdirectories(&owner)?);
+        }
+    }
+    Ok(repos)
+}
+
+/// Targets of symlinks in base_dir and the archive directory, and dangling symlinks among them
+/// which pointed into the store. Other dangling symlinks were not made by mure and are kept.
+fn scan_symlinks(config: &Config) -> Result<(HashSet<PathBuf>, Vec<PathBuf>), Error> {
+    let mut linked = HashSet::new();
+    let mut dangling = vec![];
+    let store = config.repos_store_path();
+    let canonical_store = std::fs::canonicalize(&store).ok();
+    for dir in [config.base_path(), config.archive_path()] {
+        if !dir.is_dir() {
+            continue;
+        }
+        // symlinks nested by `clone --into` at any depth too, not to prune repositories linked from them
+        for path in find_all_symlinks(config, &dir)? {
+            match std::fs::canonicalize(&path) {
+                Ok(target) => {
+                    linked.insert(target);
+                }
+                Err(_) => {
+                    let Ok(target) = std::fs::read_link(&path) else {
+                        continue;
+                    };
+                    // relative targets are relative to the directory of the symlink
+                    let target = path
+                        .parent()
+                        .map_or(target.clone(), |dir| dir.join(&target));
+                    if target.starts_with(&store)
+                        || canonical_store
+                            .as_ref()
+                            .is_some_and(|store| target.starts_with(store))
+                    {
+                        dangling.push(path);
+                    }
+                }
+            }
+        }
+    }
+    Ok((linked, dangling))
+}
+
+fn subdirectories(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut dirs = vec![];
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn is_symlink(path: &Path) -> bool {
+    std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_symlink())
+}
+
+/// Whether the repository has stashes, local branches without upstream or commits not pushed to it.
+/// Failures to inspect are regarded as having them to be safe.
+pub fn has_unpushed(path: &Path) -> bool {
+    let Ok(repo) = Repository::open(path) else {
+        return true;
+    };
+    if repo.find_reference("refs/stash").is_ok() {
+        return true;
+    }
+    let Ok(branches) = repo.branches(Some(git2::BranchType::Local)) else {
+        return true;
+    };
+    for branch in branches {
+        let Ok((branch, _)) = branch else {
+            return true;
+        };
+        let Ok(Some(name)) = branch.name() else {
+            return true;
+        };
+        if branch.upstream().is_err() || repo.has_unpushed_commits(name).unwrap_or(true) {
+            return true;
+        }
+    }
+    false
+}
+
+pub fn has_unsaved(path: &Path) -> bool {
+    Repository::open(path)
+        .map_err(Error::from)
+        .and_then(|repo| repo.has_unsaved())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::config_with_base_dir;
+    use assay::assay;
+    use mktemp::Temp;
+
+    /// Create a repository in the store and link it from base_dir if `linked`
+    fn create_repo(config: &Config, name: &str, linked: bool) -> PathBuf {
+        let store_path = config.repo_store_path("github.com", "kitsuyui", name);
+        Repository::init(&store_path).unwrap();
+        if linked {
+            std::os::unix::fs::symlink(&store_path, config.base_path().join(name)).unwrap();
+        }
+        store_path
+    }
+
+    #[assay]
+    fn test_remove() {
+        let state_dir = Temp::new_dir().unwrap();
+        std::env::set_var("MURE_STATE_PATH", state_dir.join("state.json"));
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let store_path = create_repo(&config, "mure", true);
+
+        DryRun::set(true);
+        remove(&config, "mure", false).unwrap();
+        assert!(store_path.exists());
+        DryRun::set(false);
+
+        std::fs::write(store_path.join("unsaved"), "").unwrap();
+        let Err(error) = remove(&config, "mure", false) else {
+            unreachable!();
+        };
+        assert_eq!(
+            error.to_string(),
+            "mure has uncommitted changes. Use --force to remove it anyway"
+        );
+
+        remove(&config, "mure", true).unwrap();
+        assert!(!store_path.exists());
+        assert!(!is_symlink(&config.base_path().join("mure")));
+
+        let Err(error) = remove(&config, "mure", true) else {
+            unreachable!();
+        };
+        assert_eq!(error.to_string(), "mure is not found");
+    }
+
+    #[assay]
+    fn test_remove_keeps_outside_and_linked() {
+        let state_dir = Temp::new_dir().unwrap();
+        std::env::set_var("MURE_STATE_PATH", state_dir.join("state.json"));
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+
+        let documents = temp_dir.join("Documents");
+        std::fs::create_dir_all(&documents).unwrap();
+        std::fs::write(documents.join("important"), "").unwrap();
+        std::fs::create_dir_all(config.base_path()).unwrap();
+        std::os::unix::fs::symlink(&documents, config.base_path().join("docs")).unwrap();
+        let Err(error) = remove(&config, "docs", true) else {
+            unreachable!();
+        };
+        assert!(error.to_string().contains("is not in the store"));
+        assert!(documents.join("important").exists());
+        assert!(is_symlink(&config.base_path().join("docs")));
+
+        let store_path = create_repo(&config, "mure", true);
+        std::os::unix::fs::symlink(&store_path, config.base_path().join("mure-alias")).unwrap();
+        remove(&config, "mure-alias", true).unwrap();
+        assert!(!is_symlink(&config.base_path().join("mure-alias")));
+        assert!(store_path.exists());
+        remove(&config, "mure", true).unwrap();
+        assert!(!store_path.exists());
+    }
+
+    #[test]
+    fn test_prune() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        create_repo(&config, "linked", true);
+        let orphan = create_repo(&config, "orphan", false);
+        let nested = create_repo(&config, "nested", false);
+        std::fs::create_dir_all(config.base_path().join("clients")).unwrap();
+        std::os::unix::fs::symlink(&nested, config.base_path().join("clients/nested")).unwrap();
+        let unsaved = create_repo(&config, "unsaved", false);
+        std::fs::write(unsaved.join("unsaved"), "").unwrap();
+        // linked deeper than search_depth and from a hidden directory by `clone --into`
+        for (name, into) in [("deep", "a/b/c/d"), ("hidden", ".work")] {
+            let store_path = create_repo(&config, name, false);
+            let dir = config.base_path().join(into);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::os::unix::fs::symlink(&store_path, dir.join(name)).unwrap();
+        }
+        let unpushed = create_repo(&config, "unpushed", false);
+        let repo = Repository::open(&unpushed).unwrap();
+        repo.config()
+            .unwrap()
+            .set_str("user.name", "tester")
+            .unwrap();
+        repo.config()
+            .unwrap()
+            .set_str("user.email", "test@example.com")
+            .unwrap();
+        repo.command(&["commit", "--allow-empty", "-m", "local"])
+            .unwrap();
+        let dangling = config.base_path().join("dangling");
+        std::os::unix::fs::symlink(
+            config.repo_store_path("github.com", "kitsuyui", "removed"),
+            &dangling,
+        )
+        .unwrap();
+        let foreign = config.base_path().join("foreign");
+        std::os::unix::fs::symlink(temp_dir.join("nowhere"), &foreign).unwrap();
+
+        let plan = plan_prune(&config).unwrap();
+        assert_eq!(
+            plan,
+            vec![
+                Removal::Symlink(dangling),
+                Removal::Directory(orphan.clone())
+            ]
+        );
+
+        DryRun::set(true);
+        prune(&config).unwrap();
+        assert!(orphan.exists());
+        DryRun::set(false);
+
+        prune(&config).unwrap();
+        assert!(!orphan.exists());
+        assert!(unsaved.exists());
+        assert!(unpushed.exists());
+        assert!(is_symlink(&foreign));
+        assert!(plan_prune(&config).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+use git2::{BranchType, Repository};
+
+use crate::config::{Config, ConfigSupport};
+use crate::git::RepositorySupport;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+/// Recreate the branch deleted by mistake (e.g. by refresh) at its last tip found in the reflog
+pub fn reflog_rescue(config: &Config, name: &str, branch: &str) -> Result<(), Error> {
+    let path = config.base_path().join(name);
+    let repo = Repository::open(&path)?;
+    if repo.find_branch(branch, BranchType::Local).is_ok() {
+        return Err(Error::from_str(&format!("{branch} already exists")));
+    }
+    let Some(tip) = repo.find_lost_branch_tip(branch)? else {
+        return Err(Error::from_str(&format!(
+            "{branch} is not found in the reflog of {name}"
+        )));
+    };
+    let tip = tip.to_string();
+    repo.create_branch(branch, &tip)?;
+    println!(
+        "{}",
+        Message::RecreatedBranch {
+            branch,
+            commit: &tip[..7]
+        }
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{config_with_base_dir, Fixture};
+
+    #[test]
+    fn test_reflog_rescue() {
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        fixture.create_empty_commit("initial commit").unwrap();
+        repo.command(&["switch", "-c", "main"]).unwrap();
+        repo.command(&["switch", "-c", "feature"]).unwrap();
+        fixture.create_empty_commit("feature commit").unwrap();
+        let tip = repo.refname_to_id("refs/heads/feature").unwrap();
+        repo.switch("main").unwrap();
+        repo.command(&["branch", "-D", "feature"]).unwrap();
+
+        let workdir = repo.workdir().unwrap();
+        let base_dir = workdir.parent().unwrap();
+        let name = workdir.file_name().unwrap().to_str().unwrap();
+        let config = config_with_base_dir(base_dir);
+
+        assert!(reflog_rescue(&config, name, "unknown").is_err());
+        reflog_rescue(&config, name, "feature").unwrap();
+        assert_eq!(repo.refname_to_id("refs/heads/feature").unwrap(), tip);
+        // already exists
+        assert!(reflog_rescue(&config, name, "feature").is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! JSON-RPC server over stdio for editor integrations
+//!
+//! Each request and response is a JSON-RPC 2.0 message on a single line.
+//! Supported methods:
+//!
+//! - `resolve` `{"name": "mure"}` -> path of the repository
+//! - `list` -> repositories with their status
+//! - `edit` `{"name": "mure"}` -> open the repository with the editor without waiting for it to be closed
+//! - `refresh` `{"name": "mure"}` -> refresh the repository
+use std::io::{BufRead, Write};
+
+use git2::Repository;
+use serde_json::{json, Value};
+
+use crate::app::edit::spawn_editor;
+use crate::app::list::search_mure_repo;
+use crate::app::path::resolve;
+use crate::app::refresh::{refresh, RefreshOptions, RefreshStatus};
+use crate::config::Config;
+use crate::git::RepositorySupport;
+use crate::mure_error::Error;
+use crate::verbosity::Verbosity;
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const APPLICATION_ERROR: i64 = -32000;
+
+pub fn serve(config: &Config) -> Result<(), Error> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle(config, &line) {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Handle a request line and return a response line.
+/// Notifications (requests without id) have no response.
+fn handle(config: &Config, line: &str) -> Option<String> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+    };
+    let id = request.get("id").cloned();
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let result = call(config, method, &params);
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}).to_string(),
+        Err((code, message)) => error_response(id, code, &message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message},
+    })
+    .to_string()
+}
+
+fn call(config: &Config, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+    match method {
+        "resolve" => {
+            let path = resolve(config, name_param(params)?).map_err(application_error)?;
+            Ok(json!({"path": path}))
+        }
+        "list" => Ok(Value::Array(list(config))),
+        "edit" => {
+            spawn_editor(config, name_param(params)?).map_err(application_error)?;
+            Ok(Value::Null)
+        }
+        "refresh" => {
+            let path = resolve(config, name_param(params)?).map_err(application_error)?;
+            let status = refresh(
+                &path.to_string_lossy(),
+                &RefreshOptions::from_config(config),
+                Verbosity::Quiet,
+            )
+            .map_err(application_error)?;
+            Ok(match status {
+                RefreshStatus::DoNothing(reason) => {
+                    json!({"status": "skipped", "reason": format!("{reason:?}")})
+                }
+                RefreshStatus::Update { message, .. } => {
+                    json!({"status": "updated", "message": message})
+                }
+            })
+        }
+        _ => Err((METHOD_NOT_FOUND, format!("method not found: {method}"))),
+    }
+}
+
+fn name_param(params: &Value) -> Result<&str, (i64, String)> {
+    params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "name is required".to_string()))
+}
+
+fn application_error(e: Error) -> (i64, String) {
+    (APPLICATION_ERROR, e.message())
+}
+
+fn list(config: &Config) -> Vec<Value> {
+    search_mure_repo(config)
+        .into_iter()
+        .filter_map(Result::ok)
+        .map(|mure_repo| {
+            let dirty = Repository::open(&mure_repo.absolute_path)
+                .ok()
+                .and_then(|repo| repo.has_unsaved().ok());
+            json!({
+                "name": mure_repo.repo.repo,
+                "owner": mure_repo.repo.owner,
+                "domain": mure_repo.repo.domain,
+                "path": mure_repo.relative_path,
+                "dirty": dirty,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::config_with_base_dir;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_handle() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+
+        let response = handle(&config, r#"{"jsonrpc":"2.0","id":1,"method":"list"}"#).unwrap();
+        assert_eq!(response, r#"{"id":1,"jsonrpc":"2.0","result":[]}"#);
+
+        let response = handle(&config, r#"{"jsonrpc":"2.0","id":2,"method":"unknown"}"#).unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = handle(&config, r#"{"jsonrpc":"2.0","id":3,"method":"resolve"}"#).unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = handle(
+            &config,
+            r#"{"jsonrpc":"2.0","id":4,"method":"resolve","params":{"name":"nothing"}}"#,
+        )
+        .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["error"]["code"], APPLICATION_ERROR);
+
+        let response = handle(&config, "not json").unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        // notification has no response
+        assert!(handle(&config, r#"{"jsonrpc":"2.0","method":"list"}"#).is_none());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        git2::Repository::init(temp_dir.as_path().join("test_repo")).unwrap();
+
+        let response = handle(
+            &config,
+            r#"{"jsonrpc":"2.0","id":1,"method":"resolve","params":{"name":"test_repo"}}"#,
+        )
+        .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(
+            response["result"]["path"],
+            temp_dir.as_path().join("test_repo").to_str().unwrap()
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::app::clone::{clone, CloneOptions};
+use crate::app::doctor::{format_check, TINY_REPO_URL};
+use crate::app::list::search_mure_repo;
+use crate::app::path::resolve;
+use crate::app::refresh::{refresh, RefreshOptions};
+use crate::app::remove::remove;
+use crate::config::{Config, ConfigSupport, Core};
+use crate::github::repo::RepoInfo;
+use crate::mure_error::Error;
+use crate::verbosity::Verbosity;
+
+/// Exercise clone, list, path, refresh and remove in a scratch directory and report each step.
+/// The state and the refresh cache are kept in the scratch directory not to touch the real ones.
+pub fn selftest(url: Option<&str>, keep: bool) -> Result<(), Error> {
+    let url = url.unwrap_or(TINY_REPO_URL);
+    let sandbox = std::env::temp_dir().join(format!("mure-selftest-{}", std::process::id()));
+    std::fs::create_dir_all(&sandbox)?;
+    std::env::set_var("MURE_STATE_PATH", sandbox.join("state.json"));
+    std::env::set_var("MURE_REFRESH_CACHE_PATH", sandbox.join("refresh.json"));
+    let config = sandbox_config(&sandbox.join("dev"))?;
+
+    let failed = run_steps(&config, url);
+    if keep {
+        println!("sandbox: {}", sandbox.display());
+    } else {
+        std::fs::remove_dir_all(&sandbox)?;
+    }
+    match failed {
+        Some(step) => Err(Error::from_str(&format!("selftest failed at {step}"))),
+        None => Ok(()),
+    }
+}
+
+fn sandbox_config(base: &Path) -> Result<Config, Error> {
+    std::fs::create_dir_all(base)?;
+    Ok(Config {
+        core: Core {
+            base_dir: base.display().to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+}
+
+/// Run the steps in order. Steps after a failure are skipped. Returns the failed step.
+fn run_steps(config: &Config, url: &str) -> Option<&'static str> {
+    let name = RepoInfo::parse_url(url).map(|info| info.repo);
+    let name = name.as_deref().unwrap_or_default();
+    let mut failed = None;
+    let mut step = |step: &'static str, f: &dyn Fn() -> Result<String, Error>| {
+        if failed.is_some() {
+            println!("[SKIP] {step}");
+            return;
+        }
+        let started = Instant::now();
+        let result = f();
+        println!("{}", format_check(step, &result, started.elapsed()));
+        if result.is_err() {
+            failed = Some(step);
+        }
+    };
+
+    step("clone", &|| {
+        clone(config, url, &CloneOptions::default(), Verbosity::Quiet)?;
+        Ok(url.to_string())
+    });
+    step("list", &|| {
+        search_mure_repo(config)
+            .into_iter()
+            .flatten()
+            .find(|mure_repo| mure_repo.repo.repo == name)
+            .map(|mure_repo| mure_repo.repo.fully_qualified_name())
+            .ok_or_else(|| Error::from_str(&format!("{name} is not listed")))
+    });
+    step("path", &|| {
+        resolve(config, name).map(|path| path.display().to_string())
+    });
+    step("refresh", &|| {
+        let path = resolve(config, name)?.display().to_string();
+        refresh(
+            &path,
+            &RefreshOptions::from_config(config),
+            Verbosity::Quiet,
+        )?;
+        Ok("refreshed".to_string())
+    });
+    step("remove", &|| {
+        let store_path = store_path(config, url)?;
+        remove(config, name, false)?;
+        if store_path.exists() {
+            return Err(Error::from_str(&format!(
+                "{} still exists",
+                store_path.display()
+            )));
+        }
+        Ok("removed".to_string())
+    });
+    failed
+}
+
+fn store_path(config: &Config, url: &str) -> Result<PathBuf, Error> {
+    match RepoInfo::parse_url(url) {
+        Some(info) => Ok(config.repo_store_path(&info.domain, &info.owner, &info.repo)),
+        None => Err(Error::from_str("invalid repo url")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assay::assay;
+
+    #[assay]
+    fn test_selftest_invalid_url() {
+        let sandbox = std::env::temp_dir().join(format!("mure-selftest-{}", std::process::id()));
+        assert_eq!(
+            selftest(Some("invalid"), false).unwrap_err().to_string(),
+            "selftest failed at clone"
+        );
+        assert!(!sandbox.exists());
+        assert_eq!(
+            std::env::var("MURE_STATE_PATH").unwrap(),
+            sandbox.join("state.json").display().to_string()
+        );
+    }
+}
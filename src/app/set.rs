@@ -0,0 +1,25 @@
+//! Settings of repositories persisted in the state
+use crate::app::tag::ensure_repo_exists;
+use crate::config::Config;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+use crate::state::State;
+
+/// Assert the default branch of the repository, or forget it with `None`.
+/// Refresh uses the asserted branch without looking at origin/HEAD nor asking GitHub,
+/// which is useful where origin/HEAD is wrong and can't be corrected (e.g. read-only mirrors).
+pub fn default_branch(config: &Config, name: &str, branch: Option<&str>) -> Result<(), Error> {
+    let mut state = State::load()?;
+    match branch {
+        Some(branch) => {
+            ensure_repo_exists(config, name)?;
+            state.set_default_branch(name, branch);
+            println!("{}", Message::DefaultBranchSet { name, branch });
+        }
+        None => {
+            state.unset_default_branch(name);
+            println!("{}", Message::DefaultBranchUnset { name });
+        }
+    }
+    state.save()
+}
@@ -0,0 +1,173 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use git2::Repository;
+
+use crate::app::list::{search_mure_repo, MureRepo};
+use crate::config::Config;
+use crate::git::{DryRun, RepositorySupport, Stash};
+use crate::i18n::Message;
+use crate::misc::datetime::format_age;
+use crate::misc::permission::is_permission_denied;
+use crate::mure_error::Error;
+
+/// Show stashes of all repositories to find forgotten work in progress
+pub fn list(config: &Config) -> Result<(), Error> {
+    let mut found = false;
+    for_each_stashes(config, |mure_repo, _, stashes| {
+        let now = now();
+        for stash in stashes {
+            found = true;
+            println!("{}", describe(&mure_repo.repo.repo, &stash, now));
+        }
+        Ok(())
+    });
+    if !found {
+        println!("{}", Message::NoStashes);
+    }
+    Ok(())
+}
+
+/// Drop stashes older than the duration in all repositories
+pub fn drop(config: &Config, older_than: Duration) -> Result<(), Error> {
+    let now = now();
+    for_each_stashes(config, |mure_repo, repo, stashes| {
+        let name = &mure_repo.repo.repo;
+        // from the oldest so that indexes of the rest don't change
+        for stash in stashes
+            .iter()
+            .rev()
+            .filter(|stash| age(stash, now) >= older_than.as_secs())
+        {
+            let stash_name = format!("stash@{{{}}}", stash.index);
+            if DryRun::is_enabled() {
+                println!(
+                    "{}",
+                    Message::WouldDropStash {
+                        name,
+                        stash: &stash_name
+                    }
+                );
+                continue;
+            }
+            repo.drop_stash(stash.index)?;
+            println!(
+                "{}",
+                Message::DroppedStash {
+                    name,
+                    stash: &stash_name,
+                    message: &stash.message
+                }
+            );
+        }
+        Ok(())
+    });
+    Ok(())
+}
+
+/// Call `f` with stashes of each repository. Errors are shown and don't stop the others.
+fn for_each_stashes<F>(config: &Config, mut f: F)
+where
+    F: FnMut(&MureRepo, &mut Repository, Vec<Stash>) -> Result<(), Error>,
+{
+    for mure_repo in search_mure_repo(config) {
+        let result = mure_repo.and_then(|mure_repo| {
+            if is_permission_denied(&mure_repo.absolute_path) {
+                let name = &mure_repo.repo.repo;
+                println!("{}", Message::PermissionDenied { name });
+                return Ok(());
+            }
+            let mut repo = Repository::open(&mure_repo.absolute_path)?;
+            let stashes = repo.stashes()?;
+            if stashes.is_empty() {
+                return Ok(());
+            }
+            f(&mure_repo, &mut repo, stashes)
+        });
+        if let Err(e) = result {
+            println!("{}", e.message());
+        }
+    }
+}
+
+/// e.g. `mure stash@{0} 3d WIP on main: 1234567 initial commit`
+fn describe(name: &str, stash: &Stash, now: u64) -> String {
+    format!(
+        "{name} stash@{{{}}} {} {}",
+        stash.index,
+        format_age(age(stash, now)),
+        stash.message
+    )
+}
+
+/// Seconds since the stash was created
+fn age(stash: &Stash, now: u64) -> u64 {
+    now.saturating_sub(u64::try_from(stash.time).unwrap_or_default())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{config_with_base_dir, Fixture};
+    use mktemp::Temp;
+
+    #[test]
+    fn test_describe() {
+        let stash = Stash {
+            index: 1,
+            message: "WIP on main: 1234567 initial commit".to_string(),
+            time: 1000,
+        };
+        assert_eq!(
+            describe("mure", &stash, 1000 + 3 * 86400),
+            "mure stash@{1} 3d WIP on main: 1234567 initial commit"
+        );
+        assert_eq!(age(&stash, 500), 0);
+    }
+
+    #[test]
+    fn test_drop() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let fixture = Fixture::create().unwrap();
+        fixture.create_file("README.md", "mure").unwrap();
+        fixture.repo.command(&["add", "README.md"]).unwrap();
+        fixture.create_empty_commit("initial commit").unwrap();
+        let store_path = fixture.into_store(&config, "mure", true);
+
+        let mut repo = Repository::open(&store_path).unwrap();
+        for content in ["first", "second"] {
+            std::fs::write(store_path.join("README.md"), content).unwrap();
+            let signature = repo.signature().unwrap();
+            repo.stash_save(&signature, content, None).unwrap();
+        }
+        let stashes = repo.stashes().unwrap();
+        assert_eq!(
+            stashes
+                .iter()
+                .map(|stash| (stash.index, stash.message.ends_with("second")))
+                .collect::<Vec<_>>(),
+            vec![(0, true), (1, false)]
+        );
+        list(&config).unwrap();
+
+        // not old enough
+        drop(&config, Duration::from_secs(3600)).unwrap();
+        assert_eq!(repo.stashes().unwrap().len(), 2);
+
+        DryRun::set(true);
+        drop(&config, Duration::ZERO).unwrap();
+        DryRun::set(false);
+        assert_eq!(repo.stashes().unwrap().len(), 2);
+
+        drop(&config, Duration::ZERO).unwrap();
+        assert!(repo.stashes().unwrap().is_empty());
+        list(&config).unwrap();
+    }
+}
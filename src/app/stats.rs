@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use git2::Repository;
+
+use crate::app::issues::fetch_repository_summaries;
+use crate::app::list::search_mure_repo;
+use crate::config::Config;
+use crate::git::RepositorySupport;
+use crate::misc::datetime::format_date;
+use crate::mure_error::Error;
+use crate::state::{State, StatsSnapshot};
+
+/// Number of weeks shown by `--history`
+const HISTORY_WEEKS: usize = 12;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Show statistics of repositories and record them as a snapshot of today.
+/// With `history`, show the trend of recorded snapshots instead.
+pub fn stats(config: &Config, history: bool) -> Result<(), Error> {
+    let mut state = State::load()?;
+    if history {
+        print!("{}", format_history(&weekly(&state.stats_history)));
+        return Ok(());
+    }
+    let snapshot = collect(config)?;
+    print!("{}", format_snapshot(&snapshot));
+    state.record_stats(snapshot);
+    state.save()
+}
+
+fn collect(config: &Config) -> Result<StatsSnapshot, Error> {
+    let repos = search_mure_repo(config)
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let dirty = repos
+        .iter()
+        .filter(|mure_repo| {
+            Repository::open(&mure_repo.absolute_path)
+                .map_err(Error::from)
+                .and_then(|repo| repo.has_unsaved())
+                .unwrap_or(false)
+        })
+        .count();
+    let mut snapshot = StatsSnapshot {
+        recorded_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        repositories: repos.len(),
+        dirty,
+        ..Default::default()
+    };
+    match fetch_repository_summaries(config) {
+        Ok(summaries) => {
+            snapshot.open_issues = Some(summaries.iter().map(|s| s.number_of_issues()).sum());
+            snapshot.open_pull_requests =
+                Some(summaries.iter().map(|s| s.number_of_pull_requests()).sum());
+            let coverages = summaries
+                .iter()
+                .filter_map(|s| s.coverage())
+                .collect::<Vec<_>>();
+            if !coverages.is_empty() {
+                snapshot.coverage = Some(coverages.iter().sum::<f64>() / coverages.len() as f64);
+            }
+        }
+        Err(e) => println!("Skipped issues and coverage: {e}"),
+    }
+    Ok(snapshot)
+}
+
+fn format_snapshot(snapshot: &StatsSnapshot) -> String {
+    format!(
+        "Repositories\t{}\nDirty\t{}\nIssues\t{}\nPRs\t{}\nCoverage\t{}\n",
+        snapshot.repositories,
+        snapshot.dirty,
+        optional(snapshot.open_issues),
+        optional(snapshot.open_pull_requests),
+        coverage(snapshot.coverage),
+    )
+}
+
+/// The last snapshot of each week, of the recent weeks
+fn weekly(history: &[StatsSnapshot]) -> Vec<&StatsSnapshot> {
+    let mut weeks = BTreeMap::new();
+    for snapshot in history {
+        weeks.insert(snapshot.recorded_at / (86400 * 7), snapshot);
+    }
+    let skip = weeks.len().saturating_sub(HISTORY_WEEKS);
+    weeks.into_values().skip(skip).collect()
+}
+
+/// Table of snapshots followed by sparklines of each column
+fn format_history(snapshots: &[&StatsSnapshot]) -> String {
+    if snapshots.is_empty() {
+        return "No statistics recorded yet. Run `mure stats` to record.\n".to_string();
+    }
+    let mut lines = vec!["Date\tRepos\tDirty\tIssues\tPRs\tCoverage".to_string()];
+    for snapshot in snapshots {
+        lines.push(format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            format_date(snapshot.recorded_at),
+            snapshot.repositories,
+            snapshot.dirty,
+            optional(snapshot.open_issues),
+            optional(snapshot.open_pull_requests),
+            coverage(snapshot.coverage),
+        ));
+    }
+    lines.push(String::new());
+    let columns: [(&str, Vec<Option<f64>>); 5] = [
+        (
+            "Repos",
+            snapshots
+                .iter()
+                .map(|s| Some(s.repositories as f64))
+                .collect(),
+        ),
+        (
+            "Dirty",
+            snapshots.iter().map(|s| Some(s.dirty as f64)).collect(),
+        ),
+        (
+            "Issues",
+            snapshots
+                .iter()
+                .map(|s| s.open_issues.map(|v| v as f64))
+                .collect(),
+        ),
+        (
+            "PRs",
+            snapshots
+                .iter()
+                .map(|s| s.open_pull_requests.map(|v| v as f64))
+                .collect(),
+        ),
+        ("Coverage", snapshots.iter().map(|s| s.coverage).collect()),
+    ];
+    for (name, values) in columns {
+        lines.push(format!("{name}\t{}", sparkline(&values)));
+    }
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+/// Sparkline scaled between the min and max. Missing values are shown as spaces.
+fn sparkline(values: &[Option<f64>]) -> String {
+    let known = values.iter().flatten();
+    let min = known.clone().copied().fold(f64::INFINITY, f64::min);
+    let max = known.copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if max <= min => SPARKS[0],
+            Some(value) => {
+                let level = ((value - min) / (max - min) * (SPARKS.len() - 1) as f64).round();
+                SPARKS[level as usize]
+            }
+        })
+        .collect()
+}
+
+fn optional(value: Option<i64>) -> String {
+    value.map_or("N/A".to_string(), |v| v.to_string())
+}
+
+fn coverage(value: Option<f64>) -> String {
+    value.map_or("N/A".to_string(), |v| format!("{v:.1}%"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(day: u64, repositories: usize, coverage: Option<f64>) -> StatsSnapshot {
+        StatsSnapshot {
+            recorded_at: 86400 * day,
+            repositories,
+            dirty: 1,
+            open_issues: Some(10),
+            open_pull_requests: None,
+            coverage,
+        }
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[Some(0.0), Some(7.0), None, Some(3.5)]), "▁█ ▅");
+        assert_eq!(sparkline(&[Some(1.0), Some(1.0)]), "▁▁");
+        assert_eq!(sparkline(&[None, None]), "  ");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_weekly() {
+        let history = (0..100)
+            .map(|day| snapshot(day, day as usize, None))
+            .collect::<Vec<_>>();
+        let weeks = weekly(&history);
+        assert_eq!(weeks.len(), HISTORY_WEEKS);
+        // the last snapshot of each week
+        assert_eq!(weeks[HISTORY_WEEKS - 1].repositories, 99);
+        assert_eq!(weeks[HISTORY_WEEKS - 2].repositories, 97);
+    }
+
+    #[test]
+    fn test_format_history() {
+        assert_eq!(
+            format_history(&[]),
+            "No statistics recorded yet. Run `mure stats` to record.\n"
+        );
+        let first = snapshot(0, 10, Some(80.0));
+        let second = snapshot(7, 12, None);
+        assert_eq!(
+            format_history(&[&first, &second]),
+            "Date\tRepos\tDirty\tIssues\tPRs\tCoverage\n\
+             1970-01-01\t10\t1\t10\tN/A\t80.0%\n\
+             1970-01-08\t12\t1\t10\tN/A\tN/A\n\
+             \n\
+             Repos\t▁█\n\
+             Dirty\t▁▁\n\
+             Issues\t▁▁\n\
+             PRs\t  \n\
+             Coverage\t▁ \n"
+        );
+    }
+
+    #[test]
+    fn test_format_snapshot() {
+        assert_eq!(
+            format_snapshot(&snapshot(0, 10, Some(81.25))),
+            "Repositories\t10\nDirty\t1\nIssues\t10\nPRs\tN/A\nCoverage\t81.2%\n"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use glob::Pattern;
+
+use crate::app::list::search_mure_repo;
+use crate::config::{Config, ConfigSupport};
+use crate::gh::GhClient;
+use crate::github::repo::RepoInfo;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+use crate::state::State;
+
+pub fn add(config: &Config, name: &str, tags: &[String]) -> Result<(), Error> {
+    ensure_repo_exists(config, name)?;
+    let mut state = State::load()?;
+    state.add_tags(name, tags);
+    state.save()
+}
+
+pub fn remove(name: &str, tags: &[String]) -> Result<(), Error> {
+    let mut state = State::load()?;
+    state.remove_tags(name, tags);
+    state.save()
+}
+
+/// Show tags of the repository, or of all tagged repositories if name is not specified.
+pub fn list(name: Option<String>) -> Result<(), Error> {
+    let state = State::load()?;
+    for (repo, tags) in &state.tags {
+        if name.as_ref().is_some_and(|name| name != repo) {
+            continue;
+        }
+        let tags: Vec<&str> = tags.iter().map(|tag| tag.as_str()).collect();
+        println!("{repo}\t{}", tags.join(","));
+    }
+    Ok(())
+}
+
+/// Assign groups of `[group_rules]` to all repositories
+pub fn auto(config: &Config) -> Result<(), Error> {
+    let rules = GroupRule::from_config(config)?;
+    if rules.is_empty() {
+        return Err(Error::from_str("no [group_rules] in config"));
+    }
+    let mut state = State::load()?;
+    for mure_repo in search_mure_repo(config) {
+        let mure_repo = match mure_repo {
+            Ok(mure_repo) => mure_repo,
+            Err(e) => {
+                println!("{}", e.message());
+                continue;
+            }
+        };
+        let name = &mure_repo.repo.repo;
+        match matching_groups(&rules, &mure_repo.repo) {
+            Ok(groups) if groups.is_empty() => (),
+            Ok(groups) => {
+                state.add_tags(name, &groups);
+                println!("{name}\t{}", groups.join(","));
+            }
+            Err(e) => println!(
+                "{}",
+                Message::FailedToAssignGroups {
+                    name,
+                    reason: &e.message()
+                }
+            ),
+        }
+    }
+    state.save()
+}
+
+/// Assign groups of `[group_rules]` to the repository just cloned.
+/// Failures are reported but don't fail the clone.
+pub fn assign_groups(config: &Config, repo_info: &RepoInfo) {
+    let name = &repo_info.repo;
+    let result = GroupRule::from_config(config).and_then(|rules| {
+        let groups = matching_groups(&rules, repo_info)?;
+        if !groups.is_empty() {
+            let mut state = State::load()?;
+            state.add_tags(name, &groups);
+            state.save()?;
+        }
+        Ok(groups)
+    });
+    match result {
+        Ok(groups) if groups.is_empty() => (),
+        Ok(groups) => println!(
+            "{}",
+            Message::AssignedGroups {
+                name,
+                groups: &groups.join(",")
+            }
+        ),
+        Err(e) => println!(
+            "{}",
+            Message::FailedToAssignGroups {
+                name,
+                reason: &e.message()
+            }
+        ),
+    }
+}
+
+/// A condition of a group rule. Values of owner, name and domain are glob patterns.
+#[derive(Debug)]
+enum Condition {
+    Domain(Pattern),
+    Owner(Pattern),
+    Name(Pattern),
+    /// public, private or internal
+    Visibility(String),
+    Topic(String),
+}
+
+/// A group assigned to repositories matching all of the conditions
+#[derive(Debug)]
+struct GroupRule {
+    group: String,
+    conditions: Vec<Condition>,
+}
+
+impl GroupRule {
+    fn from_config(config: &Config) -> Result<Vec<GroupRule>, Error> {
+        config
+            .group_rules
+            .iter()
+            .flatten()
+            .map(|(group, spec)| GroupRule::parse(group, spec))
+            .collect()
+    }
+
+    /// Parse space separated `key=value` conditions like `owner=kitsuyui visibility=public`
+    fn parse(group: &str, spec: &str) -> Result<GroupRule, Error> {
+        let invalid =
+            |reason: &str| Error::from_str(&format!("invalid group rule of {group}: {reason}"));
+        let mut conditions = vec![];
+        for condition in spec.split_whitespace() {
+            let Some((key, value)) = condition.split_once('=') else {
+                return Err(invalid(&format!("{condition} is not key=value")));
+            };
+            let pattern = || Pattern::new(value).map_err(|e| invalid(&e.to_string()));
+            conditions.push(match key {
+                "domain" => Condition::Domain(pattern()?),
+                "owner" => Condition::Owner(pattern()?),
+                "name" => Condition::Name(pattern()?),
+                "visibility" => Condition::Visibility(value.to_lowercase()),
+                "topic" => Condition::Topic(value.to_string()),
+                _ => return Err(invalid(&format!("unknown key {key}"))),
+            });
+        }
+        if conditions.is_empty() {
+            return Err(invalid("no conditions"));
+        }
+        Ok(GroupRule {
+            group: group.to_string(),
+            conditions,
+        })
+    }
+
+    /// Visibility and topics need to be fetched from GitHub
+    fn needs_metadata(&self) -> bool {
+        self.conditions
+            .iter()
+            .any(|c| matches!(c, Condition::Visibility(_) | Condition::Topic(_)))
+    }
+
+    fn matches(&self, repo_info: &RepoInfo, metadata: Option<&(String, Vec<String>)>) -> bool {
+        self.conditions.iter().all(|condition| match condition {
+            Condition::Domain(pattern) => pattern.matches(&repo_info.domain),
+            Condition::Owner(pattern) => pattern.matches(&repo_info.owner),
+            Condition::Name(pattern) => pattern.matches(&repo_info.repo),
+            Condition::Visibility(visibility) => {
+                metadata.is_some_and(|(v, _)| v.eq_ignore_ascii_case(visibility))
+            }
+            Condition::Topic(topic) => metadata.is_some_and(|(_, topics)| topics.contains(topic)),
+        })
+    }
+}
+
+/// Groups of the rules the repository matches. GitHub is asked only if a rule needs it.
+fn matching_groups(rules: &[GroupRule], repo_info: &RepoInfo) -> Result<Vec<String>, Error> {
+    let metadata = if rules.iter().any(GroupRule::needs_metadata) {
+        let view = GhClient::new().repo_view(&repo_info.fully_qualified_name())?;
+        Some((view.visibility, view.topics))
+    } else {
+        None
+    };
+    Ok(rules
+        .iter()
+        .filter(|rule| rule.matches(repo_info, metadata.as_ref()))
+        .map(|rule| rule.group.clone())
+        .collect())
+}
+
+pub fn ensure_repo_exists(config: &Config, name: &str) -> Result<(), Error> {
+    if !config.base_path().join(name).exists() {
+        return Err(Error::from_str(&format!("{name} is not found")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::config_with_base_dir;
+    use assay::assay;
+    use mktemp::Temp;
+
+    #[assay]
+    fn test_tag() {
+        let state_dir = Temp::new_dir().unwrap();
+        std::env::set_var("MURE_STATE_PATH", state_dir.join("state.json"));
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        std::fs::create_dir(temp_dir.as_path().join("mure")).unwrap();
+
+        let tags = vec!["rust".to_string(), "cli".to_string()];
+        assert!(add(&config, "not-exists", &tags).is_err());
+        add(&config, "mure", &tags).unwrap();
+        assert!(State::load().unwrap().has_tag("mure", "rust"));
+        list(None).unwrap();
+        list(Some("mure".to_string())).unwrap();
+
+        remove("mure", &["rust".to_string()]).unwrap();
+        let state = State::load().unwrap();
+        assert!(!state.has_tag("mure", "rust"));
+        assert!(state.has_tag("mure", "cli"));
+    }
+
+    #[test]
+    fn test_group_rules() {
+        let repo_info = RepoInfo::parse_url("https://github.com/kitsuyui/mure").unwrap();
+        let metadata = ("PUBLIC".to_string(), vec!["rust".to_string()]);
+        let rule = |spec: &str| GroupRule::parse("group", spec).unwrap();
+
+        assert!(!rule("owner=kitsuyui").needs_metadata());
+        assert!(rule("owner=kitsuyui").matches(&repo_info, None));
+        assert!(rule("owner=kitsu* name=mure domain=github.com").matches(&repo_info, None));
+        assert!(!rule("owner=other").matches(&repo_info, None));
+
+        let public_rust = rule("owner=kitsuyui visibility=public topic=rust");
+        assert!(public_rust.needs_metadata());
+        assert!(public_rust.matches(&repo_info, Some(&metadata)));
+        assert!(!public_rust.matches(&repo_info, None));
+        let private = ("PRIVATE".to_string(), vec!["rust".to_string()]);
+        assert!(!public_rust.matches(&repo_info, Some(&private)));
+
+        for (spec, message) in [
+            (
+                "owner",
+                "invalid group rule of group: owner is not key=value",
+            ),
+            ("stars=10", "invalid group rule of group: unknown key stars"),
+            ("", "invalid group rule of group: no conditions"),
+        ] {
+            let Err(error) = GroupRule::parse("group", spec) else {
+                unreachable!();
+            };
+            assert_eq!(error.to_string(), message);
+        }
+        assert!(matching_groups(&[], &repo_info).unwrap().is_empty());
+        assert_eq!(
+            matching_groups(&[rule("owner=kitsuyui")], &repo_info).unwrap(),
+            vec!["group"]
+        );
+    }
+}
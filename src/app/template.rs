@@ -0,0 +1,218 @@
+//! Apply template files to repositories
+//!
+//! Files under the template directory are copied into repositories keeping their relative paths.
+//! `{{domain}}`, `{{owner}}` and `{{repo}}` in files are replaced with values of each repository.
+//! Files which are not UTF-8 (e.g. images) are copied as they are.
+use std::path::{Path, PathBuf};
+
+use git2::{BranchType, Repository};
+
+use crate::config::{Config, ConfigSupport};
+use crate::git::RepositorySupport;
+use crate::github::repo::RepoInfo;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+
+#[derive(Debug, PartialEq)]
+enum ApplyStatus {
+    UpToDate,
+    Applied(Vec<String>),
+}
+
+pub fn apply(config: &Config, repositories: &[String], branch: &str) -> Result<(), Error> {
+    let template_dir = config.template_path();
+    let templates = read_templates(&template_dir)?;
+    if templates.is_empty() {
+        return Err(Error::from_str(&format!(
+            "No template files found in {}",
+            template_dir.display()
+        )));
+    }
+    for name in repositories {
+        let path = config.base_path().join(name);
+        match apply_to(&path, name, &templates, branch) {
+            Ok(ApplyStatus::UpToDate) => println!("{}", Message::TemplateUpToDate { name }),
+            Ok(ApplyStatus::Applied(files)) => {
+                println!(
+                    "{}",
+                    Message::TemplateApplied {
+                        name,
+                        files: &files.join(", "),
+                        branch
+                    }
+                )
+            }
+            Err(e) => println!("{name}: {e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Read template files recursively as (relative path, content)
+pub fn read_templates(dir: &Path) -> Result<Vec<(PathBuf, Vec<u8>)>, Error> {
+    let mut templates = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(current) = dirs.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.file_name().is_some_and(|name| name == ".git") {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            templates.push((relative.to_path_buf(), std::fs::read(&path)?));
+        }
+    }
+    templates.sort();
+    Ok(templates)
+}
+
+pub fn render(content: &str, repo_info: &RepoInfo) -> String {
+    content
+        .replace("{{domain}}", &repo_info.domain)
+        .replace("{{owner}}", &repo_info.owner)
+        .replace("{{repo}}", &repo_info.repo)
+}
+
+/// Render the content of the template file if it is text, otherwise keep the bytes
+pub fn render_file(content: &[u8], repo_info: &RepoInfo) -> Vec<u8> {
+    match std::str::from_utf8(content) {
+        Ok(text) => render(text, repo_info).into_bytes(),
+        Err(_) => content.to_vec(),
+    }
+}
+
+/// Rendered templates which differ from the files in the working tree
+fn changed_files<'a>(
+    path: &Path,
+    templates: &'a [(PathBuf, Vec<u8>)],
+    repo_info: &RepoInfo,
+) -> Vec<(&'a PathBuf, Vec<u8>)> {
+    templates
+        .iter()
+        .map(|(relative, content)| (relative, render_file(content, repo_info)))
+        .filter(|(relative, content)| {
+            std::fs::read(path.join(relative)).ok().as_ref() != Some(content)
+        })
+        .collect()
+}
+
+fn apply_to(
+    path: &Path,
+    name: &str,
+    templates: &[(PathBuf, Vec<u8>)],
+    branch: &str,
+) -> Result<ApplyStatus, Error> {
+    let repo = Repository::open(path)?;
+    let repo_info = origin_repo_info(&repo).unwrap_or(RepoInfo {
+        domain: "".to_string(),
+        owner: "".to_string(),
+        repo: name.to_string(),
+    });
+    if changed_files(path, templates, &repo_info).is_empty() {
+        return Ok(ApplyStatus::UpToDate);
+    }
+    if !repo.is_clean()? {
+        return Err(Error::from_str("working tree is not clean"));
+    }
+
+    // applied before: the branch may already have the templates
+    if repo.find_branch(branch, BranchType::Local).is_ok() {
+        repo.switch(branch)?;
+    } else {
+        repo.switch_create(branch)?;
+    }
+    let changes = changed_files(path, templates, &repo_info);
+    if changes.is_empty() {
+        return Ok(ApplyStatus::UpToDate);
+    }
+    let mut files = vec![];
+    for (relative, content) in changes {
+        let file_path = path.join(relative);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, content)?;
+        files.push(relative.to_string_lossy().to_string());
+    }
+    let paths = files.iter().map(|file| file.as_str()).collect::<Vec<_>>();
+    repo.add_paths(&paths)?;
+    repo.commit_staged("Apply templates")?;
+    Ok(ApplyStatus::Applied(files))
+}
+
+fn origin_repo_info(repo: &Repository) -> Option<RepoInfo> {
+    let remote = repo.find_remote("origin").ok()?;
+    RepoInfo::parse_url(remote.url()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::Fixture;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_render() {
+        let repo_info = RepoInfo::parse_url("https://github.com/kitsuyui/mure").unwrap();
+        assert_eq!(
+            render("github: [{{owner}}] # {{domain}}/{{repo}}", &repo_info),
+            "github: [kitsuyui] # github.com/mure"
+        );
+    }
+
+    #[test]
+    fn test_apply_to() {
+        let template_dir = Temp::new_dir().unwrap();
+        std::fs::create_dir_all(template_dir.as_path().join(".github")).unwrap();
+        std::fs::write(
+            template_dir.as_path().join(".github").join("FUNDING.yml"),
+            "github: [{{owner}}]\n",
+        )
+        .unwrap();
+        let templates = read_templates(template_dir.as_path()).unwrap();
+        assert_eq!(templates.len(), 1);
+
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        fixture.create_empty_commit("initial commit").unwrap();
+        repo.remote("origin", "https://github.com/kitsuyui/mure.git")
+            .unwrap();
+        let path = repo.workdir().unwrap();
+
+        let status = apply_to(path, "mure", &templates, "mure/template").unwrap();
+        assert_eq!(
+            status,
+            ApplyStatus::Applied(vec![".github/FUNDING.yml".to_string()])
+        );
+        assert_eq!(repo.get_current_branch().unwrap(), "mure/template");
+        assert!(repo.is_clean().unwrap());
+        assert_eq!(
+            std::fs::read_to_string(path.join(".github/FUNDING.yml")).unwrap(),
+            "github: [kitsuyui]\n"
+        );
+
+        let status = apply_to(path, "mure", &templates, "mure/template").unwrap();
+        assert_eq!(status, ApplyStatus::UpToDate);
+
+        // applied again from the original branch
+        repo.command(&["switch", "-"]).unwrap();
+        let status = apply_to(path, "mure", &templates, "mure/template").unwrap();
+        assert_eq!(status, ApplyStatus::UpToDate);
+        assert_eq!(repo.get_current_branch().unwrap(), "mure/template");
+
+        // binary files are copied as they are
+        let image = [0x89, b'P', b'N', b'G', 0xff, 0x00];
+        std::fs::write(template_dir.as_path().join("logo.png"), image).unwrap();
+        let templates = read_templates(template_dir.as_path()).unwrap();
+        repo.command(&["switch", "-"]).unwrap();
+        let status = apply_to(path, "mure", &templates, "mure/template").unwrap();
+        assert_eq!(status, ApplyStatus::Applied(vec!["logo.png".to_string()]));
+        assert_eq!(std::fs::read(path.join("logo.png")).unwrap(), image);
+    }
+}
@@ -0,0 +1,146 @@
+//! `mure token check` to find out what the token can do before commands fail halfway
+use crate::config::{Config, ConfigSupport};
+use crate::github::api::{check_token, TokenCheck};
+use crate::github::token::get_github_token;
+use crate::mure_error::Error;
+
+/// Scopes of classic tokens needed by commands, and the commands needing them
+const REQUIRED_SCOPES: [(&str, &str); 2] = [
+    (
+        "repo",
+        "issues and refresh (check_open_pull_requests) of private repositories",
+    ),
+    ("read:org", "issues --org and --team"),
+];
+
+/// Show the owner, scopes, rate limit and expiry of GH_TOKEN.
+/// It fails if the token is rejected or lacks scopes needed by commands.
+pub fn check(config: &Config) -> Result<(), Error> {
+    let token = get_github_token().map_err(|_| {
+        Error::from_str(
+            "GH_TOKEN is not set. Create a token at https://github.com/settings/tokens or use `gh auth token`",
+        )
+    })?;
+    let check = check_token(&config.graphql_url("github.com"), &token).map_err(|e| {
+        Error::from_str(&format!(
+            "GitHub rejected GH_TOKEN (invalid or expired?): {}",
+            e.message()
+        ))
+    })?;
+    println!("{}", describe(&check));
+
+    let missing = check
+        .scopes
+        .as_deref()
+        .map(missing_scopes)
+        .unwrap_or_default();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let mut lines = missing
+        .iter()
+        .map(|(scope, used_by)| format!("missing scope {scope}: needed by {used_by}"))
+        .collect::<Vec<_>>();
+    let scopes = missing
+        .iter()
+        .map(|(scope, _)| *scope)
+        .collect::<Vec<_>>()
+        .join(",");
+    lines.push(format!(
+        "Add them at https://github.com/settings/tokens or run `gh auth refresh --scopes {scopes}`"
+    ));
+    Err(Error::from_str(&lines.join("\n")))
+}
+
+fn describe(check: &TokenCheck) -> String {
+    let scopes = match &check.scopes {
+        Some(scopes) if scopes.is_empty() => "(none)".to_string(),
+        Some(scopes) => scopes.join(", "),
+        None => {
+            "not reported (fine-grained token: permissions are granted per repository)".to_string()
+        }
+    };
+    let rate_limit = match &check.rate_limit {
+        Some(rate_limit) => format!(
+            "{}/{} remaining ({} used), resets at {}",
+            rate_limit.remaining, rate_limit.limit, rate_limit.used, rate_limit.reset_at
+        ),
+        None => "not available".to_string(),
+    };
+    format!(
+        "login: {}\nscopes: {scopes}\nrate limit: {rate_limit}\nexpires: {}",
+        check.login,
+        check.expires_at.as_deref().unwrap_or("never")
+    )
+}
+
+fn missing_scopes(scopes: &[String]) -> Vec<(&'static str, &'static str)> {
+    REQUIRED_SCOPES
+        .into_iter()
+        .filter(|(needed, _)| !has_scope(scopes, needed))
+        .collect()
+}
+
+/// Broader scopes imply narrower ones, e.g. admin:org includes read:org
+fn has_scope(scopes: &[String], needed: &str) -> bool {
+    scopes.iter().any(|scope| {
+        scope == needed
+            || (needed == "read:org" && matches!(scope.as_str(), "write:org" | "admin:org"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::api::token_check_query::TokenCheckQueryRateLimit;
+
+    #[test]
+    fn test_describe() {
+        let check = TokenCheck {
+            login: "kitsuyui".to_string(),
+            scopes: Some(vec!["repo".to_string(), "read:org".to_string()]),
+            expires_at: Some("2026-11-01 00:00:00 UTC".to_string()),
+            rate_limit: Some(TokenCheckQueryRateLimit {
+                limit: 5000,
+                remaining: 4990,
+                used: 10,
+                reset_at: "2026-10-16T12:00:00Z".to_string(),
+            }),
+        };
+        assert_eq!(
+            describe(&check),
+            "login: kitsuyui\n\
+             scopes: repo, read:org\n\
+             rate limit: 4990/5000 remaining (10 used), resets at 2026-10-16T12:00:00Z\n\
+             expires: 2026-11-01 00:00:00 UTC"
+        );
+        let check = TokenCheck {
+            scopes: None,
+            expires_at: None,
+            rate_limit: None,
+            ..check
+        };
+        assert_eq!(
+            describe(&check),
+            "login: kitsuyui\n\
+             scopes: not reported (fine-grained token: permissions are granted per repository)\n\
+             rate limit: not available\n\
+             expires: never"
+        );
+    }
+
+    #[test]
+    fn test_missing_scopes() {
+        let scopes = |scopes: &[&str]| scopes.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(missing_scopes(&scopes(&["repo", "read:org", "gist"])).is_empty());
+        assert!(missing_scopes(&scopes(&["repo", "admin:org"])).is_empty());
+        assert_eq!(
+            missing_scopes(&scopes(&["public_repo"])),
+            REQUIRED_SCOPES.to_vec()
+        );
+        assert_eq!(
+            missing_scopes(&scopes(&["repo"])),
+            vec![("read:org", "issues --org and --team")]
+        );
+    }
+}
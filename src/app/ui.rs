@@ -0,0 +1,357 @@
+//! Interactive dashboard of repositories
+//!
+//! The dashboard is drawn on stderr so that the path chosen by the user can be printed on stdout.
+//! e.g. `cd "$(mure ui)"`
+use std::collections::HashMap;
+use std::io::Stderr;
+use std::path::PathBuf;
+
+use git2::Repository;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState};
+use ratatui::{Frame, Terminal};
+
+use crate::app::edit::edit;
+use crate::app::list::{search_mure_repo, MureRepo};
+use crate::app::refresh::{refresh, RefreshOptions, RefreshStatus};
+use crate::config::Config;
+use crate::git::RepositorySupport;
+use crate::github;
+use crate::github::repo::RepoInfo;
+use crate::i18n::Message;
+use crate::mure_error::Error;
+use crate::verbosity::Verbosity;
+
+/// GitHub search query is limited to 256 characters
+const MAX_QUERY_LENGTH: usize = 256;
+
+type UiTerminal = Terminal<CrosstermBackend<Stderr>>;
+
+pub fn ui(config: &Config) -> Result<(), Error> {
+    let mut rows = search_mure_repo(config)
+        .into_iter()
+        .filter_map(Result::ok)
+        .map(RepoRow::new)
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        println!("{}", Message::NoRepositoriesFound);
+        return Ok(());
+    }
+    fill_github_counts(&mut rows);
+
+    let mut terminal = setup_terminal()?;
+    let result = run(config, &mut terminal, &mut rows);
+    restore_terminal(&mut terminal)?;
+    if let Some(path) = result? {
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+struct RepoRow {
+    name: String,
+    info: RepoInfo,
+    path: PathBuf,
+    dirty: Option<bool>,
+    ahead_behind: Option<(usize, usize)>,
+    issues: Option<i64>,
+    pull_requests: Option<i64>,
+    message: String,
+}
+
+impl RepoRow {
+    fn new(mure_repo: MureRepo) -> RepoRow {
+        let name = mure_repo
+            .relative_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| mure_repo.repo.repo.clone());
+        let mut row = RepoRow {
+            name,
+            info: mure_repo.repo,
+            path: mure_repo.relative_path,
+            dirty: None,
+            ahead_behind: None,
+            issues: None,
+            pull_requests: None,
+            message: String::new(),
+        };
+        row.update_status();
+        row
+    }
+
+    fn update_status(&mut self) {
+        let Ok(repo) = Repository::open(&self.path) else {
+            return;
+        };
+        self.dirty = repo.has_unsaved().ok();
+        self.ahead_behind = repo.ahead_behind("HEAD", "@{upstream}").ok();
+    }
+
+    fn refresh(&mut self, config: &Config) {
+        let result = refresh(
+            &self.path.to_string_lossy(),
+            &RefreshOptions::from_config(config),
+            Verbosity::Quiet,
+        );
+        self.message = match result {
+            Ok(RefreshStatus::Update { message, .. }) if message.is_empty() => {
+                "Refreshed".to_string()
+            }
+            Ok(RefreshStatus::Update { message, .. }) => message.replace('\n', ", "),
+            Ok(RefreshStatus::DoNothing(reason)) => format!("{reason:?}"),
+            Err(e) => e.message().replace('\n', " "),
+        };
+        self.update_status();
+    }
+
+    fn url(&self) -> String {
+        format!(
+            "https://{}/{}",
+            self.info.domain,
+            self.info.name_with_owner()
+        )
+    }
+
+    fn cells(&self) -> Vec<String> {
+        fn text<T: ToString>(value: Option<T>) -> String {
+            value.map(|v| v.to_string()).unwrap_or("-".to_string())
+        }
+        vec![
+            self.name.clone(),
+            match self.dirty {
+                Some(true) => "*".to_string(),
+                Some(false) => "".to_string(),
+                None => "-".to_string(),
+            },
+            text(self.ahead_behind.map(|(ahead, _)| ahead)),
+            text(self.ahead_behind.map(|(_, behind)| behind)),
+            text(self.issues),
+            text(self.pull_requests),
+            self.message.clone(),
+        ]
+    }
+}
+
+fn run(
+    config: &Config,
+    terminal: &mut UiTerminal,
+    rows: &mut [RepoRow],
+) -> Result<Option<PathBuf>, Error> {
+    let mut state = TableState::default().with_selected(Some(0));
+    loop {
+        terminal.draw(|frame| draw(frame, rows, &mut state))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let selected = state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+            KeyCode::Down | KeyCode::Char('j') => {
+                state.select(Some((selected + 1).min(rows.len() - 1)));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Enter | KeyCode::Char('c') => return Ok(Some(rows[selected].path.clone())),
+            KeyCode::Char('r') => rows[selected].refresh(config),
+            KeyCode::Char('e') => {
+                // editor may be a terminal application
+                restore_terminal(terminal)?;
+                let result = edit(config, rows[selected].name.clone(), false);
+                resume_terminal(terminal)?;
+                if let Err(e) = result {
+                    rows[selected].message = e.message();
+                }
+            }
+            KeyCode::Char('o') => {
+                if let Err(e) = open_browser(&rows[selected].url()) {
+                    rows[selected].message = e.message();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, rows: &[RepoRow], state: &mut TableState) {
+    let [main, footer] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let header = Row::new([
+        "Repository",
+        "Dirty",
+        "Ahead",
+        "Behind",
+        "Issues",
+        "PRs",
+        "Status",
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Percentage(30),
+        Constraint::Length(6),
+        Constraint::Length(6),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(5),
+        Constraint::Min(10),
+    ];
+    let table = Table::new(rows.iter().map(|row| Row::new(row.cells())), widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("mure"))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, main, state);
+    frame.render_widget(
+        Paragraph::new("j/k: move  r: refresh  e: edit  o: open in browser  enter: cd  q: quit"),
+        footer,
+    );
+}
+
+fn setup_terminal() -> Result<UiTerminal, Error> {
+    enable_raw_mode()?;
+    let mut stderr = std::io::stderr();
+    execute!(stderr, EnterAlternateScreen)?;
+    Ok(Terminal::new(CrosstermBackend::new(stderr))?)
+}
+
+fn resume_terminal(terminal: &mut UiTerminal) -> Result<(), Error> {
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    Ok(())
+}
+
+fn restore_terminal(terminal: &mut UiTerminal) -> Result<(), Error> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+pub fn open_browser(url: &str) -> Result<(), Error> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let result = std::process::Command::new(opener).arg(url).output()?;
+    if !result.status.success() {
+        return Err(Error::from_str("Failed to open browser"));
+    }
+    Ok(())
+}
+
+/// Fill numbers of issues and pull requests by GitHub search.
+/// This is skipped when GH_TOKEN is not set.
+fn fill_github_counts(rows: &mut [RepoRow]) {
+    let Ok(token) = github::token::get_github_token() else {
+        return;
+    };
+    let names = rows
+        .iter()
+        .filter(|row| row.info.domain == "github.com")
+        .map(|row| row.info.name_with_owner())
+        .collect::<Vec<_>>();
+    let mut counts = HashMap::new();
+    for query in repo_queries(&names) {
+        let Ok(repos) =
+            github::api::search_all_repositories(github::api::GITHUB_GRAPHQL_URL, &token, &query)
+        else {
+            continue;
+        };
+        for repo in repos {
+            if let Some(info) = RepoInfo::parse_url(&repo.url) {
+                counts.insert(
+                    info.name_with_owner(),
+                    (repo.issues.total_count, repo.pull_requests.total_count),
+                );
+            }
+        }
+    }
+    for row in rows.iter_mut() {
+        if let Some(&(issues, pull_requests)) = counts.get(&row.info.name_with_owner()) {
+            row.issues = Some(issues);
+            row.pull_requests = Some(pull_requests);
+        }
+    }
+}
+
+/// Build search queries for repositories within the length limit
+fn repo_queries(names: &[String]) -> Vec<String> {
+    // forks are excluded from search results by default
+    let base = "fork:true";
+    let mut queries = vec![];
+    let mut query = base.to_string();
+    for name in names {
+        let qualifier = format!(" repo:{name}");
+        if query != base && query.len() + qualifier.len() > MAX_QUERY_LENGTH {
+            queries.push(query);
+            query = base.to_string();
+        }
+        query.push_str(&qualifier);
+    }
+    if query != base {
+        queries.push(query);
+    }
+    queries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::Fixture;
+
+    #[test]
+    fn test_repo_queries() {
+        assert!(repo_queries(&[]).is_empty());
+        assert_eq!(
+            repo_queries(&["kitsuyui/mure".to_string()]),
+            vec!["fork:true repo:kitsuyui/mure"]
+        );
+        let names = (0..30)
+            .map(|i| format!("kitsuyui/repository{i}"))
+            .collect::<Vec<_>>();
+        let queries = repo_queries(&names);
+        assert!(queries.len() > 1);
+        assert!(queries.iter().all(|q| q.len() <= MAX_QUERY_LENGTH));
+        assert_eq!(
+            queries
+                .iter()
+                .map(|q| q.matches("repo:").count())
+                .sum::<usize>(),
+            30
+        );
+    }
+
+    #[test]
+    fn test_repo_row() {
+        let fixture = Fixture::create().unwrap();
+        fixture.create_empty_commit("initial commit").unwrap();
+        let path = fixture.repo.workdir().unwrap().to_path_buf();
+        let row = RepoRow::new(MureRepo {
+            relative_path: path.clone(),
+            absolute_path: path,
+            repo: RepoInfo::parse_url("https://github.com/kitsuyui/mure").unwrap(),
+        });
+        assert_eq!(row.dirty, Some(false));
+        // no upstream
+        assert_eq!(row.ahead_behind, None);
+        assert_eq!(row.url(), "https://github.com/kitsuyui/mure");
+        assert_eq!(row.cells()[1..6], ["", "-", "-", "-", "-"]);
+
+        fixture.create_file("1.txt", "hello").unwrap();
+        let mut row = row;
+        row.update_status();
+        assert_eq!(row.cells()[1], "*");
+    }
+}
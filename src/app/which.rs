@@ -0,0 +1,170 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::app::list::find_symlinks;
+use crate::config::{Config, ConfigSupport};
+use crate::github::repo::RepoInfo;
+use crate::mure_error::Error;
+
+/// A repository managed by mure found by reverse lookup
+#[derive(Debug, PartialEq, Eq)]
+struct Managed {
+    repo: RepoInfo,
+    /// symlink in the base directory (or the archive directory). None if it was deleted.
+    work_path: Option<PathBuf>,
+    store_path: PathBuf,
+}
+
+impl Managed {
+    fn describe(&self) -> String {
+        let work_path = self
+            .work_path
+            .as_ref()
+            .map_or("N/A".to_string(), |path| path.display().to_string());
+        format!(
+            "name: {}\nfull name: {}/{}/{}\nwork path: {work_path}\nstore path: {}",
+            self.repo.repo,
+            self.repo.domain,
+            self.repo.owner,
+            self.repo.repo,
+            self.store_path.display()
+        )
+    }
+}
+
+/// Print the repository managed by mure which the path (inside it) or the URL points to
+pub fn which(config: &Config, target: &str) -> Result<(), Error> {
+    println!("{}", lookup(config, target)?.describe());
+    Ok(())
+}
+
+fn lookup(config: &Config, target: &str) -> Result<Managed, Error> {
+    let not_managed = || Error::from_str(&format!("{target} is not managed by mure"));
+    let repo = match RepoInfo::parse_url(target) {
+        Some(repo) => repo,
+        None => repo_of_path(config, Path::new(target)).ok_or_else(not_managed)?,
+    };
+    let store_path = config.repo_store_path(&repo.domain, &repo.owner, &repo.repo);
+    if !store_path.is_dir() {
+        return Err(not_managed());
+    }
+    let work_path = find_work_path(config, &repo, &store_path);
+    Ok(Managed {
+        repo,
+        work_path,
+        store_path,
+    })
+}
+
+/// Repository of the store which contains the path. Symlinks are resolved first.
+fn repo_of_path(config: &Config, path: &Path) -> Option<RepoInfo> {
+    let path = std::fs::canonicalize(path).ok()?;
+    let store = std::fs::canonicalize(config.repos_store_path()).ok()?;
+    let mut names = path
+        .strip_prefix(store)
+        .ok()?
+        .components()
+        .map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        });
+    Some(RepoInfo {
+        domain: names.next()??,
+        owner: names.next()??,
+        repo: names.next()??,
+    })
+}
+
+/// Symlink pointing to the store in the base directory, or in the archive directory if archived.
+/// Subdirectories (`clone --into`) are searched if it is not at the usual place.
+fn find_work_path(config: &Config, repo: &RepoInfo, store_path: &Path) -> Option<PathBuf> {
+    let store_path = std::fs::canonicalize(store_path).ok()?;
+    let points_to_store =
+        |path: &PathBuf| std::fs::canonicalize(path).is_ok_and(|path| path == store_path);
+    [
+        config.repo_work_path(&repo.domain, &repo.owner, &repo.repo),
+        config.archive_path().join(&repo.repo),
+    ]
+    .into_iter()
+    .find(points_to_store)
+    .or_else(|| {
+        find_symlinks(config, &config.base_path())
+            .ok()?
+            .into_iter()
+            .find(points_to_store)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::config_with_base_dir;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_lookup() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let config = config_with_base_dir(&temp_dir);
+        let store_path = config.repo_store_path("github.com", "kitsuyui", "mure");
+        std::fs::create_dir_all(store_path.join("src")).unwrap();
+        let work_path = config.base_path().join("mure");
+        std::os::unix::fs::symlink(&store_path, &work_path).unwrap();
+        let expected = Managed {
+            repo: RepoInfo::parse_url("https://github.com/kitsuyui/mure").unwrap(),
+            work_path: Some(work_path.clone()),
+            store_path: store_path.clone(),
+        };
+
+        for target in [
+            "https://github.com/kitsuyui/mure",
+            "git@github.com:kitsuyui/mure.git",
+            work_path.to_str().unwrap(),
+            work_path.join("src").to_str().unwrap(),
+            store_path.to_str().unwrap(),
+        ] {
+            assert_eq!(lookup(&config, target).unwrap(), expected, "{target}");
+        }
+        assert_eq!(
+            lookup(&config, &expected.store_path.display().to_string())
+                .unwrap()
+                .describe(),
+            format!(
+                "name: mure\nfull name: github.com/kitsuyui/mure\nwork path: {}\nstore path: {}",
+                work_path.display(),
+                store_path.display()
+            )
+        );
+
+        // archived
+        std::fs::create_dir_all(config.archive_path()).unwrap();
+        std::fs::rename(&work_path, config.archive_path().join("mure")).unwrap();
+        assert_eq!(
+            lookup(&config, "https://github.com/kitsuyui/mure")
+                .unwrap()
+                .work_path,
+            Some(config.archive_path().join("mure"))
+        );
+
+        // nested
+        std::fs::remove_file(config.archive_path().join("mure")).unwrap();
+        let nested = config.base_path().join("clients/acme/mure");
+        std::fs::create_dir_all(nested.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(&store_path, &nested).unwrap();
+        assert_eq!(
+            lookup(&config, nested.to_str().unwrap()).unwrap().work_path,
+            Some(nested.clone())
+        );
+
+        // not managed
+        for target in [
+            "https://github.com/kitsuyui/other",
+            config.repos_store_path().to_str().unwrap(),
+            temp_dir.to_str().unwrap(),
+            "/nonexistent",
+        ] {
+            assert_eq!(
+                lookup(&config, target).unwrap_err().to_string(),
+                format!("{target} is not managed by mure")
+            );
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use git2::Repository;
+
+use crate::app::clone::clone_wiki;
+use crate::config::{Config, ConfigSupport};
+use crate::mure_error::Error;
+use crate::verbosity::Verbosity;
+
+/// Clone the wiki of an already cloned repository
+pub fn clone(config: &Config, name: &str, verbosity: Verbosity) -> Result<(), Error> {
+    let path = config.base_path().join(name);
+    let repo = Repository::open(&path)?;
+    let remote = repo.find_remote("origin")?;
+    let Some(url) = remote.url() else {
+        return Err(Error::from_str("origin url is not valid utf-8"));
+    };
+    clone_wiki(config, url, verbosity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::get_test_config;
+
+    #[test]
+    fn test_clone_not_found() {
+        let config = get_test_config();
+        let result = clone(&config, "not-existing-repository", Verbosity::Normal);
+        assert!(result.is_err());
+    }
+}
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_derive::{Deserialize, Serialize};
 
 use crate::mure_error;
 
@@ -14,6 +19,14 @@ pub struct RepoBranch {
     pub(crate) commit_id: String,
 }
 
+/// Format coverage for display. N/A is shown when coverage is not available.
+pub fn coverage_text(coverage: Option<f64>) -> String {
+    match coverage {
+        Some(coverage) => format!("{:.2}%", coverage),
+        None => "N/A".to_string(),
+    }
+}
+
 fn get_codecov_token() -> Result<String, mure_error::Error> {
     std::env::var("CODECOV_TOKEN").map_err(|e| {
         mure_error::Error::from_str(&format!(
@@ -23,6 +36,79 @@ fn get_codecov_token() -> Result<String, mure_error::Error> {
     })
 }
 
+/// Number of concurrent requests to Codecov
+const CONCURRENCY: usize = 8;
+
+/// Results of Codecov are reused within this period
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Cache of Codecov results with fetched time.
+/// Coverages are keyed by commit so they are valid as long as the commit is the same,
+/// but repositories without coverage of the commit yet would be fetched every time without TTL.
+#[derive(Serialize, Deserialize, Default)]
+struct CoverageCache {
+    /// names of inactive repositories keyed by owner
+    #[serde(default)]
+    inactive_repos: HashMap<String, CacheEntry<HashSet<String>>>,
+    /// coverage keyed by owner/repo/branch/commit
+    #[serde(default)]
+    coverages: HashMap<String, CacheEntry<f64>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+    fetched_at: u64,
+    value: T,
+}
+
+impl<T> CacheEntry<T> {
+    fn new(value: T, now: u64) -> Self {
+        CacheEntry {
+            fetched_at: now,
+            value,
+        }
+    }
+
+    fn fresh(&self, now: u64) -> Option<&T> {
+        match now.saturating_sub(self.fetched_at) < CACHE_TTL.as_secs() {
+            true => Some(&self.value),
+            false => None,
+        }
+    }
+}
+
+impl CoverageCache {
+    /// Broken or missing cache is treated as empty
+    fn load(path: &Path) -> CoverageCache {
+        std::fs::read(path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<(), mure_error::Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    fn coverage_key(username: &str, repo: &RepoBranch) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            username, repo.name, repo.branch, repo.commit_id
+        )
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
 pub fn get_repository_coverage(
     username: &str,
     repos: &Vec<RepoBranch>,
@@ -32,45 +118,158 @@ pub fn get_repository_coverage(
         .ok_or_else(|| mure_error::Error::from_str("Failed to get cache dir"))?
         .join("mure")
         .join("codecov");
+    let cache_path = cache_dir.join("coverage.json");
+    let mut cache = CoverageCache::load(&cache_path);
+    let now = now();
 
     let client = codecov_cache::Client::new(token, PathBuf::from(&cache_dir));
-    let codecov_repos = client.get_all_repos(&codecov::owner::Owner::new("github", username))?;
-    let mut repo_coverage = Vec::new();
-    let codecov_all_repositories = codecov_repos
-        .into_iter()
-        .map(|repo| (repo.name.to_string(), repo))
-        .collect::<HashMap<String, codecov::repos::Repo>>();
+    let inactive_repos = match cache
+        .inactive_repos
+        .get(username)
+        .and_then(|entry| entry.fresh(now))
+    {
+        Some(inactive_repos) => inactive_repos.clone(),
+        None => {
+            let codecov_repos =
+                client.get_all_repos(&codecov::owner::Owner::new("github", username))?;
+            let inactive_repos = codecov_repos
+                .into_iter()
+                .filter(|repo| !repo.activated || !repo.active)
+                .map(|repo| repo.name)
+                .collect::<HashSet<String>>();
+            cache.inactive_repos.insert(
+                username.to_string(),
+                CacheEntry::new(inactive_repos.clone(), now),
+            );
+            inactive_repos
+        }
+    };
 
+    let mut repo_coverage = Vec::new();
+    let mut to_fetch = Vec::new();
     for repo in repos {
-        if let Some(repo_info) = codecov_all_repositories.get(&repo.name) {
-            if !repo_info.activated {
-                continue;
-            }
-            if !repo_info.active {
-                continue;
-            }
+        if inactive_repos.contains(&repo.name) {
+            continue;
+        }
+        let key = CoverageCache::coverage_key(username, repo);
+        match cache.coverages.get(&key).and_then(|entry| entry.fresh(now)) {
+            Some(&coverage) => repo_coverage.push(Coverage {
+                name: repo.name.to_string(),
+                coverage: Some(coverage),
+            }),
+            None => to_fetch.push(repo),
         }
-        let author = codecov::author::Author::new("github", username, &repo.name);
-        match client.get_branch_detail_with_commit_id(&author, &repo.branch, &repo.commit_id) {
-            Ok(branch_detail) => {
-                let coverage = branch_detail.latest_coverage();
+    }
+
+    for (repo, result) in fetch_coverages(&client, username, &to_fetch) {
+        match result {
+            Ok(coverage) => {
+                cache.coverages.insert(
+                    CoverageCache::coverage_key(username, repo),
+                    CacheEntry::new(coverage, now),
+                );
                 repo_coverage.push(Coverage {
                     name: repo.name.to_string(),
                     coverage: Some(coverage),
                 });
             }
             Err(e) => {
-                println!("Repo {} Failed to get coverage: {:?}", repo.name, e);
+                tracing::warn!("failed to get coverage of {}: {:?}", repo.name, e);
             }
         }
     }
+    if let Err(e) = cache.save(&cache_path) {
+        tracing::warn!("failed to save codecov cache: {e}");
+    }
     Ok(repo_coverage)
 }
 
+/// Fetch coverages with at most CONCURRENCY requests at once. Results keep the order of repos.
+fn fetch_coverages<'a>(
+    client: &codecov_cache::Client,
+    username: &str,
+    repos: &[&'a RepoBranch],
+) -> Vec<(&'a RepoBranch, Result<f64, codecov_cache::errors::Error>)> {
+    let queue = Mutex::new(repos.iter().enumerate());
+    let results = Mutex::new(Vec::with_capacity(repos.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..CONCURRENCY.min(repos.len()) {
+            scope.spawn(|| loop {
+                let next = match queue.lock() {
+                    Ok(mut queue) => queue.next(),
+                    Err(_) => None,
+                };
+                let Some((index, &repo)) = next else {
+                    break;
+                };
+                let author = codecov::author::Author::new("github", username, &repo.name);
+                let result = client
+                    .get_branch_detail_with_commit_id(&author, &repo.branch, &repo.commit_id)
+                    .map(|branch_detail| branch_detail.latest_coverage());
+                if let Ok(mut results) = results.lock() {
+                    results.push((index, repo, result));
+                }
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap_or_default();
+    results.sort_by_key(|(index, _, _)| *index);
+    results
+        .into_iter()
+        .map(|(_, repo, result)| (repo, result))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_coverage_text() {
+        assert_eq!(coverage_text(Some(12.345)), "12.35%");
+        assert_eq!(coverage_text(None), "N/A");
+    }
+
+    #[test]
+    fn test_cache_entry() {
+        let entry = CacheEntry::new(1.0, 1000);
+        assert_eq!(entry.fresh(1000), Some(&1.0));
+        assert_eq!(entry.fresh(1000 + CACHE_TTL.as_secs() - 1), Some(&1.0));
+        assert_eq!(entry.fresh(1000 + CACHE_TTL.as_secs()), None);
+    }
+
+    #[test]
+    fn test_coverage_cache() {
+        let temp_dir = mktemp::Temp::new_dir().unwrap();
+        let path = temp_dir.as_path().join("codecov").join("coverage.json");
+        // not exists yet
+        let mut cache = CoverageCache::load(&path);
+        assert!(cache.coverages.is_empty());
+
+        let repo = RepoBranch {
+            name: "mure".to_string(),
+            branch: "main".to_string(),
+            commit_id: "1234567".to_string(),
+        };
+        let key = CoverageCache::coverage_key("kitsuyui", &repo);
+        assert_eq!(key, "kitsuyui/mure/main/1234567");
+        cache
+            .coverages
+            .insert(key.clone(), CacheEntry::new(80.0, 1000));
+        cache.inactive_repos.insert(
+            "kitsuyui".to_string(),
+            CacheEntry::new(HashSet::from(["old".to_string()]), 1000),
+        );
+        cache.save(&path).unwrap();
+
+        let cache = CoverageCache::load(&path);
+        assert_eq!(cache.coverages[&key].fresh(1000), Some(&80.0));
+        assert!(cache.inactive_repos["kitsuyui"].value.contains("old"));
+
+        std::fs::write(&path, "broken").unwrap();
+        assert!(CoverageCache::load(&path).coverages.is_empty());
+    }
+
     #[test]
     fn test_get_repository_coverage() {
         let repos = vec![RepoBranch {
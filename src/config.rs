@@ -2,52 +2,108 @@
 //!
 //! Usually config file is located at ~/.mure.toml
 
+use crate::gh::GhClient;
+use crate::github::api::GITHUB_GRAPHQL_URL;
+use crate::i18n::Language;
 use crate::mure_error::Error;
 
 use std::{
+    collections::BTreeMap,
     fs::File,
     io::Write,
     path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
 };
 
+/// Levels of subdirectories of base_dir searched for repositories by default
+pub const DEFAULT_SEARCH_DEPTH: usize = 3;
+
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct Config {
     pub core: Core,
+    #[serde(default)]
     pub github: GitHub,
     pub shell: Option<Shell>,
+    pub refresh: Option<Refresh>,
+    pub template: Option<Template>,
+    pub clone: Option<CloneConfig>,
+    pub hosts: Option<BTreeMap<String, Host>>,
+    /// Settings per repository keyed by repository name
+    pub repo: Option<BTreeMap<String, RepoConfig>>,
+    pub ui: Option<Ui>,
+    pub notifications: Option<Notifications>,
+    pub network: Option<Network>,
+    /// Groups (tags) assigned automatically on clone keyed by group name,
+    /// e.g. `oss = "owner=kitsuyui visibility=public"`
+    pub group_rules: Option<BTreeMap<String, String>>,
+    /// Desired versions of tools pinned in repositories keyed by the name in `.tool-versions`,
+    /// e.g. `rust = "1.79.0"`, `nodejs = "20.11.0"`
+    pub toolchain: Option<BTreeMap<String, String>>,
+    /// Aliases of commands, e.g. `up = "refresh --all --quiet"`. `!` runs the rest as a shell command
+    pub alias: Option<BTreeMap<String, String>>,
+    /// Named sets of settings selected by `--profile` or `MURE_PROFILE`, e.g. `[profiles.work]`.
+    /// Sections of the profile are merged over the top level and `[core]` keys can be written directly.
+    pub profiles: Option<BTreeMap<String, toml::Table>>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct Core {
     pub base_dir: String,
     pub editor: Option<String>,
+    /// Glob patterns of repositories to be skipped by commands working on all repositories.
+    /// Patterns are matched against `repo`, `owner/repo` and `domain/owner/repo`.
+    pub exclude: Option<Vec<String>>,
+    /// How many levels of subdirectories of base_dir are searched for repositories. Defaults to 3.
+    pub search_depth: Option<usize>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct GitHub {
-    // TODO: try .gitconfig.user.name if not set
-    pub username: String,
+    /// Detected by `git config github.user` or `gh api user` if not set
+    pub username: Option<String>,
     pub query: Option<String>,
     pub queries: Option<Vec<String>>,
+    /// Environment variable of the token used as `GH_TOKEN`, e.g. `GH_TOKEN_WORK` in a profile
+    pub token_env: Option<String>,
 }
 
 impl GitHub {
-    pub fn get_queries(&self) -> Vec<String> {
+    pub fn get_queries(&self) -> Result<Vec<String>, Error> {
         if let Some(qs) = &self.queries {
-            return qs.clone();
+            return Ok(qs.clone());
         }
         if let Some(q) = &self.query {
-            return vec![q.to_string()];
+            return Ok(vec![q.to_string()]);
         }
         let default_query = format!(
             "user:{} is:public fork:false archived:false",
-            &self.username
+            self.username()?
         );
-        match &self.query {
-            Some(q) => vec![q.to_string()],
-            None => vec![default_query],
+        Ok(vec![default_query])
+    }
+
+    /// The username of config, otherwise detected once and reused in the process.
+    /// An empty username (written by `mure init` of older versions) is not set.
+    pub fn username(&self) -> Result<String, Error> {
+        self.username_or(|| {
+            DETECTED_USERNAME
+                .get_or_init(detect_username)
+                .clone()
+                .ok_or_else(|| {
+                    Error::from_str(
+                        "github.username is not set and can't be detected by `git config github.user` or `gh api user`",
+                    )
+                })
+        })
+    }
+
+    fn username_or(&self, detect: impl FnOnce() -> Result<String, Error>) -> Result<String, Error> {
+        match &self.username {
+            Some(username) if !username.is_empty() => Ok(username.clone()),
+            _ => detect(),
         }
     }
     pub fn is_both_query_and_queries_set(&self) -> bool {
@@ -55,9 +111,135 @@ impl GitHub {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+static DETECTED_USERNAME: OnceLock<Option<String>> = OnceLock::new();
+
+/// `github.user` of git config (the convention of hub and others), then the login of gh
+fn detect_username() -> Option<String> {
+    let from_git = Command::new("git")
+        .args(["config", "github.user"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    from_git
+        .filter(|username| !username.is_empty())
+        .or_else(|| {
+            GhClient::new()
+                .login()
+                .ok()
+                .filter(|username| !username.is_empty())
+        })
+}
+
+#[derive(Serialize, Deserialize, Default)]
 pub struct Shell {
     pub cd_shims: Option<String>,
+    /// Fuzzy finder command to pick a repository with, such as `fzf` (default) or `sk`
+    pub picker: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Refresh {
+    /// Defaults of keep_current_branch and delete_merged_branches (default: tidy)
+    pub mode: Option<RefreshMode>,
+    /// Never switch branches on refresh. The default branch is updated by fetch instead.
+    pub keep_current_branch: Option<bool>,
+    /// Initialize and update submodules recursively after fast-forward.
+    pub update_submodules: Option<bool>,
+    /// Keep merged branches that open pull requests still target or are based on.
+    pub check_open_pull_requests: Option<bool>,
+    /// Delete merged branches (default: true)
+    pub delete_merged_branches: Option<bool>,
+    /// Delete only merged branches matching any of the glob patterns
+    pub delete_only: Option<Vec<String>>,
+    /// Never delete branches matching any of the glob patterns (e.g. `release/*`)
+    pub protect_branches: Option<Vec<String>>,
+    /// Fast-forward the default branch of forks with `upstream` remote from upstream
+    pub sync_forks: Option<bool>,
+    /// Push the default branch synced from upstream to origin (the fork)
+    pub push_forks: Option<bool>,
+    /// Run hooks of repositories such as post-merge (default: true)
+    pub run_hooks: Option<bool>,
+}
+
+/// Stance of refresh on changing local branches
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RefreshMode {
+    /// Only fetch and fast-forward. Never switch branches nor delete anything
+    Safe,
+    /// Switch to the default branch and delete merged branches
+    Tidy,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Notifications {
+    /// Slack or Discord compatible incoming webhook to post the summary of `refresh --all`
+    pub webhook_url: Option<String>,
+    /// Show a desktop notification when `refresh --all` or `exec` finishes
+    pub enabled: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Network {
+    /// Average bandwidth of bulk operations like `refresh --all`, e.g. `512K`, `2M` (bytes per second)
+    pub max_bandwidth: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Ui {
+    /// Language of messages. The locale of environment variables is used if not specified
+    pub language: Option<Language>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct CloneConfig {
+    /// Rewrite repository urls to the protocol before cloning
+    pub protocol: Option<CloneProtocol>,
+    /// Clone submodules too
+    pub recurse_submodules: Option<bool>,
+    /// Clone submodules also as repositories of mure so they can be refreshed independently
+    pub register_submodules: Option<bool>,
+    /// Share objects with a clone of the same name under another owner (e.g. the upstream of a fork)
+    pub auto_reference: Option<bool>,
+    /// Keep a bare mirror of all refs as a backup and share its objects with the working clone
+    pub mirror: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum CloneProtocol {
+    Ssh,
+    Https,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct RepoConfig {
+    /// Branches to be fast-forwarded from upstream on refresh in addition to the default branch
+    pub sync_branches: Option<Vec<String>>,
+    /// Editor to open this repository with instead of `core.editor`
+    pub editor: Option<String>,
+    /// Run hooks on refresh regardless of `--no-hooks` and `[refresh] run_hooks`
+    pub run_hooks: Option<bool>,
+}
+
+/// Settings per host such as GitHub Enterprise Server
+#[derive(Serialize, Deserialize, Default)]
+pub struct Host {
+    /// GraphQL API endpoint. Default is https://<host>/api/graphql
+    pub graphql_url: Option<String>,
+    /// Private key for ssh remotes of the host instead of the keys of ssh-agent and ~/.ssh/config
+    pub identity_file: Option<String>,
+    /// Use the token of the host (GH_TOKEN or GH_ENTERPRISE_TOKEN) as the password of HTTPS remotes
+    pub https_token: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Template {
+    /// Directory of template files. Default is ~/.mure/templates
+    pub dir: Option<String>,
+    /// Directory of files of repositories created by `mure new`. Default is ~/.mure/new
+    pub new_dir: Option<String>,
 }
 
 pub trait ConfigSupport {
@@ -65,7 +247,17 @@ pub trait ConfigSupport {
     fn repos_store_path(&self) -> PathBuf;
     fn repo_store_path(&self, domain: &str, owner: &str, repo: &str) -> PathBuf;
     fn repo_work_path(&self, domain: &str, owner: &str, repo: &str) -> PathBuf;
+    fn archive_path(&self) -> PathBuf;
+    fn mirrors_path(&self) -> PathBuf;
+    fn repo_mirror_path(&self, domain: &str, owner: &str, repo: &str) -> PathBuf;
     fn resolve_cd_shims(&self) -> String;
+    fn resolve_picker(&self) -> String;
+    fn template_path(&self) -> PathBuf;
+    fn new_template_path(&self) -> PathBuf;
+    fn exclude_patterns(&self) -> Vec<String>;
+    fn search_depth(&self) -> usize;
+    fn graphql_url(&self, domain: &str) -> String;
+    fn repo_config(&self, name: &str) -> Option<&RepoConfig>;
 }
 
 impl ConfigSupport for Config {
@@ -82,6 +274,18 @@ impl ConfigSupport for Config {
     fn repo_work_path(&self, _domain: &str, _owner: &str, repo: &str) -> PathBuf {
         self.base_path().join(repo)
     }
+    fn archive_path(&self) -> PathBuf {
+        self.base_path().join("archive")
+    }
+    fn mirrors_path(&self) -> PathBuf {
+        self.base_path().join("mirror")
+    }
+    fn repo_mirror_path(&self, domain: &str, owner: &str, repo: &str) -> PathBuf {
+        self.mirrors_path()
+            .join(domain)
+            .join(owner)
+            .join(format!("{repo}.git"))
+    }
     fn resolve_cd_shims(&self) -> String {
         let default = "mucd".to_string();
         match &self.shell {
@@ -89,14 +293,246 @@ impl ConfigSupport for Config {
             None => default,
         }
     }
+    fn resolve_picker(&self) -> String {
+        self.shell
+            .as_ref()
+            .and_then(|shell| shell.picker.clone())
+            .unwrap_or("fzf".to_string())
+    }
+    fn template_path(&self) -> PathBuf {
+        let dir = match &self.template {
+            Some(Template { dir: Some(dir), .. }) => dir.as_str(),
+            _ => "~/.mure/templates",
+        };
+        PathBuf::from(shellexpand::tilde(dir).to_string())
+    }
+    fn new_template_path(&self) -> PathBuf {
+        let dir = match &self.template {
+            Some(Template {
+                new_dir: Some(dir), ..
+            }) => dir.as_str(),
+            _ => "~/.mure/new",
+        };
+        PathBuf::from(shellexpand::tilde(dir).to_string())
+    }
+    /// Patterns of `core.exclude` and `.mureignore` in base_dir
+    fn exclude_patterns(&self) -> Vec<String> {
+        let mut patterns = self.core.exclude.clone().unwrap_or_default();
+        if let Ok(content) = std::fs::read_to_string(self.base_path().join(".mureignore")) {
+            patterns.extend(
+                content
+                    .lines()
+                    .map(|line| line.trim())
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| line.to_string()),
+            );
+        }
+        patterns
+    }
+    fn search_depth(&self) -> usize {
+        self.core.search_depth.unwrap_or(DEFAULT_SEARCH_DEPTH)
+    }
+    fn graphql_url(&self, domain: &str) -> String {
+        let configured = self
+            .hosts
+            .as_ref()
+            .and_then(|hosts| hosts.get(domain))
+            .and_then(|host| host.graphql_url.clone());
+        match configured {
+            Some(url) => url,
+            None if domain == "github.com" => GITHUB_GRAPHQL_URL.to_string(),
+            None => format!("https://{domain}/api/graphql"),
+        }
+    }
+    fn repo_config(&self, name: &str) -> Option<&RepoConfig> {
+        self.repo.as_ref().and_then(|repos| repos.get(name))
+    }
 }
 
-/// read $HOME/.mure.toml to get config
+/// Prefix of environment variables overriding config
+const ENV_PREFIX: &str = "MURE_";
+
+/// Environment variable of the active profile. `--profile` sets it for the process and its children.
+pub const PROFILE_ENV: &str = "MURE_PROFILE";
+
+/// Sections which `MURE_<SECTION>_<KEY>` can override.
+/// Sections keyed by names like `[repo."<name>"]` can't be expressed by a variable name.
+const ENV_SECTIONS: [&str; 9] = [
+    "core",
+    "github",
+    "shell",
+    "refresh",
+    "template",
+    "clone",
+    "ui",
+    "notifications",
+    "network",
+];
+
+/// Keys of `[core]` which can also be overridden without the section, e.g. `MURE_BASE_DIR`
+const ENV_CORE_KEYS: [&str; 4] = ["base_dir", "editor", "exclude", "search_depth"];
+
+/// read $HOME/.mure.toml to get config, overridden by `MURE_*` environment variables
 pub fn get_config() -> Result<Config, Error> {
-    let config_path = resolve_config_path();
-    let content = std::fs::read_to_string(config_path?)?;
-    let config: Config = toml::from_str(&content)?;
-    Ok(config)
+    let config_path = resolve_config_path()?;
+    let content = match std::fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        // CI and containers may configure mure by environment variables only
+        Err(e)
+            if e.kind() == std::io::ErrorKind::NotFound
+                && !env_overrides(std::env::vars()).is_empty() =>
+        {
+            String::new()
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(Error::ConfigNotFound(format!(
+                "{} is not found",
+                config_path.display()
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    parse_config(&content, std::env::vars())
+}
+
+/// Parse the config file and apply the active profile and the environment variables over it
+/// (env > profile > file > default)
+fn parse_config(
+    content: &str,
+    vars: impl Iterator<Item = (String, String)>,
+) -> Result<Config, Error> {
+    let vars = vars.collect::<Vec<_>>();
+    let mut table = content.parse::<toml::Table>()?;
+    let profile = vars
+        .iter()
+        .find(|(name, value)| name == PROFILE_ENV && !value.is_empty());
+    if let Some((_, profile)) = profile {
+        apply_profile(&mut table, profile)?;
+    }
+    let overrides = env_overrides(vars.into_iter());
+    let mut overridden = table.clone();
+    for (_, section, key, value) in &overrides {
+        set_key(&mut overridden, section, key.clone(), value.clone());
+    }
+    toml::Value::Table(overridden).try_into().map_err(|e| {
+        match invalid_override(&table, &overrides) {
+            Some((name, value)) => {
+                Error::from_str(&format!("invalid value of {name}={value}: {e}"))
+            }
+            None => e.into(),
+        }
+    })
+}
+
+/// The variable which makes the config invalid, found by leaving it out.
+/// None if the config is invalid without any of them.
+fn invalid_override<'a>(
+    table: &toml::Table,
+    overrides: &'a [(String, String, String, toml::Value)],
+) -> Option<(&'a str, &'a toml::Value)> {
+    overrides
+        .iter()
+        .enumerate()
+        .find_map(|(index, (name, _, _, value))| {
+            let mut table = table.clone();
+            for (_, section, key, value) in overrides
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != index)
+                .map(|(_, entry)| entry)
+            {
+                set_key(&mut table, section, key.clone(), value.clone());
+            }
+            toml::Value::Table(table)
+                .try_into::<Config>()
+                .is_ok()
+                .then_some((name.as_str(), value))
+        })
+}
+
+/// Merge `[profiles.<name>]` over the top level of the config
+fn apply_profile(table: &mut toml::Table, name: &str) -> Result<(), Error> {
+    let profile = table
+        .get("profiles")
+        .and_then(|profiles| profiles.get(name))
+        .and_then(toml::Value::as_table)
+        .cloned()
+        .ok_or_else(|| Error::from_str(&format!("profile {name} is not defined in [profiles]")))?;
+    for (key, value) in profile {
+        match value {
+            toml::Value::Table(section) => {
+                for (section_key, value) in section {
+                    set_key(table, &key, section_key, value);
+                }
+            }
+            value if ENV_CORE_KEYS.contains(&key.as_str()) => set_key(table, "core", key, value),
+            value => {
+                table.insert(key, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn set_key(table: &mut toml::Table, section: &str, key: String, value: toml::Value) {
+    let section = table
+        .entry(section)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let Some(section) = section.as_table_mut() {
+        section.insert(key, value);
+    }
+}
+
+/// Value of `--profile` in the arguments before they are parsed, because config is loaded first
+pub fn profile_arg(args: &[String]) -> Option<&str> {
+    let mut args = args.iter().take_while(|arg| *arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next().map(String::as_str);
+        }
+        if let Some(profile) = arg.strip_prefix("--profile=") {
+            return Some(profile);
+        }
+    }
+    None
+}
+
+/// `(variable, section, key, value)` of `MURE_<SECTION>_<KEY>` and `MURE_<CORE KEY>` variables.
+/// Other variables such as `MURE_CONFIG_PATH` are not config.
+fn env_overrides(
+    vars: impl Iterator<Item = (String, String)>,
+) -> Vec<(String, String, String, toml::Value)> {
+    vars.filter_map(|(variable, value)| {
+        let name = variable.strip_prefix(ENV_PREFIX)?.to_lowercase();
+        let (section, key) = split_env_name(&name)?;
+        Some((
+            variable.clone(),
+            section.to_string(),
+            key.to_string(),
+            env_value(&value),
+        ))
+    })
+    .collect()
+}
+
+fn split_env_name(name: &str) -> Option<(&str, &str)> {
+    if ENV_CORE_KEYS.contains(&name) {
+        return Some(("core", name));
+    }
+    // sections may contain `_` in the future, so any `_` can separate the section
+    name.match_indices('_')
+        .map(|(index, _)| (&name[..index], &name[index + 1..]))
+        .find(|(section, key)| ENV_SECTIONS.contains(section) && !key.is_empty())
+}
+
+/// The value as TOML like `true`, `3` or `["a", "b"]`, otherwise as a string.
+/// Quote strings which look like other types, e.g. `"1234"`.
+fn env_value(value: &str) -> toml::Value {
+    format!("value = {value}")
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
 }
 
 pub fn initialize_config() -> Result<Config, Error> {
@@ -113,15 +549,19 @@ fn create_config(path: &Path) -> Result<Config, Error> {
         core: Core {
             base_dir: "~/.dev".to_string(),
             editor: None,
+            ..Default::default()
         },
         github: GitHub {
-            username: "".to_string(),
+            username: None,
             query: None,
             queries: Some(vec![]),
+            token_env: None,
         },
         shell: Some(Shell {
             cd_shims: Some("mucd".to_string()),
+            picker: None,
         }),
+        ..Default::default()
     };
     let content = toml::to_string(&config)?;
     let mut file = File::create(path)?;
@@ -132,7 +572,7 @@ fn create_config(path: &Path) -> Result<Config, Error> {
 /// resolve config path
 ///
 /// Resolve mure configuration path. Usually this is $HOME/.mure.toml
-fn resolve_config_path() -> Result<PathBuf, Error> {
+pub fn resolve_config_path() -> Result<PathBuf, Error> {
     // TODO: Is $HOME/.murerc better?
     // Or should try ~/.config/mure.toml?
 
@@ -168,15 +608,19 @@ pub mod tests {
             core: Core {
                 base_dir: "~/.dev".to_string(),
                 editor: Some("great_editor".to_string()),
+                ..Default::default()
             },
             github: GitHub {
-                username: "".to_string(),
+                username: None,
                 query: None,
                 queries: Some(vec![]),
+                token_env: None,
             },
             shell: Some(Shell {
                 cd_shims: Some("mucd".to_string()),
+                picker: None,
             }),
+            ..Default::default()
         }
     }
 
@@ -219,7 +663,94 @@ pub mod tests {
         )
         .unwrap();
         assert!(config.core.base_dir == "~/.dev");
-        assert_eq!(config.github.username, "kitsuyui");
+        assert_eq!(config.github.username.as_deref(), Some("kitsuyui"));
+        assert!(config.refresh.is_none());
+
+        let config: Config = toml::from_str(
+            r#"
+            [core]
+            base_dir = "~/.dev"
+
+            [github]
+            username = "kitsuyui"
+
+            [refresh]
+            keep_current_branch = true
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.refresh.unwrap().keep_current_branch, Some(true));
+
+        let config: Config = toml::from_str(
+            r#"
+            [core]
+            base_dir = "~/.dev"
+
+            [github]
+            username = "kitsuyui"
+
+            [clone]
+            protocol = "ssh"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.clone.unwrap().protocol, Some(CloneProtocol::Ssh));
+    }
+
+    #[test]
+    fn test_graphql_url() {
+        let config: Config = toml::from_str(
+            r#"
+            [core]
+            base_dir = "~/.dev"
+
+            [github]
+            username = "kitsuyui"
+
+            [hosts."ghe.corp"]
+            graphql_url = "https://ghe.corp/custom/graphql"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.graphql_url("github.com"),
+            "https://api.github.com/graphql"
+        );
+        assert_eq!(
+            config.graphql_url("ghe.corp"),
+            "https://ghe.corp/custom/graphql"
+        );
+        assert_eq!(
+            config.graphql_url("ghe.example.com"),
+            "https://ghe.example.com/api/graphql"
+        );
+    }
+
+    #[test]
+    fn test_repo_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [core]
+            base_dir = "~/.dev"
+
+            [github]
+            username = "kitsuyui"
+
+            [repo."mure"]
+            sync_branches = ["main", "develop"]
+            editor = "code"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.repo_config("mure").unwrap().sync_branches,
+            Some(vec!["main".to_string(), "develop".to_string()])
+        );
+        assert_eq!(
+            config.repo_config("mure").unwrap().editor,
+            Some("code".to_string())
+        );
+        assert!(config.repo_config("other").is_none());
     }
 
     #[test]
@@ -233,6 +764,136 @@ pub mod tests {
         let config: Config =
             toml::from_str(&std::fs::read_to_string(config_path).unwrap()).unwrap();
         assert!(config.core.base_dir == "~/.dev");
-        assert_eq!(config.github.username, "");
+        assert_eq!(config.github.username, None);
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let vars = |vars: &[(&str, &str)]| {
+            vars.iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        let config = parse_config(
+            "[core]\nbase_dir = \"~/.dev\"\n\n[refresh]\nmode = \"safe\"\n",
+            vars(&[
+                ("MURE_BASE_DIR", "/workspace"),
+                ("MURE_GITHUB_USERNAME", "kitsuyui"),
+                ("MURE_REFRESH_KEEP_CURRENT_BRANCH", "true"),
+                ("MURE_CORE_SEARCH_DEPTH", "5"),
+                ("MURE_CORE_EXCLUDE", r#"["archive/*"]"#),
+                ("MURE_CONFIG_PATH", "/tmp/mure.toml"),
+                ("HOME", "/root"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(config.core.base_dir, "/workspace");
+        assert_eq!(config.core.search_depth, Some(5));
+        assert_eq!(config.core.exclude, Some(vec!["archive/*".to_string()]));
+        assert_eq!(config.github.username.as_deref(), Some("kitsuyui"));
+        let refresh = config.refresh.unwrap();
+        // keys of the file not overridden are kept
+        assert_eq!(refresh.mode, Some(RefreshMode::Safe));
+        assert_eq!(refresh.keep_current_branch, Some(true));
+
+        // environment variables only
+        let config = parse_config("", vars(&[("MURE_BASE_DIR", "~/src")])).unwrap();
+        assert_eq!(config.core.base_dir, "~/src");
+        assert!(parse_config("", vars(&[])).is_err());
+
+        // the variable of the invalid value is reported
+        match parse_config(
+            "[core]\nbase_dir = \"~/.dev\"\n",
+            vars(&[
+                ("MURE_GITHUB_USERNAME", "kitsuyui"),
+                ("MURE_CORE_SEARCH_DEPTH", "abc"),
+            ]),
+        ) {
+            Ok(_) => unreachable!("search_depth must be a number"),
+            Err(e) => assert!(e
+                .message()
+                .starts_with(r#"invalid value of MURE_CORE_SEARCH_DEPTH="abc": "#)),
+        }
+
+        assert_eq!(
+            split_env_name("notifications_webhook_url"),
+            Some(("notifications", "webhook_url"))
+        );
+        assert_eq!(split_env_name("repo_path"), None);
+        assert_eq!(split_env_name("core_"), None);
+        assert_eq!(env_value("1234"), toml::Value::Integer(1234));
+        assert_eq!(
+            env_value(r#""1234""#),
+            toml::Value::String("1234".to_string())
+        );
+        assert_eq!(
+            env_value("~/.dev"),
+            toml::Value::String("~/.dev".to_string())
+        );
+    }
+
+    #[test]
+    fn test_profiles() {
+        let content = r#"
+            [core]
+            base_dir = "~/.dev"
+
+            [github]
+            username = "kitsuyui"
+            queries = ["user:kitsuyui"]
+
+            [profiles.work]
+            base_dir = "~/work"
+
+            [profiles.work.github]
+            queries = ["org:example"]
+            token_env = "GH_TOKEN_WORK"
+        "#;
+        let profile = |name: &str| vec![(PROFILE_ENV.to_string(), name.to_string())].into_iter();
+        let config = parse_config(content, profile("work")).unwrap();
+        assert_eq!(config.core.base_dir, "~/work");
+        assert_eq!(config.github.username.as_deref(), Some("kitsuyui"));
+        assert_eq!(config.github.queries, Some(vec!["org:example".to_string()]));
+        assert_eq!(config.github.token_env.as_deref(), Some("GH_TOKEN_WORK"));
+
+        let config = parse_config(content, profile("")).unwrap();
+        assert_eq!(config.core.base_dir, "~/.dev");
+        assert!(parse_config(content, profile("home")).is_err());
+
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            profile_arg(&args(&["mure", "--profile", "work", "list"])),
+            Some("work")
+        );
+        assert_eq!(
+            profile_arg(&args(&["mure", "list", "--profile=work"])),
+            Some("work")
+        );
+        assert_eq!(
+            profile_arg(&args(&["mure", "exec", "--", "--profile", "x"])),
+            None
+        );
+        assert_eq!(profile_arg(&args(&["mure", "list"])), None);
+    }
+
+    #[test]
+    fn test_username() {
+        // only base_dir is required
+        let config: Config = toml::from_str("[core]\nbase_dir = \"~/.dev\"\n").unwrap();
+        let detected = || Ok("detected".to_string());
+        assert_eq!(config.github.username_or(detected).unwrap(), "detected");
+
+        let mut github = GitHub {
+            username: Some("".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(github.username_or(detected).unwrap(), "detected");
+        github.username = Some("kitsuyui".to_string());
+        assert_eq!(github.username_or(detected).unwrap(), "kitsuyui");
+        assert_eq!(
+            github.get_queries().unwrap(),
+            vec!["user:kitsuyui is:public fork:false archived:false"]
+        );
     }
 }
@@ -0,0 +1,407 @@
+//! Credentials of git talking to remotes, configured per host in `[hosts]`
+//!
+//! A host can have
+//!
+//! - `identity_file`: private key used instead of the keys of ssh-agent and `~/.ssh/config`
+//! - `https_token = true`: the token of the host (`GH_TOKEN` or `GH_ENTERPRISE_TOKEN`) as the password of HTTPS remotes
+//!
+//! The git command runs ssh by itself, so ssh-agent and `~/.ssh/config` work as they are.
+//! The key is passed by `GIT_SSH_COMMAND` and the token to the credential helper by an environment variable,
+//! never as an argument.
+//!
+//! Operations done by git2 get the same credentials from [`remote_callbacks`]:
+//! ssh-agent and the identity files of `~/.ssh/config` for ssh, and the token or the credential helpers for HTTPS.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+use git2::{Cred, CredentialType, RemoteCallbacks, Repository};
+
+use crate::config::Config;
+use crate::github::token::get_github_token_for;
+
+static HOSTS: OnceLock<BTreeMap<String, HostCredentials>> = OnceLock::new();
+
+/// Environment variable to pass the token to the credential helper
+const TOKEN_ENV: &str = "MURE_GIT_TOKEN";
+
+/// Git subcommands which may talk to remotes
+const NETWORK_COMMANDS: [&str; 6] = ["clone", "fetch", "pull", "push", "ls-remote", "submodule"];
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HostCredentials {
+    pub identity_file: Option<String>,
+    pub https_token: bool,
+}
+
+/// Take the credentials of `[hosts]`. Only the first call takes effect.
+pub fn init(config: &Config) {
+    let hosts = config
+        .hosts
+        .iter()
+        .flatten()
+        .map(|(domain, host)| {
+            let credentials = HostCredentials {
+                identity_file: host.identity_file.clone(),
+                https_token: host.https_token.unwrap_or(false),
+            };
+            (domain.clone(), credentials)
+        })
+        .filter(|(_, credentials)| *credentials != HostCredentials::default())
+        .collect();
+    let _ = HOSTS.set(hosts);
+}
+
+/// Add the credentials of the remote host to the git command.
+/// Must be called before the arguments of the subcommand are added.
+pub fn apply(command: &mut Command, args: &[&str], workdir: &Path) {
+    let Some(hosts) = HOSTS.get().filter(|hosts| !hosts.is_empty()) else {
+        return;
+    };
+    if !args
+        .first()
+        .is_some_and(|subcommand| NETWORK_COMMANDS.contains(subcommand))
+    {
+        return;
+    }
+    let Some(host) = remote_host(args, workdir) else {
+        return;
+    };
+    if let Some(credentials) = hosts.get(&host) {
+        apply_credentials(command, &host, credentials, |host| {
+            get_github_token_for(host).ok()
+        });
+    }
+}
+
+fn apply_credentials(
+    command: &mut Command,
+    host: &str,
+    credentials: &HostCredentials,
+    token_for: impl Fn(&str) -> Option<String>,
+) {
+    if let Some(identity_file) = &credentials.identity_file {
+        command.env("GIT_SSH_COMMAND", ssh_command(identity_file));
+    }
+    if !credentials.https_token {
+        return;
+    }
+    let Some(token) = token_for(host) else {
+        tracing::warn!(host, "https_token is set but no token is found");
+        return;
+    };
+    // the empty helper resets helpers configured in gitconfig for the host
+    let key = format!("credential.https://{host}.helper");
+    command
+        .env(TOKEN_ENV, token)
+        .arg("-c")
+        .arg(format!("{key}="))
+        .arg("-c")
+        .arg(format!(
+            "{key}=!f() {{ test \"$1\" = get && echo username=x-access-token && echo \"password=${TOKEN_ENV}\"; }}; f"
+        ));
+}
+
+/// Way of ssh authentication tried by git2, in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SshAttempt {
+    Agent,
+    Key(PathBuf),
+}
+
+/// Callbacks of remote operations done by git2 with the credentials of the host of the url.
+/// libgit2 asks again after a rejected credential, so each call returns the next one to try.
+pub fn remote_callbacks(url: &str) -> RemoteCallbacks<'static> {
+    let host = url_host(url).unwrap_or_default();
+    let credentials = HOSTS
+        .get()
+        .and_then(|hosts| hosts.get(&host))
+        .cloned()
+        .unwrap_or_default();
+    let ssh_config = dirs::home_dir()
+        .and_then(|home| std::fs::read_to_string(home.join(".ssh").join("config")).ok())
+        .unwrap_or_default();
+    let ssh_attempts = ssh_attempts(&credentials, &ssh_config, &host);
+    let mut ssh_tried = 0;
+    let mut https_tried = false;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        let username = username.unwrap_or("git");
+        if allowed.contains(CredentialType::SSH_KEY) {
+            let attempt = ssh_attempts.get(ssh_tried).cloned();
+            ssh_tried += 1;
+            return match attempt {
+                Some(SshAttempt::Agent) => Cred::ssh_key_from_agent(username),
+                Some(SshAttempt::Key(path)) => Cred::ssh_key(username, None, &path, None),
+                None => Err(git2::Error::from_str(&format!(
+                    "no ssh key is accepted by {host}"
+                ))),
+            };
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if std::mem::replace(&mut https_tried, true) {
+                return Err(git2::Error::from_str(&format!(
+                    "authentication to {host} failed"
+                )));
+            }
+            let token = credentials
+                .https_token
+                .then(|| get_github_token_for(&host).ok())
+                .flatten();
+            return match token {
+                Some(token) => Cred::userpass_plaintext("x-access-token", &token),
+                None => {
+                    Cred::credential_helper(&git2::Config::open_default()?, url, Some(username))
+                }
+            };
+        }
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username);
+        }
+        Cred::default()
+    });
+    callbacks
+}
+
+/// Only the identity_file of the host if set, like `IdentitiesOnly`.
+/// Otherwise ssh-agent, the identity files of `~/.ssh/config` for the host and the default keys.
+fn ssh_attempts(credentials: &HostCredentials, ssh_config: &str, host: &str) -> Vec<SshAttempt> {
+    if let Some(identity_file) = &credentials.identity_file {
+        let path = PathBuf::from(shellexpand::tilde(identity_file).to_string());
+        return vec![SshAttempt::Key(path)];
+    }
+    let mut attempts = vec![SshAttempt::Agent];
+    let default_keys = dirs::home_dir()
+        .map(|home| {
+            ["id_ed25519", "id_ecdsa", "id_rsa"]
+                .map(|name| home.join(".ssh").join(name))
+                .to_vec()
+        })
+        .unwrap_or_default();
+    for key in ssh_config_identity_files(ssh_config, host)
+        .into_iter()
+        .chain(default_keys)
+    {
+        if key.is_file() && !attempts.contains(&SshAttempt::Key(key.clone())) {
+            attempts.push(SshAttempt::Key(key));
+        }
+    }
+    attempts
+}
+
+/// `IdentityFile`s of the host in the content of `~/.ssh/config`.
+/// Only `Host` sections are understood. `Match` sections are skipped.
+fn ssh_config_identity_files(content: &str, host: &str) -> Vec<PathBuf> {
+    // options before the first section apply to all hosts
+    let mut matched = true;
+    let mut files = vec![];
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(|c: char| c.is_whitespace() || c == '=') else {
+            continue;
+        };
+        let value = value
+            .trim_start_matches(|c: char| c.is_whitespace() || c == '=')
+            .trim()
+            .trim_matches('"');
+        match key.to_lowercase().as_str() {
+            "host" => matched = host_matches(value, host),
+            "match" => matched = false,
+            "identityfile" if matched => {
+                files.push(PathBuf::from(shellexpand::tilde(value).to_string()))
+            }
+            _ => (),
+        }
+    }
+    files
+}
+
+/// Patterns of `Host` such as `*.corp !secret.corp`
+fn host_matches(patterns: &str, host: &str) -> bool {
+    let matches = |pattern: &str| glob::Pattern::new(pattern).is_ok_and(|p| p.matches(host));
+    let mut matched = false;
+    for pattern in patterns.split_whitespace() {
+        match pattern.strip_prefix('!') {
+            Some(negated) if matches(negated) => return false,
+            Some(_) => (),
+            None => matched |= matches(pattern),
+        }
+    }
+    matched
+}
+
+/// ssh using only the key, not the other keys of ssh-agent which the server may try first
+fn ssh_command(identity_file: &str) -> String {
+    let path = shellexpand::tilde(identity_file).replace('\'', r"'\''");
+    format!("ssh -i '{path}' -o IdentitiesOnly=yes")
+}
+
+/// Host of the url in the arguments (clone), otherwise of the remote of the repository
+fn remote_host(args: &[&str], workdir: &Path) -> Option<String> {
+    if let Some(host) = args.iter().find_map(|arg| url_host(arg)) {
+        return Some(host);
+    }
+    let repo = Repository::open(workdir).ok()?;
+    let remotes = repo.remotes().ok()?;
+    let name = args
+        .iter()
+        .find(|arg| remotes.iter().flatten().any(|remote| remote == **arg))
+        .copied()
+        .unwrap_or("origin");
+    let remote = repo.find_remote(name).ok()?;
+    url_host(remote.url()?)
+}
+
+/// Host of `scheme://[user@]host[:port]/path` or scp-like `user@host:path`
+fn url_host(url: &str) -> Option<String> {
+    let authority = match url.split_once("://") {
+        Some((_, rest)) => rest.split('/').next()?,
+        None => {
+            let (user_host, _) = url.split_once(':')?;
+            user_host.contains('@').then_some(user_host)?
+        }
+    };
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://github.com/kitsuyui/mure"),
+            Some("github.com".to_string())
+        );
+        assert_eq!(
+            url_host("ssh://git@ghe.corp:2222/owner/repo.git"),
+            Some("ghe.corp".to_string())
+        );
+        assert_eq!(
+            url_host("git@github.com:kitsuyui/mure.git"),
+            Some("github.com".to_string())
+        );
+        assert_eq!(url_host("/tmp/mure"), None);
+        assert_eq!(url_host("--depth=1"), None);
+        assert_eq!(url_host("origin"), None);
+    }
+
+    #[test]
+    fn test_apply_credentials() {
+        let credentials = HostCredentials {
+            identity_file: Some("/keys/it's".to_string()),
+            https_token: true,
+        };
+        let mut command = Command::new("git");
+        apply_credentials(&mut command, "ghe.corp", &credentials, |host| {
+            Some(format!("token-of-{host}"))
+        });
+        let envs = command
+            .get_envs()
+            .map(|(key, value)| (key.to_owned(), value.map(|v| v.to_owned())))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            envs[std::ffi::OsStr::new("GIT_SSH_COMMAND")],
+            Some(r"ssh -i '/keys/it'\''s' -o IdentitiesOnly=yes".into())
+        );
+        assert_eq!(
+            envs[std::ffi::OsStr::new(TOKEN_ENV)],
+            Some("token-of-ghe.corp".into())
+        );
+        let args = command.get_args().collect::<Vec<_>>();
+        assert_eq!(args[1], "credential.https://ghe.corp.helper=");
+        // the token is never in the arguments
+        assert!(args
+            .iter()
+            .all(|arg| !arg.to_string_lossy().contains("token-of")));
+
+        // without a token, only ssh is configured
+        let mut command = Command::new("git");
+        apply_credentials(&mut command, "ghe.corp", &credentials, |_| None);
+        assert_eq!(command.get_args().count(), 0);
+        assert_eq!(command.get_envs().count(), 1);
+    }
+
+    #[test]
+    fn test_ssh_config_identity_files() {
+        let content = r#"
+            IdentityFile /keys/all
+            Host github.com gist.github.com
+              IdentityFile /keys/github
+            Host *.corp !secret.corp
+              User git
+              IdentityFile="/keys/corp"
+            Match host ghe.corp
+              IdentityFile /keys/match
+        "#;
+        let files = |host: &str| {
+            ssh_config_identity_files(content, host)
+                .into_iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(files("github.com"), vec!["/keys/all", "/keys/github"]);
+        assert_eq!(files("ghe.corp"), vec!["/keys/all", "/keys/corp"]);
+        assert_eq!(files("secret.corp"), vec!["/keys/all"]);
+    }
+
+    #[test]
+    fn test_ssh_attempts() {
+        let temp_dir = mktemp::Temp::new_dir().unwrap();
+        let key = temp_dir.join("id_corp");
+        std::fs::write(&key, "").unwrap();
+        let ssh_config = format!(
+            "Host ghe.corp\n  IdentityFile {}\n  IdentityFile /no/such/key\n",
+            key.display()
+        );
+        let attempts = ssh_attempts(&HostCredentials::default(), &ssh_config, "ghe.corp");
+        assert_eq!(attempts[0], SshAttempt::Agent);
+        assert_eq!(attempts[1], SshAttempt::Key(key.clone()));
+        assert!(!attempts.contains(&SshAttempt::Key(PathBuf::from("/no/such/key"))));
+
+        // only the key of the host, like IdentitiesOnly
+        let credentials = HostCredentials {
+            identity_file: Some("/keys/deploy".to_string()),
+            https_token: false,
+        };
+        assert_eq!(
+            ssh_attempts(&credentials, &ssh_config, "ghe.corp"),
+            vec![SshAttempt::Key(PathBuf::from("/keys/deploy"))]
+        );
+    }
+
+    #[test]
+    fn test_credential_helper() {
+        let mut command = Command::new("git");
+        let credentials = HostCredentials {
+            identity_file: None,
+            https_token: true,
+        };
+        apply_credentials(&mut command, "example.com", &credentials, |_| {
+            Some("secret".to_string())
+        });
+        let output = command
+            .args(["credential", "fill"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                use std::io::Write;
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(b"protocol=https\nhost=example.com\n\n")?;
+                }
+                child.wait_with_output()
+            })
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("username=x-access-token\n"));
+        assert!(stdout.contains("password=secret\n"));
+    }
+}
@@ -0,0 +1,149 @@
+//! Structured event stream for automation
+//!
+//! Long-running commands emit one JSON object per line instead of prose
+//! so that wrappers, GUIs or editors can render their own progress.
+//! Events are a stable interface: fields are only added, never renamed or removed.
+
+use serde_derive::Serialize;
+
+use crate::app::refresh::{Reason, RefreshReport};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    JsonLines,
+}
+
+impl OutputFormat {
+    pub fn from_bool(json_lines: bool) -> Self {
+        match json_lines {
+            true => OutputFormat::JsonLines,
+            false => OutputFormat::Text,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Started {
+        repository: String,
+    },
+    Progress {
+        repository: String,
+        message: String,
+    },
+    Finished {
+        repository: String,
+        /// `updated`, `skipped` or `failed`
+        status: String,
+        /// message for humans, which may change or be localized
+        message: String,
+        /// why nothing was done if skipped
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<Reason>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        report: Option<RefreshReport>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    Error {
+        message: String,
+    },
+    /// at the end of `refresh --all`
+    Summary {
+        updated: usize,
+        skipped: usize,
+        failed: usize,
+        /// not started because of `--fail-fast`
+        cancelled: usize,
+    },
+}
+
+impl Event {
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn emit(&self) {
+        println!("{}", self.to_json_line());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::refresh::PullStatus;
+
+    #[test]
+    fn test_to_json_line() {
+        let event = Event::Started {
+            repository: "mure".to_string(),
+        };
+        assert_eq!(
+            event.to_json_line(),
+            r#"{"event":"started","repository":"mure"}"#
+        );
+
+        let event = Event::Finished {
+            repository: "mure".to_string(),
+            status: "updated".to_string(),
+            message: "Already up to date".to_string(),
+            reason: None,
+            report: None,
+            error: None,
+        };
+        assert_eq!(
+            event.to_json_line(),
+            r#"{"event":"finished","repository":"mure","status":"updated","message":"Already up to date"}"#
+        );
+
+        // typed results for automation
+        let event = Event::Finished {
+            repository: "mure".to_string(),
+            status: "updated".to_string(),
+            message: "Fast-forwarded\nDeleted branch feature".to_string(),
+            reason: None,
+            report: Some(RefreshReport {
+                switched_to: Some("main".to_string()),
+                pulled: PullStatus::FastForwarded,
+                deleted_branches: vec!["feature".to_string()],
+                to_push: 0,
+            }),
+            error: None,
+        };
+        assert_eq!(
+            event.to_json_line(),
+            r#"{"event":"finished","repository":"mure","status":"updated","message":"Fast-forwarded\nDeleted branch feature","report":{"switched_to":"main","pulled":"fast_forwarded","deleted_branches":["feature"],"to_push":0}}"#
+        );
+        let event = Event::Finished {
+            repository: "mure".to_string(),
+            status: "skipped".to_string(),
+            message: "no remote".to_string(),
+            reason: Some(Reason::NoRemote),
+            report: None,
+            error: None,
+        };
+        assert_eq!(
+            event.to_json_line(),
+            r#"{"event":"finished","repository":"mure","status":"skipped","message":"no remote","reason":"no_remote"}"#
+        );
+
+        let event = Event::Summary {
+            updated: 2,
+            skipped: 1,
+            failed: 0,
+            cancelled: 0,
+        };
+        assert_eq!(
+            event.to_json_line(),
+            r#"{"event":"summary","updated":2,"skipped":1,"failed":0,"cancelled":0}"#
+        );
+    }
+
+    #[test]
+    fn test_output_format() {
+        assert_eq!(OutputFormat::from_bool(true), OutputFormat::JsonLines);
+        assert_eq!(OutputFormat::from_bool(false), OutputFormat::Text);
+    }
+}
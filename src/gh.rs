@@ -1,34 +1,286 @@
+//! gh (GitHub CLI) with typed results
+//!
+//! Operations which the GitHub API token alone can't do, or which need to follow
+//! the repository and the login gh is configured with.
+use crate::misc::command_wrapper::RawCommandOutput;
 use crate::mure_error::Error;
-use std::{path::PathBuf, process::Command};
+use crate::provider::Cli;
+use serde_derive::Deserialize;
+use std::path::Path;
+use std::process::Command;
 
-pub fn get_default_branch(workdir: &PathBuf) -> Result<String, Error> {
-    let result = match Command::new("gh")
-        .args([
+/// Client running gh. Whether gh is installed and authenticated is detected once per run
+/// (see `provider::Cli`), so PATH is not probed for each operation.
+#[derive(Debug, Clone, Copy)]
+pub struct GhClient {
+    cli: &'static Cli,
+}
+
+/// Repository as `gh repo view` shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoView {
+    /// e.g. `kitsuyui/mure`
+    pub name_with_owner: String,
+    /// `PUBLIC`, `PRIVATE` or `INTERNAL`
+    pub visibility: String,
+    pub topics: Vec<String>,
+    /// None for empty repositories
+    pub default_branch: Option<String>,
+    pub is_archived: bool,
+}
+
+/// Open pull request as `gh pr list` shows
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub head_ref_name: String,
+    pub base_ref_name: String,
+    pub url: String,
+}
+
+/// Account gh is logged in with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthAccount {
+    pub host: String,
+    pub login: String,
+    /// Empty for fine-grained tokens, which have no scopes
+    pub scopes: Vec<String>,
+}
+
+impl AuthAccount {
+    /// e.g. `kitsuyui on github.com (gist, repo)`
+    pub fn describe(&self) -> String {
+        if self.scopes.is_empty() {
+            return format!("{} on {}", self.login, self.host);
+        }
+        format!(
+            "{} on {} ({})",
+            self.login,
+            self.host,
+            self.scopes.join(", ")
+        )
+    }
+}
+
+impl Default for GhClient {
+    fn default() -> Self {
+        GhClient::new()
+    }
+}
+
+impl GhClient {
+    pub fn new() -> Self {
+        GhClient { cli: Cli::gh() }
+    }
+
+    /// Default branch of the repository checked out in `repo_path`
+    pub fn default_branch(&self, repo_path: &Path) -> Result<String, Error> {
+        self.cli
+            .require("Resolving the default branch not guessed locally")?;
+        let raw = run(
+            &[
+                "repo",
+                "view",
+                "--json",
+                "defaultBranchRef",
+                "-t",
+                "{{.defaultBranchRef.name}}",
+            ],
+            Some(repo_path),
+        )?;
+        Ok(raw.stdout.trim().to_string())
+    }
+
+    /// `repo` is `[HOST/]OWNER/REPO`
+    pub fn repo_view(&self, repo: &str) -> Result<RepoView, Error> {
+        self.cli.require("Viewing the repository on GitHub")?;
+        let raw = run(
+            &[
+                "repo",
+                "view",
+                repo,
+                "--json",
+                "nameWithOwner,visibility,repositoryTopics,defaultBranchRef,isArchived",
+            ],
+            None,
+        )?;
+        parse_repo_view(&raw.stdout)
+    }
+
+    /// Open pull requests of the repository checked out in `repo_path`
+    pub fn pr_list(&self, repo_path: &Path) -> Result<Vec<PullRequest>, Error> {
+        self.cli.require("Listing pull requests")?;
+        let raw = run(
+            &[
+                "pr",
+                "list",
+                "--state",
+                "open",
+                "--json",
+                "number,title,headRefName,baseRefName,url",
+            ],
+            Some(repo_path),
+        )?;
+        Ok(serde_json::from_str(&raw.stdout)?)
+    }
+
+    /// Accounts gh is logged in with. Unlike the other operations, gh doesn't have to be authenticated.
+    pub fn auth_status(&self) -> Result<Vec<AuthAccount>, Error> {
+        // it exits with 1 if any of the hosts fails, so the accounts are read regardless
+        let raw = spawn(&["auth", "status"], None)?;
+        // older gh prints the status to stderr
+        Ok(parse_auth_status(&format!(
+            "{}\n{}",
+            raw.stdout, raw.stderr
+        )))
+    }
+
+    /// Login name of the authenticated user of gh
+    pub fn login(&self) -> Result<String, Error> {
+        self.cli.require("Detecting github.username")?;
+        let raw = run(&["api", "user", "--jq", ".login"], None)?;
+        Ok(raw.stdout.trim().to_string())
+    }
+
+    /// Archive or unarchive the repository on GitHub. `repo` is `[HOST/]OWNER/REPO`.
+    pub fn set_archived(&self, repo: &str, archived: bool) -> Result<(), Error> {
+        let subcommand = if archived { "archive" } else { "unarchive" };
+        self.cli.require(&format!("{subcommand} --remote"))?;
+        run(&["repo", subcommand, repo, "--yes"], None)?;
+        Ok(())
+    }
+
+    /// Create the repository on GitHub, from the template repository if given. `repo` is `OWNER/REPO`.
+    pub fn create_repository(
+        &self,
+        repo: &str,
+        template: Option<&str>,
+        public: bool,
+    ) -> Result<(), Error> {
+        self.cli.require("mure create")?;
+        let mut args = vec!["repo", "create", repo, visibility_flag(public)];
+        if let Some(template) = template {
+            args.extend(["--template", template]);
+        }
+        run(&args, None)?;
+        Ok(())
+    }
+
+    /// Create the repository on GitHub from the local repository and push it as origin
+    pub fn publish_repository(&self, repo: &str, source: &Path, public: bool) -> Result<(), Error> {
+        self.cli.require("mure new --github")?;
+        let source = format!("--source={}", source.display());
+        let args = [
             "repo",
-            "view",
-            "--json",
-            "defaultBranchRef",
-            "-t",
-            "{{.defaultBranchRef.name}}",
-        ])
-        .current_dir(workdir)
-        .output()
-    {
-        Ok(output) => output,
-        Err(e) => return Err(Error::GHCommandError(e.to_string())),
-    };
-
-    if !result.status.success() {
-        let Ok(message) = String::from_utf8(result.stderr) else {
-            return Err(Error::from_str("failed to get default branch"));
-        };
-        return Err(Error::from_str(&message));
-    }
-
-    let Ok(message) = String::from_utf8(result.stdout) else {
-        return Err(Error::from_str("failed to get default branch"));
-    };
-    Ok(message)
+            "create",
+            repo,
+            visibility_flag(public),
+            &source,
+            "--push",
+        ];
+        run(&args, None)?;
+        Ok(())
+    }
+}
+
+fn visibility_flag(public: bool) -> &'static str {
+    if public {
+        "--public"
+    } else {
+        "--private"
+    }
+}
+
+/// Run gh and fail with its stderr unless it succeeds
+fn run(args: &[&str], workdir: Option<&Path>) -> Result<RawCommandOutput, Error> {
+    let raw = spawn(args, workdir)?;
+    if !raw.success() {
+        return Err(Error::from_str(&raw.stderr));
+    }
+    Ok(raw)
+}
+
+fn spawn(args: &[&str], workdir: Option<&Path>) -> Result<RawCommandOutput, Error> {
+    let mut command = Command::new("gh");
+    command.args(args);
+    if let Some(workdir) = workdir {
+        command.current_dir(workdir);
+    }
+    match command.output() {
+        Ok(output) => Ok(RawCommandOutput::from(output)),
+        Err(e) => Err(Error::GHCommandError(e.to_string())),
+    }
+}
+
+fn parse_repo_view(json: &str) -> Result<RepoView, Error> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct View {
+        name_with_owner: String,
+        visibility: String,
+        repository_topics: Option<Vec<Topic>>,
+        default_branch_ref: Option<BranchRef>,
+        is_archived: bool,
+    }
+    #[derive(Deserialize)]
+    struct Topic {
+        name: String,
+    }
+    #[derive(Deserialize)]
+    struct BranchRef {
+        name: String,
+    }
+    let view: View = serde_json::from_str(json)?;
+    Ok(RepoView {
+        name_with_owner: view.name_with_owner,
+        visibility: view.visibility,
+        topics: view
+            .repository_topics
+            .unwrap_or_default()
+            .into_iter()
+            .map(|topic| topic.name)
+            .collect(),
+        // an empty repository has an empty name
+        default_branch: view
+            .default_branch_ref
+            .map(|branch| branch.name)
+            .filter(|name| !name.is_empty()),
+        is_archived: view.is_archived,
+    })
+}
+
+/// Accounts in the output like below. Older gh says `as kitsuyui` instead of `account kitsuyui`.
+///
+/// ```text
+/// github.com
+///   ✓ Logged in to github.com account kitsuyui (keyring)
+///   - Token scopes: 'gist', 'read:org', 'repo'
+/// ```
+fn parse_auth_status(output: &str) -> Vec<AuthAccount> {
+    let mut accounts: Vec<AuthAccount> = vec![];
+    for line in output.lines() {
+        if let Some((_, rest)) = line.split_once("Logged in to ") {
+            let words = rest.split_whitespace().collect::<Vec<_>>();
+            if let [host, "account" | "as", login, ..] = words[..] {
+                accounts.push(AuthAccount {
+                    host: host.to_string(),
+                    login: login.to_string(),
+                    scopes: vec![],
+                });
+            }
+        } else if let Some((_, scopes)) = line.split_once("Token scopes:") {
+            if let Some(account) = accounts.last_mut() {
+                account.scopes = scopes
+                    .split(',')
+                    .map(|scope| scope.trim().trim_matches('\'').to_string())
+                    .filter(|scope| !scope.is_empty() && scope != "none")
+                    .collect();
+            }
+        }
+    }
+    accounts
 }
 
 #[cfg(test)]
@@ -39,8 +291,13 @@ mod tests {
     use assay::assay;
 
     #[test]
-    fn test_get_default_branch() {
-        assert_eq!(get_default_branch(&current_dir().unwrap()).unwrap(), "main");
+    fn test_default_branch() {
+        assert_eq!(
+            GhClient::new()
+                .default_branch(&current_dir().unwrap())
+                .unwrap(),
+            "main"
+        );
     }
 
     #[assay(
@@ -49,12 +306,95 @@ mod tests {
         ]
       )]
     fn test_gh_is_not_installed() {
-        let result = get_default_branch(&current_dir().unwrap());
+        let gh = GhClient::new();
+        let result = gh.default_branch(&current_dir().unwrap());
         assert!(result.is_err());
         assert_eq!(
             result.err().unwrap().to_string(),
-            "No such file or directory (os error 2)"
+            "Resolving the default branch not guessed locally requires gh (GitHub CLI) but it is not found in PATH. Install it from https://cli.github.com/"
+        );
+        assert_eq!(
+            gh.set_archived("kitsuyui/mure", true)
+                .unwrap_err()
+                .to_string(),
+            "archive --remote requires gh (GitHub CLI) but it is not found in PATH. Install it from https://cli.github.com/"
+        );
+        assert!(gh.repo_view("kitsuyui/mure").is_err());
+        assert!(gh.auth_status().is_err());
+    }
+
+    #[test]
+    fn test_parse_repo_view() {
+        assert_eq!(
+            parse_repo_view(
+                r#"{"nameWithOwner":"kitsuyui/mure","visibility":"PUBLIC","repositoryTopics":[{"name":"rust"},{"name":"cli"}],"defaultBranchRef":{"name":"main"},"isArchived":false}"#
+            )
+            .unwrap(),
+            RepoView {
+                name_with_owner: "kitsuyui/mure".to_string(),
+                visibility: "PUBLIC".to_string(),
+                topics: vec!["rust".to_string(), "cli".to_string()],
+                default_branch: Some("main".to_string()),
+                is_archived: false,
+            }
+        );
+        let view = parse_repo_view(
+            r#"{"nameWithOwner":"kitsuyui/empty","visibility":"PRIVATE","repositoryTopics":null,"defaultBranchRef":{"name":""},"isArchived":true}"#,
+        )
+        .unwrap();
+        assert!(view.topics.is_empty());
+        assert_eq!(view.default_branch, None);
+        assert!(view.is_archived);
+        assert!(parse_repo_view("{}").is_err());
+    }
+
+    #[test]
+    fn test_parse_pull_requests() {
+        let pull_requests: Vec<PullRequest> = serde_json::from_str(
+            r#"[{"number":12,"title":"Add gist","headRefName":"gist","baseRefName":"main","url":"https://github.com/kitsuyui/mure/pull/12"}]"#,
+        )
+        .unwrap();
+        assert_eq!(pull_requests[0].number, 12);
+        assert_eq!(pull_requests[0].head_ref_name, "gist");
+        assert_eq!(pull_requests[0].base_ref_name, "main");
+    }
+
+    #[test]
+    fn test_parse_auth_status() {
+        let accounts = parse_auth_status(
+            "github.com\n  \
+             ✓ Logged in to github.com account kitsuyui (keyring)\n  \
+             - Active account: true\n  \
+             - Token scopes: 'gist', 'read:org', 'repo'\n\
+             ghe.corp\n  \
+             ✓ Logged in to ghe.corp as someone (/home/someone/.config/gh/hosts.yml)\n  \
+             ✓ Token scopes: none\n",
+        );
+        assert_eq!(
+            accounts,
+            vec![
+                AuthAccount {
+                    host: "github.com".to_string(),
+                    login: "kitsuyui".to_string(),
+                    scopes: vec![
+                        "gist".to_string(),
+                        "read:org".to_string(),
+                        "repo".to_string()
+                    ],
+                },
+                AuthAccount {
+                    host: "ghe.corp".to_string(),
+                    login: "someone".to_string(),
+                    scopes: vec![],
+                },
+            ]
+        );
+        assert_eq!(
+            accounts[0].describe(),
+            "kitsuyui on github.com (gist, read:org, repo)"
         );
+        assert_eq!(accounts[1].describe(), "someone on ghe.corp");
+        assert!(parse_auth_status("You are not logged into any GitHub hosts.").is_empty());
     }
 
     #[assay(
@@ -63,7 +403,7 @@ mod tests {
         ]
       )]
     fn test_gh_token_is_not_set() {
-        let result = get_default_branch(&current_dir().unwrap());
+        let result = GhClient::new().default_branch(&current_dir().unwrap());
         assert!(result.is_err());
     }
 }
@@ -1,7 +1,49 @@
+use crate::credentials;
 use crate::misc::command_wrapper::{CommandOutput as GitCommandOutput, Error, RawCommandOutput};
 use crate::mure_error;
+use crate::verbosity::Verbosity;
 use git2::{BranchType, Repository};
-use std::{path::Path, process::Command, string::FromUtf8Error};
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+    process::Command,
+    string::FromUtf8Error,
+};
+
+thread_local! {
+    static DRY_RUN: Cell<bool> = const { Cell::new(false) };
+    static SKIP_HOOKS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Dry-run mode of mutating git commands of RepositorySupport.
+/// While enabled, mutating commands are logged to stderr instead of being executed
+/// and treated as succeeded with empty output.
+/// The mode is per thread. Threads spawned for git commands need to enable it by themselves.
+pub struct DryRun;
+
+impl DryRun {
+    pub fn set(enabled: bool) {
+        DRY_RUN.with(|dry_run| dry_run.set(enabled));
+    }
+
+    pub fn is_enabled() -> bool {
+        DRY_RUN.with(|dry_run| dry_run.get())
+    }
+}
+
+/// Git commands of RepositorySupport run without hooks (e.g. post-merge) while enabled,
+/// by overriding core.hooksPath. The mode is per thread like DryRun.
+pub struct SkipHooks;
+
+impl SkipHooks {
+    pub fn set(enabled: bool) {
+        SKIP_HOOKS.with(|skip_hooks| skip_hooks.set(enabled));
+    }
+
+    pub fn is_enabled() -> bool {
+        SKIP_HOOKS.with(|skip_hooks| skip_hooks.get())
+    }
+}
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum PullFastForwardStatus {
@@ -10,24 +52,136 @@ pub enum PullFastForwardStatus {
     Abort,
 }
 
+/// Signature status of a commit as reported by `git log --format=%G?`.
+/// This covers both GPG and SSH signatures.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SignatureStatus {
+    Good,
+    Bad,
+    UnknownValidity,
+    Expired,
+    ExpiredKey,
+    RevokedKey,
+    CannotCheck,
+    Unsigned,
+}
+
+impl SignatureStatus {
+    fn from_code(code: &str) -> Self {
+        match code.trim() {
+            "G" => SignatureStatus::Good,
+            "B" => SignatureStatus::Bad,
+            "U" => SignatureStatus::UnknownValidity,
+            "X" => SignatureStatus::Expired,
+            "Y" => SignatureStatus::ExpiredKey,
+            "R" => SignatureStatus::RevokedKey,
+            "E" => SignatureStatus::CannotCheck,
+            _ => SignatureStatus::Unsigned,
+        }
+    }
+
+    /// A commit is regarded as signed when it has a valid signature.
+    /// The trust level of the key is not considered here.
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            SignatureStatus::Good | SignatureStatus::UnknownValidity
+        )
+    }
+}
+
+/// Entry of the stash list
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Stash {
+    /// N of stash@{N}
+    pub index: usize,
+    pub message: String,
+    /// seconds since the epoch
+    pub time: i64,
+}
+
 pub trait RepositorySupport {
     fn merged_branches(&self) -> Result<GitCommandOutput<Vec<String>>, Error>;
+    /// Same as merged_branches but into the branch instead of HEAD
+    fn merged_branches_into(&self, into: &str) -> Result<GitCommandOutput<Vec<String>>, Error>;
     fn is_clean(&self) -> Result<bool, mure_error::Error>;
     fn clone(url: &str, into: &Path) -> Result<GitCommandOutput<()>, Error>;
+    fn clone_with_args(
+        url: &str,
+        into: &Path,
+        args: &[&str],
+    ) -> Result<GitCommandOutput<()>, Error>;
+    fn clone_mirror(url: &str, path: &Path) -> Result<GitCommandOutput<()>, Error>;
+    fn update_mirror(&self) -> Result<GitCommandOutput<()>, Error>;
     fn has_unsaved(&self) -> Result<bool, mure_error::Error>;
     fn is_remote_exists(&self) -> Result<bool, mure_error::Error>;
-    #[allow(dead_code)]
+    fn remote_default_branch(&self, remote: &str) -> Result<Option<String>, mure_error::Error>;
+    fn guess_default_branch(&self, remote: &str) -> Result<Option<String>, mure_error::Error>;
+    fn set_remote_head(&self, remote: &str, branch: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn query_remote_head(&self, remote: &str) -> Result<GitCommandOutput<Option<String>>, Error>;
     fn get_current_branch(&self) -> Result<String, mure_error::Error>;
     fn pull_fast_forwarded(
         &self,
         remote: &str,
         branch: &str,
     ) -> Result<GitCommandOutput<PullFastForwardStatus>, Error>;
+    fn update_branch_from_remote(
+        &self,
+        remote: &str,
+        branch: &str,
+    ) -> Result<GitCommandOutput<PullFastForwardStatus>, Error>;
     fn fetch_prune(&self) -> Result<GitCommandOutput<()>, Error>;
+    fn fetch_remote_prune(&self, remote: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn fetch_refspec(&self, remote: &str, refspec: &str) -> Result<(), mure_error::Error>;
+    fn ahead_behind(
+        &self,
+        local: &str,
+        upstream: &str,
+    ) -> Result<(usize, usize), mure_error::Error>;
+    fn has_unpushed_commits(&self, branch: &str) -> Result<bool, mure_error::Error>;
+    fn submodule_heads(&self) -> Result<Vec<(String, Option<String>)>, mure_error::Error>;
+    fn submodule_urls(&self) -> Result<Vec<String>, mure_error::Error>;
+    fn alternates(&self) -> Result<Vec<PathBuf>, mure_error::Error>;
+    fn update_submodules(&self) -> Result<GitCommandOutput<()>, Error>;
+    fn repack_all(&self) -> Result<GitCommandOutput<()>, Error>;
+    fn gc(&self, aggressive: bool) -> Result<GitCommandOutput<()>, Error>;
+    fn verify_head_signature(&self) -> Result<GitCommandOutput<SignatureStatus>, Error>;
+    fn commit_signatures(
+        &self,
+        revision: &str,
+        max_count: usize,
+    ) -> Result<GitCommandOutput<Vec<(String, SignatureStatus)>>, Error>;
     fn switch(&self, branch: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn switch_create(&self, branch: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn add_paths(&self, paths: &[&str]) -> Result<GitCommandOutput<()>, Error>;
+    fn commit_staged(&self, message: &str) -> Result<GitCommandOutput<()>, Error>;
     fn delete_branch(&self, branch: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn force_delete_branch(&self, branch: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn push_upstream(&self, remote: &str, branch: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn push(&self, remote: &str, branch: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn push_delete(
+        &self,
+        remote: &str,
+        branch: &str,
+        expected: &str,
+    ) -> Result<GitCommandOutput<()>, Error>;
+    fn create_branch(&self, branch: &str, start_point: &str)
+        -> Result<GitCommandOutput<()>, Error>;
+    fn rename_branch(&self, from: &str, to: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn merge_fast_forward(&self, revision: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn set_upstream(&self, branch: &str, upstream: &str) -> Result<GitCommandOutput<()>, Error>;
+    fn find_lost_branch_tip(&self, branch: &str) -> Result<Option<git2::Oid>, mure_error::Error>;
+    fn stashes(&mut self) -> Result<Vec<Stash>, mure_error::Error>;
+    fn drop_stash(&mut self, index: usize) -> Result<(), mure_error::Error>;
     fn command(&self, args: &[&str]) -> Result<RawCommandOutput, Error>;
+    /// Same as command but skipped in dry-run mode
+    fn mutating_command(&self, args: &[&str]) -> Result<RawCommandOutput, Error>;
     fn git_command_on_dir(args: &[&str], workdir: &Path) -> Result<RawCommandOutput, Error>;
+    /// Same as git_command_on_dir but skipped in dry-run mode
+    fn mutating_git_command_on_dir(
+        args: &[&str],
+        workdir: &Path,
+    ) -> Result<RawCommandOutput, Error>;
 }
 
 impl RepositorySupport for Repository {
@@ -45,12 +199,53 @@ impl RepositorySupport for Repository {
             interpreted_to: branches,
         })
     }
+    fn merged_branches_into(&self, into: &str) -> Result<GitCommandOutput<Vec<String>>, Error> {
+        // git for-each-ref --format=%(refname:short) refs/heads/**/* --merged $into
+        let raw = self.command(&[
+            "for-each-ref",
+            "--format=%(refname:short)",
+            "refs/heads/**/*",
+            "--merged",
+            into,
+        ])?;
+        let branches = split_lines(&raw.stdout);
+        Ok(GitCommandOutput {
+            raw,
+            interpreted_to: branches,
+        })
+    }
     fn is_clean(&self) -> Result<bool, mure_error::Error> {
         Ok(!self.has_unsaved()?)
     }
 
     fn clone(url: &str, into: &Path) -> Result<GitCommandOutput<()>, Error> {
-        Repository::git_command_on_dir(&["clone", url], into)?.try_into()
+        Repository::clone_with_args(url, into, &[])
+    }
+
+    /// git clone with extra options such as --recurse-submodules
+    fn clone_with_args(
+        url: &str,
+        into: &Path,
+        args: &[&str],
+    ) -> Result<GitCommandOutput<()>, Error> {
+        let mut command = vec!["clone"];
+        command.extend_from_slice(args);
+        command.push(url);
+        Repository::mutating_git_command_on_dir(&command, into)?.try_into()
+    }
+
+    /// git clone --mirror into the path. Its parent directory must exist.
+    fn clone_mirror(url: &str, path: &Path) -> Result<GitCommandOutput<()>, Error> {
+        let parent = path.parent().unwrap_or(Path::new("."));
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        Repository::mutating_git_command_on_dir(&["clone", "--mirror", url, &name], parent)?
+            .try_into()
+    }
+
+    /// Fetch all refs of the bare mirror. Refs deleted on the remote are pruned.
+    fn update_mirror(&self) -> Result<GitCommandOutput<()>, Error> {
+        Repository::mutating_git_command_on_dir(&["remote", "update", "--prune"], self.path())?
+            .try_into()
     }
 
     fn has_unsaved(&self) -> Result<bool, mure_error::Error> {
@@ -73,6 +268,68 @@ impl RepositorySupport for Repository {
         Ok(!self.remotes()?.is_empty())
     }
 
+    fn remote_default_branch(&self, remote: &str) -> Result<Option<String>, mure_error::Error> {
+        // refs/remotes/origin/HEAD -> refs/remotes/origin/main
+        let prefix = format!("refs/remotes/{remote}/");
+        let reference = match self.find_reference(&format!("{prefix}HEAD")) {
+            Ok(reference) => reference,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let Some(target) = reference.symbolic_target() else {
+            return Ok(None);
+        };
+        Ok(target
+            .strip_prefix(&prefix)
+            .map(|branch| branch.to_string()))
+    }
+
+    /// Guess the default branch of the remote without network.
+    /// {remote}/HEAD is used if it is known. Otherwise init.defaultBranch, main and master are
+    /// tried in this order and the first one existing as a branch of the remote is used.
+    fn guess_default_branch(&self, remote: &str) -> Result<Option<String>, mure_error::Error> {
+        if let Some(branch) = self.remote_default_branch(remote)? {
+            return Ok(Some(branch));
+        }
+        let init_default_branch = self
+            .config()
+            .and_then(|config| config.get_string("init.defaultBranch"))
+            .ok();
+        let candidates = init_default_branch
+            .into_iter()
+            .chain(["main".to_string(), "master".to_string()]);
+        for branch in candidates {
+            if self
+                .find_reference(&format!("refs/remotes/{remote}/{branch}"))
+                .is_ok()
+            {
+                return Ok(Some(branch));
+            }
+        }
+        Ok(None)
+    }
+
+    fn set_remote_head(&self, remote: &str, branch: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["remote", "set-head", remote, branch])?
+            .try_into()
+    }
+
+    /// Ask the remote which branch its HEAD points to now (network).
+    /// Unlike remote_default_branch, it isn't the one cached at clone.
+    fn query_remote_head(&self, remote: &str) -> Result<GitCommandOutput<Option<String>>, Error> {
+        // ref: refs/heads/main\tHEAD
+        let raw = self.command(&["ls-remote", "--symref", remote, "HEAD"])?;
+        let branch = raw.stdout.lines().find_map(|line| {
+            line.strip_prefix("ref: refs/heads/")?
+                .strip_suffix("\tHEAD")
+                .map(str::to_string)
+        });
+        Ok(GitCommandOutput {
+            raw,
+            interpreted_to: branch,
+        })
+    }
+
     fn get_current_branch(&self) -> Result<String, mure_error::Error> {
         if self.is_empty()? {
             return Err(mure_error::Error::from_str("repository is empty"));
@@ -94,7 +351,7 @@ impl RepositorySupport for Repository {
         remote: &str,
         branch: &str,
     ) -> Result<GitCommandOutput<PullFastForwardStatus>, Error> {
-        let raw = self.command(&["pull", "--ff-only", remote, branch])?;
+        let raw = self.mutating_command(&["pull", "--ff-only", remote, branch])?;
         let status = {
             let message = raw.stdout.as_str();
             if message.contains("Already up to date.") {
@@ -111,33 +368,355 @@ impl RepositorySupport for Repository {
         })
     }
 
+    fn update_branch_from_remote(
+        &self,
+        remote: &str,
+        branch: &str,
+    ) -> Result<GitCommandOutput<PullFastForwardStatus>, Error> {
+        // git fetch $remote $branch:$branch
+        // This fast-forwards the local branch without checking it out.
+        // Note: git refuses to fetch into the branch currently checked out.
+        let refname = format!("refs/heads/{branch}");
+        let before = self.refname_to_id(&refname).ok();
+        let raw = self.mutating_command(&["fetch", remote, &format!("{branch}:{branch}")])?;
+        let after = self.refname_to_id(&refname).ok();
+        let status = if !raw.success() {
+            PullFastForwardStatus::Abort
+        } else if before == after {
+            PullFastForwardStatus::AlreadyUpToDate
+        } else {
+            PullFastForwardStatus::FastForwarded
+        };
+        Ok(GitCommandOutput {
+            raw,
+            interpreted_to: status,
+        })
+    }
+
     fn fetch_prune(&self) -> Result<GitCommandOutput<()>, Error> {
-        self.command(&["fetch", "--prune"])?.try_into()
+        self.mutating_command(&["fetch", "--prune"])?.try_into()
+    }
+
+    fn fetch_remote_prune(&self, remote: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["fetch", "--prune", remote])?
+            .try_into()
+    }
+
+    /// e.g. refspec = refs/pull/1/head, which is fetched into FETCH_HEAD.
+    /// Fetched by git2 with the credentials of the host of the remote.
+    fn fetch_refspec(&self, remote: &str, refspec: &str) -> Result<(), mure_error::Error> {
+        if DryRun::is_enabled() {
+            eprintln!("(dry-run) fetch {remote} {refspec}");
+            return Ok(());
+        }
+        let mut remote = self.find_remote(remote)?;
+        let url = remote.url().unwrap_or_default().to_string();
+        let mut options = git2::FetchOptions::new();
+        options.remote_callbacks(credentials::remote_callbacks(&url));
+        remote.fetch(&[refspec], Some(&mut options), None)?;
+        Ok(())
+    }
+
+    fn verify_head_signature(&self) -> Result<GitCommandOutput<SignatureStatus>, Error> {
+        let raw = self.command(&["log", "-1", "--format=%G?", "HEAD"])?;
+        if !raw.success() {
+            return Err(Error::Raw(raw));
+        }
+        let status = SignatureStatus::from_code(&raw.stdout);
+        Ok(GitCommandOutput {
+            raw,
+            interpreted_to: status,
+        })
+    }
+
+    fn commit_signatures(
+        &self,
+        revision: &str,
+        max_count: usize,
+    ) -> Result<GitCommandOutput<Vec<(String, SignatureStatus)>>, Error> {
+        // git log --format="%H %G?" -n $max_count $revision
+        let raw = self.command(&[
+            "log",
+            "--format=%H %G?",
+            &format!("--max-count={max_count}"),
+            revision,
+        ])?;
+        if !raw.success() {
+            return Err(Error::Raw(raw));
+        }
+        let commits = split_lines(&raw.stdout)
+            .iter()
+            .filter_map(|line| {
+                let (commit_id, code) = line.split_once(' ')?;
+                Some((commit_id.to_string(), SignatureStatus::from_code(code)))
+            })
+            .collect();
+        Ok(GitCommandOutput {
+            raw,
+            interpreted_to: commits,
+        })
+    }
+
+    /// Number of commits (ahead, behind) of local compared to upstream.
+    /// Both are revisions such as "main", "origin/main" or "@{upstream}".
+    fn ahead_behind(
+        &self,
+        local: &str,
+        upstream: &str,
+    ) -> Result<(usize, usize), mure_error::Error> {
+        let local = self.revparse_single(local)?.peel_to_commit()?.id();
+        let upstream = self.revparse_single(upstream)?.peel_to_commit()?.id();
+        Ok(self.graph_ahead_behind(local, upstream)?)
+    }
+
+    /// Whether the local branch is ahead of its upstream. Branches without upstream are not.
+    fn has_unpushed_commits(&self, branch: &str) -> Result<bool, mure_error::Error> {
+        let local = self.find_branch(branch, BranchType::Local)?;
+        let Ok(upstream) = local.upstream() else {
+            return Ok(false);
+        };
+        let (Some(local), Some(upstream)) = (local.get().target(), upstream.get().target()) else {
+            return Ok(false);
+        };
+        let (ahead, _) = self.graph_ahead_behind(local, upstream)?;
+        Ok(ahead > 0)
+    }
+
+    /// Names of submodules and the commit checked out in each of them.
+    /// The commit is None when the submodule is not initialized.
+    fn submodule_heads(&self) -> Result<Vec<(String, Option<String>)>, mure_error::Error> {
+        let mut heads = vec![];
+        for submodule in self.submodules()? {
+            let name = submodule.name().unwrap_or_default().to_string();
+            heads.push((name, submodule.workdir_id().map(|id| id.to_string())));
+        }
+        Ok(heads)
+    }
+
+    /// URLs of submodules as written in .gitmodules
+    fn submodule_urls(&self) -> Result<Vec<String>, mure_error::Error> {
+        Ok(self
+            .submodules()?
+            .iter()
+            .filter_map(|submodule| submodule.url().map(|url| url.to_string()))
+            .collect())
+    }
+
+    /// Object directories shared by `git clone --reference` (objects/info/alternates)
+    fn alternates(&self) -> Result<Vec<PathBuf>, mure_error::Error> {
+        let objects = self.path().join("objects");
+        let path = objects.join("info").join("alternates");
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        Ok(std::fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            // relative paths are relative to the objects directory
+            .map(|line| objects.join(line))
+            .collect())
+    }
+
+    fn update_submodules(&self) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["submodule", "update", "--init", "--recursive"])?
+            .try_into()
+    }
+
+    /// Copy all reachable objects into the repository, including ones borrowed from alternates
+    fn repack_all(&self) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["repack", "-a", "-d"])?.try_into()
+    }
+
+    fn gc(&self, aggressive: bool) -> Result<GitCommandOutput<()>, Error> {
+        let mut args = vec!["gc", "--quiet"];
+        if aggressive {
+            args.push("--aggressive");
+        }
+        self.mutating_command(&args)?.try_into()
     }
 
     fn switch(&self, branch: &str) -> Result<GitCommandOutput<()>, Error> {
-        self.command(&["switch", branch])?.try_into()
+        self.mutating_command(&["switch", branch])?.try_into()
+    }
+
+    fn switch_create(&self, branch: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["switch", "-c", branch])?.try_into()
+    }
+
+    fn add_paths(&self, paths: &[&str]) -> Result<GitCommandOutput<()>, Error> {
+        let mut args = vec!["add", "--"];
+        args.extend_from_slice(paths);
+        self.mutating_command(&args)?.try_into()
+    }
+
+    fn commit_staged(&self, message: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["commit", "-m", message])?
+            .try_into()
     }
 
     fn delete_branch(&self, branch: &str) -> Result<GitCommandOutput<()>, Error> {
-        self.command(&["branch", "-d", branch])?.try_into()
+        self.mutating_command(&["branch", "-d", branch])?.try_into()
+    }
+
+    /// Delete the branch even if git doesn't think it is merged (e.g. merged into origin only)
+    fn force_delete_branch(&self, branch: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["branch", "-D", branch])?.try_into()
+    }
+
+    /// Push the branch and set the pushed branch as its upstream
+    fn push_upstream(&self, remote: &str, branch: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["push", "-u", remote, branch])?
+            .try_into()
+    }
+
+    /// Push the branch without force, so that it fails unless it is a fast-forward
+    fn push(&self, remote: &str, branch: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["push", remote, branch])?.try_into()
+    }
+
+    /// Delete the branch on the remote only if it is still at the expected commit,
+    /// not to lose commits pushed by others after the last fetch
+    fn push_delete(
+        &self,
+        remote: &str,
+        branch: &str,
+        expected: &str,
+    ) -> Result<GitCommandOutput<()>, Error> {
+        // git push --force-with-lease=refs/heads/$branch:$expected $remote --delete $branch
+        let lease = format!("--force-with-lease=refs/heads/{branch}:{expected}");
+        self.mutating_command(&["push", &lease, remote, "--delete", branch])?
+            .try_into()
+    }
+
+    fn create_branch(
+        &self,
+        branch: &str,
+        start_point: &str,
+    ) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["branch", branch, start_point])?
+            .try_into()
+    }
+
+    fn rename_branch(&self, from: &str, to: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["branch", "-m", from, to])?
+            .try_into()
+    }
+
+    /// Fast-forward the current branch to the revision, failing if it has diverged
+    fn merge_fast_forward(&self, revision: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["merge", "--ff-only", revision])?
+            .try_into()
+    }
+
+    /// e.g. upstream = origin/main
+    fn set_upstream(&self, branch: &str, upstream: &str) -> Result<GitCommandOutput<()>, Error> {
+        self.mutating_command(&["branch", "-u", upstream, branch])?
+            .try_into()
+    }
+
+    /// Find the last tip of the branch from the reflog of HEAD.
+    /// The reflog of the branch itself is removed with the branch, but HEAD remembers
+    /// when it left the branch (checkout) or came back to it (rebase).
+    fn find_lost_branch_tip(&self, branch: &str) -> Result<Option<git2::Oid>, mure_error::Error> {
+        let reflog = self.reflog("HEAD")?;
+        let checkout_from = format!("checkout: moving from {branch} to ");
+        let rebase_to = format!("rebase (finish): returning to refs/heads/{branch}");
+        // entries are ordered from the newest
+        for entry in reflog.iter() {
+            let Some(message) = entry.message() else {
+                continue;
+            };
+            if message.starts_with(&checkout_from) {
+                return Ok(Some(entry.id_old()));
+            }
+            if message == rebase_to {
+                return Ok(Some(entry.id_new()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Stashes from the newest
+    fn stashes(&mut self) -> Result<Vec<Stash>, mure_error::Error> {
+        let mut entries = vec![];
+        self.stash_foreach(|index, message, oid| {
+            entries.push((index, message.to_string(), *oid));
+            true
+        })?;
+        entries
+            .into_iter()
+            .map(|(index, message, oid)| {
+                Ok(Stash {
+                    index,
+                    message,
+                    time: self.find_commit(oid)?.time().seconds(),
+                })
+            })
+            .collect()
+    }
+
+    /// Indexes of newer stashes don't change but older ones are shifted
+    fn drop_stash(&mut self, index: usize) -> Result<(), mure_error::Error> {
+        self.stash_drop(index)?;
+        Ok(())
     }
 
     fn git_command_on_dir(args: &[&str], workdir: &Path) -> Result<RawCommandOutput, Error> {
-        let output = Command::new("git").current_dir(workdir).args(args).output();
+        let mut command = Command::new("git");
+        command.current_dir(workdir);
+        if SkipHooks::is_enabled() {
+            command.args(["-c", "core.hooksPath=/dev/null"]);
+        }
+        credentials::apply(&mut command, args, workdir);
+        let output = command.args(args).output();
         match output {
-            Ok(out) => Ok(RawCommandOutput::from(out)),
-            Err(err) => Err(Error::FailedToExecute(err)),
+            Ok(out) => {
+                let raw = RawCommandOutput::from(out);
+                tracing::debug!(?args, status = raw.status, stderr = raw.stderr, "git");
+                if Verbosity::current() == Verbosity::Debug {
+                    eprintln!("{}", format_git_command(args, workdir, &raw));
+                }
+                Ok(raw)
+            }
+            Err(err) => {
+                tracing::error!(?args, "failed to execute git: {err}");
+                Err(Error::FailedToExecute(err))
+            }
         }
     }
 
     fn command(&self, args: &[&str]) -> Result<RawCommandOutput, Error> {
         let Some(workdir) = self.workdir() else {
-            return Err(Error::FailedToExecute(std::io::Error::new(
-                std::io::ErrorKind::Other,
+            return Err(Error::FailedToExecute(std::io::Error::other(
+                "workdir is not found",
+            )));
+        };
+        Self::git_command_on_dir(args, workdir)
+    }
+
+    fn mutating_command(&self, args: &[&str]) -> Result<RawCommandOutput, Error> {
+        let Some(workdir) = self.workdir() else {
+            return Err(Error::FailedToExecute(std::io::Error::other(
                 "workdir is not found",
             )));
         };
+        Self::mutating_git_command_on_dir(args, workdir)
+    }
+
+    fn mutating_git_command_on_dir(
+        args: &[&str],
+        workdir: &Path,
+    ) -> Result<RawCommandOutput, Error> {
+        if DryRun::is_enabled() {
+            tracing::info!(?args, "skipped by dry-run");
+            eprintln!("(dry-run) git {} in {}", args.join(" "), workdir.display());
+            return Ok(RawCommandOutput {
+                status: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+            });
+        }
         Self::git_command_on_dir(args, workdir)
     }
 }
@@ -154,6 +733,19 @@ impl From<FromUtf8Error> for mure_error::Error {
     }
 }
 
+/// The git command and its raw output for `-vv`
+fn format_git_command(args: &[&str], workdir: &Path, raw: &RawCommandOutput) -> String {
+    let mut lines = vec![format!(
+        "$ git {} (in {}) -> {}",
+        args.join(" "),
+        workdir.display(),
+        raw.status
+    )];
+    lines.extend(raw.stdout.lines().map(|line| format!("  out| {line}")));
+    lines.extend(raw.stderr.lines().map(|line| format!("  err| {line}")));
+    lines.join("\n")
+}
+
 fn split_lines(lines: &str) -> Vec<String> {
     lines
         .split('\n')
@@ -166,8 +758,22 @@ fn split_lines(lines: &str) -> Vec<String> {
 mod tests {
     use super::*;
     use crate::test_fixture::Fixture;
+    use assay::assay;
     use mktemp::Temp;
 
+    #[test]
+    fn test_format_git_command() {
+        let raw = RawCommandOutput {
+            status: 1,
+            stdout: "a\nb\n".to_string(),
+            stderr: "error: failed\n".to_string(),
+        };
+        assert_eq!(
+            format_git_command(&["pull", "--ff-only"], Path::new("/repo"), &raw),
+            "$ git pull --ff-only (in /repo) -> 1\n  out| a\n  out| b\n  err| error: failed"
+        );
+    }
+
     #[test]
     fn test_split_lines() {
         let lines = "a\nb\nc\n";
@@ -325,6 +931,295 @@ mod tests {
         assert_eq!(result.interpreted_to, PullFastForwardStatus::Abort);
     }
 
+    #[test]
+    fn test_remote_default_branch() {
+        let fixture1 = Fixture::create().unwrap();
+        let repo1 = &fixture1.repo;
+        let fixture2 = Fixture::create().unwrap();
+        let repo2 = &fixture2.repo;
+
+        fixture1.create_empty_commit("initial commit").unwrap();
+        repo1
+            .command(&["switch", "-c", "main"])
+            .expect("failed to switch to main branch");
+
+        // no remote yet
+        assert_eq!(repo2.remote_default_branch("origin").unwrap(), None);
+
+        let remote_path = format!("{}{}", repo1.workdir().unwrap().to_str().unwrap(), ".git");
+        repo2
+            .command(&["remote", "add", "origin", &remote_path])
+            .expect("failed to add remote");
+        repo2
+            .command(&["fetch", "origin"])
+            .expect("failed to fetch");
+
+        // fetch doesn't set origin/HEAD
+        assert_eq!(repo2.remote_default_branch("origin").unwrap(), None);
+
+        repo2
+            .command(&["remote", "set-head", "origin", "main"])
+            .expect("failed to set head");
+        assert_eq!(
+            repo2.remote_default_branch("origin").unwrap(),
+            Some("main".to_string())
+        );
+    }
+
+    #[test]
+    fn test_guess_default_branch() {
+        let fixture1 = Fixture::create().unwrap();
+        let repo1 = &fixture1.repo;
+        let fixture2 = Fixture::create().unwrap();
+        let repo2 = &fixture2.repo;
+
+        fixture1.create_empty_commit("initial commit").unwrap();
+        repo1.command(&["switch", "-c", "trunk"]).unwrap();
+        repo1.command(&["branch", "master"]).unwrap();
+
+        let remote_path = format!("{}{}", repo1.workdir().unwrap().to_str().unwrap(), ".git");
+        repo2
+            .command(&["remote", "add", "origin", &remote_path])
+            .unwrap();
+        // not fetched yet
+        assert_eq!(repo2.guess_default_branch("origin").unwrap(), None);
+
+        repo2.command(&["fetch", "origin"]).unwrap();
+        assert_eq!(
+            repo2.guess_default_branch("origin").unwrap(),
+            Some("master".to_string())
+        );
+
+        repo2
+            .command(&["config", "init.defaultBranch", "trunk"])
+            .unwrap();
+        assert_eq!(
+            repo2.guess_default_branch("origin").unwrap(),
+            Some("trunk".to_string())
+        );
+
+        // origin/HEAD has priority
+        repo2.set_remote_head("origin", "master").unwrap();
+        assert_eq!(
+            repo2.guess_default_branch("origin").unwrap(),
+            Some("master".to_string())
+        );
+    }
+
+    #[test]
+    fn test_update_branch_from_remote() {
+        let fixture1 = Fixture::create().unwrap();
+        let repo1 = &fixture1.repo;
+        let fixture2 = Fixture::create().unwrap();
+        let repo2 = &fixture2.repo;
+
+        fixture1.create_empty_commit("initial commit").unwrap();
+        repo1
+            .command(&["switch", "-c", "main"])
+            .expect("failed to switch to main branch");
+
+        let remote_path = format!("{}{}", repo1.workdir().unwrap().to_str().unwrap(), ".git");
+        repo2
+            .command(&["remote", "add", "origin", &remote_path])
+            .expect("failed to add remote");
+        repo2
+            .command(&["fetch", "origin"])
+            .expect("failed to fetch");
+        repo2
+            .command(&["checkout", "-b", "main", "origin/main"])
+            .expect("failed to fetch");
+        repo2
+            .command(&["switch", "-c", "feature"])
+            .expect("failed to switch to feature branch");
+
+        let result = repo2.update_branch_from_remote("origin", "main").unwrap();
+        assert_eq!(
+            result.interpreted_to,
+            PullFastForwardStatus::AlreadyUpToDate
+        );
+
+        fixture1.create_empty_commit("second commit").unwrap();
+        let result = repo2.update_branch_from_remote("origin", "main").unwrap();
+        assert_eq!(result.interpreted_to, PullFastForwardStatus::FastForwarded);
+        // current branch is kept
+        assert_eq!(repo2.get_current_branch().unwrap(), "feature");
+
+        // diverged branch can't be fast-forwarded
+        fixture1.create_empty_commit("commit A").unwrap();
+        repo2.switch("main").unwrap();
+        fixture2.create_empty_commit("commit B").unwrap();
+        repo2.switch("feature").unwrap();
+        let result = repo2.update_branch_from_remote("origin", "main").unwrap();
+        assert_eq!(result.interpreted_to, PullFastForwardStatus::Abort);
+    }
+
+    #[test]
+    fn test_alternates() {
+        let fixture_origin = Fixture::create().unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        assert!(fixture_origin.repo.alternates().unwrap().is_empty());
+
+        let origin_path = fixture_origin.repo.workdir().unwrap();
+        let temp_dir = Temp::new_dir().unwrap();
+        Repository::clone_with_args(
+            origin_path.to_str().unwrap(),
+            &temp_dir,
+            &["--reference", origin_path.to_str().unwrap()],
+        )
+        .unwrap();
+        let name = origin_path.file_name().unwrap();
+        let repo = Repository::open(temp_dir.join(name)).unwrap();
+        let alternates = repo.alternates().unwrap();
+        assert_eq!(alternates.len(), 1);
+        assert_eq!(
+            std::fs::canonicalize(&alternates[0]).unwrap(),
+            std::fs::canonicalize(fixture_origin.repo.path().join("objects")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_submodules() {
+        let fixture_sub = Fixture::create().unwrap();
+        let fixture_origin = Fixture::create().unwrap();
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+
+        fixture_sub.create_empty_commit("initial commit").unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        let sub_path = fixture_sub.repo.workdir().unwrap().to_str().unwrap();
+        let origin_path = fixture_origin.repo.workdir().unwrap().to_str().unwrap();
+
+        // no submodule
+        assert!(fixture_origin.repo.submodule_heads().unwrap().is_empty());
+
+        // local file transport is not allowed for submodules by default
+        fixture_origin
+            .repo
+            .command(&[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                sub_path,
+                "sub",
+            ])
+            .expect("failed to add submodule");
+        fixture_origin.create_empty_commit("add submodule").unwrap();
+
+        repo.command(&["remote", "add", "origin", origin_path])
+            .expect("failed to add remote");
+        repo.command(&["pull", "origin", "HEAD"])
+            .expect("failed to pull");
+        let heads = repo.submodule_heads().unwrap();
+        assert_eq!(heads, vec![("sub".to_string(), None)]);
+        assert_eq!(repo.submodule_urls().unwrap(), vec![sub_path.to_string()]);
+
+        repo.command(&[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "update",
+            "--init",
+        ])
+        .expect("failed to init submodule");
+        let before = repo.submodule_heads().unwrap();
+        assert!(before[0].1.is_some());
+
+        // move submodule in origin
+        fixture_sub.create_empty_commit("second commit").unwrap();
+        fixture_origin
+            .repo
+            .command(&["-C", "sub", "pull", "origin", "HEAD"])
+            .expect("failed to pull submodule");
+        fixture_origin
+            .repo
+            .command(&["commit", "-am", "bump submodule"])
+            .expect("failed to commit");
+        repo.command(&["pull", "origin", "HEAD"])
+            .expect("failed to pull");
+
+        repo.update_submodules().unwrap();
+        let after = repo.submodule_heads().unwrap();
+        assert_ne!(before, after);
+        assert_eq!(
+            after[0].1,
+            Some(fixture_sub.repo.refname_to_id("HEAD").unwrap().to_string())
+        );
+    }
+
+    // local file transport is not allowed for submodules by default
+    #[assay(
+        env = [
+          ("GIT_CONFIG_COUNT", "1"),
+          ("GIT_CONFIG_KEY_0", "protocol.file.allow"),
+          ("GIT_CONFIG_VALUE_0", "always"),
+        ]
+      )]
+    fn test_clone_with_args() {
+        let fixture_sub = Fixture::create().unwrap();
+        let fixture_origin = Fixture::create().unwrap();
+        fixture_sub.create_empty_commit("initial commit").unwrap();
+        fixture_origin
+            .create_empty_commit("initial commit")
+            .unwrap();
+        let sub_path = fixture_sub.repo.workdir().unwrap().to_str().unwrap();
+        let origin_path = fixture_origin.repo.workdir().unwrap().to_str().unwrap();
+        fixture_origin
+            .repo
+            .command(&["submodule", "add", sub_path, "sub"])
+            .expect("failed to add submodule");
+        fixture_origin.create_empty_commit("add submodule").unwrap();
+
+        let temp_dir = Temp::new_dir().unwrap();
+        Repository::clone_with_args(origin_path, temp_dir.as_path(), &["--recurse-submodules"])
+            .unwrap();
+        let name = Path::new(origin_path).file_name().unwrap();
+        let cloned = Repository::open(temp_dir.as_path().join(name)).unwrap();
+        assert!(cloned.submodule_heads().unwrap()[0].1.is_some());
+    }
+
+    #[test]
+    fn test_ahead_behind() {
+        let fixture1 = Fixture::create().unwrap();
+        let repo1 = &fixture1.repo;
+        let fixture2 = Fixture::create().unwrap();
+        let repo2 = &fixture2.repo;
+
+        fixture1.create_empty_commit("initial commit").unwrap();
+        repo1
+            .command(&["switch", "-c", "main"])
+            .expect("failed to switch to main branch");
+        let remote_path = format!("{}{}", repo1.workdir().unwrap().to_str().unwrap(), ".git");
+        repo2
+            .command(&["remote", "add", "origin", &remote_path])
+            .expect("failed to add remote");
+        repo2
+            .command(&["fetch", "origin"])
+            .expect("failed to fetch");
+        repo2
+            .command(&["checkout", "-b", "main", "--track", "origin/main"])
+            .expect("failed to checkout");
+
+        assert_eq!(repo2.ahead_behind("HEAD", "@{upstream}").unwrap(), (0, 0));
+        assert!(!repo2.has_unpushed_commits("main").unwrap());
+
+        fixture2.create_empty_commit("local commit").unwrap();
+        assert!(repo2.has_unpushed_commits("main").unwrap());
+        fixture1.create_empty_commit("remote commit 1").unwrap();
+        fixture1.create_empty_commit("remote commit 2").unwrap();
+        repo2
+            .command(&["fetch", "origin"])
+            .expect("failed to fetch");
+        assert_eq!(repo2.ahead_behind("HEAD", "@{upstream}").unwrap(), (1, 2));
+        assert_eq!(repo2.ahead_behind("main", "origin/main").unwrap(), (1, 2));
+
+        assert!(repo2.ahead_behind("main", "origin/nothing").is_err());
+    }
+
     #[test]
     fn test_get_current_branch() {
         let fixture = Fixture::create().unwrap();
@@ -341,6 +1236,41 @@ mod tests {
         assert!(branch_name == "main" || branch_name == "master");
     }
 
+    #[test]
+    fn test_signatures() {
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        repo.config()
+            .unwrap()
+            .set_bool("commit.gpgsign", false)
+            .unwrap();
+
+        // no commit to verify yet
+        assert!(repo.verify_head_signature().is_err());
+
+        fixture.create_empty_commit("initial commit").unwrap();
+        fixture.create_empty_commit("second commit").unwrap();
+
+        let status = repo.verify_head_signature().unwrap().interpreted_to;
+        assert_eq!(status, SignatureStatus::Unsigned);
+        assert!(!status.is_signed());
+
+        let commits = repo.commit_signatures("HEAD", 10).unwrap().interpreted_to;
+        assert_eq!(commits.len(), 2);
+        assert!(commits
+            .iter()
+            .all(|(_, status)| *status == SignatureStatus::Unsigned));
+    }
+
+    #[test]
+    fn test_signature_status_from_code() {
+        assert_eq!(SignatureStatus::from_code("G\n"), SignatureStatus::Good);
+        assert_eq!(SignatureStatus::from_code("B"), SignatureStatus::Bad);
+        assert_eq!(SignatureStatus::from_code("N"), SignatureStatus::Unsigned);
+        assert!(SignatureStatus::from_code("U").is_signed());
+        assert!(!SignatureStatus::from_code("E").is_signed());
+    }
+
     #[test]
     fn test_switch() {
         let fixture = Fixture::create().unwrap();
@@ -360,6 +1290,101 @@ mod tests {
             .expect("failed to switch to main branch");
     }
 
+    #[test]
+    fn test_switch_create_add_commit() {
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        fixture.create_empty_commit("initial commit").unwrap();
+
+        repo.switch_create("feature").unwrap();
+        assert_eq!(repo.get_current_branch().unwrap(), "feature");
+        // already exists
+        assert!(repo.switch_create("feature").is_err());
+
+        fixture.create_file("1.txt", "hello").unwrap();
+        repo.add_paths(&["1.txt"]).unwrap();
+        repo.commit_staged("add 1.txt").unwrap();
+        assert!(repo.is_clean().unwrap());
+
+        // nothing to commit
+        assert!(repo.commit_staged("empty").is_err());
+    }
+
+    #[test]
+    fn test_skip_hooks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        let hook = repo.path().join("hooks").join("pre-commit");
+        std::fs::create_dir_all(hook.parent().unwrap()).unwrap();
+        std::fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let commit = |message: &str| {
+            repo.command(&["commit", "--allow-empty", "-m", message])
+                .unwrap()
+                .success()
+        };
+        assert!(!commit("rejected by the hook"));
+
+        SkipHooks::set(true);
+        assert!(SkipHooks::is_enabled());
+        assert!(commit("initial commit"));
+        SkipHooks::set(false);
+        assert!(!commit("rejected again"));
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        fixture.create_empty_commit("initial commit").unwrap();
+        repo.command(&["switch", "-c", "main"]).unwrap();
+        repo.command(&["branch", "feature"]).unwrap();
+
+        DryRun::set(true);
+        assert!(DryRun::is_enabled());
+        repo.switch("feature").unwrap();
+        repo.delete_branch("feature").unwrap();
+        // read-only commands are still executed
+        assert_eq!(repo.get_current_branch().unwrap(), "main");
+        assert!(repo
+            .merged_branches()
+            .unwrap()
+            .interpreted_to
+            .contains(&"feature".to_string()));
+        DryRun::set(false);
+
+        repo.switch("feature").unwrap();
+        assert_eq!(repo.get_current_branch().unwrap(), "feature");
+        assert!(repo
+            .merged_branches_into("main")
+            .unwrap()
+            .interpreted_to
+            .contains(&"main".to_string()));
+    }
+
+    #[test]
+    fn test_find_lost_branch_tip() {
+        let fixture = Fixture::create().unwrap();
+        let repo = &fixture.repo;
+        fixture.create_empty_commit("initial commit").unwrap();
+        repo.command(&["switch", "-c", "main"]).unwrap();
+        repo.command(&["switch", "-c", "feature"]).unwrap();
+        fixture.create_empty_commit("feature commit").unwrap();
+        let tip = repo.refname_to_id("refs/heads/feature").unwrap();
+        repo.switch("main").unwrap();
+        repo.command(&["branch", "-D", "feature"]).unwrap();
+
+        assert_eq!(repo.find_lost_branch_tip("feature").unwrap(), Some(tip));
+        assert_eq!(repo.find_lost_branch_tip("unknown").unwrap(), None);
+
+        repo.create_branch("feature", &tip.to_string()).unwrap();
+        assert_eq!(repo.refname_to_id("refs/heads/feature").unwrap(), tip);
+        // already exists
+        assert!(repo.create_branch("feature", &tip.to_string()).is_err());
+    }
+
     #[test]
     fn test_delete_branch() {
         let fixture = Fixture::create().unwrap();
@@ -405,6 +1430,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_push_delete() {
+        let origin = Fixture::create().unwrap();
+        origin.create_empty_commit("initial commit").unwrap();
+        origin.repo.command(&["branch", "feature"]).unwrap();
+        let origin_path = origin.repo.workdir().unwrap();
+        let temp_dir = Temp::new_dir().unwrap();
+        let repo = Repository::clone(origin_path.to_str().unwrap(), &temp_dir).unwrap();
+        let fetched = repo
+            .revparse_single("origin/feature")
+            .unwrap()
+            .id()
+            .to_string();
+
+        // someone pushed to the branch after the fetch
+        origin.repo.command(&["switch", "feature"]).unwrap();
+        origin.create_empty_commit("pushed by others").unwrap();
+        origin.repo.command(&["switch", "-"]).unwrap();
+        assert!(repo.push_delete("origin", "feature", &fetched).is_err());
+        assert!(origin
+            .repo
+            .find_branch("feature", BranchType::Local)
+            .is_ok());
+
+        let tip = origin
+            .repo
+            .revparse_single("feature")
+            .unwrap()
+            .id()
+            .to_string();
+        repo.push_delete("origin", "feature", &tip).unwrap();
+        assert!(origin
+            .repo
+            .find_branch("feature", BranchType::Local)
+            .is_err());
+    }
+
     #[test]
     fn test_clone() {
         let temp_dir = Temp::new_dir().expect("failed to create temp dir");
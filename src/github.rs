@@ -1,3 +1,5 @@
 pub mod api;
+pub mod rate_limit;
 pub mod repo;
+pub mod rest;
 pub mod token;
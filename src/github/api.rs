@@ -1,7 +1,9 @@
+use std::collections::HashMap;
 use std::str::FromStr;
 
+use crate::github::rate_limit;
 use crate::mure_error::Error;
-use graphql_client::{GraphQLQuery, QueryBody};
+use graphql_client::GraphQLQuery;
 
 #[allow(clippy::upper_case_acronyms)]
 type URI = String;
@@ -12,28 +14,586 @@ type DateTime = String;
 #[allow(clippy::upper_case_acronyms)]
 type GitObjectID = String;
 
+/// GraphQL endpoint of github.com
+pub const GITHUB_GRAPHQL_URL: &str = "https://api.github.com/graphql";
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "graphql/schema/schema.docs.graphql",
     query_path = "graphql/schema/query.graphql",
-    response_derives = "Debug,PartialEq,Eq,Clone,serde::Serialize"
+    response_derives = "Debug,PartialEq,Eq,Clone"
 )]
 pub struct SearchRepositoryQuery;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.docs.graphql",
+    query_path = "graphql/schema/pull_requests.graphql",
+    response_derives = "Debug,PartialEq,Eq,Clone"
+)]
+pub struct OpenPullRequestsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.docs.graphql",
+    query_path = "graphql/schema/owner.graphql",
+    response_derives = "Debug,PartialEq,Eq,Clone"
+)]
+pub struct OwnerQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.docs.graphql",
+    query_path = "graphql/schema/rate_limit.graphql",
+    response_derives = "Debug,PartialEq,Eq,Clone"
+)]
+pub struct RateLimitQuery;
+
+/// Fetch the current rate limit budget of the token. The query itself costs nothing.
+pub fn get_rate_limit(
+    url: &str,
+    token: &str,
+) -> Result<Option<rate_limit_query::RateLimitQueryRateLimit>, Error> {
+    let request_body = RateLimitQuery::build_query(rate_limit_query::Variables {});
+    let response: rate_limit_query::ResponseData = github_api_request_with_retry(
+        url,
+        token,
+        request_body,
+        std::time::Duration::from_secs(10),
+        std::time::Duration::from_secs(1),
+        std::time::Duration::from_secs(10),
+        5,
+    )?;
+    Ok(response.rate_limit)
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.docs.graphql",
+    query_path = "graphql/schema/viewer.graphql",
+    response_derives = "Debug,PartialEq,Eq,Clone"
+)]
+pub struct ViewerQuery;
+
+/// Fetch the login of the token owner. Useful to check the token is valid.
+pub fn get_viewer_login(
+    url: &str,
+    token: &str,
+    timeout: std::time::Duration,
+) -> Result<String, Error> {
+    let request_body = ViewerQuery::build_query(viewer_query::Variables {});
+    let response: viewer_query::ResponseData = github_api_request_with_retry(
+        url,
+        token,
+        request_body,
+        timeout,
+        std::time::Duration::from_secs(1),
+        std::time::Duration::from_secs(1),
+        1,
+    )?;
+    Ok(response.viewer.login)
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.docs.graphql",
+    query_path = "graphql/schema/token_check.graphql",
+    response_derives = "Debug,PartialEq,Eq,Clone"
+)]
+pub struct TokenCheckQuery;
+
+/// What GitHub tells about the token
+#[derive(Debug, PartialEq, Eq)]
+pub struct TokenCheck {
+    pub login: String,
+    /// Scopes of a classic token. None for fine-grained tokens which have permissions instead.
+    pub scopes: Option<Vec<String>>,
+    /// e.g. `2026-11-01 00:00:00 UTC`. None if the token never expires.
+    pub expires_at: Option<String>,
+    pub rate_limit: Option<token_check_query::TokenCheckQueryRateLimit>,
+}
+
+/// Fetch the owner and the rate limit of the token. Scopes and expiry come in the response headers.
+/// Unlike other queries, it isn't retried because the token itself is in question.
+pub fn check_token(url: &str, token: &str) -> Result<TokenCheck, Error> {
+    let request_body = TokenCheckQuery::build_query(token_check_query::Variables {});
+    let response = reqwest::blocking::Client::new()
+        .post(url)
+        .header("Authorization", format!("bearer {token}"))
+        .header("User-Agent", "mure")
+        .timeout(std::time::Duration::from_secs(10))
+        .json(&request_body)
+        .send()?;
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let scopes = header("X-OAuth-Scopes").map(|scopes| parse_scopes(&scopes));
+    let expires_at = header("GitHub-Authentication-Token-Expiration");
+    let status = response.status();
+    let response_text = response.text()?;
+    if !status.is_success() {
+        return Err(Error::from_str(&format!("{status} {response_text}")));
+    }
+    let response: graphql_client::Response<token_check_query::ResponseData> =
+        serde_json::from_str(&response_text)?;
+    let Some(data) = response.data else {
+        return Err(Error::from_str(&response_text));
+    };
+    Ok(TokenCheck {
+        login: data.viewer.login,
+        scopes,
+        expires_at,
+        rate_limit: data.rate_limit,
+    })
+}
+
+/// `X-OAuth-Scopes: repo, read:org` -> `["repo", "read:org"]`
+fn parse_scopes(header: &str) -> Vec<String> {
+    header
+        .split(',')
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Fetch the user or organization. None is returned if it doesn't exist.
+pub fn get_owner(
+    token: &str,
+    login: &str,
+) -> Result<Option<owner_query::OwnerQueryRepositoryOwner>, Error> {
+    let variables = owner_query::Variables {
+        login: login.to_string(),
+    };
+    let request_body = OwnerQuery::build_query(variables);
+    let response: owner_query::ResponseData = github_api_request_with_retry(
+        GITHUB_GRAPHQL_URL,
+        token,
+        request_body,
+        std::time::Duration::from_secs(10),
+        std::time::Duration::from_secs(1),
+        std::time::Duration::from_secs(10),
+        5,
+    )?;
+    Ok(response.repository_owner)
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.docs.graphql",
+    query_path = "graphql/schema/org_repositories.graphql",
+    response_derives = "Debug,PartialEq,Eq,Clone"
+)]
+pub struct OrgRepositoriesQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.docs.graphql",
+    query_path = "graphql/schema/org_repositories.graphql",
+    response_derives = "Debug,PartialEq,Eq,Clone"
+)]
+pub struct TeamRepositoriesQuery;
+
+/// `owner/name` of repositories of the organization, or only of the team if specified.
+/// Archived repositories are excluded.
+pub fn list_org_repositories(
+    url: &str,
+    token: &str,
+    org: &str,
+    team: Option<&str>,
+) -> Result<Vec<String>, Error> {
+    let mut results = vec![];
+    let mut cursor = None as Option<String>;
+    // Avoid infinite loop to prevent reaching github api limit.
+    for _ in 0..100 {
+        let (nodes, page_info) = match team {
+            Some(team) => list_team_repositories_page(url, token, org, team, cursor)?,
+            None => list_org_repositories_page(url, token, org, cursor)?,
+        };
+        results.extend(
+            nodes
+                .into_iter()
+                .filter(|(_, is_archived)| !is_archived)
+                .map(|(name_with_owner, _)| name_with_owner),
+        );
+        let (has_next_page, end_cursor) = page_info;
+        if !has_next_page {
+            break;
+        }
+        cursor = end_cursor;
+    }
+    Ok(results)
+}
+
+/// Repositories as `(nameWithOwner, isArchived)` and `(hasNextPage, endCursor)` of a page
+type RepositoriesPage = (Vec<(String, bool)>, (bool, Option<String>));
+
+fn list_org_repositories_page(
+    url: &str,
+    token: &str,
+    org: &str,
+    cursor: Option<String>,
+) -> Result<RepositoriesPage, Error> {
+    let variables = org_repositories_query::Variables {
+        org: org.to_string(),
+        cursor,
+    };
+    let request_body = OrgRepositoriesQuery::build_query(variables);
+    let response: org_repositories_query::ResponseData = github_api_request_with_retry(
+        url,
+        token,
+        request_body,
+        std::time::Duration::from_secs(10),
+        std::time::Duration::from_secs(1),
+        std::time::Duration::from_secs(10),
+        5,
+    )?;
+    let Some(organization) = response.organization else {
+        return Err(Error::from_str(&format!("organization {org} is not found")));
+    };
+    let repositories = organization.repositories;
+    Ok((
+        repositories
+            .nodes
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|repo| (repo.name_with_owner, repo.is_archived))
+            .collect(),
+        (
+            repositories.page_info.has_next_page,
+            repositories.page_info.end_cursor,
+        ),
+    ))
+}
+
+fn list_team_repositories_page(
+    url: &str,
+    token: &str,
+    org: &str,
+    team: &str,
+    cursor: Option<String>,
+) -> Result<RepositoriesPage, Error> {
+    let variables = team_repositories_query::Variables {
+        org: org.to_string(),
+        team: team.to_string(),
+        cursor,
+    };
+    let request_body = TeamRepositoriesQuery::build_query(variables);
+    let response: team_repositories_query::ResponseData = github_api_request_with_retry(
+        url,
+        token,
+        request_body,
+        std::time::Duration::from_secs(10),
+        std::time::Duration::from_secs(1),
+        std::time::Duration::from_secs(10),
+        5,
+    )?;
+    let Some(team_) = response.organization.and_then(|org| org.team) else {
+        return Err(Error::from_str(&format!("team {org}/{team} is not found")));
+    };
+    let repositories = team_.repositories;
+    Ok((
+        repositories
+            .nodes
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|repo| (repo.name_with_owner, repo.is_archived))
+            .collect(),
+        (
+            repositories.page_info.has_next_page,
+            repositories.page_info.end_cursor,
+        ),
+    ))
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.docs.graphql",
+    query_path = "graphql/schema/work_queue.graphql",
+    response_derives = "Debug,PartialEq,Eq,Clone"
+)]
+pub struct WorkQueueQuery;
+
+/// An open issue or pull request found by `search_work_items`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WorkItem {
+    pub repository: String,
+    pub title: String,
+    pub url: String,
+    /// e.g. 2024-05-01T12:34:56Z
+    pub created_at: String,
+}
+
+/// Issues and pull requests matching the search query like `is:open assignee:@me`
+pub fn search_work_items(url: &str, token: &str, query: &str) -> Result<Vec<WorkItem>, Error> {
+    use work_queue_query::WorkQueueQuerySearchNodes as Node;
+    let mut results = vec![];
+    let mut cursor = None as Option<String>;
+    // Avoid infinite loop to prevent reaching github api limit.
+    for _ in 0..10 {
+        let variables = work_queue_query::Variables {
+            query: query.to_string(),
+            cursor,
+        };
+        let request_body = WorkQueueQuery::build_query(variables);
+        let response: work_queue_query::ResponseData = github_api_request_with_retry(
+            url,
+            token,
+            request_body,
+            std::time::Duration::from_secs(10),
+            std::time::Duration::from_secs(1),
+            std::time::Duration::from_secs(10),
+            5,
+        )?;
+        let search = response.search;
+        for node in search.nodes.into_iter().flatten().flatten() {
+            let item = match node {
+                Node::Issue(issue) => WorkItem {
+                    repository: issue.repository.name_with_owner,
+                    title: issue.title,
+                    url: issue.url,
+                    created_at: issue.created_at,
+                },
+                Node::PullRequest(pull_request) => WorkItem {
+                    repository: pull_request.repository.name_with_owner,
+                    title: pull_request.title,
+                    url: pull_request.url,
+                    created_at: pull_request.created_at,
+                },
+                _ => continue,
+            };
+            results.push(item);
+        }
+        if !search.page_info.has_next_page {
+            break;
+        }
+        cursor = search.page_info.end_cursor;
+    }
+    Ok(results)
+}
+
+/// Fetch all open pull requests of the repository
+pub fn open_pull_requests(
+    token: &str,
+    owner: &str,
+    name: &str,
+) -> Result<Vec<open_pull_requests_query::OpenPullRequestsQueryRepositoryPullRequestsNodes>, Error>
+{
+    let mut results = vec![];
+    let mut cursor = None as Option<String>;
+    // Avoid infinite loop to prevent reaching github api limit.
+    for _ in 0..100 {
+        let variables = open_pull_requests_query::Variables {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            cursor,
+        };
+        let request_body = OpenPullRequestsQuery::build_query(variables);
+        let response: open_pull_requests_query::ResponseData = github_api_request_with_retry(
+            GITHUB_GRAPHQL_URL,
+            token,
+            request_body,
+            std::time::Duration::from_secs(10),
+            std::time::Duration::from_secs(1),
+            std::time::Duration::from_secs(10),
+            5,
+        )?;
+        let Some(repository) = response.repository else {
+            return Err(Error::from_str(&format!("{owner}/{name} is not found")));
+        };
+        let pull_requests = repository.pull_requests;
+        results.extend(pull_requests.nodes.into_iter().flatten().flatten());
+        if !pull_requests.page_info.has_next_page {
+            break;
+        }
+        cursor = pull_requests.page_info.end_cursor;
+    }
+    Ok(results)
+}
+
+/// Same fields as repositories of SearchRepositoryQuery, so that the responses share the type
+const REPOSITORY_SUMMARY_FRAGMENT: &str =
+    include_str!("../../graphql/schema/repository_summary.graphql");
+
+/// Number of repositories requested in one query by `RepositoriesQuery`
+const REPOSITORIES_PER_QUERY: usize = 50;
+
+/// Query requesting the same fields of many repositories at once with aliases `r0`, `r1`, ...
+/// graphql_client can't generate queries with a variable number of fields, so it is built here.
+struct RepositoriesQuery<'a> {
+    /// Fields requested for each repository, e.g. `defaultBranchRef { name }`
+    selection: &'a str,
+    /// Fragments used in the selection
+    fragments: &'a str,
+}
+
+impl RepositoriesQuery<'_> {
+    /// Request body for the repositories given by owner and name
+    fn build(&self, repos: &[(String, String)]) -> serde_json::Value {
+        let mut params = vec![];
+        let mut fields = vec![];
+        let mut variables = serde_json::Map::new();
+        for (i, (owner, name)) in repos.iter().enumerate() {
+            params.push(format!("$owner{i}: String!, $name{i}: String!"));
+            fields.push(format!(
+                "  r{i}: repository(owner: $owner{i}, name: $name{i}) {{ {} }}",
+                self.selection
+            ));
+            variables.insert(format!("owner{i}"), owner.clone().into());
+            variables.insert(format!("name{i}"), name.clone().into());
+        }
+        let query = format!(
+            "query RepositoriesQuery({}) {{\n{}\n}}\n{}",
+            params.join(", "),
+            fields.join("\n"),
+            self.fragments
+        );
+        serde_json::json!({
+            "query": query,
+            "variables": variables,
+            "operationName": "RepositoriesQuery",
+        })
+    }
+
+    /// Request the repositories in chunks of REPOSITORIES_PER_QUERY.
+    /// Results are in the order of `repos` and None is returned for ones not found.
+    fn fetch<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        token: &str,
+        repos: &[(String, String)],
+    ) -> Result<Vec<Option<T>>, Error> {
+        let mut results = vec![];
+        for chunk in repos.chunks(REPOSITORIES_PER_QUERY) {
+            let mut response: HashMap<String, Option<T>> = github_api_request_with_retry(
+                url,
+                token,
+                self.build(chunk),
+                std::time::Duration::from_secs(10),
+                std::time::Duration::from_secs(1),
+                std::time::Duration::from_secs(10),
+                5,
+            )?;
+            results.extend((0..chunk.len()).map(|i| response.remove(&format!("r{i}")).flatten()));
+        }
+        Ok(results)
+    }
+}
+
+/// Fetch the repositories given by owner and name. None is returned for ones not found.
+/// Unlike search, no repository is missed.
+pub fn get_repositories(
+    url: &str,
+    token: &str,
+    repos: &[(String, String)],
+) -> Result<
+    Vec<Option<search_repository_query::SearchRepositoryQueryReposEdgesNodeOnRepository>>,
+    Error,
+> {
+    RepositoriesQuery {
+        selection: "...RepositorySummary",
+        fragments: REPOSITORY_SUMMARY_FRAGMENT,
+    }
+    .fetch(url, token, repos)
+}
+
+#[derive(serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DefaultBranchOfRepository {
+    default_branch_ref: Option<BranchName>,
+}
+
+#[derive(serde_derive::Deserialize)]
+struct BranchName {
+    name: String,
+}
+
+/// Default branches of the repositories given by owner and name.
+/// None is returned for ones not found or empty.
+pub fn get_default_branches(
+    url: &str,
+    token: &str,
+    repos: &[(String, String)],
+) -> Result<Vec<Option<String>>, Error> {
+    let results: Vec<Option<DefaultBranchOfRepository>> = RepositoriesQuery {
+        selection: "defaultBranchRef { name }",
+        fragments: "",
+    }
+    .fetch(url, token, repos)?;
+    Ok(results
+        .into_iter()
+        .map(|repo| Some(repo?.default_branch_ref?.name))
+        .collect())
+}
+
+/// Hygiene of a repository for the optional columns of `mure issues`.
+/// Only the fields in the selection are requested, so all of them are optional.
+#[derive(serde_derive::Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryHealth {
+    #[serde(default)]
+    pub license_info: Option<LicenseInfo>,
+    #[serde(default)]
+    pub is_archived: Option<bool>,
+    #[serde(default)]
+    pub has_vulnerability_alerts_enabled: Option<bool>,
+    /// open Dependabot alerts
+    #[serde(default)]
+    pub vulnerability_alerts: Option<TotalCount>,
+}
+
+#[derive(serde_derive::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LicenseInfo {
+    /// e.g. `MIT`. None for licenses not in the SPDX list
+    pub spdx_id: Option<String>,
+    pub name: String,
+}
+
+#[derive(serde_derive::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TotalCount {
+    pub total_count: i64,
+}
+
+/// Health of the repositories given by owner and name with the fields of the selection,
+/// e.g. `licenseInfo { spdxId name } isArchived`.
+/// Fields such as vulnerability alerts need admin rights, and None is returned for repositories without them.
+pub fn get_repository_health(
+    url: &str,
+    token: &str,
+    repos: &[(String, String)],
+    selection: &str,
+) -> Result<Vec<Option<RepositoryHealth>>, Error> {
+    RepositoriesQuery {
+        selection,
+        fragments: "",
+    }
+    .fetch(url, token, repos)
+}
+
 pub fn search_all_repositories_by_queries(
+    url: &str,
     token: &str,
     queries: &Vec<String>,
 ) -> Result<Vec<search_repository_query::SearchRepositoryQueryReposEdgesNodeOnRepository>, Error> {
     let mut results =
         vec![] as Vec<search_repository_query::SearchRepositoryQueryReposEdgesNodeOnRepository>;
     for query in queries {
-        let mut repos = search_all_repositories(token, query)?;
+        let mut repos = search_all_repositories(url, token, query)?;
         results.append(&mut repos);
     }
     Ok(results)
 }
 
 pub fn search_all_repositories(
+    url: &str,
     token: &str,
     query: &str,
 ) -> Result<Vec<search_repository_query::SearchRepositoryQueryReposEdgesNodeOnRepository>, Error> {
@@ -48,7 +608,7 @@ pub fn search_all_repositories(
             first: 100,
             cursor,
         };
-        let response = search_repositories(token, variables);
+        let response = search_repositories(url, token, variables);
         match response {
             Ok(response) => {
                 let page_info = response.repos.page_info;
@@ -88,6 +648,7 @@ pub fn search_all_repositories(
 }
 
 fn search_repositories(
+    url: &str,
     token: &str,
     variables: search_repository_query::Variables,
 ) -> Result<search_repository_query::ResponseData, Error> {
@@ -97,6 +658,7 @@ fn search_repositories(
     let max_backoff = std::time::Duration::from_secs(10);
     let max_retries = 5;
     github_api_request_with_retry(
+        url,
         token,
         request_body,
         timeout,
@@ -106,9 +668,10 @@ fn search_repositories(
     )
 }
 
-fn github_api_request_with_retry<T: serde::Serialize, S: serde::de::DeserializeOwned>(
+fn github_api_request_with_retry<B: serde::Serialize, S: serde::de::DeserializeOwned>(
+    url: &str,
     token: &str,
-    variables: QueryBody<T>,
+    request_body: B,
     timeout: std::time::Duration,
     // exponential backoff
     base_backoff: std::time::Duration,
@@ -116,17 +679,16 @@ fn github_api_request_with_retry<T: serde::Serialize, S: serde::de::DeserializeO
     max_retries: u32,
 ) -> Result<S, Error> {
     let client = reqwest::blocking::Client::new();
-    let url = "https://api.github.com/graphql";
     let bearer = format!("bearer {token}");
-    let request_body = variables;
     // I don't know the best value for timeout. But 10 seconds is the upper limit of REST API.
-    // GraphQL API has a rate limit but it is complicated to calculate in the code.
+    // GraphQL API has a rate limit. Queries requesting rateLimit are throttled by the last budget.
     // https://docs.github.com/en/rest/using-the-rest-api/troubleshooting-the-rest-api?apiVersion=2022-11-28#timeouts
     // https://docs.github.com/en/graphql/overview/rate-limits-and-node-limits-for-the-graphql-api
 
     for retries in 0..max_retries {
         let backoff = base_backoff * 2u32.pow(retries);
         let backoff = std::cmp::min(backoff, max_backoff);
+        rate_limit::throttle(url);
         let res = client
             .post(url)
             .header("Authorization", &bearer)
@@ -145,6 +707,7 @@ fn github_api_request_with_retry<T: serde::Serialize, S: serde::de::DeserializeO
                     let Some(data) = json_value.get("data") else {
                         return Err(Error::from_str(&response_text));
                     };
+                    rate_limit::record(url, data);
                     // Valid as JSON but not expected response
                     match S::deserialize(data) {
                         Ok(deserialized) => return Ok(deserialized),
@@ -156,14 +719,13 @@ fn github_api_request_with_retry<T: serde::Serialize, S: serde::de::DeserializeO
                         }
                     }
                 }
-                // Retry if status is not success and server error.
-                if res.status().is_server_error() {
-                    continue;
+                // Retry after backoff if status is not success and server error.
+                if !res.status().is_server_error() {
+                    return Err(Error::from_str(&res.text()?));
                 }
-                return Err(Error::from_str(&res.text()?));
             }
             Err(err) => {
-                if retries >= max_retries {
+                if retries + 1 >= max_retries {
                     return Err(Error::from(err));
                 }
             }
@@ -178,3 +740,90 @@ impl From<reqwest::Error> for Error {
         Error::from_str(&e.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scopes() {
+        assert_eq!(parse_scopes("repo, read:org"), vec!["repo", "read:org"]);
+        assert!(parse_scopes("").is_empty());
+    }
+
+    #[test]
+    fn test_repositories_query() {
+        let body = RepositoriesQuery {
+            selection: "defaultBranchRef { name }",
+            fragments: "",
+        }
+        .build(&[
+            ("kitsuyui".to_string(), "mure".to_string()),
+            ("rust-lang".to_string(), "rust".to_string()),
+        ]);
+        assert_eq!(
+            body["query"],
+            "query RepositoriesQuery($owner0: String!, $name0: String!, $owner1: String!, $name1: String!) {\n  r0: repository(owner: $owner0, name: $name0) { defaultBranchRef { name } }\n  r1: repository(owner: $owner1, name: $name1) { defaultBranchRef { name } }\n}\n"
+        );
+        assert_eq!(body["variables"]["owner1"], "rust-lang");
+        assert_eq!(body["variables"]["name1"], "rust");
+        assert_eq!(body["operationName"], "RepositoriesQuery");
+    }
+
+    #[test]
+    fn test_deserialize_repositories() {
+        let data = serde_json::json!({
+            "r0": {
+                "url": "https://github.com/kitsuyui/mure",
+                "name": "mure",
+                "__typename": "Repository",
+                "defaultBranchRef": {
+                    "name": "main",
+                    "target": {
+                        "__typename": "Commit",
+                        "oid": "abc",
+                        "statusCheckRollup": { "state": "SUCCESS" }
+                    }
+                },
+                "latestRelease": null,
+                "issues": { "totalCount": 3 },
+                "pullRequests": { "totalCount": 1 }
+            },
+            "r1": null
+        });
+        let response: HashMap<
+            String,
+            Option<search_repository_query::SearchRepositoryQueryReposEdgesNodeOnRepository>,
+        > = serde_json::from_value(data).unwrap();
+        let repo = response["r0"].as_ref().unwrap();
+        assert_eq!(repo.name, "mure");
+        assert_eq!(repo.issues.total_count, 3);
+        assert_eq!(repo.pull_requests.total_count, 1);
+        assert!(response["r1"].is_none());
+    }
+
+    #[test]
+    fn test_deserialize_repository_health() {
+        let data = serde_json::json!({
+            "r0": {
+                "licenseInfo": { "spdxId": "BSD-3-Clause", "name": "BSD 3-Clause" },
+                "isArchived": false,
+                "vulnerabilityAlerts": { "totalCount": 2 }
+            },
+            "r1": { "licenseInfo": null },
+            "r2": null
+        });
+        let response: HashMap<String, Option<RepositoryHealth>> =
+            serde_json::from_value(data).unwrap();
+        let health = response["r0"].as_ref().unwrap();
+        assert_eq!(
+            health.license_info.as_ref().unwrap().spdx_id.as_deref(),
+            Some("BSD-3-Clause")
+        );
+        assert_eq!(health.is_archived, Some(false));
+        assert_eq!(health.has_vulnerability_alerts_enabled, None);
+        assert_eq!(health.vulnerability_alerts.as_ref().unwrap().total_count, 2);
+        assert_eq!(response["r1"], Some(RepositoryHealth::default()));
+        assert!(response["r2"].is_none());
+    }
+}
@@ -0,0 +1,129 @@
+//! Rate limit of GitHub GraphQL API
+//!
+//! Queries request `rateLimit { limit remaining resetAt cost }` and the last one is kept per endpoint.
+//! Requests wait for the reset when the budget is nearly exhausted instead of failing.
+//! https://docs.github.com/en/graphql/overview/rate-limits-and-node-limits-for-the-graphql-api
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_derive::Deserialize;
+
+use crate::misc::datetime::parse_timestamp;
+
+/// Points kept in reserve not to exhaust the budget by other tools sharing the token
+const RESERVE: i64 = 10;
+
+static LAST_RATE_LIMITS: Mutex<Option<HashMap<String, RateLimit>>> = Mutex::new(None);
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    pub limit: i64,
+    pub remaining: i64,
+    pub cost: i64,
+    /// e.g. 2024-05-01T12:34:56Z
+    pub reset_at: String,
+}
+
+impl RateLimit {
+    /// How long to wait before the next request. None if the budget is enough.
+    pub fn wait_duration(&self, now: SystemTime) -> Option<Duration> {
+        if self.remaining > self.cost + RESERVE {
+            return None;
+        }
+        let reset_at = UNIX_EPOCH + Duration::from_secs(parse_timestamp(&self.reset_at)?);
+        // wait a second more because the reset time is truncated to seconds
+        reset_at
+            .duration_since(now)
+            .ok()
+            .map(|d| d + Duration::from_secs(1))
+    }
+}
+
+/// Keep `rateLimit` of the response data if the query requested it
+pub fn record(url: &str, data: &serde_json::Value) {
+    let Some(rate_limit) = data.get("rateLimit") else {
+        return;
+    };
+    let Ok(rate_limit) = serde_json::from_value::<RateLimit>(rate_limit.clone()) else {
+        return;
+    };
+    tracing::debug!(url, ?rate_limit, "rate limit");
+    if let Ok(mut last) = LAST_RATE_LIMITS.lock() {
+        last.get_or_insert_with(HashMap::new)
+            .insert(url.to_string(), rate_limit);
+    }
+}
+
+/// The last rate limit recorded for the endpoint
+pub fn last(url: &str) -> Option<RateLimit> {
+    let last = LAST_RATE_LIMITS.lock().ok()?;
+    last.as_ref()?.get(url).cloned()
+}
+
+/// Sleep until the reset if the last request used up the budget of the endpoint
+pub fn throttle(url: &str) {
+    let Some(rate_limit) = last(url) else {
+        return;
+    };
+    if let Some(wait) = rate_limit.wait_duration(SystemTime::now()) {
+        eprintln!(
+            "Rate limit of {url} is nearly exhausted ({} remaining). Waiting {}s until {}",
+            rate_limit.remaining,
+            wait.as_secs(),
+            rate_limit.reset_at
+        );
+        std::thread::sleep(wait);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate_limit(remaining: i64) -> RateLimit {
+        RateLimit {
+            limit: 5000,
+            remaining,
+            cost: 1,
+            reset_at: "2024-05-01T12:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_wait_duration() {
+        let now = UNIX_EPOCH + Duration::from_secs(1714564800 - 60);
+        assert_eq!(rate_limit(4999).wait_duration(now), None);
+        assert_eq!(
+            rate_limit(5).wait_duration(now),
+            Some(Duration::from_secs(61))
+        );
+        // already reset
+        let now = UNIX_EPOCH + Duration::from_secs(1714564800 + 60);
+        assert_eq!(rate_limit(5).wait_duration(now), None);
+    }
+
+    #[test]
+    fn test_record() {
+        let url = "https://example.com/api/graphql";
+        record(
+            url,
+            &serde_json::json!({ "viewer": { "login": "kitsuyui" } }),
+        );
+        assert_eq!(last(url), None);
+        record(
+            url,
+            &serde_json::json!({
+                "rateLimit": {
+                    "limit": 5000,
+                    "remaining": 4999,
+                    "cost": 1,
+                    "resetAt": "2024-05-01T12:00:00Z",
+                }
+            }),
+        );
+        assert_eq!(last(url), Some(rate_limit(4999)));
+    }
+}
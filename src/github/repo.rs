@@ -1,6 +1,8 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+const GIST_DOMAIN: &str = "gist.github.com";
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct RepoInfo {
     pub domain: String,
@@ -17,6 +19,15 @@ impl RepoInfo {
         }
     }
 
+    /// Gist whose owner is known, e.g. by the API
+    pub fn gist(owner: &str, id: &str) -> Self {
+        RepoInfo::new(GIST_DOMAIN, owner, id)
+    }
+
+    pub fn is_gist(&self) -> bool {
+        self.domain == GIST_DOMAIN
+    }
+
     #[allow(dead_code)]
     pub fn fully_qualified_name(&self) -> String {
         format!("{}/{}/{}", self.domain, self.owner, self.repo)
@@ -26,11 +37,43 @@ impl RepoInfo {
         format!("{}/{}", self.owner, self.repo)
     }
 
+    /// e.g. git@github.com:kitsuyui/mure.git
+    /// SSH urls of gists have no owner, e.g. git@gist.github.com:0123abcd.git
+    pub fn ssh_url(&self) -> String {
+        if self.is_gist() {
+            return format!("git@{}:{}.git", self.domain, self.repo);
+        }
+        format!("git@{}:{}/{}.git", self.domain, self.owner, self.repo)
+    }
+
+    /// e.g. https://github.com/kitsuyui/mure.git
+    pub fn https_url(&self) -> String {
+        format!("https://{}/{}/{}.git", self.domain, self.owner, self.repo)
+    }
+
+    /// RepoInfo of the wiki companion repository (`<repo>.wiki`)
+    pub fn wiki(&self) -> Self {
+        RepoInfo::new(&self.domain, &self.owner, &format!("{}.wiki", self.repo))
+    }
+
+    pub fn is_wiki(&self) -> bool {
+        self.repo.ends_with(".wiki")
+    }
+
+    /// Convert repository url to its wiki url keeping the protocol.
+    /// e.g. https://github.com/kitsuyui/mure -> https://github.com/kitsuyui/mure.wiki.git
+    pub fn wiki_url(url: &str) -> String {
+        let url = url.trim_end_matches('/');
+        let url = url.strip_suffix(".git").unwrap_or(url);
+        format!("{url}.wiki.git")
+    }
+
     pub fn parse_url(url: &str) -> Option<Self> {
         let patterns = [
             GITHUB_HTTPS_URL.clone(),
             GITHUB_GIT_URL.clone(),
             GITHUB_SSH_URL.clone(),
+            GIST_HTTPS_URL.clone(),
         ];
         for pattern in patterns.iter() {
             if let Some(repo_info) = RepoInfo::parse_with_regex(pattern, url) {
@@ -39,6 +82,12 @@ impl RepoInfo {
         }
         None
     }
+    /// ID of the gist of the SSH url, which doesn't tell the owner. e.g. git@gist.github.com:0123abcd.git
+    pub fn parse_gist_ssh_url(url: &str) -> Option<String> {
+        let caps = GIST_GIT_URL.captures(url)?;
+        Some(caps.name("repo")?.as_str().to_string())
+    }
+
     fn parse_with_regex(pattern: &Regex, url: &str) -> Option<Self> {
         if let Some(caps) = pattern.captures(url) {
             let domain = caps.name("domain")?.as_str();
@@ -50,10 +99,14 @@ impl RepoInfo {
     }
 }
 
+/// Also URLs copied from the browser, e.g. https://www.github.com/kitsuyui/mure/tree/main/src
 static GITHUB_HTTPS_URL: Lazy<Regex> = Lazy::new(|| {
     #[allow(clippy::unwrap_used)]
-    Regex::new("^https?://(?P<domain>github\\.com)/(?P<owner>.*?)/(?P<repo>.*?)(/?|(?:\\.git))$")
-        .unwrap()
+    Regex::new(
+        "^https?://(?:www\\.)?(?P<domain>github\\.com)/(?P<owner>[^/?#]+)/(?P<repo>[^/?#]+?)(?:\\.git)?\
+         (?:/(?:tree|blob|commit|commits|pull|pulls|issues|releases|actions)(?:/[^?#]*)?)?/?(?:[?#].*)?$",
+    )
+    .unwrap()
 });
 
 static GITHUB_GIT_URL: Lazy<Regex> = Lazy::new(|| {
@@ -69,6 +122,21 @@ static GITHUB_SSH_URL: Lazy<Regex> = Lazy::new(|| {
     .unwrap()
 });
 
+/// e.g. https://gist.github.com/kitsuyui/0123abcd
+static GIST_HTTPS_URL: Lazy<Regex> = Lazy::new(|| {
+    #[allow(clippy::unwrap_used)]
+    Regex::new(
+        "^https?://(?P<domain>gist\\.github\\.com)/(?P<owner>[^/?#]+)/(?P<repo>[0-9a-f]+)(?:\\.git)?/?(?:[?#].*)?$",
+    )
+    .unwrap()
+});
+
+/// e.g. git@gist.github.com:0123abcd.git
+static GIST_GIT_URL: Lazy<Regex> = Lazy::new(|| {
+    #[allow(clippy::unwrap_used)]
+    Regex::new("^git@gist\\.github\\.com:(?P<repo>[0-9a-f]+)(?:\\.git)?$").unwrap()
+});
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,17 +160,113 @@ mod tests {
             tobe
         );
 
+        // copied from the browser
+        for url in [
+            "https://www.github.com/kitsuyui/mure",
+            "https://github.com/kitsuyui/mure/",
+            "https://github.com/kitsuyui/mure/tree/main/src",
+            "https://github.com/kitsuyui/mure/pull/123",
+            "https://github.com/kitsuyui/mure#readme",
+            "https://github.com/kitsuyui/mure?tab=readme-ov-file",
+        ] {
+            assert_eq!(parse(url).unwrap(), tobe, "{url}");
+        }
+
         // not match
         assert!(parse("https://github.com/").is_none());
+        assert!(parse("https://github.com/kitsuyui").is_none());
+        assert!(parse("https://github.com/kitsuyui/mure/unknown/path").is_none());
         assert!(parse("https://example.com/something/else").is_none());
         assert!(parse("git@example.com:kitsuyui/mure.git").is_none());
         assert!(parse("ssh://git@example.com/kitsuyui/mure.git").is_none());
     }
 
+    #[test]
+    fn test_parse_gist_url() {
+        let tobe = RepoInfo::new("gist.github.com", "kitsuyui", "0123abcd");
+        assert_eq!(RepoInfo::gist("kitsuyui", "0123abcd"), tobe);
+        assert!(tobe.is_gist());
+        for url in [
+            "https://gist.github.com/kitsuyui/0123abcd",
+            "https://gist.github.com/kitsuyui/0123abcd.git",
+        ] {
+            assert_eq!(RepoInfo::parse_url(url).unwrap(), tobe, "{url}");
+        }
+        assert_eq!(tobe.ssh_url(), "git@gist.github.com:0123abcd.git");
+        assert_eq!(
+            tobe.https_url(),
+            "https://gist.github.com/kitsuyui/0123abcd.git"
+        );
+        assert_eq!(
+            RepoInfo::parse_gist_ssh_url(&tobe.ssh_url()).unwrap(),
+            "0123abcd"
+        );
+        assert_eq!(RepoInfo::parse_url(&tobe.https_url()).unwrap(), tobe);
+
+        // the owner is unknown
+        assert!(RepoInfo::parse_url("git@gist.github.com:0123abcd.git").is_none());
+        assert!(RepoInfo::parse_gist_ssh_url("git@gist.github.com:0123abcd").is_some());
+        assert!(
+            RepoInfo::parse_gist_ssh_url("git@gist.github.com:kitsuyui/0123abcd.git").is_none()
+        );
+        assert!(RepoInfo::parse_gist_ssh_url("git@github.com:kitsuyui/mure.git").is_none());
+        assert!(RepoInfo::parse_url("https://gist.github.com/0123abcd").is_none());
+        assert!(RepoInfo::parse_url("https://gist.github.com/kitsuyui/not-a-gist").is_none());
+    }
+
+    #[test]
+    fn test_wiki() {
+        let repo_info = RepoInfo::new("github.com", "kitsuyui", "mure");
+        assert_eq!(repo_info.wiki().repo, "mure.wiki");
+        assert!(repo_info.wiki().is_wiki());
+        assert!(!repo_info.is_wiki());
+
+        for url in [
+            "https://github.com/kitsuyui/mure",
+            "https://github.com/kitsuyui/mure/",
+            "https://github.com/kitsuyui/mure.git",
+        ] {
+            assert_eq!(
+                RepoInfo::wiki_url(url),
+                "https://github.com/kitsuyui/mure.wiki.git"
+            );
+        }
+        assert_eq!(
+            RepoInfo::wiki_url("git@github.com:kitsuyui/mure.git"),
+            "git@github.com:kitsuyui/mure.wiki.git"
+        );
+        assert_eq!(
+            RepoInfo::parse_url("https://github.com/kitsuyui/mure.wiki.git").unwrap(),
+            repo_info.wiki()
+        );
+    }
+
     #[test]
     fn test_names() {
         let repo_info = RepoInfo::new("github.com", "kitsuyui", "mure");
         assert_eq!(repo_info.fully_qualified_name(), "github.com/kitsuyui/mure");
         assert_eq!(repo_info.name_with_owner(), "kitsuyui/mure");
     }
+
+    #[test]
+    fn test_urls() {
+        let repo_info = RepoInfo::new("github.com", "kitsuyui", "mure");
+        assert_eq!(repo_info.ssh_url(), "git@github.com:kitsuyui/mure.git");
+        assert_eq!(
+            repo_info.https_url(),
+            "https://github.com/kitsuyui/mure.git"
+        );
+        assert_eq!(
+            RepoInfo::parse_url(&repo_info.ssh_url()).unwrap(),
+            repo_info
+        );
+        assert_eq!(
+            RepoInfo::parse_url(&repo_info.https_url()).unwrap(),
+            repo_info
+        );
+        assert_eq!(
+            repo_info.wiki().ssh_url(),
+            "git@github.com:kitsuyui/mure.wiki.git"
+        );
+    }
 }
@@ -0,0 +1,271 @@
+//! Conditional requests to GitHub REST API
+//!
+//! The ETag of each response is kept with the body in the cache dir:
+//! $XDG_CACHE_HOME/mure/etag.json (or MURE_ETAG_CACHE_PATH if set).
+//! Requests send it as `If-None-Match`, and `304 Not Modified` doesn't count against the rate limit,
+//! so polling unchanged data (e.g. releases, notifications) is free.
+//! https://docs.github.com/en/rest/using-the-rest-api/best-practices-for-using-the-rest-api#use-conditional-requests-if-appropriate
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::misc::json_file;
+use crate::mure_error::Error;
+
+pub const GITHUB_REST_URL: &str = "https://api.github.com";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Responses of GET requests keyed by URL
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct EtagCache {
+    #[serde(default)]
+    pub entries: BTreeMap<String, CachedResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CachedResponse {
+    /// e.g. `W/"a1b2c3"`
+    pub etag: String,
+    pub body: String,
+}
+
+impl EtagCache {
+    /// Empty cache is returned if the file doesn't exist or is broken.
+    pub fn load() -> Result<EtagCache, Error> {
+        json_file::load_or_default(&cache_path()?)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        json_file::save(self, &cache_path()?)
+    }
+
+    /// Body of the response after the request, from the cache if the server answered it is not modified
+    fn resolve(
+        &mut self,
+        url: &str,
+        status: StatusCode,
+        etag: Option<&str>,
+        body: String,
+    ) -> Result<String, Error> {
+        if status == StatusCode::NOT_MODIFIED {
+            return match self.entries.get(url) {
+                Some(cached) => Ok(cached.body.clone()),
+                None => Err(Error::from_str(&format!(
+                    "{url} is not modified but not in the cache"
+                ))),
+            };
+        }
+        if !status.is_success() {
+            return Err(Error::from_str(&format!("{url}: {status} {body}")));
+        }
+        match etag {
+            Some(etag) => {
+                self.entries.insert(
+                    url.to_string(),
+                    CachedResponse {
+                        etag: etag.to_string(),
+                        body: body.clone(),
+                    },
+                );
+            }
+            None => {
+                self.entries.remove(url);
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// GET the URL of REST API as JSON, sending the ETag cached for it.
+/// The cache is updated in memory and the caller saves it.
+pub fn get_json<T: serde::de::DeserializeOwned>(
+    url: &str,
+    token: &str,
+    cache: &mut EtagCache,
+) -> Result<T, Error> {
+    let mut request = request(reqwest::Method::GET, url, token);
+    if let Some(cached) = cache.entries.get(url) {
+        request = request.header("If-None-Match", &cached.etag);
+    }
+    let response = request.send()?;
+    let status = response.status();
+    let etag = response
+        .headers()
+        .get("ETag")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    tracing::debug!(
+        url,
+        %status,
+        remaining = ?response.headers().get("X-RateLimit-Remaining"),
+        "rest"
+    );
+    let body = cache.resolve(url, status, etag.as_deref(), response.text()?)?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+#[derive(Deserialize)]
+struct Gist {
+    owner: GistOwner,
+}
+
+#[derive(Deserialize)]
+struct GistOwner {
+    login: String,
+}
+
+/// Owner of the gist, which its SSH url doesn't tell
+pub fn get_gist_owner(id: &str, token: &str) -> Result<String, Error> {
+    let mut cache = EtagCache::load()?;
+    let gist: Gist = get_json(&format!("{GITHUB_REST_URL}/gists/{id}"), token, &mut cache)?;
+    cache.save()?;
+    Ok(gist.owner.login)
+}
+
+/// PATCH the URL of REST API without a body, e.g. to mark a notification as read
+pub fn patch(url: &str, token: &str) -> Result<(), Error> {
+    let response = request(reqwest::Method::PATCH, url, token).send()?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::from_str(&format!(
+            "{url}: {status} {}",
+            response.text()?
+        )));
+    }
+    Ok(())
+}
+
+fn request(method: reqwest::Method, url: &str, token: &str) -> reqwest::blocking::RequestBuilder {
+    reqwest::blocking::Client::new()
+        .request(method, url)
+        .header("Authorization", format!("bearer {token}"))
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28")
+        .header("User-Agent", "mure")
+        .timeout(TIMEOUT)
+}
+
+fn cache_path() -> Result<PathBuf, Error> {
+    json_file::cache_path("MURE_ETAG_CACHE_PATH", "etag.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mktemp::Temp;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_resolve() {
+        let url = "https://api.github.com/repos/kitsuyui/mure/releases/latest";
+        let mut cache = EtagCache::default();
+        assert!(cache
+            .resolve(url, StatusCode::NOT_MODIFIED, None, String::new())
+            .is_err());
+        assert_eq!(
+            cache
+                .resolve(url, StatusCode::OK, Some("\"v1\""), "{}".to_string())
+                .unwrap(),
+            "{}"
+        );
+        assert_eq!(cache.entries[url].etag, "\"v1\"");
+        assert_eq!(
+            cache
+                .resolve(url, StatusCode::NOT_MODIFIED, None, String::new())
+                .unwrap(),
+            "{}"
+        );
+        assert!(cache
+            .resolve(url, StatusCode::NOT_FOUND, None, "Not Found".to_string())
+            .is_err());
+        // the cache is kept unless the server answers a new one
+        assert_eq!(cache.entries[url].body, "{}");
+        cache
+            .resolve(url, StatusCode::OK, None, "[]".to_string())
+            .unwrap();
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn test_get_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/repos/kitsuyui/mure",
+            listener.local_addr().unwrap()
+        );
+        // answer 304 only to the request with the ETag
+        let server = std::thread::spawn(move || {
+            let mut conditional = vec![];
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut if_none_match = None;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("if-none-match") {
+                            if_none_match = Some(value.trim().to_string());
+                        }
+                    }
+                }
+                let response = if if_none_match.as_deref() == Some("\"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                } else {
+                    let body = r#"{"name":"mure"}"#;
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+                conditional.push(if_none_match.is_some());
+            }
+            conditional
+        });
+
+        #[derive(serde_derive::Deserialize, Debug, PartialEq)]
+        struct Repository {
+            name: String,
+        }
+        let mut cache = EtagCache::default();
+        for _ in 0..2 {
+            let repository: Repository = get_json(&url, "token", &mut cache).unwrap();
+            assert_eq!(repository.name, "mure");
+        }
+        assert_eq!(server.join().unwrap(), vec![false, true]);
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let path = temp_dir.join("mure").join("etag.json");
+        assert_eq!(
+            json_file::load_or_default::<EtagCache>(&path).unwrap(),
+            EtagCache::default()
+        );
+        let mut cache = EtagCache::default();
+        cache.entries.insert(
+            "https://api.github.com/user".to_string(),
+            CachedResponse {
+                etag: "\"v1\"".to_string(),
+                body: "{}".to_string(),
+            },
+        );
+        json_file::save(&cache, &path).unwrap();
+        assert_eq!(
+            json_file::load_or_default::<EtagCache>(&path).unwrap(),
+            cache
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::Config;
 use crate::mure_error::Error;
 
 pub fn get_github_token() -> Result<String, Error> {
@@ -7,6 +8,29 @@ pub fn get_github_token() -> Result<String, Error> {
     }
 }
 
+/// Use the token in `[github] token_env` as `GH_TOKEN`, e.g. to switch tokens by profiles.
+/// Must be called at startup before other threads are spawned.
+pub fn init(config: &Config) {
+    let Some(name) = &config.github.token_env else {
+        return;
+    };
+    match std::env::var(name) {
+        Ok(token) if !token.is_empty() => std::env::set_var("GH_TOKEN", token),
+        _ => tracing::warn!("{name} of github.token_env is not set"),
+    }
+}
+
+/// Token for the host. GitHub Enterprise Server hosts use GH_ENTERPRISE_TOKEN like gh does.
+pub fn get_github_token_for(domain: &str) -> Result<String, Error> {
+    if domain == "github.com" {
+        return get_github_token();
+    }
+    match std::env::var("GH_ENTERPRISE_TOKEN") {
+        Ok(token) if !token.is_empty() => Ok(token),
+        _ => Err(Error::from_str("GH_ENTERPRISE_TOKEN is not set")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -32,4 +56,15 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "test");
     }
+
+    #[assay(
+        env = [
+          ("GH_TOKEN", "public"),
+          ("GH_ENTERPRISE_TOKEN", "enterprise"),
+        ]
+    )]
+    fn test_get_github_token_for() {
+        assert_eq!(get_github_token_for("github.com").unwrap(), "public");
+        assert_eq!(get_github_token_for("ghe.corp").unwrap(), "enterprise");
+    }
 }
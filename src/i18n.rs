@@ -0,0 +1,952 @@
+//! Message catalog of user-facing messages
+//!
+//! The language is selected by `[ui] language` of config, otherwise by `LC_ALL`, `LC_MESSAGES` or `LANG`.
+//! English is used if the language is not supported or not initialized (e.g. in tests).
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    Ja,
+}
+
+impl Language {
+    /// Language from a locale like `ja_JP.UTF-8`. `C` and `POSIX` are English.
+    pub fn from_locale(locale: &str) -> Option<Language> {
+        let code = locale.split(['_', '.', '@']).next().unwrap_or_default();
+        match code {
+            "" => None,
+            "ja" => Some(Language::Ja),
+            _ => Some(Language::En),
+        }
+    }
+
+    /// `[ui] language` is preferred to the locale of environment variables
+    pub fn resolve(config: &Config) -> Language {
+        if let Some(language) = config.ui.as_ref().and_then(|ui| ui.language) {
+            return language;
+        }
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| std::env::var(key).ok())
+            .find_map(|locale| Language::from_locale(&locale))
+            .unwrap_or_default()
+    }
+}
+
+/// Select the language of messages. Only the first call takes effect.
+pub fn init(config: &Config) {
+    let _ = LANGUAGE.set(Language::resolve(config));
+}
+
+pub fn language() -> Language {
+    LANGUAGE.get().copied().unwrap_or_default()
+}
+
+/// User-facing messages. `Display` renders it in the selected language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message<'a> {
+    NoRepositoriesFound,
+    AllOriginsMatch,
+    OriginMismatch {
+        link: &'a str,
+        origin: &'a str,
+        stored: &'a str,
+    },
+    MovedRepository {
+        from: &'a str,
+        to: &'a str,
+    },
+    Refreshing {
+        name: &'a str,
+    },
+    NotGitRepository {
+        name: &'a str,
+    },
+    NoRemote {
+        name: &'a str,
+    },
+    SwitchedTo {
+        branch: &'a str,
+    },
+    AlreadyUpToDate,
+    FastForwarded,
+    BranchFastForwarded {
+        branch: &'a str,
+    },
+    BranchAlreadyUpToDate {
+        branch: &'a str,
+    },
+    BranchNotFastForwarded {
+        branch: &'a str,
+    },
+    FastForwardedFromUpstream {
+        branch: &'a str,
+    },
+    NotFastForwardedFromUpstream {
+        branch: &'a str,
+    },
+    PushedToOrigin {
+        branch: &'a str,
+    },
+    DeletedBranch {
+        branch: &'a str,
+    },
+    SkippedDeletingMergedBranches {
+        reason: &'a str,
+    },
+    SkippedDeletingBranch {
+        branch: &'a str,
+        number: i64,
+    },
+    UpdatedSubmodule {
+        name: &'a str,
+    },
+    SkippedSubmodule {
+        url: &'a str,
+    },
+    FailedToCloneSubmodule {
+        url: &'a str,
+        reason: &'a str,
+    },
+    Archived {
+        name: &'a str,
+    },
+    Unarchived {
+        name: &'a str,
+    },
+    RecreatedBranch {
+        branch: &'a str,
+        commit: &'a str,
+    },
+    TemplateUpToDate {
+        name: &'a str,
+    },
+    CreatedBranch {
+        name: &'a str,
+        branch: &'a str,
+    },
+    PushedBranch {
+        name: &'a str,
+        branch: &'a str,
+    },
+    NothingToPush {
+        name: &'a str,
+        branch: &'a str,
+    },
+    BranchNotFound {
+        name: &'a str,
+        branch: &'a str,
+    },
+    KeptUnmergedBranch {
+        name: &'a str,
+        branch: &'a str,
+    },
+    DeletedBranchIn {
+        name: &'a str,
+        branch: &'a str,
+    },
+    BranchAlreadyExists {
+        name: &'a str,
+        branch: &'a str,
+    },
+    TemplateApplied {
+        name: &'a str,
+        files: &'a str,
+        branch: &'a str,
+    },
+    ExitedWith {
+        program: &'a str,
+        status: &'a str,
+    },
+    WouldClone {
+        url: &'a str,
+        path: &'a str,
+    },
+    WouldShareObjects {
+        path: &'a str,
+    },
+    WouldMirror {
+        url: &'a str,
+        path: &'a str,
+    },
+    UpdatedMirror {
+        path: &'a str,
+    },
+    WouldCreateSymlink {
+        link: &'a str,
+        target: &'a str,
+    },
+    WouldRemove {
+        path: &'a str,
+    },
+    Removed {
+        path: &'a str,
+    },
+    SkippedRemovingUnsaved {
+        path: &'a str,
+    },
+    SkippedRemovingUnpushed {
+        path: &'a str,
+    },
+    SkippedRemovingReferenced {
+        path: &'a str,
+    },
+    SkippedRemovingLinked {
+        path: &'a str,
+        link: &'a str,
+    },
+    Dissociated {
+        name: &'a str,
+    },
+    NotSharingObjects {
+        name: &'a str,
+    },
+    KeptProtectedBranch {
+        branch: &'a str,
+        pattern: &'a str,
+    },
+    KeptUnselectedBranch {
+        branch: &'a str,
+    },
+    KeptUnpushedBranch {
+        branch: &'a str,
+    },
+    DefaultBranchRenamed {
+        from: &'a str,
+        to: &'a str,
+    },
+    FollowedRenamedDefaultBranch {
+        from: &'a str,
+        to: &'a str,
+    },
+    NoOrphanedRepositories,
+    Cloned {
+        url: &'a str,
+    },
+    AlreadyCloned {
+        path: &'a str,
+    },
+    RemovedPartialClone {
+        path: &'a str,
+    },
+    FailedToClone {
+        url: &'a str,
+        reason: &'a str,
+    },
+    BatchCloneSummary {
+        cloned: usize,
+        skipped: usize,
+        failed: usize,
+    },
+    RefreshAllSummary {
+        updated: usize,
+        skipped: usize,
+        failed: usize,
+    },
+    CancelledByFailFast {
+        count: usize,
+    },
+    BulkFinished {
+        succeeded: usize,
+        failed: usize,
+        cancelled: usize,
+    },
+    AssignedGroups {
+        name: &'a str,
+        groups: &'a str,
+    },
+    FailedToAssignGroups {
+        name: &'a str,
+        reason: &'a str,
+    },
+    SkippedFreshRepository {
+        name: &'a str,
+        age: &'a str,
+    },
+    ConfirmGcStore {
+        path: &'a str,
+    },
+    CheckPassed {
+        name: &'a str,
+    },
+    NotCloned {
+        name: &'a str,
+        url: &'a str,
+    },
+    NoStashes,
+    CommitsToPush {
+        branch: &'a str,
+        count: usize,
+    },
+    WouldDropStash {
+        name: &'a str,
+        stash: &'a str,
+    },
+    DroppedStash {
+        name: &'a str,
+        stash: &'a str,
+        message: &'a str,
+    },
+    CheckFailed {
+        name: &'a str,
+        command: &'a str,
+    },
+    NoCheckPreset {
+        name: &'a str,
+        lang: &'a str,
+    },
+    CheckFleetSummary {
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+    },
+    NoRepositoriesToPush,
+    PermissionDenied {
+        name: &'a str,
+    },
+    CreatedRepository {
+        name: &'a str,
+    },
+    WouldCreateRepository {
+        name: &'a str,
+    },
+    CreatedLocalRepository {
+        path: &'a str,
+    },
+    WouldCreateLocalRepository {
+        path: &'a str,
+    },
+    NextStepsForRemote {
+        path: &'a str,
+        url: &'a str,
+    },
+    ConfirmPushAll {
+        count: usize,
+    },
+    SkippedPush {
+        name: &'a str,
+        branch: &'a str,
+        reason: &'a str,
+    },
+    ToolchainDrift {
+        name: &'a str,
+        file: &'a str,
+        tool: &'a str,
+        current: &'a str,
+        desired: &'a str,
+    },
+    ToolchainsPinned,
+    ToolchainUpdated {
+        name: &'a str,
+        files: &'a str,
+        branch: &'a str,
+    },
+    ToolchainUpToDateOnBranch {
+        name: &'a str,
+        branch: &'a str,
+    },
+    DefaultBranchSet {
+        name: &'a str,
+        branch: &'a str,
+    },
+    DefaultBranchUnset {
+        name: &'a str,
+    },
+    NoOpenPullRequests {
+        name: &'a str,
+    },
+    CheckedOutPullRequest {
+        number: i64,
+        branch: &'a str,
+    },
+    WouldCheckOutPullRequest {
+        number: i64,
+        branch: &'a str,
+    },
+    NoUnreadNotifications,
+    MarkedNotificationAsRead {
+        id: &'a str,
+    },
+}
+
+impl Message<'_> {
+    pub fn localize(&self, language: Language) -> String {
+        match language {
+            Language::En => self.english(),
+            Language::Ja => self.japanese(),
+        }
+    }
+
+    fn english(&self) -> String {
+        match self {
+            Message::NoRepositoriesFound => "No repositories found".to_string(),
+            Message::AllOriginsMatch => "Origins of all repositories match their paths".to_string(),
+            Message::OriginMismatch {
+                link,
+                origin,
+                stored,
+            } => format!("{link}: origin is {origin} but stored as {stored}"),
+            Message::MovedRepository { from, to } => format!("Moved {from} to {to}"),
+            Message::Refreshing { name } => format!("> Refreshing {name}"),
+            Message::NotGitRepository { name } => format!("{name} is not a git repository"),
+            Message::NoRemote { name } => format!("{name} has no remote"),
+            Message::SwitchedTo { branch } => format!("Switched to {branch}"),
+            Message::AlreadyUpToDate => "Already up to date".to_string(),
+            Message::FastForwarded => "Fast-forwarded".to_string(),
+            Message::BranchFastForwarded { branch } => format!("Fast-forwarded {branch}"),
+            Message::BranchAlreadyUpToDate { branch } => {
+                format!("{branch} is already up to date")
+            }
+            Message::BranchNotFastForwarded { branch } => {
+                format!("Could not fast-forward {branch}")
+            }
+            Message::FastForwardedFromUpstream { branch } => {
+                format!("Fast-forwarded {branch} from upstream")
+            }
+            Message::NotFastForwardedFromUpstream { branch } => {
+                format!("Could not fast-forward {branch} from upstream")
+            }
+            Message::PushedToOrigin { branch } => format!("Pushed {branch} to origin"),
+            Message::DeletedBranch { branch } => format!("Deleted branch {branch}"),
+            Message::SkippedDeletingMergedBranches { reason } => format!(
+                "Skipped deleting merged branches: failed to get open pull requests: {reason}"
+            ),
+            Message::SkippedDeletingBranch { branch, number } => {
+                format!("Skipped deleting branch {branch}: used by open pull request #{number}")
+            }
+            Message::UpdatedSubmodule { name } => format!("Updated submodule {name}"),
+            Message::SkippedSubmodule { url } => {
+                format!("Skipped submodule {url}: not a GitHub repository")
+            }
+            Message::FailedToCloneSubmodule { url, reason } => {
+                format!("Failed to clone submodule {url}: {reason}")
+            }
+            Message::Archived { name } => format!("Archived {name}"),
+            Message::Unarchived { name } => format!("Unarchived {name}"),
+            Message::RecreatedBranch { branch, commit } => {
+                format!("Recreated branch {branch} at {commit}")
+            }
+            Message::TemplateUpToDate { name } => format!("{name}: already up to date"),
+            Message::CreatedBranch { name, branch } => format!("{name}: created {branch}"),
+            Message::PushedBranch { name, branch } => format!("{name}: pushed {branch}"),
+            Message::NothingToPush { name, branch } => {
+                format!("{name}: {branch} has no commits to push")
+            }
+            Message::BranchNotFound { name, branch } => format!("{name}: {branch} is not found"),
+            Message::KeptUnmergedBranch { name, branch } => {
+                format!("{name}: kept {branch} because it is not merged yet")
+            }
+            Message::DeletedBranchIn { name, branch } => format!("{name}: deleted {branch}"),
+            Message::BranchAlreadyExists { name, branch } => {
+                format!("{name}: {branch} already exists")
+            }
+            Message::TemplateApplied {
+                name,
+                files,
+                branch,
+            } => format!("{name}: applied {files} on {branch}"),
+            Message::ExitedWith { program, status } => format!("{program} exited with {status}"),
+            Message::WouldClone { url, path } => format!("(dry-run) clone {url} into {path}"),
+            Message::WouldShareObjects { path } => {
+                format!("(dry-run) share objects with {path}")
+            }
+            Message::WouldMirror { url, path } => {
+                format!("(dry-run) mirror {url} into {path}")
+            }
+            Message::UpdatedMirror { path } => format!("Updated mirror {path}"),
+            Message::WouldCreateSymlink { link, target } => {
+                format!("(dry-run) create symlink {link} -> {target}")
+            }
+            Message::WouldRemove { path } => format!("(dry-run) remove {path}"),
+            Message::Removed { path } => format!("Removed {path}"),
+            Message::SkippedRemovingUnsaved { path } => {
+                format!("Skipped removing {path}: it has uncommitted changes")
+            }
+            Message::SkippedRemovingUnpushed { path } => {
+                format!("Skipped removing {path}: it has unpushed commits, local-only branches or stashes")
+            }
+            Message::SkippedRemovingReferenced { path } => {
+                format!("Skipped removing {path}: other repositories share its objects (see `mure dissociate`)")
+            }
+            Message::SkippedRemovingLinked { path, link } => {
+                format!("Skipped removing {path}: {link} still links to it")
+            }
+            Message::Dissociated { name } => {
+                format!("{name} no longer shares objects with other repositories")
+            }
+            Message::NotSharingObjects { name } => {
+                format!("{name} doesn't share objects with other repositories")
+            }
+            Message::KeptProtectedBranch { branch, pattern } => {
+                format!("Kept branch {branch}: protected by {pattern}")
+            }
+            Message::KeptUnselectedBranch { branch } => {
+                format!("Kept branch {branch}: not matching delete-only patterns")
+            }
+            Message::KeptUnpushedBranch { branch } => {
+                format!("Kept branch {branch}: it has unpushed commits")
+            }
+            Message::DefaultBranchRenamed { from, to } => format!(
+                "The default branch of origin was renamed from {from} to {to}. Run with --fix-head to follow it"
+            ),
+            Message::FollowedRenamedDefaultBranch { from, to } => {
+                format!("Followed the default branch renamed from {from} to {to}")
+            }
+            Message::NoOrphanedRepositories => {
+                "No repositories in the store without symlinks".to_string()
+            }
+            Message::Cloned { url } => format!("Cloned {url}"),
+            Message::AlreadyCloned { path } => format!("Already cloned in {path}"),
+            Message::RemovedPartialClone { path } => {
+                format!("Removed {path}: the previous clone was interrupted")
+            }
+            Message::FailedToClone { url, reason } => format!("Failed to clone {url}: {reason}"),
+            Message::BatchCloneSummary {
+                cloned,
+                skipped,
+                failed,
+            } => format!("{cloned} cloned, {skipped} skipped (already cloned), {failed} failed"),
+            Message::RefreshAllSummary {
+                updated,
+                skipped,
+                failed,
+            } => format!("{updated} updated, {skipped} skipped, {failed} failed"),
+            Message::CancelledByFailFast { count } => {
+                format!("{count} not started because of --fail-fast")
+            }
+            Message::BulkFinished {
+                succeeded,
+                failed,
+                cancelled: 0,
+            } => format!("{succeeded} succeeded, {failed} failed"),
+            Message::BulkFinished {
+                succeeded,
+                failed,
+                cancelled,
+            } => format!("{succeeded} succeeded, {failed} failed, {cancelled} not started"),
+            Message::AssignedGroups { name, groups } => format!("Added {name} to {groups}"),
+            Message::FailedToAssignGroups { name, reason } => {
+                format!("Failed to assign groups to {name}: {reason}")
+            }
+            Message::SkippedFreshRepository { name, age } => {
+                format!("Skipped {name}: refreshed {age} ago")
+            }
+            Message::ConfirmGcStore { path } => {
+                format!("{path}: [d]elete, [a]rchive or [s]kip? [s] ")
+            }
+            Message::CheckPassed { name } => format!("[OK] {name}"),
+            Message::CheckFailed { name, command } => format!("[FAIL] {name}: {command}"),
+            Message::NoCheckPreset { name, lang } => {
+                format!("[SKIP] {name}: no checks for {lang}")
+            }
+            Message::NotCloned { name, url } => {
+                format!("{name} is not cloned yet. run: mure clone {url}")
+            }
+            Message::NoStashes => "No stashes found".to_string(),
+            Message::CommitsToPush { branch, count } => {
+                format!("{branch} has {count} commits to push")
+            }
+            Message::WouldDropStash { name, stash } => format!("(dry-run) drop {stash} of {name}"),
+            Message::DroppedStash {
+                name,
+                stash,
+                message,
+            } => format!("Dropped {stash} of {name} ({message})"),
+            Message::CheckFleetSummary {
+                passed,
+                failed,
+                skipped,
+            } => format!("{passed} passed, {failed} failed, {skipped} skipped"),
+            Message::NoRepositoriesToPush => "No repositories to push".to_string(),
+            Message::PermissionDenied { name } => format!("{name}: skipped (permission denied)"),
+            Message::CreatedRepository { name } => format!("Created {name} on GitHub"),
+            Message::WouldCreateRepository { name } => {
+                format!("(dry-run) create {name} on GitHub")
+            }
+            Message::CreatedLocalRepository { path } => format!("Created {path}"),
+            Message::WouldCreateLocalRepository { path } => format!("(dry-run) create {path}"),
+            Message::NextStepsForRemote { path, url } => format!(
+                "Next, add a remote and push it:\n  git -C {path} remote add origin {url}\n  git -C {path} push -u origin HEAD\nor run `mure new` with --github to create it on GitHub"
+            ),
+            Message::ConfirmPushAll { count } => format!("Push {count} repositories? [y/N] "),
+            Message::SkippedPush {
+                name,
+                branch,
+                reason,
+            } => format!("{name}: skipped {branch} ({reason})"),
+            Message::ToolchainDrift {
+                name,
+                file,
+                tool,
+                current,
+                desired,
+            } => format!("{name}: {file} pins {tool} {current} (desired: {desired})"),
+            Message::ToolchainsPinned => {
+                "All toolchains are pinned to the desired versions".to_string()
+            }
+            Message::ToolchainUpdated {
+                name,
+                files,
+                branch,
+            } => format!("{name}: updated {files} on {branch}"),
+            Message::ToolchainUpToDateOnBranch { name, branch } => {
+                format!("{name}: {branch} already has the pinned versions")
+            }
+            Message::DefaultBranchSet { name, branch } => {
+                format!("{name}: the default branch is {branch} from now on")
+            }
+            Message::DefaultBranchUnset { name } => {
+                format!("{name}: the default branch is detected from origin again")
+            }
+            Message::NoOpenPullRequests { name } => format!("{name} has no open pull requests"),
+            Message::CheckedOutPullRequest { number, branch } => {
+                format!("Checked out pull request #{number} as {branch}")
+            }
+            Message::WouldCheckOutPullRequest { number, branch } => {
+                format!("Would check out pull request #{number} as {branch}")
+            }
+            Message::NoUnreadNotifications => "No unread notifications".to_string(),
+            Message::MarkedNotificationAsRead { id } => format!("Marked {id} as read"),
+        }
+    }
+
+    fn japanese(&self) -> String {
+        match self {
+            Message::NoRepositoriesFound => "リポジトリが見つかりません".to_string(),
+            Message::AllOriginsMatch => {
+                "すべてのリポジトリの origin はパスと一致しています".to_string()
+            }
+            Message::OriginMismatch {
+                link,
+                origin,
+                stored,
+            } => format!("{link}: origin は {origin} ですが {stored} として保存されています"),
+            Message::MovedRepository { from, to } => format!("{from} を {to} に移動しました"),
+            Message::Refreshing { name } => format!("> {name} を更新しています"),
+            Message::NotGitRepository { name } => format!("{name} は git リポジトリではありません"),
+            Message::NoRemote { name } => format!("{name} にはリモートがありません"),
+            Message::SwitchedTo { branch } => format!("{branch} に切り替えました"),
+            Message::AlreadyUpToDate => "すでに最新です".to_string(),
+            Message::FastForwarded => "fast-forward しました".to_string(),
+            Message::BranchFastForwarded { branch } => {
+                format!("{branch} を fast-forward しました")
+            }
+            Message::BranchAlreadyUpToDate { branch } => format!("{branch} はすでに最新です"),
+            Message::BranchNotFastForwarded { branch } => {
+                format!("{branch} を fast-forward できませんでした")
+            }
+            Message::FastForwardedFromUpstream { branch } => {
+                format!("{branch} を upstream から fast-forward しました")
+            }
+            Message::NotFastForwardedFromUpstream { branch } => {
+                format!("{branch} を upstream から fast-forward できませんでした")
+            }
+            Message::PushedToOrigin { branch } => format!("{branch} を origin に push しました"),
+            Message::DeletedBranch { branch } => format!("ブランチ {branch} を削除しました"),
+            Message::SkippedDeletingMergedBranches { reason } => format!(
+                "オープンなプルリクエストを取得できなかったため、マージ済みブランチの削除をスキップしました: {reason}"
+            ),
+            Message::SkippedDeletingBranch { branch, number } => format!(
+                "ブランチ {branch} はオープンなプルリクエスト #{number} で使われているため削除をスキップしました"
+            ),
+            Message::UpdatedSubmodule { name } => format!("サブモジュール {name} を更新しました"),
+            Message::SkippedSubmodule { url } => {
+                format!("サブモジュール {url} は GitHub のリポジトリではないためスキップしました")
+            }
+            Message::FailedToCloneSubmodule { url, reason } => {
+                format!("サブモジュール {url} をクローンできませんでした: {reason}")
+            }
+            Message::Archived { name } => format!("{name} をアーカイブしました"),
+            Message::Unarchived { name } => format!("{name} のアーカイブを解除しました"),
+            Message::RecreatedBranch { branch, commit } => {
+                format!("ブランチ {branch} を {commit} に復元しました")
+            }
+            Message::TemplateUpToDate { name } => format!("{name}: すでに最新です"),
+            Message::CreatedBranch { name, branch } => format!("{name}: {branch} を作成しました"),
+            Message::PushedBranch { name, branch } => format!("{name}: {branch} をプッシュしました"),
+            Message::NothingToPush { name, branch } => {
+                format!("{name}: {branch} にプッシュするコミットはありません")
+            }
+            Message::BranchNotFound { name, branch } => {
+                format!("{name}: {branch} が見つかりません")
+            }
+            Message::KeptUnmergedBranch { name, branch } => {
+                format!("{name}: {branch} はまだマージされていないため残しました")
+            }
+            Message::DeletedBranchIn { name, branch } => {
+                format!("{name}: {branch} を削除しました")
+            }
+            Message::BranchAlreadyExists { name, branch } => {
+                format!("{name}: {branch} はすでに存在します")
+            }
+            Message::TemplateApplied {
+                name,
+                files,
+                branch,
+            } => format!("{name}: {branch} に {files} を適用しました"),
+            Message::ExitedWith { program, status } => {
+                format!("{program} が終了しました ({status})")
+            }
+            Message::WouldClone { url, path } => {
+                format!("(dry-run) {url} を {path} にクローンします")
+            }
+            Message::WouldShareObjects { path } => {
+                format!("(dry-run) {path} とオブジェクトを共有します")
+            }
+            Message::WouldMirror { url, path } => {
+                format!("(dry-run) {url} を {path} にミラーします")
+            }
+            Message::UpdatedMirror { path } => format!("ミラー {path} を更新しました"),
+            Message::WouldCreateSymlink { link, target } => {
+                format!("(dry-run) シンボリックリンク {link} -> {target} を作成します")
+            }
+            Message::WouldRemove { path } => format!("(dry-run) {path} を削除します"),
+            Message::Removed { path } => format!("{path} を削除しました"),
+            Message::SkippedRemovingUnsaved { path } => {
+                format!("{path} にはコミットされていない変更があるため削除をスキップしました")
+            }
+            Message::SkippedRemovingUnpushed { path } => {
+                format!("{path} にはプッシュされていないコミット、ローカルのみのブランチまたはスタッシュがあるため削除をスキップしました")
+            }
+            Message::SkippedRemovingReferenced { path } => {
+                format!("{path} のオブジェクトは他のリポジトリに共有されているため削除をスキップしました (`mure dissociate` を参照)")
+            }
+            Message::SkippedRemovingLinked { path, link } => {
+                format!("{link} がまだリンクしているため {path} の削除をスキップしました")
+            }
+            Message::Dissociated { name } => {
+                format!("{name} は他のリポジトリとオブジェクトを共有しなくなりました")
+            }
+            Message::NotSharingObjects { name } => {
+                format!("{name} は他のリポジトリとオブジェクトを共有していません")
+            }
+            Message::KeptProtectedBranch { branch, pattern } => {
+                format!("ブランチ {branch} は {pattern} で保護されているため残しました")
+            }
+            Message::KeptUnselectedBranch { branch } => {
+                format!("ブランチ {branch} は削除対象のパターンに一致しないため残しました")
+            }
+            Message::KeptUnpushedBranch { branch } => {
+                format!("ブランチ {branch} にはプッシュされていないコミットがあるため残しました")
+            }
+            Message::DefaultBranchRenamed { from, to } => format!(
+                "origin のデフォルトブランチが {from} から {to} に変更されました。--fix-head を付けて実行すると追従します"
+            ),
+            Message::FollowedRenamedDefaultBranch { from, to } => {
+                format!("デフォルトブランチの変更 ({from} -> {to}) に追従しました")
+            }
+            Message::NoOrphanedRepositories => {
+                "シンボリックリンクのないリポジトリはストアにありません".to_string()
+            }
+            Message::Cloned { url } => format!("{url} をクローンしました"),
+            Message::AlreadyCloned { path } => format!("{path} にクローン済みです"),
+            Message::RemovedPartialClone { path } => {
+                format!("中断されたクローン {path} を削除しました")
+            }
+            Message::FailedToClone { url, reason } => {
+                format!("{url} をクローンできませんでした: {reason}")
+            }
+            Message::BatchCloneSummary {
+                cloned,
+                skipped,
+                failed,
+            } => format!(
+                "クローン {cloned} 件、スキップ (クローン済み) {skipped} 件、失敗 {failed} 件"
+            ),
+            Message::RefreshAllSummary {
+                updated,
+                skipped,
+                failed,
+            } => format!("更新 {updated} 件、スキップ {skipped} 件、失敗 {failed} 件"),
+            Message::CancelledByFailFast { count } => {
+                format!("--fail-fast により {count} 件を実行しませんでした")
+            }
+            Message::BulkFinished {
+                succeeded,
+                failed,
+                cancelled: 0,
+            } => format!("成功 {succeeded} 件、失敗 {failed} 件"),
+            Message::BulkFinished {
+                succeeded,
+                failed,
+                cancelled,
+            } => format!("成功 {succeeded} 件、失敗 {failed} 件、未実行 {cancelled} 件"),
+            Message::AssignedGroups { name, groups } => {
+                format!("{name} を {groups} に追加しました")
+            }
+            Message::FailedToAssignGroups { name, reason } => {
+                format!("{name} のグループを割り当てられませんでした: {reason}")
+            }
+            Message::SkippedFreshRepository { name, age } => {
+                format!("{name} は {age} 前に更新済みのためスキップしました")
+            }
+            Message::ConfirmGcStore { path } => {
+                format!("{path}: 削除 [d] / アーカイブ [a] / スキップ [s] [s] ")
+            }
+            Message::CheckPassed { name } => format!("[OK] {name}"),
+            Message::CheckFailed { name, command } => format!("[FAIL] {name}: {command}"),
+            Message::NoCheckPreset { name, lang } => {
+                format!("[SKIP] {name}: {lang} のチェックはありません")
+            }
+            Message::NotCloned { name, url } => {
+                format!("{name} はまだクローンされていません。実行: mure clone {url}")
+            }
+            Message::NoStashes => "stash はありません".to_string(),
+            Message::CommitsToPush { branch, count } => {
+                format!("{branch} に push されていないコミットが {count} 件あります")
+            }
+            Message::WouldDropStash { name, stash } => {
+                format!("(dry-run) {name} の {stash} を削除")
+            }
+            Message::DroppedStash {
+                name,
+                stash,
+                message,
+            } => format!("{name} の {stash} を削除しました ({message})"),
+            Message::CheckFleetSummary {
+                passed,
+                failed,
+                skipped,
+            } => format!("成功 {passed} 件、失敗 {failed} 件、スキップ {skipped} 件"),
+            Message::NoRepositoriesToPush => "プッシュするリポジトリはありません".to_string(),
+            Message::PermissionDenied { name } => {
+                format!("{name}: スキップしました (権限がありません)")
+            }
+            Message::CreatedRepository { name } => format!("GitHub に {name} を作成しました"),
+            Message::WouldCreateRepository { name } => {
+                format!("(dry-run) GitHub に {name} を作成")
+            }
+            Message::CreatedLocalRepository { path } => format!("{path} を作成しました"),
+            Message::WouldCreateLocalRepository { path } => format!("(dry-run) {path} を作成"),
+            Message::NextStepsForRemote { path, url } => format!(
+                "次にリモートを追加して push してください:\n  git -C {path} remote add origin {url}\n  git -C {path} push -u origin HEAD\nGitHub に作成するには `mure new` に --github を付けて実行してください"
+            ),
+            Message::ConfirmPushAll { count } => {
+                format!("{count} 件のリポジトリをプッシュしますか? [y/N] ")
+            }
+            Message::SkippedPush {
+                name,
+                branch,
+                reason,
+            } => format!("{name}: {branch} をスキップしました ({reason})"),
+            Message::ToolchainDrift {
+                name,
+                file,
+                tool,
+                current,
+                desired,
+            } => format!("{name}: {file} の {tool} は {current} です (指定: {desired})"),
+            Message::ToolchainsPinned => {
+                "すべてのツールチェーンが指定のバージョンです".to_string()
+            }
+            Message::ToolchainUpdated {
+                name,
+                files,
+                branch,
+            } => format!("{name}: {branch} で {files} を更新しました"),
+            Message::ToolchainUpToDateOnBranch { name, branch } => {
+                format!("{name}: {branch} はすでにバージョンが固定されています")
+            }
+            Message::DefaultBranchSet { name, branch } => {
+                format!("{name}: 今後はデフォルトブランチを {branch} とします")
+            }
+            Message::DefaultBranchUnset { name } => {
+                format!("{name}: デフォルトブランチを origin から再び判定します")
+            }
+            Message::NoOpenPullRequests { name } => {
+                format!("{name} にオープンなプルリクエストはありません")
+            }
+            Message::CheckedOutPullRequest { number, branch } => {
+                format!("プルリクエスト #{number} を {branch} にチェックアウトしました")
+            }
+            Message::WouldCheckOutPullRequest { number, branch } => {
+                format!("プルリクエスト #{number} を {branch} にチェックアウトします")
+            }
+            Message::NoUnreadNotifications => "未読の通知はありません".to_string(),
+            Message::MarkedNotificationAsRead { id } => format!("{id} を既読にしました"),
+        }
+    }
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.localize(language()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assay::assay;
+
+    fn config_with(ui: &str) -> Config {
+        toml::from_str(&format!(
+            r#"
+            [core]
+            base_dir = "~/.dev"
+
+            [github]
+            username = "kitsuyui"
+
+            [shell]
+            cd_shims = "mucd"
+
+            {ui}
+        "#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Language::from_locale("ja_JP.UTF-8"), Some(Language::Ja));
+        assert_eq!(Language::from_locale("ja"), Some(Language::Ja));
+        assert_eq!(Language::from_locale("en_US.UTF-8"), Some(Language::En));
+        assert_eq!(Language::from_locale("C"), Some(Language::En));
+        assert_eq!(Language::from_locale(""), None);
+    }
+
+    #[assay(
+        env = [
+          ("LC_ALL", ""),
+          ("LC_MESSAGES", ""),
+          ("LANG", "ja_JP.UTF-8"),
+        ]
+      )]
+    fn test_resolve() {
+        assert_eq!(Language::resolve(&config_with("")), Language::Ja);
+        assert_eq!(
+            Language::resolve(&config_with("[ui]\nlanguage = \"en\"")),
+            Language::En
+        );
+        std::env::set_var("LC_ALL", "C");
+        assert_eq!(Language::resolve(&config_with("")), Language::En);
+        assert_eq!(
+            Language::resolve(&config_with("[ui]\nlanguage = \"ja\"")),
+            Language::Ja
+        );
+    }
+
+    #[test]
+    fn test_localize() {
+        let message = Message::DeletedBranch { branch: "feature" };
+        assert_eq!(message.localize(Language::En), "Deleted branch feature");
+        assert_eq!(
+            message.localize(Language::Ja),
+            "ブランチ feature を削除しました"
+        );
+        // English unless initialized
+        assert_eq!(message.to_string(), "Deleted branch feature");
+    }
+}
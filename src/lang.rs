@@ -0,0 +1,44 @@
+//! Primary toolchain of a repository detected from files at its root, without the GitHub API
+
+use std::path::Path;
+
+/// Manifest files and the language they indicate, in order of priority
+const MANIFESTS: [(&str, &str); 6] = [
+    ("Cargo.toml", "rust"),
+    ("go.mod", "go"),
+    ("pyproject.toml", "python"),
+    ("setup.py", "python"),
+    ("tsconfig.json", "typescript"),
+    ("package.json", "javascript"),
+];
+
+/// Language of repositories without any known manifest
+pub const UNKNOWN: &str = "unknown";
+
+/// Language like `rust` or `python` of the repository at the path
+pub fn detect(path: &Path) -> &'static str {
+    MANIFESTS
+        .iter()
+        .find(|(manifest, _)| path.join(manifest).is_file())
+        .map_or(UNKNOWN, |(_, lang)| lang)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_detect() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let path = temp_dir.as_path();
+        assert_eq!(detect(path), UNKNOWN);
+        std::fs::write(path.join("package.json"), "{}").unwrap();
+        assert_eq!(detect(path), "javascript");
+        std::fs::write(path.join("tsconfig.json"), "{}").unwrap();
+        assert_eq!(detect(path), "typescript");
+        // e.g. a Rust project with a web frontend
+        std::fs::write(path.join("Cargo.toml"), "").unwrap();
+        assert_eq!(detect(path), "rust");
+    }
+}
@@ -0,0 +1,71 @@
+//! Logging of mure
+//!
+//! Logs are diagnostics for long bulk operations such as `refresh --all`.
+//! They are written to the file of `--log-file`, or to stderr when only `MURE_LOG` is set.
+//! `MURE_LOG` controls the level in the syntax of `RUST_LOG` (e.g. `MURE_LOG=debug`).
+
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
+
+use tracing_subscriber::EnvFilter;
+
+use crate::mure_error::Error;
+
+const LOG_ENV: &str = "MURE_LOG";
+
+/// Default level when `MURE_LOG` is not set
+const DEFAULT_LEVEL: &str = "info";
+
+pub fn init(log_file: Option<&Path>) -> Result<(), Error> {
+    let level = std::env::var(LOG_ENV).ok();
+    let filter = EnvFilter::try_new(level.as_deref().unwrap_or(DEFAULT_LEVEL))
+        .map_err(|e| Error::from_str(&format!("invalid {LOG_ENV}: {e}")))?;
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    let result = match (log_file, level) {
+        (Some(path), _) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            builder
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .try_init()
+        }
+        (None, Some(_)) => builder.with_writer(std::io::stderr).try_init(),
+        // Logging is disabled by default not to mix with the output
+        (None, None) => return Ok(()),
+    };
+    result.map_err(|e| Error::from_str(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assay::assay;
+    use mktemp::Temp;
+
+    #[assay(
+        env = [
+            ("MURE_LOG", "debug"),
+        ]
+    )]
+    fn test_init_with_log_file() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let path = temp_dir.as_path().join("mure.log");
+        init(Some(&path)).unwrap();
+        let span = tracing::info_span!("refresh", repo = "mure");
+        let _enter = span.enter();
+        tracing::debug!("Fast-forwarded");
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("refresh{repo=\"mure\"}"));
+        assert!(content.contains("Fast-forwarded"));
+    }
+
+    #[assay(
+        env = [
+            ("MURE_LOG", "no=such=level"),
+        ]
+    )]
+    fn test_init_invalid_level() {
+        assert!(init(None).is_err());
+    }
+}
@@ -1,33 +1,84 @@
-use crate::app::{issues::show_issues_main, refresh::refresh_main};
-use clap::{command, ArgGroup, CommandFactory, Parser, Subcommand};
-use clap_complete::{generate, Shell};
+use crate::app::{issues::show_issues_main, list::RepoFilter, refresh::refresh_main};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use event::OutputFormat;
+use misc::bulk::BulkOptions;
+use misc::output::OutputMode;
 use verbosity::Verbosity;
 use Commands::*;
 
 mod app;
 mod codecov;
 mod config;
+mod credentials;
+mod event;
 mod gh;
 mod git;
 mod github;
+mod i18n;
+mod lang;
+mod logging;
 mod misc;
 mod mure_error;
+mod notification;
+mod provider;
+mod refresh_cache;
+mod remote_cache;
+mod state;
 mod verbosity;
 
 #[cfg(test)]
 mod test_fixture;
 
 fn main() -> Result<(), mure_error::Error> {
+    let args = std::env::args().collect::<Vec<_>>();
+    // before loading config. Children like external commands inherit the profile.
+    if let Some(profile) = config::profile_arg(&args) {
+        std::env::set_var(config::PROFILE_ENV, profile);
+    }
     let config = app::initialize::get_config_or_initialize()?;
-    let cli = Cli::parse();
+    i18n::init(&config);
+    credentials::init(&config);
+    github::token::init(&config);
+    let builtins = Cli::command()
+        .get_subcommands()
+        .flat_map(|command| std::iter::once(command.get_name()).chain(command.get_all_aliases()))
+        .chain(["help"])
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let aliases = config.alias.clone().unwrap_or_default();
+    let args = match app::alias::expand(&aliases, &builtins, args) {
+        app::alias::Expansion::Args(args) => args,
+        app::alias::Expansion::Shell { command, args } => {
+            std::process::exit(app::alias::run_shell(&config, &command, &args)?)
+        }
+    };
+    if let Some((program, args)) = app::external::find_command(&builtins, &args) {
+        std::process::exit(app::external::run(&config, &program, &args)?)
+    }
+    let cli = Cli::parse_from(args);
+    if let Err(e) = logging::init(cli.log_file.as_deref()) {
+        eprintln!("{e}");
+    }
+    let verbosity = Verbosity::from_flags(cli.quiet, cli.verbose);
+    Verbosity::set(verbosity);
     let mut command = Cli::command();
     let name = command.get_name().to_string();
 
     match cli.command {
-        Init { shell: true } => {
+        Init {
+            shell: true,
+            widget,
+        } => {
             println!("{}", app::path::shell_shims(&config));
+            if let Some(widget) = widget {
+                match app::path::shell_widget(widget) {
+                    Ok(widget) => println!("{widget}"),
+                    Err(e) => eprintln!("{e}"),
+                }
+            }
         }
-        Init { shell: false } => match app::initialize::init() {
+        Init { shell: false, .. } => match app::initialize::init() {
             Ok(_) => {
                 println!("Initialized config file");
             }
@@ -35,41 +86,498 @@ fn main() -> Result<(), mure_error::Error> {
                 println!("{e}");
             }
         },
-        Completion { shell } => {
-            generate(shell, &mut command, name, &mut std::io::stdout());
+        Completion {
+            list_names: true, ..
+        } => match app::path::candidates(&config) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Completion { shell, .. } => {
+            if let Some(shell) = shell {
+                match app::completion::completion(shell, &mut command, &name) {
+                    Ok(_) => (),
+                    Err(e) => println!("{e}"),
+                }
+            }
         }
         Refresh {
             repository,
             all,
-            verbose,
-            quiet,
+            mode,
+            keep_current_branch,
+            no_delete,
+            delete_only,
+            protect,
+            stale,
+            json_lines,
+            tag,
+            filter,
+            bulk,
+            fix_head,
+            no_hooks,
+            notify,
+            dry_run,
+        } => {
+            git::DryRun::set(dry_run);
+            let format = OutputFormat::from_bool(json_lines);
+            let mut options = app::refresh::RefreshOptions::from_config(&config);
+            if let Some(mode) = mode {
+                options.apply_mode(mode);
+            }
+            options.keep_current_branch |= keep_current_branch;
+            options.keep_merged_branches |= no_delete;
+            if !delete_only.is_empty() {
+                options.delete_only = delete_only;
+            }
+            options.protect_branches.extend(protect);
+            options.stale = stale;
+            options.fix_head = fix_head;
+            if no_hooks {
+                options.skip_hooks = true;
+            }
+            let summary = refresh_main(
+                &config,
+                all,
+                repository,
+                &options,
+                format,
+                &RepoFilter::parse(tag, &filter)?,
+                &BulkOptions::from(bulk),
+            )?;
+            if all {
+                notification::notify_finished(&config, notify, "refresh", &summary);
+            }
+            if !summary.is_success() {
+                std::process::exit(summary.exit_code());
+            }
+        }
+        Issues {
+            query,
+            org,
+            team,
+            assigned,
+            review_requested,
+            local,
+            rate_limit,
+            columns,
+            sort,
+            reverse,
+            limit,
+        } => {
+            let queues = [
+                (review_requested, app::issues::WorkQueue::ReviewRequested),
+                (assigned, app::issues::WorkQueue::Assigned),
+            ]
+            .into_iter()
+            .filter_map(|(enabled, queue)| enabled.then_some(queue))
+            .collect::<Vec<_>>();
+            if queues.is_empty() {
+                let options = app::issues::TableOptions {
+                    columns,
+                    sort,
+                    reverse,
+                    limit,
+                };
+                show_issues_main(&config, &query, org, team, local, rate_limit, &options)?;
+            } else if let Err(e) = app::issues::show_work_queue(&config, &queues) {
+                println!("{e}");
+            }
+        }
+        Coverage => match app::coverage::coverage(&config) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Doctor { network } => match app::doctor::doctor(&config, network) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Notify { command } => {
+            let result = match command {
+                NotifyCommands::List { all } => app::notify::list(&config, all),
+                NotifyCommands::Read { ids } => app::notify::read(&ids),
+                NotifyCommands::Open { id } => app::notify::open(&id),
+            };
+            match result {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Pr {
+            command: PrCommands::List { name },
+        } => match app::pr::list(&config, &name) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Pr {
+            command: PrCommands::Checkout { name, number },
+        } => match app::pr::checkout(&config, &name, number) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Token {
+            command: TokenCommands::Check,
+        } => {
+            if let Err(e) = app::token::check(&config) {
+                println!("{e}");
+                std::process::exit(1);
+            }
+        }
+        Selftest { url, keep } => match app::selftest::selftest(url.as_deref(), keep) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Export { format } => match app::manifest::export(&config, format) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Import {
+            format,
+            path,
+            bulk,
+            dry_run,
+        } => {
+            git::DryRun::set(dry_run);
+            let bulk = BulkOptions::from(bulk);
+            match app::manifest::import(&config, format, &path, &bulk) {
+                Ok(summary) if !summary.is_success() => std::process::exit(summary.exit_code()),
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Create {
+            name,
+            template,
+            public,
+            dry_run,
         } => {
-            let verbosity = Verbosity::from_bools(quiet, verbose);
-            refresh_main(&config, all, repository, verbosity)?;
+            git::DryRun::set(dry_run);
+            match app::create::create(&config, &name, template.as_deref(), public, verbosity) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
         }
-        Issues { query } => {
-            show_issues_main(&config, &query)?;
+        New {
+            name,
+            template,
+            github,
+            public,
+            dry_run,
+        } => {
+            git::DryRun::set(dry_run);
+            match app::new::new(&config, &name, template.as_deref(), github, public) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
         }
         Clone {
             url,
-            quiet,
-            verbose,
+            from_file,
+            bulk,
+            with_wiki,
+            recurse_submodules,
+            reference,
+            auto_reference,
+            into,
+            register_submodules,
+            update,
+            dry_run,
+        } => {
+            git::DryRun::set(dry_run);
+            let mut options = app::clone::CloneOptions::from_config(&config);
+            options.update = update;
+            options.recurse_submodules |= recurse_submodules;
+            options.register_submodules |= register_submodules;
+            options.auto_reference |= auto_reference;
+            options.into = into;
+            if reference.is_some() {
+                options.reference = reference;
+                options.auto_reference = false;
+            }
+            if let Some(path) = from_file {
+                let bulk = BulkOptions::from(bulk);
+                match app::clone::clone_from_file(&config, &path, &options, &bulk) {
+                    Ok(summary) if !summary.is_success() => std::process::exit(summary.exit_code()),
+                    Ok(_) => (),
+                    Err(e) => println!("{e}"),
+                }
+            }
+            if let Some(url) = url {
+                match app::clone::clone(&config, &url, &options, verbosity) {
+                    Ok(_) => (),
+                    Err(e) => println!("{e}"),
+                }
+                if with_wiki {
+                    match app::clone::clone_wiki(&config, &url, verbosity) {
+                        Ok(_) => (),
+                        Err(e) => println!("{e}"),
+                    }
+                }
+            }
+        }
+        Remove {
+            name,
+            force,
+            dry_run,
+        } => {
+            git::DryRun::set(dry_run);
+            match app::remove::remove(&config, &name, force) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        BranchCreate {
+            name,
+            repos,
+            switch,
+            dry_run,
+        } => {
+            git::DryRun::set(dry_run);
+            match app::branch_set::branch_create(&config, &name, &repos, switch) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        BranchStatus { name } => match app::branch_set::branch_status(&config, &name) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        BranchPush { name, dry_run } => {
+            git::DryRun::set(dry_run);
+            match app::branch_set::branch_push(&config, &name) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        BranchClean { name, dry_run } => {
+            git::DryRun::set(dry_run);
+            match app::branch_set::branch_clean(&config, &name) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Push {
+            all_clean: _,
+            yes,
+            dry_run,
+        } => {
+            git::DryRun::set(dry_run);
+            match app::push::push_all_clean(&config, yes) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Dissociate { name, dry_run } => {
+            git::DryRun::set(dry_run);
+            match app::dissociate::dissociate(&config, &name) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Prune { dry_run } => {
+            git::DryRun::set(dry_run);
+            match app::remove::prune(&config) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Gc {
+            tag,
+            filter,
+            aggressive,
+            bulk,
+            dry_run,
+        } => {
+            git::DryRun::set(dry_run);
+            let filter = RepoFilter::parse(tag, &filter)?;
+            let bulk = BulkOptions::from(bulk);
+            match app::gc::gc(&config, &filter, aggressive, &bulk) {
+                Ok(summary) if !summary.is_success() => std::process::exit(summary.exit_code()),
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        GcStore => match app::gc_store::gc_store(&config) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Exec {
+            tag,
+            filter,
+            bulk,
+            notify,
+            output,
+            command,
+        } => {
+            let filter = RepoFilter::parse(tag, &filter)?;
+            let bulk = BulkOptions::from(bulk);
+            let output = OutputMode::resolve(output, bulk.jobs);
+            match app::exec::exec(&config, &command, &filter, &bulk, output) {
+                Ok(summary) => {
+                    notification::notify_finished(&config, notify, "exec", &summary);
+                    if !summary.is_success() {
+                        std::process::exit(summary.exit_code());
+                    }
+                }
+                Err(e) => println!("{e}"),
+            }
+        }
+        CheckFleet { tag, filter, bulk } => {
+            let filter = RepoFilter::parse(tag, &filter)?;
+            let bulk = BulkOptions::from(bulk);
+            match app::check_fleet::check_fleet(&config, &filter, &bulk) {
+                Ok(summary) if !summary.is_success() => std::process::exit(summary.exit_code()),
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        PinToolversion {
+            tag,
+            filter,
+            update,
+            branch,
+        } => {
+            let filter = RepoFilter::parse(tag, &filter)?;
+            match app::pin_toolversion::pin_toolversion(&config, &filter, update, &branch) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        LspIsh => app::rpc::serve(&config)?,
+        Stats { history } => match app::stats::stats(&config, history) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Du { tag, filter, json } => {
+            let filter = RepoFilter::parse(tag, &filter)?;
+            match app::du::du(&config, &filter, json) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Releases { tag, filter, json } => {
+            let filter = RepoFilter::parse(tag, &filter)?;
+            match app::releases::releases(&config, &filter, json) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Owners => match app::owners::owners(&config) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Stash { command } => {
+            let result = match command {
+                StashCommands::List => app::stash::list(&config),
+                StashCommands::Drop {
+                    older_than,
+                    dry_run,
+                } => {
+                    git::DryRun::set(dry_run);
+                    app::stash::drop(&config, older_than)
+                }
+            };
+            match result {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Set {
+            command: SetCommands::DefaultBranch { name, branch, .. },
+        } => match app::set::default_branch(&config, &name, branch.as_deref()) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Tag { command } => {
+            let result = match command {
+                TagCommands::Add { name, tags } => app::tag::add(&config, &name, &tags),
+                TagCommands::Remove { name, tags } => app::tag::remove(&name, &tags),
+                TagCommands::List { name } => app::tag::list(name),
+                TagCommands::Auto => app::tag::auto(&config),
+            };
+            match result {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Template {
+            command:
+                TemplateCommands::Apply {
+                    repositories,
+                    branch,
+                },
+        } => match app::template::apply(&config, &repositories, &branch) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Ui => match app::ui::ui(&config) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Wiki {
+            command: WikiCommands::Clone { name },
+        } => match app::wiki::clone(&config, &name, verbosity) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Candidates => match app::path::candidates(&config) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Alias => match app::alias::list(&config) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        Path {
+            name,
+            picker,
+            clone_missing,
+        } => match app::path::path(&config, name.as_deref(), picker.as_deref(), clone_missing) {
+            Ok(Some(_)) => std::process::exit(app::path::NOT_CLONED_EXIT_CODE),
+            Ok(None) => (),
+            Err(e) => println!("{e}"),
+        },
+        Which { target } => app::which::which(&config, &target)?,
+        InspectSymlink { path } => match app::inspect::inspect_symlink(&config, &path) {
+            Ok(_) => (),
+            Err(e) => println!("{e}"),
+        },
+        List {
+            path,
+            full,
+            unsigned,
+            archived,
+            tree,
+            check_origin,
+            fix,
+            tag,
+            filter,
         } => {
-            let verbosity = Verbosity::from_bools(quiet, verbose);
-            match app::clone::clone(&config, &url, verbosity) {
+            let filter = RepoFilter::parse(tag, &filter)?;
+            let result = if check_origin {
+                app::origin::check_origin(&config, archived, fix, &filter)
+            } else {
+                app::list::list(&config, path, full, unsigned, archived, tree, &filter)
+            };
+            match result {
                 Ok(_) => (),
                 Err(e) => println!("{e}"),
             }
         }
-        Path { name } => match app::path::path(&config, &name) {
+        Archive { name, remote } => match app::archive::archive(&config, &name, remote) {
             Ok(_) => (),
             Err(e) => println!("{e}"),
         },
-        List { path, full } => match app::list::list(&config, path, full) {
+        Unarchive { name, remote } => match app::archive::unarchive(&config, &name, remote) {
             Ok(_) => (),
             Err(e) => println!("{e}"),
         },
-        Edit { name } => match app::edit::edit(&config, name) {
+        ReflogRescue { name, branch } => {
+            match app::rescue::reflog_rescue(&config, &name, &branch) {
+                Ok(_) => (),
+                Err(e) => println!("{e}"),
+            }
+        }
+        Edit { name, wait } => match app::edit::edit(&config, name, wait) {
             Ok(_) => (),
             Err(e) => println!("{e}"),
         },
@@ -77,11 +585,61 @@ fn main() -> Result<(), mure_error::Error> {
     Ok(())
 }
 
+/// Value parser of durations like `6h`
+fn parse_duration(text: &str) -> Result<std::time::Duration, String> {
+    misc::datetime::parse_duration(text)
+        .ok_or_else(|| format!("invalid duration {text}: use a number with s, m, h or d"))
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None, next_line_help = true, name = "mure")]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    #[arg(
+        long,
+        global = true,
+        help = "append logs to the file. the level is controlled by MURE_LOG"
+    )]
+    log_file: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        help = "use [profiles.<PROFILE>] of config. Defaults to MURE_PROFILE"
+    )]
+    profile: Option<String>,
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = clap::ArgAction::Count,
+        help = "verbose. -vv also prints every git command and its output"
+    )]
+    verbose: u8,
+    #[arg(short, long, global = true, conflicts_with = "verbose", help = "quiet")]
+    quiet: bool,
+}
+
+/// Default of `--jobs` shared by the commands working on many repositories
+const DEFAULT_JOBS: usize = 4;
+
+/// `--jobs` and `--fail-fast` of the commands working on many repositories
+#[derive(Args, Debug, Clone, Copy, PartialEq, Eq)]
+struct BulkArgs {
+    #[arg(
+        long,
+        default_value_t = DEFAULT_JOBS,
+        help = "number of repositories processed at once"
+    )]
+    jobs: usize,
+    #[arg(long, help = "don't start the rest after the first failure")]
+    fail_fast: bool,
+}
+
+impl From<BulkArgs> for BulkOptions {
+    fn from(args: BulkArgs) -> Self {
+        BulkOptions::new(args.jobs, args.fail_fast)
+    }
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -90,18 +648,30 @@ enum Commands {
     Init {
         #[arg(short, long, help = "Output shims for mure. To be evaluated in shell.")]
         shell: bool,
+        #[arg(
+            long,
+            requires = "shell",
+            help = "Also output Ctrl-G key binding to pick a repository with fzf for the shell"
+        )]
+        widget: Option<Shell>,
     },
     #[command(about = "completion for shell")]
     Completion {
         #[arg(
             short,
             long,
+            required_unless_present = "list_names",
             help = "Output completion for shell. To be evaluated in shell."
         )]
-        shell: Shell,
+        shell: Option<Shell>,
+        #[arg(
+            long,
+            conflicts_with = "shell",
+            help = "list repository names to complete arguments of repositories"
+        )]
+        list_names: bool,
     },
     #[command(about = "refresh repository")]
-    #[clap(group(ArgGroup::new("verbosity").args(&["verbose", "quiet"])))]
     Refresh {
         #[arg(
             index = 1,
@@ -115,10 +685,73 @@ enum Commands {
             default_value = "false"
         )]
         all: bool,
-        #[arg(short, long, help = "verbose", default_value = "false")]
-        verbose: bool,
-        #[arg(short, long, help = "quiet", default_value = "false")]
-        quiet: bool,
+        #[arg(
+            long,
+            value_enum,
+            help = "safe only fetches and fast-forwards, tidy also switches branches and deletes merged ones (default: [refresh] mode)"
+        )]
+        mode: Option<config::RefreshMode>,
+        #[arg(
+            long,
+            help = "never switch branches. the default branch is updated by fetch instead"
+        )]
+        keep_current_branch: bool,
+        #[arg(long, help = "never delete merged branches")]
+        no_delete: bool,
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "delete only merged branches matching the pattern (repeatable)"
+        )]
+        delete_only: Vec<String>,
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "never delete branches matching the pattern, e.g. 'release/*' (repeatable)"
+        )]
+        protect: Vec<String>,
+        #[arg(
+            long,
+            alias = "since",
+            requires = "all",
+            value_name = "DURATION",
+            value_parser = parse_duration,
+            help = "skip repositories refreshed within the duration, e.g. 6h (30m, 2d, ...)"
+        )]
+        stale: Option<std::time::Duration>,
+        #[arg(long, help = "emit newline-delimited JSON events instead of text")]
+        json_lines: bool,
+        #[arg(
+            short,
+            long,
+            help = "refresh only repositories with the tag (with --all)"
+        )]
+        tag: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY=VALUE",
+            help = "refresh only repositories matching the filter (with --all), e.g. lang=rust (keys: tag, lang)"
+        )]
+        filter: Vec<String>,
+        #[command(flatten)]
+        bulk: BulkArgs,
+        #[arg(
+            long,
+            help = "follow the default branch renamed on origin: update origin/HEAD and rename the local branch"
+        )]
+        fix_head: bool,
+        #[arg(
+            long,
+            help = "don't run hooks of repositories (e.g. post-merge) unless run_hooks is set for the repository"
+        )]
+        no_hooks: bool,
+        #[arg(long, help = "show a desktop notification when finished (with --all)")]
+        notify: bool,
+        #[arg(
+            long,
+            help = "show git commands which change repositories instead of running them"
+        )]
+        dry_run: bool,
     },
     #[command(about = "show issues")]
     Issues {
@@ -128,37 +761,647 @@ enum Commands {
         // multiple arguments
         #[arg(short = 'Q', long, help = "query to search issues")]
         query: Vec<String>,
+        #[arg(
+            long,
+            conflicts_with = "query",
+            help = "show repositories of the organization instead of queries"
+        )]
+        org: Option<String>,
+        #[arg(
+            long,
+            requires = "org",
+            help = "show only repositories of the team (slug) of the organization"
+        )]
+        team: Option<String>,
+        #[arg(
+            long,
+            conflicts_with_all = ["query", "org"],
+            help = "list open issues and pull requests assigned to me instead"
+        )]
+        assigned: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["query", "org"],
+            help = "list open pull requests requesting my review instead"
+        )]
+        review_requested: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["query", "org", "assigned", "review_requested"],
+            help = "show repositories cloned locally instead of queries"
+        )]
+        local: bool,
+        #[arg(long, help = "also show the rate limit budget of GitHub API")]
+        rate_limit: bool,
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            help = "columns to show separated by commas (default: issues,prs,branch,ci,coverage,last-release,url)"
+        )]
+        columns: Vec<app::issues::Column>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "prs",
+            help = "order of repositories"
+        )]
+        sort: app::issues::SortKey,
+        #[arg(long, help = "reverse the order")]
+        reverse: bool,
+        #[arg(long, value_name = "N", help = "show only the first N repositories")]
+        limit: Option<usize>,
     },
-    #[command(about = "clone repository")]
-    #[clap(group(ArgGroup::new("verbosity").args(&["verbose", "quiet"])))]
-    Clone {
-        #[arg(index = 1, help = "repository url")]
-        url: String,
-        #[arg(short, long, help = "verbose", default_value = "false")]
-        verbose: bool,
-        #[arg(short, long, help = "quiet", default_value = "false")]
-        quiet: bool,
+    #[command(about = "show coverage of repositories")]
+    Coverage,
+    #[command(about = "diagnose the environment of mure")]
+    Doctor {
+        #[arg(long, help = "also check connectivity to GitHub")]
+        network: bool,
     },
-    #[command(about = "show repository path for name")]
-    Path {
-        #[arg(index = 1, help = "repository name")]
-        name: String,
+    #[command(about = "clone, list, refresh and remove a tiny repository in a scratch directory")]
+    Selftest {
+        #[arg(long, help = "repository to use instead of octocat/Hello-World")]
+        url: Option<String>,
+        #[arg(long, help = "keep the scratch directory to inspect it")]
+        keep: bool,
     },
-    #[command(about = "list repositories")]
-    List {
-        #[arg(short, long, help = "show full name")]
-        full: bool,
-        #[arg(short, long, help = "show path")]
-        path: bool,
+    #[command(about = "print repositories in the format of another tool")]
+    Export {
+        #[arg(long, value_enum, help = "output format")]
+        format: app::manifest::ManifestFormat,
     },
-    #[command(about = "edit repository")]
-    Edit {
-        #[arg(index = 1, help = "repository name")]
+    #[command(about = "clone repositories listed in the format of another tool")]
+    Import {
+        #[arg(long, value_enum, help = "input format")]
+        format: app::manifest::ManifestFormat,
+        #[arg(index = 1, help = "file to import, e.g. default.xml of repo")]
+        path: std::path::PathBuf,
+        #[command(flatten)]
+        bulk: BulkArgs,
+        #[arg(long, help = "show what would be cloned instead of cloning")]
+        dry_run: bool,
+    },
+    #[command(about = "create repository on GitHub and clone it")]
+    Create {
+        #[arg(index = 1, help = "owner/repo of the new repository")]
         name: String,
+        #[arg(
+            long,
+            help = "template repository on GitHub (owner/repo) or local template directory"
+        )]
+        template: Option<String>,
+        #[arg(long, help = "create as a public repository (default: private)")]
+        public: bool,
+        #[arg(long, help = "show what would be done instead of doing it")]
+        dry_run: bool,
     },
-}
-
-#[cfg(test)]
+    #[command(about = "create a new local repository in the store without a remote")]
+    New {
+        #[arg(index = 1, help = "owner/repo, or repo owned by github.username")]
+        name: String,
+        #[arg(
+            long,
+            help = "directory of template files (default: [template] new_dir)"
+        )]
+        template: Option<String>,
+        #[arg(long, help = "also create the repository on GitHub and push it")]
+        github: bool,
+        #[arg(
+            long,
+            requires = "github",
+            help = "create as a public repository (default: private)"
+        )]
+        public: bool,
+        #[arg(long, help = "show what would be done instead of doing it")]
+        dry_run: bool,
+    },
+    #[command(about = "clone repository")]
+    Clone {
+        #[arg(
+            index = 1,
+            help = "repository url",
+            required_unless_present = "from_file"
+        )]
+        url: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = ["url", "with_wiki"],
+            help = "clone missing repositories listed in the file (one url or owner/repo per line)"
+        )]
+        from_file: Option<std::path::PathBuf>,
+        #[command(flatten)]
+        bulk: BulkArgs,
+        #[arg(long, help = "also clone the wiki of the repository")]
+        with_wiki: bool,
+        #[arg(long, help = "clone submodules too")]
+        recurse_submodules: bool,
+        #[arg(
+            long,
+            value_name = "REPO",
+            help = "share objects with the existing repository (name or path) via git alternates"
+        )]
+        reference: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "reference",
+            help = "share objects with an existing clone of the same name under another owner"
+        )]
+        auto_reference: bool,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "create the symlink in the subdirectory of base_dir, e.g. clients/acme"
+        )]
+        into: Option<std::path::PathBuf>,
+        #[arg(
+            long,
+            help = "clone submodules also as repositories of mure to refresh them independently"
+        )]
+        register_submodules: bool,
+        #[arg(
+            long,
+            conflicts_with = "from_file",
+            help = "refresh the repository if it is already cloned"
+        )]
+        update: bool,
+        #[arg(
+            long,
+            help = "show what would be cloned and linked instead of doing it"
+        )]
+        dry_run: bool,
+    },
+    #[command(about = "remove repository and its symlink")]
+    Remove {
+        #[arg(index = 1, help = "repository name")]
+        name: String,
+        #[arg(short, long, help = "remove even if it has uncommitted changes")]
+        force: bool,
+        #[arg(long, help = "show what would be removed instead of removing")]
+        dry_run: bool,
+    },
+    #[command(about = "remove dangling symlinks and repositories not linked from base_dir")]
+    Prune {
+        #[arg(long, help = "show what would be removed instead of removing")]
+        dry_run: bool,
+    },
+    #[command(about = "run git gc in each repository and show the sizes before and after")]
+    Gc {
+        #[arg(short, long, help = "gc only repositories with the tag")]
+        tag: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY=VALUE",
+            help = "gc only repositories matching the filter, e.g. lang=rust (keys: tag, lang)"
+        )]
+        filter: Vec<String>,
+        #[arg(long, help = "optimize packs harder at the cost of much more time")]
+        aggressive: bool,
+        #[command(flatten)]
+        bulk: BulkArgs,
+        #[arg(long, help = "show git commands instead of running them")]
+        dry_run: bool,
+    },
+    #[command(
+        about = "delete or archive repositories in the store whose symlink was removed manually"
+    )]
+    GcStore,
+    #[command(about = "create the same branch across repositories from each default branch")]
+    BranchCreate {
+        #[arg(index = 1, help = "branch name")]
+        name: String,
+        #[arg(
+            long,
+            required = true,
+            value_delimiter = ',',
+            help = "repository names separated by commas"
+        )]
+        repos: Vec<String>,
+        #[arg(long, help = "also switch to the branch in each repository")]
+        switch: bool,
+        #[arg(long, help = "show git commands instead of running them")]
+        dry_run: bool,
+    },
+    #[command(about = "show progress of the branch created by branch-create in each repository")]
+    BranchStatus {
+        #[arg(index = 1, help = "branch name")]
+        name: String,
+    },
+    #[command(about = "push the branch created by branch-create in each repository with commits")]
+    BranchPush {
+        #[arg(index = 1, help = "branch name")]
+        name: String,
+        #[arg(long, help = "show git commands instead of running them")]
+        dry_run: bool,
+    },
+    #[command(
+        about = "delete the branch created by branch-create where it is merged, locally and on origin"
+    )]
+    BranchClean {
+        #[arg(index = 1, help = "branch name")]
+        name: String,
+        #[arg(long, help = "show git commands instead of running them")]
+        dry_run: bool,
+    },
+    #[command(about = "push the default branch of repositories where it is ahead of origin")]
+    Push {
+        #[arg(
+            long,
+            required = true,
+            help = "push in every repository where the default branch is strictly ahead and the working tree is clean"
+        )]
+        all_clean: bool,
+        #[arg(short, long, help = "push without confirmation")]
+        yes: bool,
+        #[arg(long, help = "show git commands instead of running them")]
+        dry_run: bool,
+    },
+    #[command(
+        about = "copy objects shared by clone --reference so the referenced repository can be removed"
+    )]
+    Dissociate {
+        #[arg(index = 1, help = "repository name")]
+        name: String,
+        #[arg(long, help = "show what would be done instead of doing it")]
+        dry_run: bool,
+    },
+    #[command(about = "list repository names for pickers", hide = true)]
+    Candidates,
+    #[command(about = "show aliases of commands defined in [alias] of config")]
+    Alias,
+    #[command(about = "show repository path for name")]
+    Path {
+        #[arg(
+            index = 1,
+            help = "repository name. Picked with the fuzzy finder if omitted"
+        )]
+        name: Option<String>,
+        #[arg(
+            long,
+            help = "fuzzy finder command to pick with (default: shell.picker or fzf)"
+        )]
+        picker: Option<String>,
+        #[arg(
+            long,
+            help = "clone the repository first if it is one of yours on GitHub but not cloned yet"
+        )]
+        clone_missing: bool,
+    },
+    #[command(about = "show the managed repository of a path or URL")]
+    Which {
+        #[arg(index = 1, help = "path inside a repository or URL of it")]
+        target: String,
+    },
+    #[command(about = "explain whether an entry of base_dir is a repository managed by mure")]
+    InspectSymlink {
+        #[arg(index = 1, help = "symlink or directory in base_dir")]
+        path: std::path::PathBuf,
+    },
+    #[command(about = "list repositories")]
+    List {
+        #[arg(short, long, help = "show full name")]
+        full: bool,
+        #[arg(short, long, help = "show path")]
+        path: bool,
+        #[arg(
+            short,
+            long,
+            help = "show only repositories whose recent commits on the default branch are not signed"
+        )]
+        unsigned: bool,
+        #[arg(short, long, help = "show archived repositories instead")]
+        archived: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["full", "path", "unsigned"],
+            help = "show repositories as a tree grouped by domain and owner"
+        )]
+        tree: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["full", "path", "unsigned", "tree"],
+            help = "show repositories whose origin doesn't match the path in the store"
+        )]
+        check_origin: bool,
+        #[arg(
+            long,
+            requires = "check_origin",
+            help = "move repositories to the store path of their origin with --check-origin"
+        )]
+        fix: bool,
+        #[arg(short, long, help = "show only repositories with the tag")]
+        tag: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY=VALUE",
+            help = "show only repositories matching the filter, e.g. lang=rust (keys: tag, lang)"
+        )]
+        filter: Vec<String>,
+    },
+    #[command(about = "move repository to the archive directory")]
+    Archive {
+        #[arg(index = 1, help = "repository name")]
+        name: String,
+        #[arg(long, help = "also archive the repository on GitHub")]
+        remote: bool,
+    },
+    #[command(about = "move repository back from the archive directory")]
+    Unarchive {
+        #[arg(index = 1, help = "repository name")]
+        name: String,
+        #[arg(long, help = "also unarchive the repository on GitHub")]
+        remote: bool,
+    },
+    #[command(about = "recreate a deleted branch from the reflog")]
+    ReflogRescue {
+        #[arg(index = 1, help = "repository name")]
+        name: String,
+        #[arg(index = 2, help = "branch name")]
+        branch: String,
+    },
+    #[command(about = "edit repository")]
+    Edit {
+        #[arg(index = 1, help = "repository name")]
+        name: String,
+        #[arg(long, help = "wait until the editor is closed (for GUI editors)")]
+        wait: bool,
+    },
+    #[command(about = "execute command in each repository")]
+    Exec {
+        #[arg(short, long, help = "execute only in repositories with the tag")]
+        tag: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY=VALUE",
+            help = "execute only in repositories matching the filter, e.g. lang=rust (keys: tag, lang)"
+        )]
+        filter: Vec<String>,
+        #[command(flatten)]
+        bulk: BulkArgs,
+        #[arg(long, help = "show a desktop notification when finished")]
+        notify: bool,
+        #[arg(
+            long,
+            value_enum,
+            help = "how output is shown. Defaults to passing through with --jobs 1, otherwise prefixed"
+        )]
+        output: Option<OutputMode>,
+        #[arg(last = true, required = true, help = "command to execute")]
+        command: Vec<String>,
+    },
+    #[command(about = "run formatting and lint checks of the language in each repository")]
+    CheckFleet {
+        #[arg(short, long, help = "check only repositories with the tag")]
+        tag: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY=VALUE",
+            help = "check only repositories matching the filter, e.g. lang=rust (keys: tag, lang)"
+        )]
+        filter: Vec<String>,
+        #[command(flatten)]
+        bulk: BulkArgs,
+    },
+    #[command(
+        name = "pin-toolversion",
+        about = "audit toolchain pin files of repositories against [toolchain] of config"
+    )]
+    PinToolversion {
+        #[arg(short, long, help = "audit only repositories with the tag")]
+        tag: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY=VALUE",
+            help = "audit only repositories matching the filter, e.g. lang=rust (keys: tag, lang)"
+        )]
+        filter: Vec<String>,
+        #[arg(
+            long,
+            help = "rewrite drifted pin files and commit them on a new branch"
+        )]
+        update: bool,
+        #[arg(
+            long,
+            default_value = "mure/toolchain",
+            help = "branch to commit updates on"
+        )]
+        branch: String,
+    },
+    #[command(
+        name = "lsp-ish",
+        about = "serve JSON-RPC over stdio for editor integrations"
+    )]
+    LspIsh,
+    #[command(about = "show repositories grouped by owner")]
+    Owners,
+    #[command(about = "show statistics of repositories and record them")]
+    Stats {
+        #[arg(long, help = "show the weekly trend of recorded statistics instead")]
+        history: bool,
+    },
+    #[command(about = "show disk usage of each repository, the largest first")]
+    Du {
+        #[arg(short, long, help = "show only repositories with the tag")]
+        tag: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY=VALUE",
+            help = "show only repositories matching the filter, e.g. lang=rust (keys: tag, lang)"
+        )]
+        filter: Vec<String>,
+        #[arg(long, help = "print as JSON with sizes in bytes")]
+        json: bool,
+    },
+    #[command(about = "show the latest release, tag and unreleased commits of each repository")]
+    Releases {
+        #[arg(short, long, help = "show only repositories with the tag")]
+        tag: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY=VALUE",
+            help = "show only repositories matching the filter, e.g. lang=rust (keys: tag, lang)"
+        )]
+        filter: Vec<String>,
+        #[arg(long, help = "print as JSON")]
+        json: bool,
+    },
+    #[command(about = "manage template files of repositories")]
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommands,
+    },
+    #[command(about = "set settings of a repository")]
+    Set {
+        #[command(subcommand)]
+        command: SetCommands,
+    },
+    #[command(about = "manage stashes of repositories")]
+    Stash {
+        #[command(subcommand)]
+        command: StashCommands,
+    },
+    #[command(about = "manage tags of repositories", visible_alias = "pin")]
+    Tag {
+        #[command(subcommand)]
+        command: TagCommands,
+    },
+    #[command(about = "interactive dashboard of repositories")]
+    Ui,
+    #[command(about = "GitHub notifications of the repositories in base_dir")]
+    Notify {
+        #[command(subcommand)]
+        command: NotifyCommands,
+    },
+    #[command(about = "list and check out pull requests")]
+    Pr {
+        #[command(subcommand)]
+        command: PrCommands,
+    },
+    #[command(about = "inspect the GitHub token")]
+    Token {
+        #[command(subcommand)]
+        command: TokenCommands,
+    },
+    #[command(about = "manage wiki repositories")]
+    Wiki {
+        #[command(subcommand)]
+        command: WikiCommands,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum TemplateCommands {
+    #[command(about = "apply template files to repositories on a new branch and commit")]
+    Apply {
+        #[arg(required = true, help = "repository names")]
+        repositories: Vec<String>,
+        #[arg(
+            short,
+            long,
+            help = "branch to commit templates",
+            default_value = "mure/template"
+        )]
+        branch: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum TagCommands {
+    #[command(about = "add tags to the repository")]
+    Add {
+        #[arg(help = "repository name")]
+        name: String,
+        #[arg(required = true, help = "tags")]
+        tags: Vec<String>,
+    },
+    #[command(about = "remove tags from the repository")]
+    Remove {
+        #[arg(help = "repository name")]
+        name: String,
+        #[arg(required = true, help = "tags")]
+        tags: Vec<String>,
+    },
+    #[command(about = "show tags of repositories")]
+    List {
+        #[arg(index = 1, help = "repository name")]
+        name: Option<String>,
+    },
+    #[command(about = "add repositories to groups (tags) by [group_rules] of config")]
+    Auto,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum SetCommands {
+    #[command(
+        name = "default-branch",
+        about = "assert the default branch used by refresh instead of origin/HEAD"
+    )]
+    DefaultBranch {
+        #[arg(help = "repository name")]
+        name: String,
+        #[arg(required_unless_present = "unset", help = "default branch")]
+        branch: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "branch",
+            help = "detect the default branch from origin again"
+        )]
+        unset: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum StashCommands {
+    #[command(about = "show stashes of all repositories")]
+    List,
+    #[command(about = "drop old stashes of all repositories")]
+    Drop {
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = parse_duration,
+            help = "drop stashes older than the duration, e.g. 90d (30m, 6h, ...)"
+        )]
+        older_than: std::time::Duration,
+        #[arg(long, help = "show stashes which would be dropped instead of dropping")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum NotifyCommands {
+    #[command(about = "list unread notifications of the repositories cloned locally")]
+    List {
+        #[arg(long, help = "include repositories not cloned locally")]
+        all: bool,
+    },
+    #[command(about = "mark notifications as read")]
+    Read {
+        #[arg(required = true, help = "notification ids shown by list")]
+        ids: Vec<String>,
+    },
+    #[command(about = "open the subject of the notification in the browser")]
+    Open {
+        #[arg(index = 1, help = "notification id shown by list")]
+        id: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum PrCommands {
+    #[command(about = "list open pull requests of the repository")]
+    List {
+        #[arg(index = 1, help = "repository name")]
+        name: String,
+    },
+    #[command(about = "fetch the pull request into the branch pr/<number> and switch to it")]
+    Checkout {
+        #[arg(index = 1, help = "repository name")]
+        name: String,
+        #[arg(index = 2, help = "pull request number")]
+        number: i64,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum TokenCommands {
+    #[command(
+        about = "show the login, scopes, rate limit and expiry of GH_TOKEN and fail if scopes are missing"
+    )]
+    Check,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum WikiCommands {
+    #[command(about = "clone wiki of the repository")]
+    Clone {
+        #[arg(index = 1, help = "repository name")]
+        name: String,
+    },
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
     use assert_cmd::Command;
@@ -361,14 +1604,54 @@ cd_shims = "mucd"
     fn test_parser() {
         match Cli::parse_from(vec!["mure", "init"]) {
             Cli {
-                command: Commands::Init { shell: false },
+                command:
+                    Commands::Init {
+                        shell: false,
+                        widget: None,
+                    },
+                ..
             } => (),
             _ => panic!("failed to parse"),
         }
 
         match Cli::parse_from(vec!["mure", "init", "--shell"]) {
             Cli {
-                command: Commands::Init { shell: true },
+                command:
+                    Commands::Init {
+                        shell: true,
+                        widget: None,
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "init", "--shell", "--widget", "zsh"]) {
+            Cli {
+                command:
+                    Commands::Init {
+                        shell: true,
+                        widget: Some(Shell::Zsh),
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        assert!(Cli::try_parse_from(vec!["mure", "init", "--widget", "zsh"]).is_err());
+
+        match Cli::parse_from(vec!["mure", "alias"]) {
+            Cli {
+                command: Commands::Alias,
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "candidates"]) {
+            Cli {
+                command: Commands::Candidates,
+                ..
             } => (),
             _ => panic!("failed to parse"),
         }
@@ -379,9 +1662,40 @@ cd_shims = "mucd"
                     Commands::Refresh {
                         repository: None,
                         all: false,
-                        quiet: false,
-                        verbose: false,
+                        mode: None,
+                        keep_current_branch: false,
+                        no_delete: false,
+                        delete_only: _,
+                        protect: _,
+                        stale: None,
+                        json_lines: false,
+                        tag: None,
+                        filter: _,
+                        bulk:
+                            BulkArgs {
+                                jobs: DEFAULT_JOBS,
+                                fail_fast: false,
+                            },
+                        fix_head: false,
+                        no_hooks: false,
+                        notify: false,
+                        dry_run: false,
+                    },
+                quiet: false,
+                verbose: 0,
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "refresh", "--mode", "safe"]) {
+            Cli {
+                command:
+                    Commands::Refresh {
+                        mode: Some(config::RefreshMode::Safe),
+                        ..
                     },
+                ..
             } => (),
             _ => panic!("failed to parse"),
         }
@@ -392,9 +1706,28 @@ cd_shims = "mucd"
                     Commands::Refresh {
                         repository: Some(repo),
                         all: false,
-                        quiet: true,
-                        verbose: false,
+                        mode: None,
+                        keep_current_branch: false,
+                        no_delete: false,
+                        delete_only: _,
+                        protect: _,
+                        stale: None,
+                        json_lines: false,
+                        tag: None,
+                        filter: _,
+                        bulk:
+                            BulkArgs {
+                                jobs: DEFAULT_JOBS,
+                                fail_fast: false,
+                            },
+                        fix_head: false,
+                        no_hooks: false,
+                        notify: false,
+                        dry_run: false,
                     },
+                quiet: true,
+                verbose: 0,
+                ..
             } => assert_eq!(repo, "react"),
             _ => panic!("failed to parse"),
         }
@@ -405,26 +1738,286 @@ cd_shims = "mucd"
                     Commands::Refresh {
                         repository: None,
                         all: true,
-                        quiet: false,
-                        verbose: true,
+                        mode: None,
+                        keep_current_branch: false,
+                        no_delete: false,
+                        delete_only: _,
+                        protect: _,
+                        stale: None,
+                        json_lines: false,
+                        tag: None,
+                        filter: _,
+                        bulk:
+                            BulkArgs {
+                                jobs: DEFAULT_JOBS,
+                                fail_fast: false,
+                            },
+                        fix_head: false,
+                        no_hooks: false,
+                        notify: false,
+                        dry_run: false,
                     },
+                quiet: false,
+                verbose: 1,
+                ..
             } => (),
             _ => panic!("failed to parse"),
         }
 
-        match Cli::parse_from(vec!["mure", "issues"]) {
+        match Cli::parse_from(vec!["mure", "-vv", "list"]) {
             Cli {
-                command: Commands::Issues { query },
-            } => {
-                assert_eq!(query, vec![] as Vec<String>);
-            }
+                verbose: 2,
+                quiet: false,
+                ..
+            } => (),
             _ => panic!("failed to parse"),
         }
+        assert!(Cli::try_parse_from(vec!["mure", "stash", "list", "-q", "-v"]).is_err());
 
-        match Cli::parse_from(vec!["mure", "issues", "--query", "is:public"]) {
+        match Cli::parse_from(vec![
+            "mure",
+            "refresh",
+            "--no-delete",
+            "--delete-only",
+            "feature/*",
+            "--protect",
+            "release/*",
+            "--protect",
+            "hotfix/*",
+        ]) {
             Cli {
-                command: Commands::Issues { query },
-            } => assert_eq!(query, vec!["is:public"]),
+                command:
+                    Commands::Refresh {
+                        no_delete: true,
+                        delete_only,
+                        protect,
+                        ..
+                    },
+                ..
+            } => {
+                assert_eq!(delete_only, vec!["feature/*"]);
+                assert_eq!(protect, vec!["release/*", "hotfix/*"]);
+            }
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "refresh", "--all", "--stale", "6h"]) {
+            Cli {
+                command:
+                    Commands::Refresh {
+                        all: true,
+                        stale: Some(stale),
+                        ..
+                    },
+                ..
+            } => assert_eq!(stale, std::time::Duration::from_secs(6 * 3600)),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "refresh", "--stale", "6h"]).is_err());
+
+        match Cli::parse_from(vec![
+            "mure",
+            "refresh",
+            "--all",
+            "--jobs",
+            "4",
+            "--fail-fast",
+            "--notify",
+        ]) {
+            Cli {
+                command:
+                    Commands::Refresh {
+                        all: true,
+                        bulk:
+                            BulkArgs {
+                                jobs: 4,
+                                fail_fast: true,
+                            },
+                        notify: true,
+                        ..
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "refresh", "--all", "--stale", "6"]).is_err());
+
+        match Cli::parse_from(vec!["mure", "issues"]) {
+            Cli {
+                command:
+                    Commands::Issues {
+                        query,
+                        org: None,
+                        team: None,
+                        assigned: false,
+                        review_requested: false,
+                        local: false,
+                        rate_limit: false,
+                        columns,
+                        sort: app::issues::SortKey::Prs,
+                        reverse: false,
+                        limit: None,
+                    },
+                ..
+            } => {
+                assert_eq!(query, vec![] as Vec<String>);
+                assert!(columns.is_empty());
+            }
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec![
+            "mure",
+            "issues",
+            "--sort",
+            "release",
+            "--reverse",
+            "--limit",
+            "10",
+        ]) {
+            Cli {
+                command:
+                    Commands::Issues {
+                        sort: app::issues::SortKey::Release,
+                        reverse: true,
+                        limit: Some(10),
+                        ..
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "issues", "--query", "is:public"]) {
+            Cli {
+                command: Commands::Issues { query, .. },
+                ..
+            } => assert_eq!(query, vec!["is:public"]),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec![
+            "mure", "issues", "--org", "myorg", "--team", "platform",
+        ]) {
+            Cli {
+                command:
+                    Commands::Issues {
+                        org: Some(org),
+                        team: Some(team),
+                        ..
+                    },
+                ..
+            } => {
+                assert_eq!(org, "myorg");
+                assert_eq!(team, "platform");
+            }
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "issues", "--team", "platform"]).is_err());
+
+        match Cli::parse_from(vec![
+            "mure",
+            "issues",
+            "--columns",
+            "url,license,dependabot-alerts",
+        ]) {
+            Cli {
+                command: Commands::Issues { columns, .. },
+                ..
+            } => assert_eq!(
+                columns,
+                vec![
+                    app::issues::Column::Url,
+                    app::issues::Column::License,
+                    app::issues::Column::DependabotAlerts
+                ]
+            ),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "issues", "--assigned", "--review-requested"]) {
+            Cli {
+                command:
+                    Commands::Issues {
+                        assigned: true,
+                        review_requested: true,
+                        ..
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+        assert!(
+            Cli::try_parse_from(vec!["mure", "issues", "--assigned", "--org", "myorg"]).is_err()
+        );
+
+        match Cli::parse_from(vec!["mure", "issues", "--local"]) {
+            Cli {
+                command: Commands::Issues { local: true, .. },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "issues", "--local", "-Q", "is:public"]).is_err());
+
+        match Cli::parse_from(vec!["mure", "issues", "--rate-limit"]) {
+            Cli {
+                command:
+                    Commands::Issues {
+                        rate_limit: true, ..
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "export", "--format", "repo-manifest"]) {
+            Cli {
+                command: Commands::Export { format },
+                ..
+            } => assert_eq!(format, app::manifest::ManifestFormat::RepoManifest),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "import", "--format", "ghq", "repos.txt"]) {
+            Cli {
+                command:
+                    Commands::Import {
+                        format: app::manifest::ManifestFormat::Ghq,
+                        path,
+                        bulk:
+                            BulkArgs {
+                                jobs: 4,
+                                fail_fast: false,
+                            },
+                        dry_run: false,
+                    },
+                ..
+            } => assert_eq!(path, std::path::PathBuf::from("repos.txt")),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "export", "--format", "unknown"]).is_err());
+
+        match Cli::parse_from(vec![
+            "mure",
+            "create",
+            "myorg/newsvc",
+            "--template",
+            "myorg/service-template",
+        ]) {
+            Cli {
+                command:
+                    Commands::Create {
+                        name,
+                        template: Some(template),
+                        public: false,
+                        dry_run: false,
+                    },
+                ..
+            } => {
+                assert_eq!(name, "myorg/newsvc");
+                assert_eq!(template, "myorg/service-template");
+            }
             _ => panic!("failed to parse"),
         }
 
@@ -432,17 +2025,473 @@ cd_shims = "mucd"
             Cli {
                 command:
                     Commands::Clone {
-                        url,
-                        quiet: false,
-                        verbose: false,
+                        url: Some(url),
+                        from_file: None,
+                        bulk:
+                            BulkArgs {
+                                jobs: 4,
+                                fail_fast: false,
+                            },
+                        with_wiki: false,
+                        recurse_submodules: false,
+                        reference: None,
+                        auto_reference: false,
+                        into: None,
+                        register_submodules: false,
+                        update: false,
+                        dry_run: false,
                     },
+                quiet: false,
+                verbose: 0,
+                ..
             } => assert_eq!(url, "https://github.com/kitsuyui/mure"),
             _ => panic!("failed to parse"),
         }
 
-        match Cli::parse_from(vec!["mure", "path", "mure"]) {
+        match Cli::parse_from(vec![
+            "mure",
+            "clone",
+            "https://github.com/kitsuyui/mure",
+            "--with-wiki",
+        ]) {
+            Cli {
+                command: Commands::Clone {
+                    with_wiki: true, ..
+                },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec![
+            "mure",
+            "clone",
+            "https://github.com/someone/mure",
+            "--reference",
+            "mure",
+        ]) {
+            Cli {
+                command:
+                    Commands::Clone {
+                        reference: Some(reference),
+                        auto_reference: false,
+                        ..
+                    },
+                ..
+            } => assert_eq!(reference, "mure"),
+            _ => panic!("failed to parse"),
+        }
+        match Cli::parse_from(vec![
+            "mure",
+            "clone",
+            "https://github.com/acme/api",
+            "--into",
+            "clients/acme",
+        ]) {
+            Cli {
+                command:
+                    Commands::Clone {
+                        into: Some(into), ..
+                    },
+                ..
+            } => assert_eq!(into, std::path::PathBuf::from("clients/acme")),
+            _ => panic!("failed to parse"),
+        }
+
+        assert!(Cli::try_parse_from(vec![
+            "mure",
+            "clone",
+            "https://github.com/someone/mure",
+            "--reference",
+            "mure",
+            "--auto-reference",
+        ])
+        .is_err());
+        match Cli::parse_from(vec![
+            "mure",
+            "clone",
+            "https://github.com/kitsuyui/mure",
+            "--update",
+        ]) {
+            Cli {
+                command: Commands::Clone { update: true, .. },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec![
+            "mure",
+            "clone",
+            "--from-file",
+            "repos.txt",
+            "--update"
+        ])
+        .is_err());
+
+        match Cli::parse_from(vec![
+            "mure",
+            "clone",
+            "https://github.com/kitsuyui/mure",
+            "--recurse-submodules",
+            "--register-submodules",
+        ]) {
+            Cli {
+                command:
+                    Commands::Clone {
+                        recurse_submodules: true,
+                        register_submodules: true,
+                        ..
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec![
+            "mure",
+            "clone",
+            "--from-file",
+            "repos.txt",
+            "--jobs",
+            "8",
+        ]) {
+            Cli {
+                command:
+                    Commands::Clone {
+                        url: None,
+                        from_file: Some(path),
+                        bulk: BulkArgs { jobs: 8, .. },
+                        ..
+                    },
+                ..
+            } => assert_eq!(path, std::path::PathBuf::from("repos.txt")),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "clone"]).is_err());
+        assert!(Cli::try_parse_from(vec![
+            "mure",
+            "clone",
+            "https://github.com/kitsuyui/mure",
+            "--from-file",
+            "repos.txt",
+        ])
+        .is_err());
+
+        match Cli::parse_from(vec!["mure", "wiki", "clone", "mure"]) {
+            Cli {
+                command:
+                    Commands::Wiki {
+                        command: WikiCommands::Clone { name },
+                    },
+                ..
+            } => assert_eq!(name, "mure"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "coverage"]) {
+            Cli {
+                command: Commands::Coverage,
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "lsp-ish"]) {
+            Cli {
+                command: Commands::LspIsh,
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "pin-toolversion", "--update"]) {
+            Cli {
+                command:
+                    Commands::PinToolversion {
+                        tag: None,
+                        filter,
+                        update: true,
+                        branch,
+                    },
+                ..
+            } => {
+                assert!(filter.is_empty());
+                assert_eq!(branch, "mure/toolchain");
+            }
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "template", "apply", "a", "b"]) {
+            Cli {
+                command:
+                    Commands::Template {
+                        command:
+                            TemplateCommands::Apply {
+                                repositories,
+                                branch,
+                            },
+                    },
+                ..
+            } => {
+                assert_eq!(repositories, vec!["a", "b"]);
+                assert_eq!(branch, "mure/template");
+            }
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "refresh", "--all", "--tag", "work"]) {
+            Cli {
+                command:
+                    Commands::Refresh {
+                        all: true,
+                        tag: Some(tag),
+                        ..
+                    },
+                ..
+            } => assert_eq!(tag, "work"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec![
+            "mure",
+            "exec",
+            "--tag",
+            "oss",
+            "--filter",
+            "lang=rust",
+            "--jobs",
+            "4",
+            "--output",
+            "failures-only",
+            "--",
+            "git",
+            "fetch",
+        ]) {
+            Cli {
+                command:
+                    Commands::Exec {
+                        tag: Some(tag),
+                        filter,
+                        bulk:
+                            BulkArgs {
+                                jobs: 4,
+                                fail_fast: false,
+                            },
+                        notify: false,
+                        output: Some(OutputMode::FailuresOnly),
+                        command,
+                    },
+                ..
+            } => {
+                assert_eq!(tag, "oss");
+                assert_eq!(filter, vec!["lang=rust"]);
+                assert_eq!(command, vec!["git", "fetch"]);
+            }
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec![
+            "mure",
+            "check-fleet",
+            "--filter",
+            "lang=go",
+            "--jobs",
+            "2",
+        ]) {
+            Cli {
+                command:
+                    Commands::CheckFleet {
+                        tag: None,
+                        filter,
+                        bulk:
+                            BulkArgs {
+                                jobs: 2,
+                                fail_fast: false,
+                            },
+                    },
+                ..
+            } => assert_eq!(filter, vec!["lang=go"]),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "set", "default-branch", "api", "main"]) {
+            Cli {
+                command:
+                    Commands::Set {
+                        command:
+                            SetCommands::DefaultBranch {
+                                name,
+                                branch: Some(branch),
+                                unset: false,
+                            },
+                    },
+                ..
+            } => {
+                assert_eq!(name, "api");
+                assert_eq!(branch, "main");
+            }
+            _ => panic!("failed to parse"),
+        }
+        match Cli::parse_from(vec!["mure", "set", "default-branch", "api", "--unset"]) {
+            Cli {
+                command:
+                    Commands::Set {
+                        command:
+                            SetCommands::DefaultBranch {
+                                branch: None,
+                                unset: true,
+                                ..
+                            },
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "set", "default-branch", "api"]).is_err());
+        assert!(Cli::try_parse_from(vec![
+            "mure",
+            "set",
+            "default-branch",
+            "api",
+            "main",
+            "--unset"
+        ])
+        .is_err());
+
+        match Cli::parse_from(vec!["mure", "tag", "add", "mure", "rust", "cli"]) {
+            Cli {
+                command:
+                    Commands::Tag {
+                        command: TagCommands::Add { name, tags },
+                    },
+                ..
+            } => {
+                assert_eq!(name, "mure");
+                assert_eq!(tags, vec!["rust", "cli"]);
+            }
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "stash", "drop", "--older-than", "90d"]) {
+            Cli {
+                command:
+                    Commands::Stash {
+                        command:
+                            StashCommands::Drop {
+                                older_than,
+                                dry_run: false,
+                            },
+                    },
+                ..
+            } => assert_eq!(older_than, std::time::Duration::from_secs(90 * 86400)),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "stash", "drop"]).is_err());
+
+        match Cli::parse_from(vec!["mure", "tag", "auto"]) {
+            Cli {
+                command:
+                    Commands::Tag {
+                        command: TagCommands::Auto,
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "pin", "list"]) {
+            Cli {
+                command:
+                    Commands::Tag {
+                        command: TagCommands::List { name: None },
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "refresh", "--all", "--log-file", "mure.log"]) {
+            Cli {
+                command: Commands::Refresh { all: true, .. },
+                log_file: Some(log_file),
+                ..
+            } => assert_eq!(log_file, std::path::PathBuf::from("mure.log")),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "reflog-rescue", "mure", "feature"]) {
+            Cli {
+                command: Commands::ReflogRescue { name, branch },
+                ..
+            } => {
+                assert_eq!(name, "mure");
+                assert_eq!(branch, "feature");
+            }
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "owners"]) {
+            Cli {
+                command: Commands::Owners,
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "ui"]) {
+            Cli {
+                command: Commands::Ui,
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "path", "mure", "--clone-missing"]) {
+            Cli {
+                command:
+                    Commands::Path {
+                        name,
+                        picker: None,
+                        clone_missing: true,
+                    },
+                ..
+            } => assert_eq!(name, Some("mure".to_string())),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "path", "--picker", "sk"]) {
+            Cli {
+                command:
+                    Commands::Path {
+                        name: None,
+                        picker,
+                        clone_missing: false,
+                    },
+                ..
+            } => assert_eq!(picker, Some("sk".to_string())),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "inspect-symlink", "mure"]) {
+            Cli {
+                command: Commands::InspectSymlink { path },
+                ..
+            } => assert_eq!(path, std::path::PathBuf::from("mure")),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "which", "."]) {
+            Cli {
+                command: Commands::Which { target },
+                ..
+            } => assert_eq!(target, "."),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "edit", "mure", "--wait"]) {
             Cli {
-                command: Commands::Path { name },
+                command: Commands::Edit { name, wait: true },
+                ..
             } => assert_eq!(name, "mure"),
             _ => panic!("failed to parse"),
         }
@@ -453,10 +2502,32 @@ cd_shims = "mucd"
                     Commands::List {
                         full: false,
                         path: false,
+                        unsigned: false,
+                        archived: false,
+                        tree: false,
+                        check_origin: false,
+                        fix: false,
+                        tag: None,
+                        filter: _,
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "list", "--check-origin", "--fix"]) {
+            Cli {
+                command:
+                    Commands::List {
+                        check_origin: true,
+                        fix: true,
+                        ..
                     },
+                ..
             } => (),
             _ => panic!("failed to parse"),
         }
+        assert!(Cli::try_parse_from(vec!["mure", "list", "--fix"]).is_err());
 
         match Cli::parse_from(vec!["mure", "list", "--full"]) {
             Cli {
@@ -464,7 +2535,15 @@ cd_shims = "mucd"
                     Commands::List {
                         full: true,
                         path: false,
+                        unsigned: false,
+                        archived: false,
+                        tree: false,
+                        check_origin: false,
+                        fix: false,
+                        tag: None,
+                        filter: _,
                     },
+                ..
             } => (),
             _ => panic!("failed to parse"),
         }
@@ -475,10 +2554,352 @@ cd_shims = "mucd"
                     Commands::List {
                         full: false,
                         path: true,
+                        unsigned: false,
+                        archived: false,
+                        tree: false,
+                        check_origin: false,
+                        fix: false,
+                        tag: None,
+                        filter: _,
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "doctor", "--network"]) {
+            Cli {
+                command: Commands::Doctor { network: true },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "notify", "list", "--all"]) {
+            Cli {
+                command:
+                    Commands::Notify {
+                        command: NotifyCommands::List { all: true },
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "notify", "read", "101", "102"]) {
+            Cli {
+                command:
+                    Commands::Notify {
+                        command: NotifyCommands::Read { ids },
+                    },
+                ..
+            } => assert_eq!(ids, vec!["101", "102"]),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "notify", "read"]).is_err());
+
+        match Cli::parse_from(vec!["mure", "pr", "list", "mure"]) {
+            Cli {
+                command:
+                    Commands::Pr {
+                        command: PrCommands::List { name },
+                    },
+                ..
+            } => assert_eq!(name, "mure"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "pr", "checkout", "mure", "12"]) {
+            Cli {
+                command:
+                    Commands::Pr {
+                        command: PrCommands::Checkout { name, number },
+                    },
+                ..
+            } => {
+                assert_eq!(name, "mure");
+                assert_eq!(number, 12);
+            }
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "token", "check"]) {
+            Cli {
+                command:
+                    Commands::Token {
+                        command: TokenCommands::Check,
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "completion", "--list-names"]) {
+            Cli {
+                command:
+                    Commands::Completion {
+                        shell: None,
+                        list_names: true,
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "completion"]).is_err());
+
+        match Cli::parse_from(vec!["mure", "selftest", "--keep"]) {
+            Cli {
+                command:
+                    Commands::Selftest {
+                        url: None,
+                        keep: true,
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "list", "--tree"]) {
+            Cli {
+                command: Commands::List { tree: true, .. },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "list", "--tree", "--full"]).is_err());
+
+        match Cli::parse_from(vec![
+            "mure",
+            "clone",
+            "https://github.com/kitsuyui/mure",
+            "--dry-run",
+        ]) {
+            Cli {
+                command: Commands::Clone { dry_run: true, .. },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "remove", "mure", "--force", "--dry-run"]) {
+            Cli {
+                command:
+                    Commands::Remove {
+                        name,
+                        force: true,
+                        dry_run: true,
+                    },
+                ..
+            } => assert_eq!(name, "mure"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "prune", "--dry-run"]) {
+            Cli {
+                command: Commands::Prune { dry_run: true },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec![
+            "mure",
+            "branch-create",
+            "feature",
+            "--repos",
+            "api,web",
+            "--switch",
+        ]) {
+            Cli {
+                command:
+                    Commands::BranchCreate {
+                        name,
+                        repos,
+                        switch: true,
+                        dry_run: false,
+                    },
+                ..
+            } => {
+                assert_eq!(name, "feature");
+                assert_eq!(repos, vec!["api", "web"]);
+            }
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "branch-create", "feature"]).is_err());
+
+        match Cli::parse_from(vec!["mure", "branch-status", "feature"]) {
+            Cli {
+                command: Commands::BranchStatus { name },
+                ..
+            } => assert_eq!(name, "feature"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "branch-push", "feature", "--dry-run"]) {
+            Cli {
+                command:
+                    Commands::BranchPush {
+                        name,
+                        dry_run: true,
+                    },
+                ..
+            } => assert_eq!(name, "feature"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "branch-clean", "feature"]) {
+            Cli {
+                command:
+                    Commands::BranchClean {
+                        name,
+                        dry_run: false,
+                    },
+                ..
+            } => assert_eq!(name, "feature"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "push", "--all-clean", "-y"]) {
+            Cli {
+                command:
+                    Commands::Push {
+                        all_clean: true,
+                        yes: true,
+                        dry_run: false,
                     },
+                ..
             } => (),
             _ => panic!("failed to parse"),
         }
+        assert!(Cli::try_parse_from(vec!["mure", "push"]).is_err());
+
+        match Cli::parse_from(vec!["mure", "dissociate", "mure-fork"]) {
+            Cli {
+                command:
+                    Commands::Dissociate {
+                        name,
+                        dry_run: false,
+                    },
+                ..
+            } => assert_eq!(name, "mure-fork"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "gc", "--aggressive", "--jobs", "2"]) {
+            Cli {
+                command:
+                    Commands::Gc {
+                        tag: None,
+                        aggressive: true,
+                        bulk:
+                            BulkArgs {
+                                jobs: 2,
+                                fail_fast: false,
+                            },
+                        dry_run: false,
+                        ..
+                    },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "new", "newsvc", "--github", "--public"]) {
+            Cli {
+                command:
+                    Commands::New {
+                        name,
+                        template: None,
+                        github: true,
+                        public: true,
+                        dry_run: false,
+                    },
+                ..
+            } => assert_eq!(name, "newsvc"),
+            _ => panic!("failed to parse"),
+        }
+        assert!(Cli::try_parse_from(vec!["mure", "new", "newsvc", "--public"]).is_err());
+
+        match Cli::parse_from(vec!["mure", "gc-store"]) {
+            Cli {
+                command: Commands::GcStore,
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "stats", "--history"]) {
+            Cli {
+                command: Commands::Stats { history: true },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "du", "--json"]) {
+            Cli {
+                command:
+                    Commands::Du {
+                        tag: None,
+                        filter,
+                        json: true,
+                    },
+                ..
+            } => assert!(filter.is_empty()),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "releases", "--tag", "oss", "--json"]) {
+            Cli {
+                command:
+                    Commands::Releases {
+                        tag: Some(tag),
+                        filter,
+                        json: true,
+                    },
+                ..
+            } => {
+                assert_eq!(tag, "oss");
+                assert!(filter.is_empty());
+            }
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "list", "--archived"]) {
+            Cli {
+                command: Commands::List { archived: true, .. },
+                ..
+            } => (),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "archive", "mure", "--remote"]) {
+            Cli {
+                command: Commands::Archive { name, remote: true },
+                ..
+            } => assert_eq!(name, "mure"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "unarchive", "mure"]) {
+            Cli {
+                command:
+                    Commands::Unarchive {
+                        name,
+                        remote: false,
+                    },
+                ..
+            } => assert_eq!(name, "mure"),
+            _ => panic!("failed to parse"),
+        }
+
+        match Cli::parse_from(vec!["mure", "list", "--tag", "rust"]) {
+            Cli {
+                command: Commands::List { tag: Some(tag), .. },
+                ..
+            } => assert_eq!(tag, "rust"),
+            _ => panic!("failed to parse"),
+        }
 
         match Cli::parse_from(vec!["mure", "list", "--full", "--path"]) {
             Cli {
@@ -486,7 +2907,15 @@ cd_shims = "mucd"
                     Commands::List {
                         full: true,
                         path: true,
+                        unsigned: false,
+                        archived: false,
+                        tree: false,
+                        check_origin: false,
+                        fix: false,
+                        tag: None,
+                        filter: _,
                     },
+                ..
             } => (),
             _ => panic!("failed to parse"),
         }
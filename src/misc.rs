@@ -1 +1,8 @@
+pub mod bandwidth;
+pub mod bulk;
 pub mod command_wrapper;
+pub mod datetime;
+pub mod json_file;
+pub mod output;
+pub mod permission;
+pub mod size;
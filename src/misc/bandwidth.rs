@@ -0,0 +1,109 @@
+//! Pacing of bulk network operations by `[network] max_bandwidth`
+//!
+//! git can't limit its own bandwidth, so the average is kept under the limit
+//! by waiting between repositories for the bytes they have transferred.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::misc::size::disk_usage;
+use crate::mure_error::Error;
+
+pub struct Pacer {
+    bytes_per_sec: u64,
+    started: Instant,
+    transferred: Mutex<u64>,
+}
+
+impl Pacer {
+    pub fn new(bytes_per_sec: u64) -> Pacer {
+        Pacer {
+            bytes_per_sec,
+            started: Instant::now(),
+            transferred: Mutex::new(0),
+        }
+    }
+
+    /// Pacer of `[network] max_bandwidth`. None if it is not set.
+    pub fn from_config(config: &Config) -> Result<Option<Pacer>, Error> {
+        let Some(text) = config
+            .network
+            .as_ref()
+            .and_then(|network| network.max_bandwidth.as_ref())
+        else {
+            return Ok(None);
+        };
+        match parse_bandwidth(text) {
+            Some(bytes_per_sec) => Ok(Some(Pacer::new(bytes_per_sec))),
+            None => Err(Error::from_str(&format!(
+                "invalid max_bandwidth {text}. Use bytes per second like 512K or 2M"
+            ))),
+        }
+    }
+
+    /// Record the bytes transferred and wait until the average falls under the limit.
+    /// It can be shared by concurrent jobs.
+    pub fn pace(&self, bytes: u64) {
+        let delay = match self.transferred.lock() {
+            Ok(mut transferred) => {
+                *transferred += bytes;
+                delay(*transferred, self.started.elapsed(), self.bytes_per_sec)
+            }
+            Err(_) => Duration::ZERO,
+        };
+        std::thread::sleep(delay);
+    }
+}
+
+/// Time to wait so that `transferred` bytes in `elapsed` don't exceed `bytes_per_sec` on average
+fn delay(transferred: u64, elapsed: Duration, bytes_per_sec: u64) -> Duration {
+    if bytes_per_sec == 0 {
+        return Duration::ZERO;
+    }
+    let required = Duration::from_secs_f64(transferred as f64 / bytes_per_sec as f64);
+    required.saturating_sub(elapsed)
+}
+
+/// Bytes per second like `800`, `512K`, `2M` or `1G` (binary units)
+pub fn parse_bandwidth(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (value, multiplier) = match text.chars().last()? {
+        'K' | 'k' => (&text[..text.len() - 1], 1024),
+        'M' | 'm' => (&text[..text.len() - 1], 1024 * 1024),
+        'G' | 'g' => (&text[..text.len() - 1], 1024 * 1024 * 1024),
+        _ => (text, 1),
+    };
+    let bytes = value.parse::<u64>().ok()?.checked_mul(multiplier)?;
+    (bytes > 0).then_some(bytes)
+}
+
+/// Size of the git objects of the repository, which grows by the bytes fetched
+pub fn objects_size(repo_path: &Path) -> u64 {
+    disk_usage(&repo_path.join(".git").join("objects")).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bandwidth() {
+        assert_eq!(parse_bandwidth("800"), Some(800));
+        assert_eq!(parse_bandwidth("512K"), Some(512 * 1024));
+        assert_eq!(parse_bandwidth("2M"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_bandwidth("1g"), Some(1024 * 1024 * 1024));
+        assert_eq!(parse_bandwidth("0"), None);
+        assert_eq!(parse_bandwidth("fast"), None);
+        assert_eq!(parse_bandwidth(""), None);
+    }
+
+    #[test]
+    fn test_delay() {
+        let second = Duration::from_secs(1);
+        assert_eq!(delay(2048, second, 1024), second);
+        assert_eq!(delay(1024, second * 3, 1024), Duration::ZERO);
+        assert_eq!(delay(0, Duration::ZERO, 1024), Duration::ZERO);
+    }
+}
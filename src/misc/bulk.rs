@@ -0,0 +1,245 @@
+//! Running an operation over many repositories at once
+//!
+//! Commands working on many repositories share this runner
+//! so that `--jobs`, `--fail-fast`, progress and the exit code behave the same.
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::git::DryRun;
+use crate::verbosity::Verbosity;
+
+/// Exit code of a command when any of the items failed or was cancelled
+pub const FAILURE_EXIT_CODE: i32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkOptions {
+    /// number of items processed at once
+    pub jobs: usize,
+    /// don't start the rest of the items after the first failure
+    pub fail_fast: bool,
+    /// show `[done/total]` on stderr while running.
+    /// Only for operations which print nothing until they finish.
+    pub progress: bool,
+}
+
+impl BulkOptions {
+    pub fn new(jobs: usize, fail_fast: bool) -> Self {
+        BulkOptions {
+            jobs,
+            fail_fast,
+            progress: false,
+        }
+    }
+
+    pub fn with_progress(self) -> Self {
+        BulkOptions {
+            progress: true,
+            ..self
+        }
+    }
+}
+
+impl Default for BulkOptions {
+    fn default() -> Self {
+        BulkOptions::new(1, false)
+    }
+}
+
+/// Counts of the items after a run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BulkSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// items not started because of `--fail-fast`
+    pub cancelled: usize,
+}
+
+impl BulkSummary {
+    pub fn is_success(&self) -> bool {
+        self.failed == 0 && self.cancelled == 0
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        if self.is_success() {
+            0
+        } else {
+            FAILURE_EXIT_CODE
+        }
+    }
+}
+
+/// Run `operation` for each item with `jobs` workers and pass the results to `on_done` as they finish.
+/// `on_done` is called one at a time, so it can print and update state without locking.
+/// An `Err` of the operation counts as a failure.
+pub fn run<I, T, E, O, D>(
+    items: &[I],
+    options: &BulkOptions,
+    operation: O,
+    on_done: D,
+) -> BulkSummary
+where
+    I: Sync,
+    T: Send,
+    E: Send,
+    O: Fn(&I) -> Result<T, E> + Sync,
+    D: FnMut(usize, Result<T, E>) + Send,
+{
+    let dry_run = DryRun::is_enabled();
+    let queue = Mutex::new(items.iter().enumerate());
+    let stopped = AtomicBool::new(false);
+    let progress = Progress::new(options.progress, items.len());
+    let done = Mutex::new((BulkSummary::default(), on_done, progress));
+    std::thread::scope(|scope| {
+        for _ in 0..options.jobs.max(1).min(items.len()) {
+            scope.spawn(|| {
+                DryRun::set(dry_run);
+                loop {
+                    if stopped.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let next = match queue.lock() {
+                        Ok(mut queue) => queue.next(),
+                        Err(_) => None,
+                    };
+                    let Some((index, item)) = next else {
+                        break;
+                    };
+                    let result = operation(item);
+                    if result.is_err() && options.fail_fast {
+                        stopped.store(true, Ordering::Relaxed);
+                    }
+                    let Ok(mut done) = done.lock() else {
+                        break;
+                    };
+                    let (summary, on_done, progress) = &mut *done;
+                    match &result {
+                        Ok(_) => summary.succeeded += 1,
+                        Err(_) => summary.failed += 1,
+                    }
+                    progress.clear();
+                    on_done(index, result);
+                    progress.show(summary.succeeded + summary.failed);
+                }
+            });
+        }
+    });
+    let (mut summary, _, progress) = match done.into_inner() {
+        Ok(done) => done,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    progress.clear();
+    summary.cancelled = items.len() - summary.succeeded - summary.failed;
+    summary
+}
+
+/// `[done/total]` on stderr, redrawn after each item
+struct Progress {
+    enabled: bool,
+    total: usize,
+}
+
+impl Progress {
+    /// Shown only on a terminal and at the normal verbosity, where nothing else is printed while running
+    fn new(requested: bool, total: usize) -> Self {
+        let enabled = requested
+            && Verbosity::current() == Verbosity::Normal
+            && std::io::stderr().is_terminal();
+        Progress { enabled, total }
+    }
+
+    fn show(&self, done: usize) {
+        if self.enabled && done < self.total {
+            eprint!("[{done}/{}]", self.total);
+            let _ = std::io::stderr().flush();
+        }
+    }
+
+    fn clear(&self) {
+        if self.enabled {
+            eprint!("\r\x1b[K");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let items = (0..10).collect::<Vec<_>>();
+        let mut results = vec![];
+        let summary = run(
+            &items,
+            &BulkOptions::new(4, false),
+            |&item| {
+                if item % 3 == 0 {
+                    Err(item)
+                } else {
+                    Ok(item * 2)
+                }
+            },
+            |index, result| results.push((index, result)),
+        );
+        assert_eq!(
+            summary,
+            BulkSummary {
+                succeeded: 6,
+                failed: 4,
+                cancelled: 0
+            }
+        );
+        assert_eq!(summary.exit_code(), FAILURE_EXIT_CODE);
+        results.sort_by_key(|(index, _)| *index);
+        assert_eq!(results[1], (1, Ok(2)));
+        assert_eq!(results[3], (3, Err(3)));
+    }
+
+    #[test]
+    fn test_fail_fast() {
+        let items = (0..10).collect::<Vec<_>>();
+        let summary = run(
+            &items,
+            &BulkOptions::new(1, true),
+            |&item| if item == 2 { Err(()) } else { Ok(()) },
+            |_, _| (),
+        );
+        assert_eq!(
+            summary,
+            BulkSummary {
+                succeeded: 2,
+                failed: 1,
+                cancelled: 7
+            }
+        );
+
+        let summary = run(
+            &items,
+            &BulkOptions::default(),
+            |_| Ok::<_, ()>(()),
+            |_, _| (),
+        );
+        assert!(summary.is_success());
+        assert_eq!(summary.exit_code(), 0);
+    }
+
+    #[test]
+    fn test_dry_run_is_propagated() {
+        DryRun::set(true);
+        let summary = run(
+            &[()],
+            &BulkOptions::default(),
+            |_| {
+                if DryRun::is_enabled() {
+                    Ok(())
+                } else {
+                    Err(())
+                }
+            },
+            |_, _| (),
+        );
+        DryRun::set(false);
+        assert!(summary.is_success());
+    }
+}
@@ -0,0 +1,118 @@
+//! Conversion between seconds since the epoch and UTC dates without a date library
+
+use std::time::Duration;
+
+/// Seconds since the epoch of `YYYY-MM-DDTHH:MM:SSZ`
+pub fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|v| v.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time.splitn(3, ':').map(|v| v.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    let seconds = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second;
+    u64::try_from(seconds).ok()
+}
+
+/// `YYYY-MM-DD` of seconds since the epoch
+pub fn format_date(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Short elapsed time like `12m`, `5h` or `3d`
+pub fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..=3599 => format!("{}m", seconds / 60),
+        3600..=86399 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+/// Duration like `45s`, `30m`, `6h` or `2d`
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let unit = text.chars().last()?;
+    let value = text[..text.len() - unit.len_utf8()].parse::<u64>().ok()?;
+    let seconds = match unit {
+        's' => value,
+        'm' => value * 60,
+        'h' => value * 3600,
+        'd' => value * 86400,
+        _ => return None,
+    };
+    Some(Duration::from_secs(seconds))
+}
+
+/// Days since 1970-01-01 of the proleptic Gregorian calendar
+/// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of days_from_civil
+/// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_timestamp("2024-05-01T12:00:00Z"), Some(1714564800));
+        assert_eq!(parse_timestamp("2000-02-29T23:59:59Z"), Some(951868799));
+        assert_eq!(parse_timestamp("2024-05-01 12:00:00"), None);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(720), "12m");
+        assert_eq!(format_age(5 * 3600), "5h");
+        assert_eq!(format_age(3 * 86400 + 1), "3d");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45s"), Some(Duration::from_secs(45)));
+        assert_eq!(parse_duration("30m"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("6h"), Some(Duration::from_secs(21600)));
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(172800)));
+        assert_eq!(parse_duration("6"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("1w"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1714564800), "2024-05-01");
+        assert_eq!(format_date(951868799), "2000-02-29");
+        for days in [0, 59, 60, 365, 10957, 19844, 30000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! JSON files which mure keeps by itself, e.g. the caches in $XDG_CACHE_HOME/mure
+
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::mure_error::Error;
+
+/// Load the value from the path. Default is returned if the file doesn't exist or is broken.
+pub fn load_or_default<T: DeserializeOwned + Default>(path: &Path) -> Result<T, Error> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+pub fn save<T: Serialize>(value: &T, path: &Path) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(value)?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Path of the file in $XDG_CACHE_HOME/mure, or the value of the environment variable if set.
+pub fn cache_path(variable: &str, file_name: &str) -> Result<PathBuf, Error> {
+    if let Ok(path) = std::env::var(variable) {
+        return Ok(PathBuf::from(path));
+    }
+    let Some(cache_dir) = dirs::cache_dir() else {
+        return Err(Error::from_str("Failed to get cache dir"));
+    };
+    Ok(cache_dir.join("mure").join(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_load_and_save() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let path = temp_dir.join("mure").join("cache.json");
+        let empty: BTreeMap<String, u64> = load_or_default(&path).unwrap();
+        assert!(empty.is_empty());
+
+        let value = BTreeMap::from([("mure".to_string(), 1000)]);
+        save(&value, &path).unwrap();
+        assert_eq!(
+            load_or_default::<BTreeMap<String, u64>>(&path).unwrap(),
+            value
+        );
+
+        std::fs::write(&path, "broken").unwrap();
+        let broken: BTreeMap<String, u64> = load_or_default(&path).unwrap();
+        assert!(broken.is_empty());
+    }
+}
@@ -0,0 +1,332 @@
+//! How output of commands run in many repositories is presented
+//!
+//! The mode decides whether the output is passed through, streamed with prefixes,
+//! or captured and shown per repository when it finishes.
+//! Both external commands (`exec`) and operations done in this process (`refresh`) go through it.
+//! Lines streamed in parallel are prefixed with the repository name and written at once,
+//! so that lines of repositories don't get mixed up in the middle.
+use std::io::{IsTerminal, Read, Write};
+use std::process::{Command, ExitStatus, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputMode {
+    /// inherit the terminal with a `> name` header. Only one repository at a time
+    #[value(skip)]
+    Passthrough,
+    /// stream each line prefixed with the repository name
+    Prefixed,
+    /// show the output of each repository at once when it finishes
+    Grouped,
+    /// show the output of only the repositories which failed
+    FailuresOnly,
+}
+
+impl OutputMode {
+    /// The mode chosen by the user, otherwise passing through one at a time and prefixed in parallel
+    pub fn resolve(mode: Option<OutputMode>, jobs: usize) -> OutputMode {
+        match mode {
+            Some(mode) => mode,
+            None if jobs > 1 => OutputMode::Prefixed,
+            None => OutputMode::Passthrough,
+        }
+    }
+
+    /// Run the command of the repository. The output captured by the mode is returned to be passed to `show`.
+    pub fn run(
+        self,
+        command: &mut Command,
+        name: &str,
+        color: bool,
+    ) -> std::io::Result<(ExitStatus, Vec<u8>)> {
+        match self {
+            OutputMode::Passthrough => {
+                println!("> {name}");
+                Ok((command.status()?, vec![]))
+            }
+            OutputMode::Prefixed => Ok((run_prefixed(command, name, color)?, vec![])),
+            OutputMode::Grouped | OutputMode::FailuresOnly => run_captured(command),
+        }
+    }
+
+    /// Show the captured output and `failure`, the message of the failure if any, after the repository finished.
+    /// Output of a repository is written at once so that it doesn't interleave with the others.
+    pub fn show(self, name: &str, captured: &[u8], failure: Option<&str>, color: bool) {
+        match self {
+            OutputMode::Passthrough => {
+                if let Some(failure) = failure {
+                    println!("{failure}");
+                }
+            }
+            OutputMode::Prefixed => {
+                if let Some(failure) = failure {
+                    let mut writer = PrefixedWriter::new(name, color, std::io::stdout());
+                    let _ = writeln!(writer, "{failure}");
+                }
+            }
+            OutputMode::FailuresOnly if failure.is_none() => (),
+            OutputMode::Grouped | OutputMode::FailuresOnly => {
+                let _ = std::io::stdout()
+                    .lock()
+                    .write_all(&group(name, captured, failure));
+            }
+        }
+    }
+
+    /// Show the output of an operation done in this process (e.g. refresh) after the repository finished.
+    /// `header` is shown above the output unless the lines are prefixed with the name.
+    pub fn show_output(self, name: &str, header: &str, output: &str, failed: bool, color: bool) {
+        let _ = std::io::stdout()
+            .lock()
+            .write_all(&self.format_output(name, header, output, failed, color));
+    }
+
+    fn format_output(
+        self,
+        name: &str,
+        header: &str,
+        output: &str,
+        failed: bool,
+        color: bool,
+    ) -> Vec<u8> {
+        match self {
+            OutputMode::FailuresOnly if !failed => vec![],
+            OutputMode::Prefixed => {
+                let mut formatted = vec![];
+                let mut writer = PrefixedWriter::new(name, color, &mut formatted);
+                let _ = writer.write_all(output.as_bytes());
+                drop(writer);
+                formatted
+            }
+            OutputMode::Passthrough | OutputMode::Grouped | OutputMode::FailuresOnly => {
+                let mut formatted = format!("{header}\n").into_bytes();
+                formatted.extend_from_slice(output.as_bytes());
+                if !output.is_empty() && !output.ends_with('\n') {
+                    formatted.push(b'\n');
+                }
+                formatted
+            }
+        }
+    }
+}
+
+/// `> name` followed by the output and the failure
+fn group(name: &str, captured: &[u8], failure: Option<&str>) -> Vec<u8> {
+    let mut output = format!("> {name}\n").into_bytes();
+    output.extend_from_slice(captured);
+    if !captured.is_empty() && !captured.ends_with(b"\n") {
+        output.push(b'\n');
+    }
+    if let Some(failure) = failure {
+        output.extend_from_slice(format!("{failure}\n").as_bytes());
+    }
+    output
+}
+
+/// Run the command with stdout and stderr line-buffered and prefixed
+/// so that they don't interleave with the commands in the other repositories.
+fn run_prefixed(command: &mut Command, name: &str, color: bool) -> std::io::Result<ExitStatus> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    std::thread::scope(|scope| {
+        if let Some(mut stderr) = stderr {
+            scope.spawn(move || {
+                let mut writer = PrefixedWriter::new(name, color, std::io::stderr());
+                let _ = std::io::copy(&mut stderr, &mut writer);
+            });
+        }
+        if let Some(mut stdout) = stdout {
+            let mut writer = PrefixedWriter::new(name, color, std::io::stdout());
+            let _ = std::io::copy(&mut stdout, &mut writer);
+        }
+    });
+    child.wait()
+}
+
+/// Run the command with stdout and stderr captured into one buffer in the order they are written
+fn run_captured(command: &mut Command) -> std::io::Result<(ExitStatus, Vec<u8>)> {
+    let (mut reader, writer) = std::io::pipe()?;
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(writer.try_clone()?)
+        .stderr(writer)
+        .spawn();
+    // the command keeps the write ends, so the reader would never reach the end
+    command.stdout(Stdio::null()).stderr(Stdio::null());
+    let mut child = child?;
+    let mut captured = vec![];
+    reader.read_to_end(&mut captured)?;
+    Ok((child.wait()?, captured))
+}
+
+/// ANSI colors of prefixes. Bright colors are avoided to be readable on both dark and light backgrounds.
+const COLORS: [&str; 6] = ["31", "32", "33", "34", "35", "36"];
+
+/// Buffers written bytes and writes complete lines as `name | line`.
+/// An incomplete last line is written on flush or drop.
+pub struct PrefixedWriter<W: Write> {
+    prefix: String,
+    inner: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> PrefixedWriter<W> {
+    pub fn new(name: &str, color: bool, inner: W) -> Self {
+        let prefix = if color {
+            format!("\x1b[{}m{name}\x1b[0m | ", color_of(name))
+        } else {
+            format!("{name} | ")
+        };
+        PrefixedWriter {
+            prefix,
+            inner,
+            buffer: vec![],
+        }
+    }
+
+    /// Write the lines with a single write so that they are not interleaved with the others
+    fn write_lines(&mut self, lines: &[u8]) -> std::io::Result<()> {
+        let mut output = vec![];
+        for line in lines.split_inclusive(|&b| b == b'\n') {
+            output.extend_from_slice(self.prefix.as_bytes());
+            output.extend_from_slice(line);
+            if !line.ends_with(b"\n") {
+                output.push(b'\n');
+            }
+        }
+        self.inner.write_all(&output)
+    }
+}
+
+impl<W: Write> Write for PrefixedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if let Some(pos) = self.buffer.iter().rposition(|&b| b == b'\n') {
+            let lines = self.buffer.drain(..=pos).collect::<Vec<_>>();
+            self.write_lines(&lines)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.buffer.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            self.write_lines(&rest)?;
+        }
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for PrefixedWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Color prefixes only when stdout is a terminal and `NO_COLOR` is not set
+pub fn use_color() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// The same name always gets the same color
+fn color_of(name: &str) -> &'static str {
+    let hash = name.bytes().fold(0usize, |hash, b| {
+        hash.wrapping_mul(31).wrapping_add(b as usize)
+    });
+    COLORS[hash % COLORS.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(OutputMode::resolve(None, 1), OutputMode::Passthrough);
+        assert_eq!(OutputMode::resolve(None, 4), OutputMode::Prefixed);
+        assert_eq!(
+            OutputMode::resolve(Some(OutputMode::Grouped), 1),
+            OutputMode::Grouped
+        );
+    }
+
+    #[test]
+    fn test_run_captured() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo out; echo err >&2; echo out again; exit 3"]);
+        let (status, captured) = run_captured(&mut command).unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(captured, b"out\nerr\nout again\n");
+    }
+
+    #[test]
+    fn test_group() {
+        assert_eq!(
+            group("mure", b"line\nno newline", Some("sh exited with 1")),
+            b"> mure\nline\nno newline\nsh exited with 1\n"
+        );
+        assert_eq!(group("mure", b"", None), b"> mure\n");
+    }
+
+    #[test]
+    fn test_format_output() {
+        let format = |mode: OutputMode, failed: bool| {
+            String::from_utf8(mode.format_output("mure", "Refreshing mure", "done", failed, false))
+                .unwrap()
+        };
+        assert_eq!(
+            format(OutputMode::Passthrough, false),
+            "Refreshing mure\ndone\n"
+        );
+        assert_eq!(format(OutputMode::Prefixed, false), "mure | done\n");
+        assert_eq!(format(OutputMode::FailuresOnly, false), "");
+        assert_eq!(
+            format(OutputMode::FailuresOnly, true),
+            "Refreshing mure\ndone\n"
+        );
+    }
+
+    #[test]
+    fn test_prefixed_writer() {
+        let mut output = vec![];
+        {
+            let mut writer = PrefixedWriter::new("mure", false, &mut output);
+            writer.write_all(b"first line\nsec").unwrap();
+            writer.write_all(b"ond line\nno newline").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "mure | first line\nmure | second line\nmure | no newline\n"
+        );
+
+        let mut output = vec![];
+        {
+            let mut writer = PrefixedWriter::new("mure", true, &mut output);
+            writer.write_all(b"colored\n").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("\x1b[{}mmure\x1b[0m | colored\n", color_of("mure"))
+        );
+    }
+
+    #[test]
+    fn test_incomplete_line_is_buffered() {
+        let mut output = vec![];
+        let mut writer = PrefixedWriter::new("mure", false, &mut output);
+        writer.write_all(b"partial").unwrap();
+        assert!(writer.inner.is_empty());
+        writer.flush().unwrap();
+        assert_eq!(writer.inner.as_slice(), b"mure | partial\n");
+    }
+
+    #[test]
+    fn test_color_of() {
+        assert_eq!(color_of("mure"), color_of("mure"));
+        assert!(COLORS.contains(&color_of("")));
+    }
+}
@@ -0,0 +1,73 @@
+//! Detection of repositories which the current user can't access, e.g. owned by another user on shared machines
+
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+/// Whether the repository can't be read or updated by the current user.
+/// Checked before running git so that bulk commands skip it instead of failing halfway.
+pub fn is_permission_denied(path: &Path) -> bool {
+    let git_dir = path.join(".git");
+    if denied(std::fs::read_dir(path).map(|_| ())) || denied(git_dir.symlink_metadata().map(|_| ()))
+    {
+        return true;
+    }
+    // `.git` is a file in worktrees and submodules
+    if !git_dir.is_dir() {
+        return false;
+    }
+    // opened for appending not to modify it
+    denied(std::fs::read_dir(git_dir.join("objects")).map(|_| ()))
+        || denied(
+            OpenOptions::new()
+                .append(true)
+                .open(git_dir.join("HEAD"))
+                .map(|_| ()),
+        )
+}
+
+fn denied(result: std::io::Result<()>) -> bool {
+    matches!(result, Err(e) if e.kind() == ErrorKind::PermissionDenied)
+}
+
+/// How to fix the ownership of the repository, e.g. `owned by uid 1001. run: sudo chown -R "$(id -un)" /path`
+pub fn ownership_hint(path: &Path) -> String {
+    let fix = format!("run: sudo chown -R \"$(id -un)\" {}", path.display());
+    match path.metadata() {
+        Ok(metadata) => format!("owned by uid {}. {fix}", metadata.uid()),
+        Err(_) => fix,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::Fixture;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_is_permission_denied() {
+        let fixture = Fixture::create().unwrap();
+        let path = fixture.repo.workdir().unwrap();
+        assert!(!is_permission_denied(path));
+        let temp_dir = Temp::new_dir().unwrap();
+        assert!(!is_permission_denied(&temp_dir));
+        assert!(!is_permission_denied(&temp_dir.join("missing")));
+
+        assert!(denied(Err(ErrorKind::PermissionDenied.into())));
+        assert!(!denied(Err(ErrorKind::NotFound.into())));
+        assert!(!denied(Ok(())));
+    }
+
+    #[test]
+    fn test_ownership_hint() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let hint = ownership_hint(&temp_dir);
+        assert!(hint.starts_with("owned by uid "));
+        assert!(hint.ends_with(&format!(
+            "run: sudo chown -R \"$(id -un)\" {}",
+            temp_dir.display()
+        )));
+    }
+}
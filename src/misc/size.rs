@@ -0,0 +1,46 @@
+//! Sizes of files on the disk
+
+use std::path::Path;
+
+use crate::mure_error::Error;
+
+/// Total size of files under the directory. Symlinks are not followed.
+pub fn disk_usage(path: &Path) -> Result<u64, Error> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in path.read_dir()? {
+        size += disk_usage(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+/// Human readable size in binary units, e.g. `1.5 GiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
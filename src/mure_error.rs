@@ -9,6 +9,8 @@ pub enum Error {
     GitCommandError(String),
     GHCommandError(String),
     CodecovError(String),
+    /// The config file doesn't exist yet
+    ConfigNotFound(String),
 }
 
 impl Error {
@@ -21,6 +23,7 @@ impl Error {
             Error::GitCommandError(message) => message.to_string(),
             Error::GHCommandError(message) => message.to_string(),
             Error::CodecovError(message) => message.to_string(),
+            Error::ConfigNotFound(message) => message.to_string(),
         }
     }
 }
@@ -0,0 +1,156 @@
+//! Notifications of unattended runs to chat services and of long runs to the desktop
+
+use std::process::Command;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::i18n::Message;
+use crate::misc::bulk::BulkSummary;
+use crate::mure_error::Error;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Post the text to an incoming webhook.
+/// The payload has both `text` (Slack) and `content` (Discord) so that either service accepts it.
+pub fn post_webhook(url: &str, text: &str) -> Result<(), Error> {
+    let payload = serde_json::json!({
+        "text": text,
+        "content": text,
+    });
+    let res = reqwest::blocking::Client::new()
+        .post(url)
+        .header("User-Agent", "mure")
+        .timeout(TIMEOUT)
+        .json(&payload)
+        .send()?;
+    let status = res.status();
+    if !status.is_success() {
+        return Err(Error::from_str(&format!(
+            "webhook responded {status}: {}",
+            res.text()?
+        )));
+    }
+    Ok(())
+}
+
+/// Show the summary of a command run on many repositories on the desktop
+/// if `requested` by `--notify` or `[notifications] enabled = true`.
+/// Failures are only logged not to fail the command itself.
+pub fn notify_finished(config: &Config, requested: bool, command: &str, summary: &BulkSummary) {
+    let enabled = config
+        .notifications
+        .as_ref()
+        .and_then(|n| n.enabled)
+        .unwrap_or(false);
+    if !requested && !enabled {
+        return;
+    }
+    let body = Message::BulkFinished {
+        succeeded: summary.succeeded,
+        failed: summary.failed,
+        cancelled: summary.cancelled,
+    }
+    .to_string();
+    if let Err(e) = notify_desktop(&format!("mure {command}"), &body) {
+        tracing::warn!("failed to show the notification: {e}");
+    }
+}
+
+/// Show a desktop notification by `osascript` on macOS and `notify-send` elsewhere
+pub fn notify_desktop(title: &str, body: &str) -> Result<(), Error> {
+    let mut command = desktop_command(title, body);
+    let program = command.get_program().to_string_lossy().to_string();
+    let status = command
+        .status()
+        .map_err(|e| Error::from_str(&format!("failed to run {program}: {e}")))?;
+    if !status.success() {
+        return Err(Error::from_str(&format!("{program} exited with {status}")));
+    }
+    Ok(())
+}
+
+fn desktop_command(title: &str, body: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title)
+        ));
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg(title).arg(body);
+        command
+    }
+}
+
+/// Quote the text as an AppleScript string literal
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serve one request with the status and return the request
+    fn serve_once(status: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut buf = [0; 1024];
+            // read until the whole JSON body arrives
+            while !String::from_utf8_lossy(&request).ends_with('}') {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            write!(stream, "HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_post_webhook() {
+        let (url, handle) = serve_once("204 No Content");
+        post_webhook(&url, "mure refresh: 1 updated").unwrap();
+        let request = handle.join().unwrap();
+        assert!(request.starts_with("POST /webhook"));
+        let body = request.split("\r\n\r\n").nth(1).unwrap();
+        let body: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["text"], "mure refresh: 1 updated");
+        assert_eq!(body["content"], "mure refresh: 1 updated");
+
+        let (url, handle) = serve_once("404 Not Found");
+        let Err(error) = post_webhook(&url, "text") else {
+            unreachable!();
+        };
+        handle.join().unwrap();
+        assert_eq!(error.to_string(), "webhook responded 404 Not Found: ");
+    }
+
+    #[test]
+    fn test_desktop_command() {
+        assert_eq!(
+            applescript_string(r#"say "hi" \ bye"#),
+            r#""say \"hi\" \\ bye""#
+        );
+        let command = desktop_command("mure refresh", "3 succeeded, 0 failed");
+        let args = command.get_args().collect::<Vec<_>>();
+        if cfg!(target_os = "macos") {
+            assert_eq!(command.get_program(), "osascript");
+            assert_eq!(
+                args[1],
+                r#"display notification "3 succeeded, 0 failed" with title "mure refresh""#
+            );
+        } else {
+            assert_eq!(command.get_program(), "notify-send");
+            assert_eq!(args, ["mure refresh", "3 succeeded, 0 failed"]);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! External command line tools which mure shells out to opportunistically
+//!
+//! Availability is detected once per run and cached. Features depending on a tool check it first
+//! so that they fail with an actionable error (or are skipped) instead of a raw spawn error.
+
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::mure_error::Error;
+
+static GH: OnceLock<Cli> = OnceLock::new();
+
+/// Detected capabilities of a command line tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    program: &'static str,
+    /// None if the program is not found
+    version: Option<String>,
+    authenticated: bool,
+}
+
+impl Cli {
+    /// GitHub CLI. Detected at the first call.
+    pub fn gh() -> &'static Cli {
+        GH.get_or_init(|| {
+            let version = detect_version("gh");
+            // a token in the environment is used by gh without login
+            let authenticated = version.is_some()
+                && (["GH_TOKEN", "GITHUB_TOKEN"]
+                    .iter()
+                    .any(|key| std::env::var(key).is_ok_and(|v| !v.is_empty()))
+                    || Command::new("gh")
+                        .args(["auth", "status"])
+                        .output()
+                        .is_ok_and(|output| output.status.success()));
+            Cli {
+                program: "gh",
+                version,
+                authenticated,
+            }
+        })
+    }
+
+    /// Ok if the feature can use the tool, otherwise an error telling how to enable it
+    pub fn require(&self, feature: &str) -> Result<(), Error> {
+        let program = self.program;
+        if self.version.is_none() {
+            return Err(Error::GHCommandError(format!(
+                "{feature} requires {program} (GitHub CLI) but it is not found in PATH. Install it from https://cli.github.com/"
+            )));
+        }
+        if !self.authenticated {
+            return Err(Error::GHCommandError(format!(
+                "{feature} requires {program} to be authenticated. Run `{program} auth login` or set GH_TOKEN"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Summary like `gh 2.40.1 (authenticated)` for diagnosis
+    pub fn status(&self) -> Result<String, Error> {
+        let Some(version) = &self.version else {
+            return Err(Error::from_str(&format!(
+                "{} is not found in PATH",
+                self.program
+            )));
+        };
+        let auth = if self.authenticated {
+            "authenticated"
+        } else {
+            "not authenticated"
+        };
+        Ok(format!("{} {version} ({auth})", self.program))
+    }
+}
+
+fn detect_version(program: &str) -> Option<String> {
+    let output = Command::new(program).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Version of the first line like `gh version 2.40.1 (2023-12-13)`
+fn parse_version(output: &str) -> Option<String> {
+    let line = output.lines().next()?;
+    let version = line.split_whitespace().nth(2).unwrap_or(line.trim());
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli(version: Option<&str>, authenticated: bool) -> Cli {
+        Cli {
+            program: "gh",
+            version: version.map(str::to_string),
+            authenticated,
+        }
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version(
+                "gh version 2.40.1 (2023-12-13)\nhttps://github.com/cli/cli/releases/tag/v2.40.1\n"
+            ),
+            Some("2.40.1".to_string())
+        );
+        assert_eq!(parse_version("1.0.0\n"), Some("1.0.0".to_string()));
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn test_require() {
+        assert!(cli(Some("2.40.1"), true)
+            .require("archive --remote")
+            .is_ok());
+        assert_eq!(
+            cli(None, false).require("archive --remote").unwrap_err().to_string(),
+            "archive --remote requires gh (GitHub CLI) but it is not found in PATH. Install it from https://cli.github.com/"
+        );
+        assert_eq!(
+            cli(Some("2.40.1"), false)
+                .require("archive --remote")
+                .unwrap_err()
+                .to_string(),
+            "archive --remote requires gh to be authenticated. Run `gh auth login` or set GH_TOKEN"
+        );
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(
+            cli(Some("2.40.1"), true).status().unwrap(),
+            "gh 2.40.1 (authenticated)"
+        );
+        assert_eq!(
+            cli(Some("2.40.1"), false).status().unwrap(),
+            "gh 2.40.1 (not authenticated)"
+        );
+        assert!(cli(None, false).status().is_err());
+    }
+}
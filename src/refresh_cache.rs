@@ -0,0 +1,112 @@
+//! When and how each repository was refreshed last
+//!
+//! It is only an optimization of `refresh --stale`, so it lives in the cache dir:
+//! $XDG_CACHE_HOME/mure/refresh.json (or MURE_REFRESH_CACHE_PATH if set).
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::misc::json_file;
+use crate::mure_error::Error;
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct RefreshCache {
+    /// last refresh keyed by repository name
+    #[serde(default)]
+    pub repositories: BTreeMap<String, RefreshRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RefreshRecord {
+    /// seconds since the epoch
+    pub refreshed_at: u64,
+    pub result: RefreshResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshResult {
+    Updated,
+    Unchanged,
+    Failed(String),
+}
+
+impl RefreshCache {
+    /// Empty cache is returned if the file doesn't exist or is broken.
+    pub fn load() -> Result<RefreshCache, Error> {
+        json_file::load_or_default(&cache_path()?)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        json_file::save(self, &cache_path()?)
+    }
+
+    pub fn record(&mut self, name: &str, refreshed_at: u64, result: RefreshResult) {
+        self.repositories.insert(
+            name.to_string(),
+            RefreshRecord {
+                refreshed_at,
+                result,
+            },
+        );
+    }
+
+    /// Seconds since the last successful refresh if it is within `stale`.
+    /// Failed refreshes don't count so that they are retried.
+    pub fn fresh_age(&self, name: &str, now: u64, stale: Duration) -> Option<u64> {
+        let record = self.repositories.get(name)?;
+        if matches!(record.result, RefreshResult::Failed(_)) {
+            return None;
+        }
+        let age = now.saturating_sub(record.refreshed_at);
+        (age < stale.as_secs()).then_some(age)
+    }
+}
+
+fn cache_path() -> Result<PathBuf, Error> {
+    json_file::cache_path("MURE_REFRESH_CACHE_PATH", "refresh.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_fresh_age() {
+        let mut cache = RefreshCache::default();
+        let stale = Duration::from_secs(3600);
+        cache.record("updated", 1000, RefreshResult::Updated);
+        cache.record("failed", 1000, RefreshResult::Failed("error".to_string()));
+        assert_eq!(cache.fresh_age("updated", 1600, stale), Some(600));
+        assert_eq!(cache.fresh_age("updated", 4600, stale), None);
+        assert_eq!(cache.fresh_age("failed", 1600, stale), None);
+        assert_eq!(cache.fresh_age("unknown", 1600, stale), None);
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let path = temp_dir.join("mure").join("refresh.json");
+        assert_eq!(
+            json_file::load_or_default::<RefreshCache>(&path).unwrap(),
+            RefreshCache::default()
+        );
+        let mut cache = RefreshCache::default();
+        cache.record("mure", 1000, RefreshResult::Unchanged);
+        json_file::save(&cache, &path).unwrap();
+        assert_eq!(
+            json_file::load_or_default::<RefreshCache>(&path).unwrap(),
+            cache
+        );
+
+        std::fs::write(&path, "broken").unwrap();
+        assert_eq!(
+            json_file::load_or_default::<RefreshCache>(&path).unwrap(),
+            RefreshCache::default()
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Repositories of the user on GitHub, to tell names which are not cloned yet
+//!
+//! It is fetched again when it gets old, so it lives in the cache dir:
+//! $XDG_CACHE_HOME/mure/remote.json (or MURE_REMOTE_CACHE_PATH if set).
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::github::repo::RepoInfo;
+use crate::misc::json_file;
+use crate::mure_error::Error;
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct RemoteCache {
+    /// seconds since the epoch
+    #[serde(default)]
+    pub fetched_at: u64,
+    /// URLs of the repositories
+    #[serde(default)]
+    pub urls: Vec<String>,
+}
+
+impl RemoteCache {
+    /// Empty cache is returned if the file doesn't exist or is broken.
+    pub fn load() -> Result<RemoteCache, Error> {
+        json_file::load_or_default(&cache_path()?)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        json_file::save(self, &cache_path()?)
+    }
+
+    pub fn is_fresh(&self, now: u64, max_age: Duration) -> bool {
+        now.saturating_sub(self.fetched_at) < max_age.as_secs()
+    }
+
+    /// Repository of the name. Names on GitHub are case insensitive.
+    pub fn find(&self, name: &str) -> Option<RepoInfo> {
+        self.urls
+            .iter()
+            .filter_map(|url| RepoInfo::parse_url(url))
+            .find(|repo| repo.repo.eq_ignore_ascii_case(name))
+    }
+}
+
+fn cache_path() -> Result<PathBuf, Error> {
+    json_file::cache_path("MURE_REMOTE_CACHE_PATH", "remote.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_find() {
+        let cache = RemoteCache {
+            fetched_at: 1000,
+            urls: vec![
+                "https://github.com/kitsuyui/mure".to_string(),
+                "https://github.com/kitsuyui/dotfiles".to_string(),
+            ],
+        };
+        assert_eq!(
+            cache.find("Mure").map(|repo| repo.name_with_owner()),
+            Some("kitsuyui/mure".to_string())
+        );
+        assert_eq!(cache.find("unknown"), None);
+        assert!(cache.is_fresh(1600, Duration::from_secs(3600)));
+        assert!(!cache.is_fresh(4600, Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let path = temp_dir.join("mure").join("remote.json");
+        assert_eq!(
+            json_file::load_or_default::<RemoteCache>(&path).unwrap(),
+            RemoteCache::default()
+        );
+        let cache = RemoteCache {
+            fetched_at: 1000,
+            urls: vec!["https://github.com/kitsuyui/mure".to_string()],
+        };
+        json_file::save(&cache, &path).unwrap();
+        assert_eq!(
+            json_file::load_or_default::<RemoteCache>(&path).unwrap(),
+            cache
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Persistent state shared across commands
+//!
+//! Unlike config, state is written by mure itself.
+//! It is stored as JSON at $XDG_DATA_HOME/mure/state.json (or MURE_STATE_PATH if set).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::mure_error::Error;
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct State {
+    /// tags of repositories keyed by repository name
+    #[serde(default)]
+    pub tags: BTreeMap<String, BTreeSet<String>>,
+    /// snapshots of statistics of repositories, one per day at most
+    #[serde(default)]
+    pub stats_history: Vec<StatsSnapshot>,
+    /// branches created across repositories by `branch-create` keyed by branch name
+    #[serde(default)]
+    pub branch_sets: BTreeMap<String, BranchSet>,
+    /// default branches asserted by `mure set default-branch` keyed by repository name.
+    /// They take precedence over origin/HEAD and GitHub.
+    #[serde(default)]
+    pub default_branches: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct BranchSet {
+    /// seconds since the epoch
+    pub created_at: u64,
+    /// where the branch started in each repository keyed by repository name
+    pub repositories: BTreeMap<String, BranchStart>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BranchStart {
+    /// default branch the branch was created from
+    pub base: String,
+    pub commit: String,
+}
+
+/// Number of snapshots kept in the history (about a year)
+const STATS_HISTORY_LIMIT: usize = 366;
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct StatsSnapshot {
+    /// seconds since the epoch
+    pub recorded_at: u64,
+    pub repositories: usize,
+    /// repositories with uncommitted changes
+    pub dirty: usize,
+    /// None if GitHub is not available
+    pub open_issues: Option<i64>,
+    pub open_pull_requests: Option<i64>,
+    /// average coverage of repositories reported to Codecov
+    pub coverage: Option<f64>,
+}
+
+impl State {
+    pub fn load() -> Result<State, Error> {
+        State::load_from(&resolve_state_path()?)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(&resolve_state_path()?)
+    }
+
+    /// Load state from the path. Empty state is returned if the file doesn't exist.
+    pub fn load_from(path: &Path) -> Result<State, Error> {
+        if !path.exists() {
+            return Ok(State::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn add_tags(&mut self, repo: &str, tags: &[String]) {
+        self.tags
+            .entry(repo.to_string())
+            .or_default()
+            .extend(tags.iter().cloned());
+    }
+
+    pub fn remove_tags(&mut self, repo: &str, tags: &[String]) {
+        if let Some(current) = self.tags.get_mut(repo) {
+            for tag in tags {
+                current.remove(tag);
+            }
+            if current.is_empty() {
+                self.tags.remove(repo);
+            }
+        }
+    }
+
+    pub fn has_tag(&self, repo: &str, tag: &str) -> bool {
+        self.tags.get(repo).is_some_and(|tags| tags.contains(tag))
+    }
+
+    pub fn set_default_branch(&mut self, repo: &str, branch: &str) {
+        self.default_branches
+            .insert(repo.to_string(), branch.to_string());
+    }
+
+    pub fn unset_default_branch(&mut self, repo: &str) {
+        self.default_branches.remove(repo);
+    }
+
+    pub fn default_branch(&self, repo: &str) -> Option<&str> {
+        self.default_branches.get(repo).map(String::as_str)
+    }
+
+    /// Add repositories to the branch set.
+    /// The creation time of an existing set and where its repositories started are kept.
+    pub fn record_branch_set(
+        &mut self,
+        branch: &str,
+        created_at: u64,
+        repositories: BTreeMap<String, BranchStart>,
+    ) {
+        let set = self
+            .branch_sets
+            .entry(branch.to_string())
+            .or_insert_with(|| BranchSet {
+                created_at,
+                ..Default::default()
+            });
+        for (name, start) in repositories {
+            set.repositories.entry(name).or_insert(start);
+        }
+    }
+
+    /// Append the snapshot. A snapshot of the same day is replaced.
+    pub fn record_stats(&mut self, snapshot: StatsSnapshot) {
+        let day = snapshot.recorded_at / 86400;
+        if self
+            .stats_history
+            .last()
+            .is_some_and(|last| last.recorded_at / 86400 == day)
+        {
+            self.stats_history.pop();
+        }
+        self.stats_history.push(snapshot);
+        if self.stats_history.len() > STATS_HISTORY_LIMIT {
+            let excess = self.stats_history.len() - STATS_HISTORY_LIMIT;
+            self.stats_history.drain(..excess);
+        }
+    }
+}
+
+fn resolve_state_path() -> Result<PathBuf, Error> {
+    if let Ok(path) = std::env::var("MURE_STATE_PATH") {
+        return Ok(PathBuf::from(path));
+    }
+    let Some(data_dir) = dirs::data_dir() else {
+        return Err(Error::from_str("Failed to get data dir"));
+    };
+    Ok(data_dir.join("mure").join("state.json"))
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::from_str(&e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assay::assay;
+    use mktemp::Temp;
+
+    #[test]
+    fn test_tags() {
+        let mut state = State::default();
+        state.add_tags("mure", &["rust".to_string(), "cli".to_string()]);
+        assert!(state.has_tag("mure", "rust"));
+        assert!(state.has_tag("mure", "cli"));
+        assert!(!state.has_tag("mure", "work"));
+        assert!(!state.has_tag("other", "rust"));
+
+        state.remove_tags("mure", &["rust".to_string()]);
+        assert!(!state.has_tag("mure", "rust"));
+        state.remove_tags("mure", &["cli".to_string()]);
+        assert!(state.tags.is_empty());
+    }
+
+    #[test]
+    fn test_default_branch() {
+        let mut state = State::default();
+        assert_eq!(state.default_branch("api"), None);
+        state.set_default_branch("api", "main");
+        state.set_default_branch("api", "trunk");
+        assert_eq!(state.default_branch("api"), Some("trunk"));
+        state.unset_default_branch("api");
+        assert!(state.default_branches.is_empty());
+    }
+
+    #[test]
+    fn test_record_stats() {
+        let snapshot = |recorded_at: u64, repositories: usize| StatsSnapshot {
+            recorded_at,
+            repositories,
+            ..Default::default()
+        };
+        let mut state = State::default();
+        state.record_stats(snapshot(86400, 1));
+        state.record_stats(snapshot(86400 + 3600, 2));
+        assert_eq!(state.stats_history, vec![snapshot(86400 + 3600, 2)]);
+        state.record_stats(snapshot(86400 * 2, 3));
+        assert_eq!(state.stats_history.len(), 2);
+
+        for day in 3..400 {
+            state.record_stats(snapshot(86400 * day, 4));
+        }
+        assert_eq!(state.stats_history.len(), STATS_HISTORY_LIMIT);
+        assert_eq!(state.stats_history[0].recorded_at, 86400 * 34);
+    }
+
+    #[test]
+    fn test_record_branch_set() {
+        let start = |base: &str| BranchStart {
+            base: base.to_string(),
+            commit: "0123abc".to_string(),
+        };
+        let mut state = State::default();
+        state.record_branch_set("feature", 100, [("api".to_string(), start("main"))].into());
+        state.record_branch_set(
+            "feature",
+            200,
+            [
+                ("api".to_string(), start("develop")),
+                ("web".to_string(), start("master")),
+            ]
+            .into(),
+        );
+        let set = &state.branch_sets["feature"];
+        assert_eq!(set.created_at, 100);
+        assert_eq!(
+            set.repositories,
+            [
+                ("api".to_string(), start("main")),
+                ("web".to_string(), start("master"))
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let temp_dir = Temp::new_dir().unwrap();
+        let path = temp_dir.as_path().join("mure").join("state.json");
+
+        // not exists yet
+        let mut state = State::load_from(&path).unwrap();
+        assert_eq!(state, State::default());
+
+        state.add_tags("mure", &["rust".to_string()]);
+        state.save_to(&path).unwrap();
+        assert_eq!(State::load_from(&path).unwrap(), state);
+
+        std::fs::write(&path, "broken").unwrap();
+        assert!(State::load_from(&path).is_err());
+    }
+
+    #[assay(
+        env = [
+            ("MURE_STATE_PATH", "/tmp/mure-state.json"),
+        ]
+    )]
+    fn test_resolve_state_path() {
+        assert_eq!(
+            resolve_state_path().unwrap(),
+            PathBuf::from("/tmp/mure-state.json")
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use git2::Repository;
 use mktemp::Temp;
 
+use crate::config::{Config, ConfigSupport};
 use crate::misc::command_wrapper::CommandOutput;
 use crate::{git::RepositorySupport, mure_error::Error};
 
@@ -61,4 +63,39 @@ impl Fixture {
         file.sync_all()?;
         Ok(())
     }
+
+    /// Move the repository into the store as github.com/kitsuyui/<name>, and link it from base_dir if `linked`.
+    /// Returns the path in the store.
+    pub fn into_store(self, config: &Config, name: &str, linked: bool) -> PathBuf {
+        let store_path = config.repo_store_path("github.com", "kitsuyui", name);
+        std::fs::create_dir_all(store_path.parent().expect("store path has no parent"))
+            .expect("failed to create the store");
+        let workdir = self.repo.workdir().expect("workdir not found");
+        std::fs::rename(workdir, &store_path).expect("failed to move into the store");
+        if linked {
+            std::os::unix::fs::symlink(&store_path, config.base_path().join(name))
+                .expect("failed to link the repository");
+        }
+        store_path
+    }
+}
+
+/// Config of which base_dir is the directory, e.g. a temporary one.
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+pub fn config_with_base_dir(base_dir: &Path) -> Config {
+    toml::from_str(&format!(
+        r#"
+        [core]
+        base_dir = "{}"
+
+        [github]
+        username = "kitsuyui"
+
+        [shell]
+        cd_shims = "mucd"
+    "#,
+        base_dir.display()
+    ))
+    .expect("failed to parse config")
 }
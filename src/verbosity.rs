@@ -1,16 +1,53 @@
-#[derive(Clone, Copy)]
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Verbosity of the whole process set by the global `-v` and `-q` flags
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Verbosity {
     Quiet,
     Normal,
     Verbose,
+    /// Also print every git command and its result on stderr
+    Debug,
 }
 
 impl Verbosity {
-    pub fn from_bools(quiet: bool, verbose: bool) -> Self {
+    /// `-q`, nothing, `-v` or `-vv` (or more)
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
         match (quiet, verbose) {
             (true, _) => Verbosity::Quiet,
-            (_, true) => Verbosity::Verbose,
-            _ => Verbosity::Normal,
+            (_, 0) => Verbosity::Normal,
+            (_, 1) => Verbosity::Verbose,
+            _ => Verbosity::Debug,
         }
     }
+
+    /// Set the verbosity of the process. Unlike DryRun, it is shared by all threads.
+    pub fn set(verbosity: Verbosity) {
+        VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+    }
+
+    pub fn current() -> Self {
+        match VERBOSITY.load(Ordering::Relaxed) {
+            0 => Verbosity::Quiet,
+            1 => Verbosity::Normal,
+            2 => Verbosity::Verbose,
+            _ => Verbosity::Debug,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_flags() {
+        assert_eq!(Verbosity::from_flags(true, 2), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, 3), Verbosity::Debug);
+        assert!(Verbosity::Debug > Verbosity::Verbose);
+    }
 }